@@ -1,4 +1,10 @@
-import {init, Vector2, World} from "../builds/2d-deterministic/pkg";
+import {
+    init,
+    ColliderDesc,
+    RigidBodyDesc,
+    Vector2,
+    World,
+} from "../builds/2d-deterministic/pkg";
 
 describe("2d/World", () => {
     let world: World;
@@ -20,4 +26,38 @@ describe("2d/World", () => {
     test("constructor", () => {
         expect(world.colliders.len()).toBe(0);
     });
+
+    test("rotations are normalized and follow the shortest path", () => {
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setRotation(3.0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), body);
+        expect(body.rotationNormalized()).toBeCloseTo(3.0);
+
+        let delta = body.teleportRotation(-3.0, true);
+        expect(delta).toBeCloseTo(2.0 * Math.PI - 6.0);
+        expect(body.rotationNormalized()).toBeCloseTo(-3.0);
+
+        expect(body.isRotationLocked()).toBe(false);
+        body.pushRotationLock(true, true);
+        expect(body.isRotationLocked()).toBe(true);
+        expect(body.popRotationLock(true)).toBe(true);
+        expect(body.isRotationLocked()).toBe(false);
+        expect(body.popRotationLock(true)).toBe(false);
+    });
+
+    test("popping a rotation lock keeps the translation locks", () => {
+        let body = world.createRigidBody(RigidBodyDesc.dynamic());
+        world.createCollider(ColliderDesc.ball(0.5), body);
+        body.lockTranslations(true, true);
+
+        body.pushRotationLock(true, true);
+        body.popRotationLock(true);
+        expect(body.isRotationLocked()).toBe(false);
+
+        for (let i = 0; i < 10; ++i) {
+            world.step();
+        }
+        expect(body.translation().y).toBe(0);
+    });
 });
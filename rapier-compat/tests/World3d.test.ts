@@ -1,4 +1,41 @@
-import {init, Vector3, World} from "../builds/3d-deterministic/pkg";
+import {
+    init,
+    ActiveCollisionTypes,
+    ActiveEvents,
+    ActiveHooks,
+    Ball,
+    BroadPhaseOptimizationStrategy,
+    ColliderDesc,
+    Compound,
+    ContactModificationContext,
+    ContactReductionStrategy,
+    Cuboid,
+    EventQueue,
+    exportGltfPhysics,
+    FeatureType,
+    Heightfield,
+    importGltfPhysics,
+    JointAxesMask,
+    JointAxis,
+    JointData,
+    MiniSimulator,
+    PhysicsHooks,
+    PrismaticImpulseJoint,
+    Quaternion,
+    Ray,
+    RayCastBatch,
+    RevoluteImpulseJoint,
+    RigidBodyDesc,
+    RigidBodyType,
+    SerializationPipeline,
+    ShapeType,
+    SharedShape,
+    SimulationTier,
+    SolverFlags,
+    Vector,
+    Vector3,
+    World,
+} from "../builds/3d-deterministic/pkg";
 
 describe("3d/World", () => {
     let world: World;
@@ -20,4 +57,2244 @@ describe("3d/World", () => {
     test("constructor", () => {
         expect(world.colliders.len()).toBe(0);
     });
+
+    test("castShapeAll packs every hit by time of impact", () => {
+        let far = world.createCollider(
+            ColliderDesc.ball(0.5).setTranslation(5, 0, 0),
+        );
+        let near = world.createCollider(
+            ColliderDesc.ball(0.5).setTranslation(2, 0, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5).setTranslation(0, 5, 0));
+        world.step();
+
+        let hits = world.castShapeAll(
+            new Vector3(0, 0, 0),
+            new Quaternion(0, 0, 0, 1),
+            new Vector3(1, 0, 0),
+            new Ball(0.5),
+            0.0,
+            10.0,
+            true,
+        );
+
+        // [handle, toi, witness1, witness2, normal1, normal2] for each hit.
+        expect(hits.length).toBe(28);
+        expect(hits[0]).toBe(near.handle);
+        expect(hits[1]).toBeCloseTo(1.0);
+        expect(hits[2]).toBeCloseTo(0.5);
+        expect(hits[8]).toBeCloseTo(1.0);
+        expect(hits[14]).toBe(far.handle);
+        expect(hits[15]).toBeCloseTo(4.0);
+    });
+
+    test("setActiveHooks updates the hooks of several colliders", () => {
+        world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let ball1 = world.createCollider(
+            ColliderDesc.ball(0.5),
+            world.createRigidBody(
+                RigidBodyDesc.dynamic().setTranslation(-2, 0.55, 0),
+            ),
+        );
+        let ball2 = world.createCollider(
+            ColliderDesc.ball(0.5),
+            world.createRigidBody(
+                RigidBodyDesc.dynamic().setTranslation(2, 0.55, 0),
+            ),
+        );
+        world.step();
+        expect(
+            world.numContactPairsWithActiveHooks(
+                ActiveHooks.FILTER_CONTACT_PAIRS,
+            ),
+        ).toBe(0);
+
+        world.setActiveHooks([ball1, ball2], ActiveHooks.FILTER_CONTACT_PAIRS);
+        expect(ball2.activeHooks()).toBe(ActiveHooks.FILTER_CONTACT_PAIRS);
+        expect(
+            world.numContactPairsWithActiveHooks(
+                ActiveHooks.FILTER_CONTACT_PAIRS,
+            ),
+        ).toBe(2);
+    });
+
+    test("compound and shared shapes", () => {
+        let identity = new Quaternion(0, 0, 0, 1);
+        let compound = world.createCollider(
+            ColliderDesc.compound(
+                [new Ball(0.5), new Cuboid(0.5, 0.5, 0.5)],
+                [new Vector3(-2, 0, 0), new Vector3(2, 0, 0)],
+                [identity, identity],
+            ),
+        );
+        expect(compound.containsPoint(new Vector3(-2, 0.4, 0))).toBe(true);
+        expect(compound.containsPoint(new Vector3(2.4, 0.4, 0))).toBe(true);
+        expect(compound.containsPoint(new Vector3(0, 0, 0))).toBe(false);
+
+        let shared = new SharedShape(new Ball(0.5));
+        let collider1 = world.createCollider(ColliderDesc.shared(shared));
+        let collider2 = world.createCollider(ColliderDesc.shared(shared));
+        expect(shared.refCount()).toBe(3);
+        expect(collider1.shapeRefCount()).toBe(3);
+        world.removeCollider(collider2, false);
+        expect(shared.refCount()).toBe(2);
+        shared.free();
+        expect(collider1.shapeRefCount()).toBe(1);
+    });
+
+    test("copyVelocitiesToBuffer packs the velocities of the bodies", () => {
+        let body1 = world.createRigidBody(
+            RigidBodyDesc.dynamic().setLinvel(1, 2, 3).setAngvel(
+                new Vector3(4, 5, 6),
+            ),
+        );
+        let body2 = world.createRigidBody(
+            RigidBodyDesc.dynamic().setLinvel(-1, 0, 0),
+        );
+        world.createRigidBody(RigidBodyDesc.fixed());
+
+        let handles = new Float64Array(2);
+        let velocities = new Float32Array(12);
+        expect(world.copyVelocitiesToBuffer(handles, velocities)).toBe(2);
+        expect(Array.from(handles)).toEqual([body1.handle, body2.handle]);
+        expect(Array.from(velocities.subarray(0, 6))).toEqual([
+            1, 2, 3, 4, 5, 6,
+        ]);
+        expect(velocities[6]).toBe(-1);
+
+        let small = new Float32Array(6);
+        expect(world.copyVelocitiesToBuffer(handles, small)).toBe(1);
+    });
+
+    test("shapes and colliders report their bounding volumes", () => {
+        let cuboid = new Cuboid(1, 2, 3);
+        let localAabb = cuboid.localAabb();
+        expect(localAabb.mins).toEqual({x: -1, y: -2, z: -3});
+        expect(localAabb.maxs).toEqual({x: 1, y: 2, z: 3});
+        expect(cuboid.localBoundingSphere().radius).toBeCloseTo(
+            Math.sqrt(14),
+        );
+
+        let collider = world.createCollider(
+            ColliderDesc.ball(0.5).setTranslation(1, 2, 3),
+        );
+        let aabb = collider.aabb();
+        expect(aabb.mins.x).toBeCloseTo(0.5);
+        expect(aabb.maxs.z).toBeCloseTo(3.5);
+        let sphere = collider.boundingSphere();
+        expect(sphere.center.y).toBeCloseTo(2);
+        expect(sphere.radius).toBeCloseTo(0.5);
+    });
+
+    test("joint motor target velocities ramp up to their target", () => {
+        let body1 = world.createRigidBody(RigidBodyDesc.fixed());
+        let body2 = world.createRigidBody(RigidBodyDesc.dynamic());
+        world.createCollider(ColliderDesc.ball(0.5), body2);
+        let joint = world.createImpulseJoint(
+            JointData.revolute(
+                new Vector3(0, 0, 0),
+                new Vector3(0, 0, 0),
+                new Vector3(0, 0, 1),
+            ),
+            body1,
+            body2,
+            true,
+        ) as RevoluteImpulseJoint;
+        world.integrationParameters.dt = 0.1;
+
+        joint.configureMotorVelocity(10, 1, 20);
+        world.step();
+        expect(joint.motorTargetVel()).toBeCloseTo(2);
+        world.step();
+        expect(joint.motorTargetVel()).toBeCloseTo(4);
+        for (let i = 0; i < 5; ++i) {
+            world.step();
+        }
+        expect(joint.motorTargetVel()).toBeCloseTo(10);
+
+        joint.configureMotorVelocity(-1, 1);
+        expect(joint.motorTargetVel()).toBeCloseTo(-1);
+    });
+
+    test("modifier volumes affect the bodies inside their sensor", () => {
+        let sensor = world.createCollider(
+            ColliderDesc.cuboid(5, 5, 5).setSensor(true),
+        );
+        let inside = world.createRigidBody(RigidBodyDesc.dynamic());
+        world.createCollider(ColliderDesc.ball(0.5), inside);
+        let outside = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(20, 0, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), outside);
+
+        let volume = world.createModifierVolume(sensor);
+        volume.setGravityScale(0);
+        for (let i = 0; i < 5; ++i) {
+            world.step();
+        }
+
+        expect(volume.numAffectedBodies()).toBe(1);
+        expect(inside.gravityScale()).toBe(0);
+        expect(outside.gravityScale()).toBe(1);
+        expect(Math.abs(inside.linvel().y)).toBeLessThan(
+            outside.linvel().y,
+        );
+
+        world.removeModifierVolume(volume);
+        expect(inside.gravityScale()).toBe(1);
+    });
+
+    test("collider pose changes update the parent mass properties", () => {
+        let body = world.createRigidBody(RigidBodyDesc.dynamic());
+        world.createCollider(ColliderDesc.ball(0.5), body);
+        let moved = world.createCollider(ColliderDesc.ball(0.5), body);
+        world.step();
+        expect(body.localCom().x).toBeCloseTo(0);
+
+        moved.setTranslationWrtParent(new Vector3(2, 0, 0), true);
+        expect(body.localCom().x).toBeCloseTo(1);
+
+        moved.setTranslationWrtParent(new Vector3(4, 0, 0));
+        expect(body.localCom().x).toBeCloseTo(1);
+        world.recomputeMassProperties();
+        expect(body.localCom().x).toBeCloseTo(2);
+    });
+
+    test("snapshots can leave out the static geometry", () => {
+        let heights = new Float32Array(51 * 51);
+        let ground = world.createCollider(
+            ColliderDesc.heightfield(
+                50,
+                50,
+                heights,
+                new Vector3(100, 1, 100),
+            ),
+        );
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 2, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), body);
+        let fixed = world.createRigidBody(
+            RigidBodyDesc.fixed().setTranslation(5, 0.5, 0),
+        );
+        let prop = world.createCollider(
+            ColliderDesc.cuboid(0.5, 0.5, 0.5),
+            fixed,
+        );
+        world.step();
+
+        let full = world.takeSnapshot();
+        let stripped = world.takeSnapshot(true);
+        expect(stripped.length).toBeLessThan(full.length);
+
+        let restored = World.restoreSnapshot(stripped, world);
+        expect(restored.getCollider(ground.handle).shape.type).toBe(
+            ShapeType.HeightField,
+        );
+        expect(restored.getRigidBody(body.handle).translation()).toEqual(
+            body.translation(),
+        );
+        expect(restored.getRigidBody(fixed.handle).numColliders()).toBe(1);
+
+        // The static colliders are back in the broad-phase.
+        let hit = restored.castRay(
+            new Ray(new Vector3(5, 5, 0), new Vector3(0, -1, 0)),
+            10,
+            true,
+        );
+        expect(hit.collider.handle).toBe(prop.handle);
+
+        world.gravity = new Vector3(0, -9.81, 0);
+        restored.gravity = new Vector3(0, -9.81, 0);
+        for (let i = 0; i < 60; ++i) {
+            world.step();
+            restored.step();
+        }
+        expect(restored.getRigidBody(body.handle).translation()).toEqual(
+            body.translation(),
+        );
+        restored.free();
+    });
+
+    test("contact manifolds report the features in contact", () => {
+        let ground = world.createCollider(ColliderDesc.cuboid(5, 0.5, 5));
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 0.99, 0),
+        );
+        let box = world.createCollider(
+            ColliderDesc.cuboid(0.5, 0.5, 0.5),
+            body,
+        );
+        world.step();
+
+        let numContacts = 0;
+        world.contactPair(ground, box, (manifold) => {
+            for (let i = 0; i < manifold.numContacts(); ++i) {
+                numContacts += 1;
+                expect(manifold.contactFeatureType1(i)).not.toBe(
+                    FeatureType.Unknown,
+                );
+                expect(manifold.contactFeatureType2(i)).not.toBe(
+                    FeatureType.Unknown,
+                );
+                expect(manifold.contactFeatureIndex1(i)).toBeDefined();
+                expect(manifold.contactFeatureIndex2(i)).toBeDefined();
+            }
+        });
+        expect(numContacts).toBeGreaterThan(0);
+    });
+
+    test("pose double-buffering keeps the poses of the previous step", () => {
+        let body = world.createRigidBody(RigidBodyDesc.dynamic());
+        world.enablePoseDoubleBuffering(true);
+        expect(world.isPoseDoubleBufferingEnabled()).toBe(true);
+
+        world.step();
+        let previous = body.translation();
+        world.step();
+        expect(world.latestPoses().translation(body)).toEqual(
+            body.translation(),
+        );
+        expect(world.displayedPoses().translation(body)).toEqual(previous);
+        expect(world.latestPoses().rotation(body.handle).w).toBeCloseTo(1);
+
+        world.enablePoseDoubleBuffering(false);
+        expect(world.latestPoses()).toBeNull();
+    });
+
+    test("stepAsync runs one step at a time", async () => {
+        let body = world.createRigidBody(RigidBodyDesc.dynamic());
+        let pending = world.stepAsync();
+        let rejected = world.stepAsync();
+        expect(body.translation().y).toBe(0);
+        await expect(rejected).rejects.toThrow();
+
+        await pending;
+        expect(body.translation().y).toBeGreaterThan(0);
+    });
+
+    test("stepAsync runs before the next timer", async () => {
+        let body = world.createRigidBody(RigidBodyDesc.dynamic());
+        let timer = new Promise((resolve) => setTimeout(resolve, 0));
+        let stepped = false;
+        let pending = world.stepAsync().then(() => {
+            stepped = true;
+        });
+        expect(stepped).toBe(false);
+
+        await timer;
+        expect(stepped).toBe(true);
+        expect(body.translation().y).toBeGreaterThan(0);
+        await pending;
+    });
+
+    test("the physics hooks statistics count the filtered pairs", () => {
+        world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body1 = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(-2, 0.34, 0),
+        );
+        world.createCollider(
+            ColliderDesc.ball(0.25).setActiveHooks(
+                ActiveHooks.FILTER_CONTACT_PAIRS,
+            ),
+            body1,
+        );
+        let body2 = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(2, 0.34, 0),
+        );
+        world.createCollider(
+            ColliderDesc.ball(0.25).setActiveHooks(
+                ActiveHooks.FILTER_CONTACT_PAIRS,
+            ),
+            body2,
+        );
+
+        let hooks = {
+            filterContactPair: (
+                collider1: number,
+                collider2: number,
+                body1Handle: number,
+                body2Handle: number,
+            ): SolverFlags | null =>
+                body1Handle == body1.handle || body2Handle == body1.handle
+                    ? null
+                    : SolverFlags.COMPUTE_IMPULSE,
+            filterIntersectionPair: () => true,
+        };
+        world.step(undefined, hooks);
+
+        expect(world.hooksNumContactPairsFiltered()).toBe(1);
+        expect(world.hooksNumContactPairsPassed()).toBe(1);
+        expect(world.hooksNumIntersectionPairsPassed()).toBe(0);
+        expect(world.timingHooks()).toBeGreaterThanOrEqual(0);
+    });
+
+    test("convex polyhedra can be built from their faces", () => {
+        // A unit cube with its six quad faces.
+        let vertices = new Float32Array([
+            0, 0, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0, 0, 1, 1, 0, 1, 1, 1, 1, 0,
+            1, 1,
+        ]);
+        let faces = new Uint32Array([
+            0, 3, 2, 1, 4, 5, 6, 7, 0, 1, 5, 4, 2, 3, 7, 6, 1, 2, 6, 5, 0, 4,
+            7, 3,
+        ]);
+        let sizes = new Uint32Array([4, 4, 4, 4, 4, 4]);
+        let cube = world.createCollider(
+            ColliderDesc.convexMeshFromFaces(vertices, faces, sizes),
+        );
+        expect(cube.containsPoint(new Vector3(0.5, 0.5, 0.5))).toBe(true);
+        expect(cube.containsPoint(new Vector3(1.5, 0.5, 0.5))).toBe(false);
+
+        // Moving one vertex inward makes the cube concave.
+        vertices[18] = 0.5;
+        vertices[19] = 0.5;
+        vertices[20] = 0.5;
+        expect(() =>
+            world.createCollider(
+                ColliderDesc.convexMeshFromFaces(vertices, faces, sizes),
+            ),
+        ).toThrow();
+    });
+
+    test("cloneInto copies the selected objects into another world", () => {
+        let ground = world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body1 = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 1, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), body1);
+        let body2 = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 3, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), body2);
+        let joint = world.createImpulseJoint(
+            JointData.spherical(new Vector3(0, 1, 0), new Vector3(0, -1, 0)),
+            body1,
+            body2,
+            true,
+        );
+        let skipped = world.createRigidBody(RigidBodyDesc.dynamic());
+
+        let target = new World(new Vector3(0, 0, 0));
+        let mapping = world.cloneInto(target, (body) => body != skipped);
+        expect(target.bodies.len()).toBe(2);
+        expect(target.colliders.len()).toBe(3);
+        expect(target.impulseJoints.len()).toBe(1);
+        expect(mapping.bodies.has(skipped.handle)).toBe(false);
+
+        let copy1 = target.getRigidBody(mapping.bodies.get(body1.handle));
+        expect(copy1.translation()).toEqual(body1.translation());
+        let copyJoint = target.getImpulseJoint(
+            mapping.impulseJoints.get(joint.handle),
+        );
+        expect(copyJoint.body2().handle).toBe(
+            mapping.bodies.get(body2.handle),
+        );
+        expect(mapping.colliders.has(ground.handle)).toBe(true);
+        target.free();
+    });
+
+    test("reduceSolverContacts keeps at most the given contacts", () => {
+        world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 0.59, 0),
+        );
+        world.createCollider(
+            ColliderDesc.cuboid(0.5, 0.5, 0.5).setActiveHooks(
+                ActiveHooks.MODIFY_SOLVER_CONTACTS,
+            ),
+            body,
+        );
+
+        let numBefore = 0;
+        let numAfter = 0;
+        let numRemoved = 0;
+        let hooks: PhysicsHooks = {
+            filterContactPair: () => SolverFlags.COMPUTE_IMPULSE,
+            filterIntersectionPair: () => true,
+            modifySolverContacts: (raw) => {
+                let context = new ContactModificationContext(raw);
+                numBefore = Math.max(numBefore, context.numSolverContacts);
+                numRemoved += context.reduceSolverContacts(
+                    2,
+                    ContactReductionStrategy.Deepest,
+                );
+                numAfter = Math.max(numAfter, context.numSolverContacts);
+            },
+        };
+        world.step(undefined, hooks);
+
+        expect(numBefore).toBe(4);
+        expect(numRemoved).toBe(2);
+        expect(numAfter).toBe(2);
+    });
+
+    test("worlds can disable the broad-phase tree optimization", () => {
+        let unoptimized = World.withBroadPhaseOptimizationStrategy(
+            new Vector3(0, -9.81, 0),
+            BroadPhaseOptimizationStrategy.None,
+        );
+        unoptimized.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body = unoptimized.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 1, 0),
+        );
+        unoptimized.createCollider(ColliderDesc.ball(0.5), body);
+        for (let i = 0; i < 120; ++i) {
+            unoptimized.step();
+        }
+
+        expect(body.translation().y).toBeCloseTo(0.6, 1);
+        unoptimized.free();
+    });
+
+    test("joint motors follow timed trajectories", () => {
+        let body1 = world.createRigidBody(RigidBodyDesc.fixed());
+        let body2 = world.createRigidBody(RigidBodyDesc.dynamic());
+        world.createCollider(ColliderDesc.ball(0.5), body2);
+        let joint = world.createImpulseJoint(
+            JointData.revolute(
+                new Vector3(0, 0, 0),
+                new Vector3(0, 0, 0),
+                new Vector3(0, 0, 1),
+            ),
+            body1,
+            body2,
+            true,
+        ) as RevoluteImpulseJoint;
+        world.integrationParameters.dt = 0.1;
+
+        expect(() =>
+            joint.followMotorTrajectory([1, 0], [0, 1], 1, 1),
+        ).toThrow();
+        joint.followMotorTrajectory([0, 0.5], [0, 1], 1000, 100);
+        world.step();
+        expect(joint.isFollowingMotorTrajectory()).toBe(true);
+        expect(joint.motorTargetVel()).toBeCloseTo(2);
+        for (let i = 0; i < 5; ++i) {
+            world.step();
+        }
+        expect(joint.isFollowingMotorTrajectory()).toBe(false);
+    });
+
+    test("the additional center of mass can be moved at runtime", () => {
+        let body = world.createRigidBody(RigidBodyDesc.dynamic());
+        expect(body.additionalMassProperties()).toBeNull();
+
+        body.setAdditionalMassProperties(
+            2.0,
+            new Vector3(0, 0, 0),
+            new Vector3(1, 1, 1),
+            new Quaternion(0, 0, 0, 1),
+            true,
+        );
+        body.setAdditionalLocalCenterOfMass(new Vector3(1, 0, 0), true);
+        let props = body.additionalMassProperties();
+        expect(props.mass).toBeCloseTo(2.0);
+        expect(props.centerOfMass.x).toBeCloseTo(1.0);
+        expect(props.principalInertia.y).toBeCloseTo(1.0);
+
+        world.step();
+        expect(body.localCom().x).toBeCloseTo(1.0);
+    });
+
+    test("collision events report the sensors and parents involved", () => {
+        let sensor = world.createCollider(
+            ColliderDesc.cuboid(1, 1, 1)
+                .setSensor(true)
+                .setActiveEvents(ActiveEvents.COLLISION_EVENTS),
+        );
+        let body = world.createRigidBody(RigidBodyDesc.dynamic());
+        world.createCollider(ColliderDesc.ball(0.5), body);
+        let eventQueue = new EventQueue(true);
+        world.step(eventQueue);
+
+        let events: {
+            handle1: number;
+            started: boolean;
+            sensor1: boolean;
+            sensor2: boolean;
+            body1: number | null;
+            body2: number | null;
+        }[] = [];
+        eventQueue.drainCollisionEventsWithColliderInfo(
+            world.colliders,
+            (handle1, handle2, started, sensor1, sensor2, body1, body2) => {
+                events.push({handle1, started, sensor1, sensor2, body1, body2});
+            },
+        );
+        expect(events.length).toBe(1);
+        let event = events[0];
+        let sensorFirst = event.handle1 == sensor.handle;
+        expect(event.started).toBe(true);
+        expect(sensorFirst ? event.sensor1 : event.sensor2).toBe(true);
+        expect(sensorFirst ? event.sensor2 : event.sensor1).toBe(false);
+        expect(sensorFirst ? event.body2 : event.body1).toBe(body.handle);
+        expect(sensorFirst ? event.body1 : event.body2).toBeNull();
+        eventQueue.free();
+    });
+
+    test("contact material tables tag the contact force events", () => {
+        world.gravity = new Vector3(0, -9.81, 0);
+        let ground = world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 0.6, 0),
+        );
+        let ball = world.createCollider(
+            ColliderDesc.ball(0.5).setActiveEvents(
+                ActiveEvents.CONTACT_FORCE_EVENTS,
+            ),
+            body,
+        );
+
+        let materials = world.contactMaterials;
+        materials.setColliderMaterial(ground.handle, 1);
+        materials.setColliderMaterial(ball.handle, 2);
+        expect(materials.colliderMaterial(ball.handle)).toBe(2);
+        expect(materials.setTags(2, 1, [0, 1.0e6], [7, 8])).toBe(true);
+        expect(materials.setTags(1, 2, [0], [7, 8])).toBe(false);
+
+        let eventQueue = new EventQueue(true);
+        let tags: number[] = [];
+        for (let i = 0; i < 10; ++i) {
+            world.step(eventQueue);
+            eventQueue.drainContactForceEvents(
+                (event) => tags.push(event.materialTag()),
+                materials,
+            );
+        }
+        expect(tags.length).toBeGreaterThan(0);
+        expect(tags.every((tag) => tag == 7)).toBe(true);
+        eventQueue.free();
+    });
+
+    test("the mini simulator steps its own bodies", () => {
+        let simulator = new MiniSimulator(new Vector3(0, -9.81, 0));
+        simulator.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body = simulator.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 2, 0),
+        );
+        simulator.createCollider(ColliderDesc.ball(0.5), body);
+
+        simulator.step(1.0 / 60.0);
+        expect(body.translation().y).toBeLessThan(2);
+        simulator.advance(1.0 / 60.0, 120);
+        expect(body.translation().y).toBeCloseTo(0.6, 1);
+
+        simulator.removeRigidBody(body);
+        expect(simulator.bodies.len()).toBe(0);
+        expect(simulator.colliders.len()).toBe(1);
+        simulator.free();
+    });
+
+    test("the mini simulator simulates a copy of part of a world", () => {
+        world.gravity = new Vector3(0, -9.81, 0);
+        world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let kept = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 2, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), kept);
+        let skipped = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(3, 2, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), skipped);
+
+        let simulator = MiniSimulator.cloneFrom(
+            world,
+            (body) => body.handle !== skipped.handle,
+        );
+        expect(simulator.gravity.y).toBeCloseTo(-9.81);
+        expect(simulator.bodies.len()).toBe(1);
+        expect(simulator.colliders.len()).toBe(2);
+
+        let mapping = simulator.copyFrom(world, () => false);
+        expect(mapping.bodies.size).toBe(0);
+        expect(mapping.colliders.size).toBe(1);
+
+        let copy = simulator.bodies.getAll()[0];
+        simulator.advance(1.0 / 60.0, 120);
+        expect(copy.translation().y).toBeCloseTo(0.6, 1);
+        expect(kept.translation().y).toBe(2);
+        simulator.free();
+    });
+
+    test("predictTrajectory stops at the first fixed collider hit", () => {
+        world.gravity = new Vector3(0, -9.81, 0);
+        let ground = world.createCollider(ColliderDesc.cuboid(50, 0.1, 50));
+        world.step();
+
+        let trajectory = world.predictTrajectory(
+            new Vector3(0, 1, 0),
+            new Quaternion(0, 0, 0, 1),
+            new Vector3(2, 5, 0),
+            new Ball(0.1),
+            5.0,
+            1.0 / 60.0,
+        );
+        expect(trajectory.hitCollider.handle).toBe(ground.handle);
+        expect(trajectory.hitNormal.y).toBeCloseTo(1);
+        expect(trajectory.hitPosition.y).toBeCloseTo(0.2, 1);
+        // The projectile rises, then falls back after about 1.2 seconds.
+        expect(trajectory.hitTime).toBeGreaterThan(1.0);
+        expect(trajectory.hitTime).toBeLessThan(1.4);
+        expect(trajectory.points.length).toBeGreaterThan(2);
+
+        let missed = world.predictTrajectory(
+            new Vector3(0, 1, 0),
+            new Quaternion(0, 0, 0, 1),
+            new Vector3(2, 5, 0),
+            new Ball(0.1),
+            0.5,
+            1.0 / 60.0,
+        );
+        expect(missed.hitCollider).toBeNull();
+    });
+
+    test("the packed states match the individual getters", () => {
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic()
+                .setTranslation(1, 2, 3)
+                .setLinvel(4, 5, 6)
+                .setGravityScale(0.5),
+        );
+        let collider = world.createCollider(
+            ColliderDesc.ball(0.5).setFriction(0.25).setSensor(true),
+            body,
+        );
+
+        let bodyState = body.state();
+        expect(bodyState.translation).toEqual(body.translation());
+        expect(bodyState.linvel).toEqual(body.linvel());
+        expect(bodyState.rotation.w).toBeCloseTo(1);
+        expect(bodyState.mass).toBeCloseTo(body.mass());
+        expect(bodyState.bodyType).toBe(RigidBodyType.Dynamic);
+        expect(bodyState.gravityScale).toBeCloseTo(0.5);
+        expect(bodyState.isSleeping).toBe(false);
+
+        let colliderState = collider.state();
+        expect(colliderState.translation).toEqual(collider.translation());
+        expect(colliderState.friction).toBeCloseTo(0.25);
+        expect(colliderState.isSensor).toBe(true);
+        expect(colliderState.parent).toBe(body.handle);
+        expect(
+            world.createCollider(ColliderDesc.ball(0.5)).state().parent,
+        ).toBeNull();
+    });
+
+    test("active collision types can be toggled incrementally", () => {
+        let collider1 = world.createCollider(ColliderDesc.ball(0.5));
+        let collider2 = world.createCollider(ColliderDesc.ball(0.5));
+        let defaultTypes = collider1.activeCollisionTypes();
+
+        collider1.enableActiveCollisionTypes(ActiveCollisionTypes.FIXED_FIXED);
+        expect(collider1.activeCollisionTypes()).toBe(
+            defaultTypes | ActiveCollisionTypes.FIXED_FIXED,
+        );
+        collider1.disableActiveCollisionTypes(
+            ActiveCollisionTypes.FIXED_FIXED,
+        );
+        expect(collider1.activeCollisionTypes()).toBe(defaultTypes);
+
+        let handles = [collider1.handle, collider2.handle];
+        world.colliders.enableActiveCollisionTypes(
+            handles,
+            ActiveCollisionTypes.KINEMATIC_FIXED,
+        );
+        expect(collider2.activeCollisionTypes()).toBe(
+            defaultTypes | ActiveCollisionTypes.KINEMATIC_FIXED,
+        );
+        let allTypes =
+            ActiveCollisionTypes.DEFAULT |
+            ActiveCollisionTypes.KINEMATIC_KINEMATIC |
+            ActiveCollisionTypes.KINEMATIC_FIXED |
+            ActiveCollisionTypes.FIXED_FIXED;
+        world.colliders.setActiveCollisionTypes(handles, allTypes);
+        expect(collider1.activeCollisionTypes()).toBe(allTypes);
+        world.colliders.disableActiveCollisionTypes(
+            handles,
+            ActiveCollisionTypes.FIXED_FIXED,
+        );
+        expect(collider2.activeCollisionTypes()).toBe(
+            allTypes & ~ActiveCollisionTypes.FIXED_FIXED,
+        );
+    });
+
+    test("resting jitter perturbs resting bodies deterministically", () => {
+        let build = (amplitude: number) => {
+            let built = new World(new Vector3(0, -9.81, 0));
+            built.integrationParameters.restingJitterAmplitude = amplitude;
+            built.integrationParameters.restingJitterSeed = 42;
+            built.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+            let body = built.createRigidBody(
+                RigidBodyDesc.dynamic().setTranslation(0, 0.6, 0),
+            );
+            built.createCollider(ColliderDesc.ball(0.5), body);
+            built.step();
+            return {built, angvel: body.angvel()};
+        };
+
+        let still = build(0.0);
+        let jittered1 = build(1.0e-3);
+        let jittered2 = build(1.0e-3);
+        let norm = (v: {x: number; y: number; z: number}) =>
+            Math.sqrt(v.x * v.x + v.y * v.y + v.z * v.z);
+        expect(norm(still.angvel)).toBeLessThan(1.0e-7);
+        expect(norm(jittered1.angvel)).toBeGreaterThan(1.0e-6);
+        expect(jittered1.angvel).toEqual(jittered2.angvel);
+        expect(
+            jittered1.built.integrationParameters.restingJitterSeed,
+        ).toBe(42);
+        [still, jittered1, jittered2].forEach(({built}) => built.free());
+    });
+
+    test("resting jitter lets resting bodies fall asleep", () => {
+        world.integrationParameters.restingJitterAmplitude = 1.0;
+        world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, -0.6, 0),
+        );
+        world.createCollider(ColliderDesc.cuboid(0.5, 0.5, 0.5), body);
+
+        for (let i = 0; i < 300 && !body.isSleeping(); ++i) {
+            world.step();
+            let angvel = body.angvel();
+            let norm = Math.sqrt(
+                angvel.x * angvel.x + angvel.y * angvel.y + angvel.z * angvel.z,
+            );
+            expect(norm).toBeLessThan(0.5);
+        }
+        expect(body.isSleeping()).toBe(true);
+    });
+
+    test("warmstarting can be tuned globally and reset per pair", () => {
+        expect(world.integrationParameters.warmstartCoefficient).toBeCloseTo(
+            1.0,
+        );
+        world.integrationParameters.warmstartCoefficient = 0.5;
+        expect(world.integrationParameters.warmstartCoefficient).toBeCloseTo(
+            0.5,
+        );
+
+        world.gravity = new Vector3(0, -9.81, 0);
+        world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 0.59, 0),
+        );
+        world.createCollider(
+            ColliderDesc.cuboid(0.5, 0.5, 0.5).setActiveHooks(
+                ActiveHooks.MODIFY_SOLVER_CONTACTS,
+            ),
+            body,
+        );
+
+        let numResets = 0;
+        let hooks: PhysicsHooks = {
+            filterContactPair: () => SolverFlags.COMPUTE_IMPULSE,
+            filterIntersectionPair: () => true,
+            modifySolverContacts: (raw) => {
+                new ContactModificationContext(raw).resetWarmstartImpulses();
+                numResets += 1;
+            },
+        };
+        for (let i = 0; i < 30; ++i) {
+            world.step(undefined, hooks);
+        }
+
+        expect(numResets).toBeGreaterThan(20);
+        expect(body.translation().y).toBeCloseTo(0.6, 1);
+    });
+
+    test("heightfield heights are replaced through their setter", () => {
+        let heights = new Float32Array(4 * 4);
+        let shared = new SharedShape(
+            new Heightfield(3, 3, heights, new Vector3(6, 1, 6)),
+        );
+        let ground = world.createCollider(ColliderDesc.shared(shared));
+        let other = world.createCollider(ColliderDesc.shared(shared));
+        expect(ground.heightfieldHeightsView().length).toBe(16);
+        expect(ground.trimeshVerticesView()).toBeUndefined();
+
+        expect(ground.setHeightfieldHeights(new Float32Array(16).fill(2))).toBe(
+            true,
+        );
+        expect(ground.heightfieldHeightsView()[5]).toBe(2);
+        expect(ground.aabb().maxs.y).toBeCloseTo(2.0);
+        expect(other.heightfieldHeights()[5]).toBe(0);
+        expect(other.aabb().maxs.y).toBeCloseTo(0.0);
+
+        expect(ground.setHeightfieldHeights(new Float32Array(4))).toBe(false);
+        expect(
+            world
+                .createCollider(ColliderDesc.ball(0.5))
+                .setHeightfieldHeights(heights),
+        ).toBe(false);
+    });
+
+    test("computeContactNow doesn't need a simulation step", () => {
+        let ball1 = world.createCollider(ColliderDesc.ball(0.5));
+        let ball2 = world.createCollider(
+            ColliderDesc.ball(0.5).setTranslation(0.9, 0, 0),
+        );
+
+        let distances: number[] = [];
+        let found = world.computeContactNow(ball1, ball2, 0.0, (manifold) => {
+            for (let i = 0; i < manifold.numContacts(); ++i) {
+                distances.push(manifold.contactDist(i));
+            }
+        });
+        expect(found).toBe(true);
+        expect(distances.length).toBe(1);
+        expect(distances[0]).toBeCloseTo(-0.1);
+
+        distances = [];
+        world.computeContactNow(
+            ball1,
+            ball2,
+            0.0,
+            (manifold) => {
+                for (let i = 0; i < manifold.numContacts(); ++i) {
+                    distances.push(manifold.contactDist(i));
+                }
+            },
+            new Vector3(-5, 0, 0),
+        );
+        expect(distances.length).toBe(0);
+    });
+
+    test("joint axes are reported in world-space", () => {
+        let s = Math.SQRT1_2;
+        let body1 = world.createRigidBody(
+            RigidBodyDesc.fixed().setRotation(new Quaternion(0, 0, s, s)),
+        );
+        let body2 = world.createRigidBody(
+            RigidBodyDesc.dynamic().setLinvel(0, 3, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), body2);
+        let joint = world.createImpulseJoint(
+            JointData.prismatic(
+                new Vector3(0, 0, 0),
+                new Vector3(0, 0, 0),
+                new Vector3(1, 0, 0),
+            ),
+            body1,
+            body2,
+            true,
+        ) as PrismaticImpulseJoint;
+
+        let axis = joint.axisWorld();
+        expect(axis.x).toBeCloseTo(0);
+        expect(axis.y).toBeCloseTo(1);
+        expect(joint.axisVelocity()).toBeCloseTo(3);
+    });
+
+    test("cone and frustum queries return the colliders inside them", () => {
+        let ahead = world.createCollider(
+            ColliderDesc.ball(0.5).setTranslation(5, 0.5, 0),
+        );
+        let behind = world.createCollider(
+            ColliderDesc.ball(0.5).setTranslation(-5, 0, 0),
+        );
+        let aside = world.createCollider(
+            ColliderDesc.ball(0.5).setTranslation(2, 5, 0),
+        );
+        world.step();
+
+        let inCone: number[] = [];
+        world.intersectionsWithCone(
+            new Vector3(0, 0, 0),
+            new Vector3(1, 0, 0),
+            Math.PI / 6,
+            10,
+            (collider) => {
+                inCone.push(collider.handle);
+                return true;
+            },
+        );
+        expect(inCone).toEqual([ahead.handle]);
+
+        // The half-space x <= 0.
+        let inFrustum: number[] = [];
+        world.intersectionsWithFrustum([-1, 0, 0, 0], (collider) => {
+            inFrustum.push(collider.handle);
+            return true;
+        });
+        expect(inFrustum).toEqual([behind.handle]);
+        expect(inFrustum).not.toContain(aside.handle);
+    });
+
+    test("impulse joints can be disabled in bulk", () => {
+        let anchor = world.createRigidBody(RigidBodyDesc.fixed());
+        let joints = [0, 1, 2].map((i) => {
+            let body = world.createRigidBody(
+                RigidBodyDesc.dynamic().setTranslation(i * 2, 0, 0),
+            );
+            return world.createImpulseJoint(
+                JointData.spherical(
+                    new Vector3(i * 2, 1, 0),
+                    new Vector3(0, 1, 0),
+                ),
+                anchor,
+                body,
+                true,
+            );
+        });
+        expect(joints[0].isEnabled()).toBe(true);
+
+        joints[0].setEnabled(false);
+        expect(joints[0].isEnabled()).toBe(false);
+
+        world.impulseJoints.setEnabled(
+            joints.map((joint) => joint.handle),
+            false,
+        );
+        expect(joints.every((joint) => !joint.isEnabled())).toBe(true);
+
+        world.impulseJoints.setEnabledForRigidBody(anchor.handle, true);
+        expect(joints.every((joint) => joint.isEnabled())).toBe(true);
+    });
+
+    test("character controller collisions can be read packed", () => {
+        let ground = world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let character = world.createCollider(
+            ColliderDesc.ball(0.5).setTranslation(0, 1, 0),
+        );
+        world.step();
+
+        let controller = world.createCharacterController(0.01);
+        controller.computeColliderMovement(character, new Vector3(0, -1, 0));
+        let numCollisions = controller.numComputedCollisions();
+        let packed = controller.computedCollisionsPacked();
+        expect(numCollisions).toBeGreaterThan(0);
+        expect(packed.length).toBe(numCollisions * 14);
+        expect(packed[0]).toBe(ground.handle);
+        // The normal on the ground.
+        expect(packed[6]).toBeCloseTo(1);
+
+        world.removeCharacterController(controller);
+    });
+
+    test("plane and line constraints restrict the body motion", () => {
+        let planar = world.createRigidBody(
+            RigidBodyDesc.dynamic().setLinvel(1, 0, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), planar);
+        expect(
+            world.createPlaneConstraint(
+                planar,
+                new Vector3(0, 0, 0),
+                new Vector3(0, 1, 0),
+            ),
+        ).not.toBeNull();
+
+        let linear = world.createRigidBody(
+            RigidBodyDesc.dynamic()
+                .setTranslation(0, 0, 5)
+                .setLinvel(1, 0, 1),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), linear);
+        world.createLineConstraint(
+            linear,
+            new Vector3(0, 0, 5),
+            new Vector3(1, 0, 0),
+        );
+
+        for (let i = 0; i < 60; ++i) {
+            world.step();
+        }
+
+        expect(planar.translation().y).toBeCloseTo(0, 2);
+        expect(planar.translation().x).toBeGreaterThan(0.5);
+        expect(linear.translation().y).toBeCloseTo(0, 2);
+        expect(linear.translation().z).toBeCloseTo(5, 2);
+        expect(linear.translation().x).toBeGreaterThan(0.5);
+    });
+
+    test("snapshots can be compressed", () => {
+        for (let i = 0; i < 50; ++i) {
+            let body = world.createRigidBody(
+                RigidBodyDesc.dynamic().setTranslation(i, 0, 0),
+            );
+            world.createCollider(ColliderDesc.ball(0.5), body);
+        }
+        world.step();
+
+        expect(SerializationPipeline.compressionSupported()).toBe(true);
+        let raw = world.takeSnapshot();
+        let compressed = world.takeSnapshot(false, true);
+        expect(compressed.length).toBeLessThan(raw.length);
+
+        let restored = World.restoreSnapshot(compressed, undefined, true);
+        expect(restored.bodies.len()).toBe(50);
+        world.bodies.forEach((body) =>
+            expect(restored.getRigidBody(body.handle).translation()).toEqual(
+                body.translation(),
+            ),
+        );
+        restored.free();
+    });
+
+    test("frozen simulation tiers stop the bodies", () => {
+        let frozen = world.createRigidBody(RigidBodyDesc.dynamic());
+        world.createCollider(ColliderDesc.ball(0.5), frozen);
+        let reduced = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(5, 0, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), reduced);
+        let resting = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, -1.5, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), resting);
+
+        world.setSimulationTier(frozen, SimulationTier.Frozen);
+        world.setSimulationTier(reduced, SimulationTier.Reduced);
+        expect(world.simulationTier(frozen)).toBe(SimulationTier.Frozen);
+        let lod = world.simulationLod;
+        expect(lod.numBodiesInTier(SimulationTier.Reduced)).toBe(1);
+        for (let i = 0; i < 60; ++i) {
+            world.step();
+        }
+        expect(frozen.translation().y).toBe(0);
+        expect(reduced.translation().y).toBeGreaterThan(0);
+        // The other bodies still collide with the frozen body.
+        expect(resting.translation().y).toBeLessThan(-0.9);
+
+        world.setSimulationTier(frozen, SimulationTier.Full);
+        expect(world.simulationTier(frozen)).toBe(SimulationTier.Full);
+        world.step();
+        expect(frozen.translation().y).toBeGreaterThan(0);
+    });
+
+    test("ray-cast batches can be resumed", () => {
+        let ground = world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        world.step();
+
+        let rays = [-3, -1, 1, 10].map(
+            (x) => new Ray(new Vector3(x, 2, 0), new Vector3(0, -1, 0)),
+        );
+        let batch = new RayCastBatch(rays, 10, true);
+        expect(batch.len()).toBe(4);
+        expect(world.resumeRayCastBatch(batch, 2)).toBe(false);
+        expect(batch.cursor()).toBe(2);
+        expect(batch.isComplete()).toBe(false);
+        expect(world.resumeRayCastBatch(batch)).toBe(true);
+        expect(batch.isComplete()).toBe(true);
+
+        let hit = batch.hit(world.colliders, 1);
+        expect(hit.collider.handle).toBe(ground.handle);
+        expect(hit.timeOfImpact).toBeCloseTo(1.9);
+        expect(batch.hit(world.colliders, 3)).toBeNull();
+
+        batch.reset();
+        expect(batch.cursor()).toBe(0);
+        batch.free();
+    });
+
+    test("the world counts its steps and the creation steps", () => {
+        let early = world.createRigidBody(RigidBodyDesc.dynamic());
+        expect(world.stepCount).toBe(0);
+        world.step();
+        world.step();
+        expect(world.stepCount).toBe(2);
+
+        let late = world.createRigidBody(RigidBodyDesc.dynamic());
+        let collider = world.createCollider(ColliderDesc.ball(0.5), late);
+        expect(early.creationStep()).toBe(0);
+        expect(late.creationStep()).toBe(2);
+        expect(collider.creationStep()).toBe(2);
+    });
+
+    test("filterContactPairs evaluates the contact pairs in batches", () => {
+        world.gravity = new Vector3(0, -9.81, 0);
+        world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 0.3, 0),
+        );
+        world.createCollider(
+            ColliderDesc.ball(0.25).setActiveHooks(
+                ActiveHooks.FILTER_CONTACT_PAIRS,
+            ),
+            body,
+        );
+
+        let numPairs = 0;
+        let numSingleCalls = 0;
+        let hooks = {
+            filterContactPair: (): SolverFlags | null => {
+                numSingleCalls += 1;
+                return SolverFlags.COMPUTE_IMPULSE;
+            },
+            filterIntersectionPair: () => true,
+            filterContactPairs: (colliders1: Float64Array) => {
+                numPairs += colliders1.length;
+                return colliders1.map(() => -1);
+            },
+        };
+
+        for (let i = 0; i < 10; ++i) {
+            world.step(undefined, hooks);
+        }
+
+        expect(numPairs).toBeGreaterThan(1);
+        expect(numSingleCalls).toBe(0);
+        expect(body.translation().y).toBeLessThan(0.25);
+
+        hooks.filterContactPairs = () => new Float64Array(0);
+        expect(() => world.step(undefined, hooks)).toThrow();
+    });
+
+    test("contact manifolds expose relative velocities and tangents", () => {
+        world.gravity = new Vector3(0, 0, 0);
+        let ground = world.createCollider(ColliderDesc.cuboid(5, 0.5, 5));
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic()
+                .setTranslation(0, 0.99, 0)
+                .setLinvel(3, 0, 0),
+        );
+        let box = world.createCollider(
+            ColliderDesc.cuboid(0.5, 0.5, 0.5).setFriction(0),
+            body,
+        );
+        world.step();
+
+        let numChecked = 0;
+        world.contactPair(ground, box, (manifold) => {
+            let n = manifold.normal();
+            let t1 = manifold.tangent1();
+            let t2 = manifold.tangent2();
+            expect(t1.x * n.x + t1.y * n.y + t1.z * n.z).toBeCloseTo(0);
+            expect(t2.x * n.x + t2.y * n.y + t2.z * n.z).toBeCloseTo(0);
+            expect(t1.x * t1.x + t1.y * t1.y + t1.z * t1.z).toBeCloseTo(1);
+
+            for (let i = 0; i < manifold.numSolverContacts(); ++i) {
+                let vel = manifold.relativeVelocityAt(i, world.bodies);
+                expect(Math.abs(vel.x)).toBeCloseTo(3, 1);
+                numChecked += 1;
+            }
+            expect(
+                manifold.relativeVelocityAt(
+                    manifold.numSolverContacts(),
+                    world.bodies,
+                ),
+            ).toBeNull();
+        });
+        expect(numChecked).toBeGreaterThan(0);
+    });
+
+    test("one-way platforms only block the contacts along their normal", () => {
+        world.gravity = new Vector3(0, -9.81, 0);
+        let platform = world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        platform.setOnewayPlatform(new Vector3(0, 1, 0), Math.PI / 4);
+
+        let falling = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(-2, 1, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.25), falling);
+        let rising = world.createRigidBody(
+            RigidBodyDesc.dynamic()
+                .setTranslation(2, -1, 0)
+                .setLinvel(0, 10, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.25), rising);
+
+        for (let i = 0; i < 60; ++i) {
+            world.step();
+        }
+
+        expect(falling.translation().y).toBeGreaterThan(0.3);
+        expect(rising.translation().y).toBeGreaterThan(1.0);
+    });
+
+    test("sweepShapeBetweenPoses finds the first collider on the way", () => {
+        let wall = world.createCollider(
+            ColliderDesc.cuboid(0.1, 5, 5).setTranslation(5, 0, 0),
+        );
+        world.step();
+
+        let identity = new Quaternion(0, 0, 0, 1);
+        let shape = new Ball(0.5);
+        let hit = world.sweepShapeBetweenPoses(
+            shape,
+            new Vector3(0, 0, 0),
+            identity,
+            new Vector3(10, 0, 0),
+            identity,
+            true,
+        );
+        expect(hit.collider.handle).toBe(wall.handle);
+        expect(hit.time_of_impact).toBeCloseTo(0.44, 2);
+
+        let miss = world.sweepShapeBetweenPoses(
+            shape,
+            new Vector3(0, 0, 0),
+            identity,
+            new Vector3(4, 0, 0),
+            identity,
+            true,
+        );
+        expect(miss).toBeNull();
+    });
+
+    test("solver iterations configured for the last step are reported", () => {
+        world.numSolverIterations = 4;
+        world.numInternalStabilizationIterations = 3;
+        expect(world.numInternalStabilizationIterations).toBe(3);
+
+        world.createRigidBody(RigidBodyDesc.dynamic());
+        world.createRigidBody(
+            RigidBodyDesc.dynamic().setAdditionalSolverIterations(2),
+        );
+        world.step();
+
+        expect(world.solverMaxConfiguredIterations()).toBe(6);
+        expect(world.solverNumBodiesWithAdditionalIterations()).toBe(1);
+    });
+
+    test("contact response scaling damps the restitution of a body", () => {
+        world.gravity = new Vector3(0, -9.81, 0);
+        world.createCollider(
+            ColliderDesc.cuboid(10, 0.1, 10).setRestitution(1.0),
+        );
+        let balls = [-2, 2].map((x) => {
+            let body = world.createRigidBody(
+                RigidBodyDesc.dynamic().setTranslation(x, 2, 0),
+            );
+            world.createCollider(
+                ColliderDesc.ball(0.5).setRestitution(1.0),
+                body,
+            );
+            return body;
+        });
+        let [bouncing, damped] = balls;
+        damped.setContactResponse(1.0, 0.0);
+        expect(damped.contactStiffnessScale()).toBeCloseTo(1.0);
+        expect(damped.contactRestitutionScale()).toBeCloseTo(0.0);
+        expect(bouncing.contactRestitutionScale()).toBeCloseTo(1.0);
+
+        for (let i = 0; i < 80; ++i) {
+            world.step();
+        }
+
+        expect(bouncing.translation().y).toBeGreaterThan(1.0);
+        expect(damped.translation().y).toBeLessThan(0.7);
+    });
+
+    test("colliders can be re-parented onto multibody links", () => {
+        let root = world.createRigidBody(RigidBodyDesc.fixed());
+        let link = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, -2, 0),
+        );
+        world.createMultibodyJoint(
+            JointData.spherical(new Vector3(0, -1, 0), new Vector3(0, 1, 0)),
+            root,
+            link,
+            true,
+        );
+        let collider = world.createCollider(ColliderDesc.ball(0.5), root);
+
+        let joints = world.multibodyJoints;
+        expect(joints.isMultibodyLink(link.handle)).toBe(true);
+        expect(joints.linkIndex(link.handle)).toBe(1);
+        expect(joints.numLinks(link.handle)).toBe(2);
+        expect(joints.linkRootBody(link.handle)).toBe(root.handle);
+
+        world.setColliderParent(collider, link);
+        expect(collider.parent().handle).toBe(link.handle);
+        world.step();
+        expect(world.getCollider(collider.handle).parent().handle).toBe(
+            link.handle,
+        );
+
+        world.setColliderParent(collider, null);
+        expect(collider.parent()).toBeNull();
+    });
+
+    test("collision events report their step and can be coalesced", () => {
+        let run = (coalesce: boolean) => {
+            let w = new World(new Vector3(0, 0, 0));
+            w.createCollider(
+                ColliderDesc.ball(0.1)
+                    .setSensor(true)
+                    .setActiveEvents(ActiveEvents.COLLISION_EVENTS),
+            );
+            let body = w.createRigidBody(
+                RigidBodyDesc.dynamic()
+                    .setTranslation(-1, 0, 0)
+                    .setLinvel(10, 0, 0),
+            );
+            w.createCollider(ColliderDesc.ball(0.1), body);
+
+            let queue = new EventQueue(false);
+            queue.setCoalesceCollisionEvents(coalesce);
+            expect(queue.coalesceCollisionEvents()).toBe(coalesce);
+            for (let i = 0; i < 20; ++i) {
+                w.step(queue);
+            }
+
+            let events: [boolean, number][] = [];
+            queue.drainCollisionEvents((h1, h2, started, stepIndex) => {
+                events.push([started, stepIndex]);
+            });
+            queue.free();
+            w.free();
+            return events;
+        };
+
+        let events = run(false);
+        expect(events.length).toBe(2);
+        expect(events[0][0]).toBe(true);
+        expect(events[1][0]).toBe(false);
+        expect(events[0][1]).toBeLessThan(events[1][1]);
+        expect(events[1][1]).toBeLessThan(20);
+
+        expect(run(true).length).toBe(0);
+    });
+
+    test("collision events report their CCD substep", () => {
+        let run = (coalesce: boolean) => {
+            let w = new World(new Vector3(0, 0, 0));
+            w.maxCcdSubsteps = 4;
+            w.createCollider(
+                ColliderDesc.cuboid(0.05, 5, 5)
+                    .setRestitution(1.0)
+                    .setActiveEvents(ActiveEvents.COLLISION_EVENTS),
+            );
+            let body = w.createRigidBody(
+                RigidBodyDesc.dynamic()
+                    .setTranslation(-1, 0, 0)
+                    .setLinvel(100, 0, 0)
+                    .setCcdEnabled(true),
+            );
+            w.createCollider(ColliderDesc.ball(0.1).setRestitution(1.0), body);
+
+            // The queue is drained before each step.
+            let queue = new EventQueue(true);
+            queue.setCoalesceCollisionEvents(coalesce);
+            let events: [boolean, number][] = [];
+            for (let i = 0; i < 4; ++i) {
+                w.step(queue);
+                queue.drainCollisionEvents(
+                    (h1, h2, started, stepIndex, seq, substepIndex) => {
+                        events.push([started, substepIndex]);
+                    },
+                );
+            }
+            queue.free();
+            w.free();
+            return events;
+        };
+
+        // The ball touches the wall at the beginning of a step, and bounces off
+        // it during the first substep of the same step.
+        expect(run(false)).toEqual([
+            [true, 0],
+            [false, 1],
+        ]);
+        expect(run(true)).toEqual([]);
+    });
+
+    test("tunneling protection splits character movements", () => {
+        let character = world.createCollider(ColliderDesc.ball(0.5));
+        world.createCollider(
+            ColliderDesc.cuboid(0.05, 5, 5).setTranslation(3, 0, 0),
+        );
+        world.step();
+
+        let controller = world.createCharacterController(0.01);
+        expect(controller.tunnelingProtectionEnabled()).toBe(false);
+        expect(controller.tunnelingProtectionMaxSubsteps()).toBeUndefined();
+        controller.enableTunnelingProtection(1.0, 20);
+        expect(controller.tunnelingProtectionEnabled()).toBe(true);
+        expect(controller.tunnelingProtectionMaxSubstepLength()).toBe(1.0);
+        expect(controller.tunnelingProtectionMaxSubsteps()).toBe(20);
+
+        controller.computeColliderMovement(character, new Vector3(10, 0, 0));
+        expect(controller.computedMovement().x).toBeGreaterThan(2.0);
+        expect(controller.computedMovement().x).toBeLessThan(2.45);
+
+        controller.disableTunnelingProtection();
+        expect(controller.tunnelingProtectionEnabled()).toBe(false);
+        world.removeCharacterController(controller);
+    });
+
+    test("the event queue reports bodies falling asleep or waking up", () => {
+        world.gravity = new Vector3(0, 0, 0);
+        let body = world.createRigidBody(RigidBodyDesc.dynamic());
+        world.createCollider(ColliderDesc.ball(0.5), body);
+        let queue = new EventQueue(false);
+
+        for (let i = 0; i < 300 && !body.isSleeping(); ++i) {
+            world.step(queue);
+        }
+        expect(body.isSleeping()).toBe(true);
+        expect(queue.numBodiesFellAsleep()).toBeGreaterThan(0);
+
+        let events: [number, boolean][] = [];
+        queue.drainSleepEvents((handle, sleeping) => {
+            events.push([handle, sleeping]);
+        });
+        expect(events[events.length - 1]).toEqual([body.handle, true]);
+        expect(queue.numBodiesFellAsleep()).toBe(0);
+
+        body.wakeUp();
+        world.step(queue);
+        expect(queue.numBodiesWokenUp()).toBe(1);
+        queue.free();
+    });
+
+    test("replaceShape swaps the shape of a resting collider", () => {
+        world.gravity = new Vector3(0, -9.81, 0);
+        world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 0.6, 0),
+        );
+        let box = world.createCollider(
+            ColliderDesc.cuboid(0.5, 0.5, 0.5),
+            body,
+        );
+        for (let i = 0; i < 20; ++i) {
+            world.step();
+        }
+
+        box.replaceShape(new Cuboid(0.6, 0.6, 0.6), true);
+        expect(box.halfExtents().x).toBeCloseTo(0.6);
+        for (let i = 0; i < 60; ++i) {
+            world.step();
+        }
+        expect(body.translation().y).toBeCloseTo(0.7, 1);
+
+        box.replaceShape(new Ball(0.6), false);
+        expect(box.shape.type).toBe(ShapeType.Ball);
+        for (let i = 0; i < 60; ++i) {
+            world.step();
+        }
+        expect(body.translation().y).toBeCloseTo(0.7, 1);
+    });
+
+    test("contactsBetweenBodies enumerates the manifolds of two bodies", () => {
+        let ground = world.createRigidBody(RigidBodyDesc.fixed());
+        world.createCollider(
+            ColliderDesc.cuboid(1, 0.5, 1).setTranslation(-1, 0, 0),
+            ground,
+        );
+        world.createCollider(
+            ColliderDesc.cuboid(1, 0.5, 1).setTranslation(1, 0, 0),
+            ground,
+        );
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 0.99, 0),
+        );
+        world.createCollider(ColliderDesc.cuboid(1.5, 0.5, 0.5), body);
+        world.step();
+
+        let numManifolds = 0;
+        world.contactsBetweenBodies(
+            ground,
+            body,
+            (manifold, flipped, collider1, collider2) => {
+                expect(collider1.parent().handle).toBe(ground.handle);
+                expect(collider2.parent().handle).toBe(body.handle);
+                expect(manifold.numContacts()).toBeGreaterThan(0);
+                numManifolds += 1;
+            },
+        );
+        expect(numManifolds).toBe(2);
+    });
+
+    test("glTF physics exports round-trip the bodies and colliders", () => {
+        world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(1, 2, 3),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), body);
+
+        let data = exportGltfPhysics(world);
+        let view = new DataView(data.buffer, data.byteOffset);
+        expect(view.getUint32(0, true)).toBe(0x46546c67);
+
+        let imported = new World(new Vector3(0, -9.81, 0));
+        importGltfPhysics(data, imported);
+        let shapeTypes: ShapeType[] = [];
+        let dynamicTranslations: Vector[] = [];
+        imported.forEachCollider((collider) => {
+            shapeTypes.push(collider.shapeType());
+            let parent = collider.parent();
+            if (!!parent && parent.isDynamic()) {
+                dynamicTranslations.push(parent.translation());
+                expect(collider.radius()).toBeCloseTo(0.5);
+            }
+        });
+        expect(shapeTypes.sort()).toEqual(
+            [ShapeType.Ball, ShapeType.Cuboid].sort(),
+        );
+        expect(dynamicTranslations.length).toBe(1);
+        expect(dynamicTranslations[0].y).toBeCloseTo(2);
+        expect(dynamicTranslations[0].z).toBeCloseTo(3);
+        imported.free();
+    });
+
+    test("glTF physics exports round-trip the joints", () => {
+        let body1 = world.createRigidBody(RigidBodyDesc.fixed());
+        let body2 = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 1, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), body2);
+        let joint = world.createImpulseJoint(
+            JointData.revolute(
+                new Vector3(0, 1, 0),
+                new Vector3(0, 0, 0),
+                new Vector3(0, 0, 1),
+            ),
+            body1,
+            body2,
+            true,
+        ) as RevoluteImpulseJoint;
+        joint.setLimits(-0.5, 0.25);
+
+        let imported = new World(new Vector3(0, -9.81, 0));
+        importGltfPhysics(exportGltfPhysics(world), imported);
+        expect(imported.impulseJoints.len()).toBe(1);
+        imported.impulseJoints.forEach((importedJoint) => {
+            expect(importedJoint).toBeInstanceOf(RevoluteImpulseJoint);
+            let revolute = importedJoint as RevoluteImpulseJoint;
+            expect(revolute.limitsEnabled()).toBe(true);
+            expect(revolute.limitsMin()).toBeCloseTo(-0.5);
+            expect(revolute.limitsMax()).toBeCloseTo(0.25);
+            expect(revolute.body2().isDynamic()).toBe(true);
+        });
+        imported.free();
+    });
+
+    test("glTF physics imports reject GLB files without JSON", () => {
+        // A GLB header followed by a single binary chunk.
+        let data = new Uint8Array(24);
+        let view = new DataView(data.buffer);
+        view.setUint32(0, 0x46546c67, true);
+        view.setUint32(4, 2, true);
+        view.setUint32(8, data.byteLength, true);
+        view.setUint32(12, 4, true);
+        view.setUint32(16, 0x004e4942, true);
+
+        let imported = new World(new Vector3(0, -9.81, 0));
+        expect(() => importGltfPhysics(data, imported)).toThrow("JSON chunk");
+        imported.free();
+    });
+
+    test("dropPointsToGround finds the ground below each point", () => {
+        let ground = world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        world.step();
+
+        let points = new Float32Array([0, 5, 0, 20, 5, 0, 1, 0.05, 1]);
+        let results = world.dropPointsToGround(points, 10);
+        expect(results.length).toBe(15);
+
+        expect(results[0]).toBe(ground.handle);
+        expect(results[1]).toBeCloseTo(0.1);
+        expect(results[3]).toBeCloseTo(1);
+
+        expect(results[5]).toBeNaN();
+        expect(results[6]).toBeNaN();
+        expect(results[8]).toBe(0);
+
+        // Points inside the ground are reported to be on the ground.
+        expect(results[10]).toBe(ground.handle);
+        expect(results[11]).toBeCloseTo(0.05);
+    });
+
+    test("joints expose their coupled axes and per-axis limits", () => {
+        let body1 = world.createRigidBody(RigidBodyDesc.fixed());
+        let body2 = world.createRigidBody(RigidBodyDesc.dynamic());
+        let joint = world.createImpulseJoint(
+            JointData.spherical(new Vector3(0, 0, 0), new Vector3(0, 1, 0)),
+            body1,
+            body2,
+            true,
+        );
+        expect(joint.coupledAxes()).toBe(0);
+        joint.setCoupledAxes(JointAxesMask.AngY | JointAxesMask.AngZ);
+        expect(joint.coupledAxes()).toBe(
+            JointAxesMask.AngY | JointAxesMask.AngZ,
+        );
+
+        expect(joint.axisLimitsEnabled(JointAxis.AngY)).toBe(false);
+        joint.setAxisLimits(JointAxis.AngY, -0.5, 0.5);
+        expect(joint.axisLimitsEnabled(JointAxis.AngY)).toBe(true);
+        expect(joint.axisLimitsMin(JointAxis.AngY)).toBeCloseTo(-0.5);
+        expect(joint.axisLimitsMax(JointAxis.AngY)).toBeCloseTo(0.5);
+        expect(joint.axisLimitsEnabled(JointAxis.AngX)).toBe(false);
+    });
+
+    test("the sets of a world can be moved to another world", () => {
+        let body = world.createRigidBody(RigidBodyDesc.dynamic());
+        world.createCollider(ColliderDesc.ball(0.5), body);
+        world.step();
+
+        let sets = world.detachSets();
+        expect(world.bodies.len()).toBe(0);
+        expect(sets.bodies.len()).toBe(1);
+
+        let other = World.fromSets(new Vector3(0, -9.81, 0), sets);
+        expect(other.colliders.len()).toBe(1);
+        other.step();
+        expect(other.getRigidBody(body.handle).linvel().y).toBeLessThan(0);
+        other.free();
+    });
+
+    test("watched colliders accumulate their contact impulses", () => {
+        world.gravity = new Vector3(0, -9.81, 0);
+        world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 0.6, 0),
+        );
+        let box = world.createCollider(
+            ColliderDesc.cuboid(0.5, 0.5, 0.5),
+            body,
+        );
+        expect(box.isImpulseAccumulationEnabled()).toBe(false);
+        box.setImpulseAccumulationEnabled(true);
+        expect(box.isImpulseAccumulationEnabled()).toBe(true);
+
+        // Supporting the weight of the box during one second.
+        for (let i = 0; i < 60; ++i) {
+            world.step();
+        }
+        expect(box.accumulatedNormalImpulse()).toBeGreaterThan(8.0);
+        expect(box.accumulatedNormalImpulse()).toBeLessThan(12.0);
+        expect(Math.abs(box.accumulatedImpulse().y)).toBeGreaterThan(8.0);
+        expect(box.accumulatedFrictionImpulse()).toBeCloseTo(0);
+
+        box.resetAccumulatedImpulse();
+        expect(box.accumulatedNormalImpulse()).toBe(0);
+    });
+
+    test("depenetrate moves a rigid-body out of the ground", () => {
+        world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        world.step();
+
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 0.3, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), body);
+        let shift = world.depenetrate(body);
+        expect(shift.x).toBeCloseTo(0);
+        expect(shift.y).toBeCloseTo(0.3, 1);
+        expect(body.translation().y).toBeCloseTo(0.6, 1);
+        expect(world.depenetrate(body).y).toBeCloseTo(0);
+    });
+
+    test("queued events are given increasing sequence numbers", () => {
+        world.gravity = new Vector3(0, -9.81, 0);
+        world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 1, 0),
+        );
+        world.createCollider(
+            ColliderDesc.ball(0.5)
+                .setActiveEvents(
+                    ActiveEvents.COLLISION_EVENTS |
+                        ActiveEvents.CONTACT_FORCE_EVENTS,
+                )
+                .setContactForceEventThreshold(0),
+            body,
+        );
+
+        let queue = new EventQueue(false);
+        for (let i = 0; i < 60; ++i) {
+            world.step(queue);
+        }
+
+        let collisionSeqs: number[] = [];
+        let forceSeqs: number[] = [];
+        queue.drainCollisionEvents((h1, h2, started, stepIndex, seq) => {
+            collisionSeqs.push(seq);
+        });
+        queue.drainContactForceEvents((event) => {
+            forceSeqs.push(event.sequenceNumber());
+            expect(event.stepIndex()).toBeLessThan(60);
+        });
+        queue.free();
+
+        expect(collisionSeqs.length).toBe(1);
+        expect(forceSeqs.length).toBeGreaterThan(1);
+        // The collision started before any contact force was applied.
+        expect(collisionSeqs[0]).toBeLessThan(forceSeqs[0]);
+        for (let i = 1; i < forceSeqs.length; ++i) {
+            expect(forceSeqs[i]).toBeGreaterThan(forceSeqs[i - 1]);
+        }
+    });
+
+    test("walkableSurfaces extracts the flat faces of fixed colliders", () => {
+        world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 2, 0),
+        );
+        world.createCollider(ColliderDesc.cuboid(0.5, 0.5, 0.5), body);
+
+        let surfaces = world.walkableSurfaces(0.5);
+        expect(surfaces.indices.length).toBe(6);
+        expect(surfaces.vertices.length).toBe(12);
+        for (let i = 1; i < surfaces.vertices.length; i += 3) {
+            expect(surfaces.vertices[i]).toBeCloseTo(0.1);
+        }
+    });
+
+    test("slow contacts below the restitution threshold don't bounce", () => {
+        expect(world.integrationParameters.restitutionThreshold).toBe(0);
+        world.gravity = new Vector3(0, -9.81, 0);
+        world.createCollider(
+            ColliderDesc.cuboid(10, 0.1, 10).setRestitution(1.0),
+        );
+        let colliders = [-2, 2].map((x) => {
+            let body = world.createRigidBody(
+                RigidBodyDesc.dynamic().setTranslation(x, 1.6, 0),
+            );
+            return world.createCollider(
+                ColliderDesc.ball(0.5).setRestitution(1.0),
+                body,
+            );
+        });
+        let [bouncing, damped] = colliders;
+        damped.setRestitutionThreshold(5.0);
+        expect(damped.restitutionThreshold()).toBeCloseTo(5.0);
+        expect(bouncing.restitutionThreshold()).toBeNull();
+
+        for (let i = 0; i < 60; ++i) {
+            world.step();
+        }
+
+        expect(bouncing.translation().y).toBeGreaterThan(1.0);
+        expect(damped.translation().y).toBeLessThan(0.7);
+
+        damped.removeRestitutionThreshold();
+        expect(damped.restitutionThreshold()).toBeNull();
+    });
+
+    test("rigid-bodies and colliders can be removed in batches", () => {
+        let bodies = [0, 1, 2].map((i) => {
+            let body = world.createRigidBody(
+                RigidBodyDesc.dynamic().setTranslation(i * 2, 0, 0),
+            );
+            world.createCollider(ColliderDesc.ball(0.5), body);
+            return body;
+        });
+        let jointed = world.createRigidBody(RigidBodyDesc.dynamic());
+        world.createImpulseJoint(
+            JointData.spherical(new Vector3(0, 0, 0), new Vector3(0, 1, 0)),
+            bodies[2],
+            jointed,
+            true,
+        );
+        let loose = [0, 1].map((i) =>
+            world.createCollider(
+                ColliderDesc.ball(0.5).setTranslation(0, i, 5),
+            ),
+        );
+        world.step();
+
+        // The body with a joint attached isn't removed without its joints.
+        let removed = world.removeRigidBodies(
+            [bodies[0], bodies[2]],
+            true,
+            false,
+        );
+        expect(removed).toEqual([bodies[0].handle]);
+        expect(world.bodies.len()).toBe(3);
+        expect(world.colliders.len()).toBe(4);
+
+        removed = world.removeRigidBodies([bodies[2]], true, true);
+        expect(removed).toEqual([bodies[2].handle]);
+        expect(world.impulseJoints.len()).toBe(0);
+
+        world.removeColliders(loose, false);
+        expect(world.colliders.len()).toBe(1);
+        world.step();
+    });
+
+    test("advance performs fixed time steps", () => {
+        world.integrationParameters.dt = 0.25;
+        expect(world.advance(0.625)).toBe(2);
+        expect(world.interpolationAlpha()).toBeCloseTo(0.5);
+        expect(world.advance(0.125)).toBe(1);
+        expect(world.interpolationAlpha()).toBeCloseTo(0.0);
+
+        // The time that can't be simulated in `maxSteps` steps is dropped.
+        expect(world.advance(10.125, 3)).toBe(3);
+        expect(world.interpolationAlpha()).toBeCloseTo(0.5);
+        expect(world.stepCount).toBe(6);
+
+        world.resetAccumulatedTime();
+        expect(world.interpolationAlpha()).toBe(0);
+    });
+
+    test("contact contexts can't be used after their callback", () => {
+        world.gravity = new Vector3(0, -9.81, 0);
+        world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 0.59, 0),
+        );
+        world.createCollider(
+            ColliderDesc.cuboid(0.5, 0.5, 0.5).setActiveHooks(
+                ActiveHooks.MODIFY_SOLVER_CONTACTS,
+            ),
+            body,
+        );
+
+        let retained: ContactModificationContext[] = [];
+        let hooks: PhysicsHooks = {
+            filterContactPair: () => SolverFlags.COMPUTE_IMPULSE,
+            filterIntersectionPair: () => true,
+            modifySolverContacts: (raw) => {
+                let context = new ContactModificationContext(raw);
+                expect(context.numSolverContacts).toBeGreaterThan(0);
+                retained.push(context);
+            },
+        };
+        world.step(undefined, hooks);
+
+        expect(retained.length).toBe(1);
+        expect(() => retained[0].numSolverContacts).toThrow();
+    });
+
+    test("snapshots keep the native contact modifications", () => {
+        let platform = world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        platform.setOnewayPlatform(new Vector3(0, 1, 0), 0.5);
+        platform.setRestitutionThreshold(2.0);
+        let body = world.createRigidBody(RigidBodyDesc.dynamic());
+        world.createCollider(ColliderDesc.ball(0.5), body);
+        body.setContactResponse(0.5, 0.25);
+
+        let restored = World.restoreSnapshot(world.takeSnapshot());
+        let restoredPlatform = restored.getCollider(platform.handle);
+        let restoredBody = restored.getRigidBody(body.handle);
+        expect(restoredPlatform.isOnewayPlatform()).toBe(true);
+        expect(restoredPlatform.restitutionThreshold()).toBeCloseTo(2.0);
+        expect(restoredBody.contactStiffnessScale()).toBeCloseTo(0.5);
+        expect(restoredBody.contactRestitutionScale()).toBeCloseTo(0.25);
+        restored.free();
+    });
+
+    test("tunneling protection clamps large character movements", () => {
+        let character = world.createCollider(ColliderDesc.ball(0.5));
+        world.createCollider(
+            ColliderDesc.cuboid(0.05, 5, 5).setTranslation(5, 0, 0),
+        );
+        world.step();
+
+        let controller = world.createCharacterController(0.01);
+        controller.enableTunnelingProtection(1.0, 4);
+        expect(controller.tunnelingProtectionEnabled()).toBe(true);
+
+        controller.computeColliderMovement(character, new Vector3(-100, 0, 0));
+        expect(controller.computedMovement().x).toBeCloseTo(-4.0, 2);
+
+        controller.enableTunnelingProtection(1.0, 100);
+        controller.computeColliderMovement(character, new Vector3(100, 0, 0));
+        expect(controller.computedMovement().x).toBeLessThan(4.5);
+
+        world.removeCharacterController(controller);
+    });
+
+    test("compound shapes are read back from their collider", () => {
+        let identity = new Quaternion(0, 0, 0, 1);
+        let collider = world.createCollider(
+            ColliderDesc.compound(
+                [new Ball(0.5), new Cuboid(1, 2, 3)],
+                [new Vector3(1, 0, 0), new Vector3(0, 2, 0)],
+                [identity, identity],
+            ),
+        );
+
+        collider.clearShapeCache();
+        let shape = collider.shape as Compound;
+        expect(shape).toBeInstanceOf(Compound);
+        expect(shape.shapes.length).toBe(2);
+        expect((shape.shapes[0] as Ball).radius).toBeCloseTo(0.5);
+        expect((shape.shapes[1] as Cuboid).halfExtents.z).toBeCloseTo(3);
+        expect(shape.translations[0].x).toBeCloseTo(1);
+        expect(shape.translations[1].y).toBeCloseTo(2);
+
+        expect(() =>
+            world.createCollider(ColliderDesc.compound([], [], [])),
+        ).toThrow();
+    });
+
+    test("only world-space constraints are removed as such", () => {
+        let body = world.createRigidBody(RigidBodyDesc.dynamic());
+        let other = world.createRigidBody(RigidBodyDesc.dynamic());
+        let plane = world.createPlaneConstraint(
+            body,
+            new Vector3(0, 0, 0),
+            new Vector3(0, 1, 0),
+        );
+        let line = world.createLineConstraint(
+            other,
+            new Vector3(0, 0, 0),
+            new Vector3(1, 0, 0),
+        );
+        let joint = world.createImpulseJoint(
+            JointData.spherical(new Vector3(0, 0, 0), new Vector3(0, 1, 0)),
+            body,
+            other,
+            true,
+        );
+        expect(world.impulseJoints.len()).toBe(3);
+
+        expect(() => world.removeWorldConstraint(joint)).toThrow();
+        world.removeWorldConstraint(plane);
+        expect(world.impulseJoints.len()).toBe(2);
+        world.step();
+        world.removeWorldConstraint(line);
+        expect(world.impulseJoints.len()).toBe(1);
+    });
+
+    test("overlapping modifier volumes restore the original values", () => {
+        let sensors = [0, 1].map(() =>
+            world.createCollider(ColliderDesc.cuboid(5, 5, 5).setSensor(true)),
+        );
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setGravityScale(2),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), body);
+
+        let volumes = sensors.map((sensor) => {
+            let volume = world.createModifierVolume(sensor);
+            volume.setGravityScale(0);
+            return volume;
+        });
+        world.step();
+        world.step();
+        expect(body.gravityScale()).toBe(0);
+
+        world.removeModifierVolume(volumes[0]);
+        world.step();
+        expect(body.gravityScale()).toBe(0);
+        world.removeModifierVolume(volumes[1]);
+        expect(body.gravityScale()).toBe(2);
+    });
+
+    test("joint motor velocity ramps are kept in snapshots", () => {
+        let body1 = world.createRigidBody(RigidBodyDesc.fixed());
+        let body2 = world.createRigidBody(RigidBodyDesc.dynamic());
+        world.createCollider(ColliderDesc.ball(0.5), body2);
+        let joint = world.createImpulseJoint(
+            JointData.revolute(
+                new Vector3(0, 0, 0),
+                new Vector3(0, 0, 0),
+                new Vector3(0, 0, 1),
+            ),
+            body1,
+            body2,
+            true,
+        ) as RevoluteImpulseJoint;
+        world.integrationParameters.dt = 0.1;
+        joint.configureMotorVelocity(10, 1, 20);
+        world.step();
+
+        let restored = World.restoreSnapshot(world.takeSnapshot());
+        restored.integrationParameters.dt = 0.1;
+        restored.step();
+        let restoredJoint = restored.getImpulseJoint(
+            joint.handle,
+        ) as RevoluteImpulseJoint;
+        expect(restoredJoint.motorTargetVel()).toBeCloseTo(4);
+        restored.free();
+    });
+
+    test("leaving a simulation tier restores the body", () => {
+        let lod = world.simulationLod;
+        expect(lod.reducedTierSolverIterations()).toBe(1);
+        lod.setReducedTierSolverIterations(2);
+        expect(lod.reducedTierSolverIterations()).toBe(2);
+        expect(lod.reducedTierSleepThresholdScale()).toBe(2);
+
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setLinvel(1, 0, 0),
+        );
+        world.createCollider(ColliderDesc.ball(0.5), body);
+        world.setSimulationTier(body, SimulationTier.Frozen);
+        expect(body.isFixed()).toBe(true);
+        world.step();
+
+        let restored = World.restoreSnapshot(world.takeSnapshot());
+        let restoredBody = restored.getRigidBody(body.handle);
+        expect(restored.simulationTier(restoredBody)).toBe(
+            SimulationTier.Frozen,
+        );
+        expect(restored.simulationLod.reducedTierSolverIterations()).toBe(2);
+        restored.setSimulationTier(restoredBody, SimulationTier.Full);
+        expect(restoredBody.isDynamic()).toBe(true);
+        expect(restoredBody.linvel().x).toBeCloseTo(1);
+        restored.free();
+    });
+
+    test("stopped intersections of removed sensors report the sensor", () => {
+        let sensor = world.createCollider(
+            ColliderDesc.cuboid(1, 1, 1)
+                .setSensor(true)
+                .setActiveEvents(ActiveEvents.COLLISION_EVENTS),
+        );
+        let body = world.createRigidBody(RigidBodyDesc.dynamic());
+        world.createCollider(ColliderDesc.ball(0.5), body);
+        let eventQueue = new EventQueue(true);
+        world.step(eventQueue);
+
+        world.removeCollider(sensor, true);
+        world.step(eventQueue);
+
+        let stopped: [boolean, boolean][] = [];
+        eventQueue.drainCollisionEventsWithColliderInfo(
+            world.colliders,
+            (h1, h2, started, sensor1, sensor2, b1, b2, removed) => {
+                if (!started) stopped.push([sensor1, removed]);
+            },
+        );
+        expect(stopped).toEqual([[true, true]]);
+        eventQueue.free();
+    });
+
+    test("canonicalizeOrdering doesn't depend on the insertion order", () => {
+        let build = (ids: number[]) => {
+            let built = new World(new Vector3(0, -9.81, 0));
+            let keys = new Map<number, number>();
+            for (let id of ids) {
+                let body = built.createRigidBody(
+                    RigidBodyDesc.dynamic().setTranslation(id * 0.3, id, 0),
+                );
+                built.createCollider(ColliderDesc.ball(0.5), body);
+                keys.set(body.handle, id);
+            }
+            built.createCollider(ColliderDesc.cuboid(10, 0.1, 10));
+
+            built.canonicalizeOrdering(
+                (body) => keys.get(body.handle),
+                (collider) =>
+                    !!collider.parent()
+                        ? keys.get(collider.parent().handle)
+                        : -1,
+            );
+            for (let i = 0; i < 30; ++i) {
+                built.step();
+            }
+            return built;
+        };
+
+        let world1 = build([0, 1, 2, 3]);
+        let world2 = build([3, 1, 0, 2]);
+        world1.bodies.forEach((body1) => {
+            let body2 = world2.getRigidBody(body1.handle);
+            expect(body2.translation()).toEqual(body1.translation());
+        });
+        world1.free();
+        world2.free();
+    });
+
+    test("scene queries count the colliders rejected by their groups", () => {
+        let hit = world.createCollider(
+            ColliderDesc.ball(0.5)
+                .setTranslation(2, 0, 0)
+                .setCollisionGroups(0x0001ffff),
+        );
+        world.createCollider(
+            ColliderDesc.ball(0.5)
+                .setTranslation(4, 0, 0)
+                .setCollisionGroups(0x0002ffff),
+        );
+        world.step();
+        world.resetQueryCounters();
+
+        let hits: number[] = [];
+        world.intersectionsWithRay(
+            new Ray(new Vector3(0, 0, 0), new Vector3(1, 0, 0)),
+            10,
+            true,
+            (intersect) => {
+                hits.push(intersect.collider.handle);
+                return true;
+            },
+            undefined,
+            0xffff0001,
+        );
+
+        expect(hits).toEqual([hit.handle]);
+        expect(world.queryNumGroupTests()).toBe(2);
+        expect(world.queryNumGroupRejections()).toBe(1);
+        expect(world.queryNumAcceptedColliders()).toBe(1);
+
+        world.resetQueryCounters();
+        expect(world.queryNumGroupTests()).toBe(0);
+    });
+
+    test("canonicalizeOrdering updates the world-owned handles", () => {
+        let bodies = [2, 1, 0].map((id) =>
+            world.createRigidBody(
+                RigidBodyDesc.dynamic().setTranslation(id * 3, 0, 0),
+            ),
+        );
+        let keys = new Map<number, number>();
+        bodies.forEach((body, i) => keys.set(body.handle, i));
+        let sensor = world.createCollider(
+            ColliderDesc.ball(1).setSensor(true).setTranslation(6, 0, 0),
+        );
+        let ball = world.createCollider(ColliderDesc.ball(0.5), bodies[0]);
+        let volume = world.createModifierVolume(sensor);
+        volume.setGravityScale(0);
+        world.setSimulationTier(bodies[1], SimulationTier.Reduced);
+        world.contactMaterials.setColliderMaterial(ball.handle, 3);
+
+        let mapping = world.canonicalizeOrdering(
+            (body) => keys.get(body.handle),
+            (collider) => (collider.isSensor() ? -1 : 0),
+        );
+        let newSensor = mapping.colliders.get(sensor.handle);
+        let newBall = mapping.colliders.get(ball.handle);
+        let reduced = world.getRigidBody(mapping.bodies.get(bodies[1].handle));
+        expect(volume.collider().handle).toBe(newSensor);
+        expect(world.simulationTier(reduced)).toBe(SimulationTier.Reduced);
+        expect(world.contactMaterials.colliderMaterial(newBall)).toBe(3);
+
+        world.step();
+        world.step();
+        expect(volume.numAffectedBodies()).toBe(1);
+        expect(world.getCollider(newBall).parent().gravityScale()).toBe(0);
+    });
+
+
+    test("snapshots without a format version are still restored", () => {
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(1, 2, 3),
+        );
+        let platform = world.createCollider(ColliderDesc.cuboid(5, 0.1, 5));
+        platform.setOnewayPlatform(new Vector3(0, 1, 0), 0.5);
+
+        // Without its magic bytes and version, the snapshot reads as a
+        // snapshot of a previous version: the trailing native state is
+        // ignored.
+        let legacy = world.takeSnapshot().slice(8);
+        let restored = World.restoreSnapshot(legacy);
+        let restoredBody = restored.getRigidBody(body.handle);
+        expect(restoredBody.translation()).toEqual({x: 1, y: 2, z: 3});
+        let restoredPlatform = restored.getCollider(platform.handle);
+        expect(restoredPlatform.isOnewayPlatform()).toBe(false);
+        restored.free();
+    });
+
+
+    test("the global restitution threshold follows restitution changes", () => {
+        world.gravity = new Vector3(0, -9.81, 0);
+        world.integrationParameters.restitutionThreshold = 5.0;
+        world.createCollider(ColliderDesc.cuboid(10, 0.1, 10));
+        let body = world.createRigidBody(
+            RigidBodyDesc.dynamic().setTranslation(0, 1.6, 0),
+        );
+        let ball = world.createCollider(ColliderDesc.ball(0.5), body);
+        world.step();
+
+        // The threshold is applied natively: no JS hook is enabled.
+        ball.setRestitution(1.0);
+        for (let i = 0; i < 60; ++i) {
+            world.step();
+        }
+        expect(ball.translation().y).toBeLessThan(0.7);
+        let hooks = ActiveHooks.MODIFY_SOLVER_CONTACTS;
+        expect(world.numContactPairsWithActiveHooks(hooks)).toBe(0);
+        expect(ball.activeHooks()).toBe(ActiveHooks.NONE);
+    });
 });
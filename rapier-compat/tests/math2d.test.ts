@@ -1,4 +1,4 @@
-import {Vector2, VectorOps} from "../builds/2d-deterministic/pkg";
+import {RotationOps, Vector2, VectorOps} from "../builds/2d-deterministic/pkg";
 
 describe("2d/math", () => {
     test("Vector2", () => {
@@ -12,4 +12,12 @@ describe("2d/math", () => {
         expect(v.x).toBe(0);
         expect(v.y).toBe(1);
     });
+
+    test("RotationOps", () => {
+        expect(RotationOps.normalizeAngle(3.0 * Math.PI)).toBeCloseTo(Math.PI);
+        expect(RotationOps.normalizeAngle(-Math.PI)).toBeCloseTo(Math.PI);
+        expect(RotationOps.angleDifference(3.0, -3.0)).toBeCloseTo(
+            2.0 * Math.PI - 6.0,
+        );
+    });
 });
@@ -1,4 +1,9 @@
-import {Vector3, VectorOps} from "../builds/3d-deterministic/pkg";
+import {
+    MassProperties,
+    Quaternion,
+    Vector3,
+    VectorOps,
+} from "../builds/3d-deterministic/pkg";
 
 describe("3d/math", () => {
     test("Vector3", () => {
@@ -14,4 +19,19 @@ describe("3d/math", () => {
         expect(v.y).toBe(1);
         expect(v.z).toBe(2);
     });
+
+    test("MassProperties", () => {
+        const ball = MassProperties.ball(2.0, 0.5);
+        expect(ball.mass).toBeCloseTo((2.0 * 4.0 * Math.PI * 0.125) / 3.0);
+        expect(ball.principalInertia.x).toBeCloseTo(0.4 * ball.mass * 0.25);
+
+        const identity = new Quaternion(0, 0, 0, 1);
+        const moved = ball.transformBy(new Vector3(2, 0, 0), identity);
+        expect(moved.centerOfMass.x).toBeCloseTo(2);
+
+        const sum = MassProperties.sum([ball, moved]);
+        expect(sum.mass).toBeCloseTo(2.0 * ball.mass);
+        expect(sum.centerOfMass.x).toBeCloseTo(1);
+        expect(sum.centerOfMass.y).toBeCloseTo(0);
+    });
 });
@@ -294,6 +294,54 @@ export class KinematicCharacterController {
         return this.raw.snapToGroundEnabled();
     }
 
+    /**
+     * If tunneling protection is enabled, the maximum length of each sub-movement.
+     */
+    public tunnelingProtectionMaxSubstepLength(): number | null {
+        return this.raw.tunnelingProtectionMaxSubstepLength();
+    }
+
+    /**
+     * If tunneling protection is enabled, the maximum number of sub-movements performed
+     * by a single call to `this.computeColliderMovement`.
+     */
+    public tunnelingProtectionMaxSubsteps(): number | null {
+        return this.raw.tunnelingProtectionMaxSubsteps();
+    }
+
+    /**
+     * Enables protection against tunneling through thin obstacles when the desired
+     * translation is very large (e.g. after a frame hitch).
+     *
+     * Desired translations longer than `maxSubstepLength` are split into equal sub-movements
+     * of at most that length, each resolved with its own shape-casts. At most
+     * `maxSubsteps` sub-movements are performed, so the desired translation is clamped
+     * to `maxSubstepLength * maxSubsteps`.
+     *
+     * @param maxSubstepLength - The maximum length of each sub-movement.
+     * @param maxSubsteps - The maximum number of sub-movements.
+     */
+    public enableTunnelingProtection(
+        maxSubstepLength: number,
+        maxSubsteps: number,
+    ) {
+        this.raw.enableTunnelingProtection(maxSubstepLength, maxSubsteps);
+    }
+
+    /**
+     * Disables the tunneling protection.
+     */
+    public disableTunnelingProtection() {
+        this.raw.disableTunnelingProtection();
+    }
+
+    /**
+     * Is the tunneling protection enabled?
+     */
+    public tunnelingProtectionEnabled(): boolean {
+        return this.raw.tunnelingProtectionEnabled();
+    }
+
     /**
      * Computes the movement the given collider is able to execute after hitting and sliding on obstacles.
      *
@@ -383,4 +431,23 @@ export class KinematicCharacterController {
             return out;
         }
     }
+
+    /**
+     * All the collisions detected along the path of the last call to
+     * `this.computeColliderMovement`, packed into a single array.
+     *
+     * Each collision is packed as `[handle, toi, witness, normal, applied, remaining]` where:
+     * - `handle` is the handle of the collider hit by the character.
+     * - `toi` is the time-of-impact between the character and the obstacle.
+     * - `witness` and `normal` are the world-space contact point and outward contact normal
+     *   on the collider.
+     * - `applied` and `remaining` are the translation deltas applied to the character before
+     *   this collision, and remaining after this collision.
+     *
+     * Each vector takes 2 values in 2D and 3 values in 3D, so each collision takes 10 values
+     * in 2D and 14 values in 3D.
+     */
+    public computedCollisionsPacked(): Float64Array {
+        return this.raw.computedCollisionsPacked();
+    }
 }
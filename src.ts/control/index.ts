@@ -1,5 +1,6 @@
 export * from "./character_controller";
 export * from "./pid_controller";
+export * from "./modifier_volume";
 
 // #if DIM3
 export * from "./ray_cast_vehicle_controller";
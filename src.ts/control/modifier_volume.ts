@@ -0,0 +1,169 @@
+import {RawModifierVolumeSet} from "../raw";
+import {Vector, VectorOps} from "../math";
+import {Collider, ColliderSet, NarrowPhase} from "../geometry";
+import {RigidBodySet} from "../dynamics";
+
+/**
+ * A volume applying an effect (gravity scale, linear damping, constant force) to all
+ * the dynamic rigid-bodies intersecting it.
+ *
+ * The volume is defined by a sensor collider. Its effect is applied automatically at
+ * each `World.step`, based on the intersections computed during the previous step. The
+ * original gravity scale and linear damping of a rigid-body are restored once it stops
+ * intersecting all the volumes, or when the volumes are removed from the world. When
+ * several volumes intersecting the same rigid-body set its gravity scale or linear
+ * damping, the most recently created one wins.
+ */
+export class ModifierVolume {
+    private rawSet: RawModifierVolumeSet;
+    /** @internal */
+    _collider: Collider;
+    /** @internal */
+    readonly id: number;
+
+    constructor(rawSet: RawModifierVolumeSet, id: number, collider: Collider) {
+        this.rawSet = rawSet;
+        this.id = id;
+        this._collider = collider;
+    }
+
+    /**
+     * The sensor collider defining this volume.
+     */
+    public collider(): Collider {
+        return this._collider;
+    }
+
+    /**
+     * The gravity scale applied to the rigid-bodies intersecting this volume, if any.
+     */
+    public gravityScale(): number | null {
+        return this.rawSet.gravityScale(this.id);
+    }
+
+    /**
+     * Sets the gravity scale applied to the rigid-bodies intersecting this volume.
+     *
+     * @param scale - The gravity scale to apply, or `null` to leave the gravity scale unchanged.
+     */
+    public setGravityScale(scale: number | null) {
+        this.rawSet.setGravityScale(this.id, scale);
+    }
+
+    /**
+     * The linear damping applied to the rigid-bodies intersecting this volume, if any.
+     */
+    public linearDamping(): number | null {
+        return this.rawSet.linearDamping(this.id);
+    }
+
+    /**
+     * Sets the linear damping applied to the rigid-bodies intersecting this volume.
+     *
+     * @param damping - The linear damping to apply, or `null` to leave the linear damping unchanged.
+     */
+    public setLinearDamping(damping: number | null) {
+        this.rawSet.setLinearDamping(this.id, damping);
+    }
+
+    /**
+     * The force applied to the rigid-bodies intersecting this volume.
+     */
+    public force(): Vector {
+        return VectorOps.fromRaw(this.rawSet.force(this.id));
+    }
+
+    /**
+     * Sets the force applied to the rigid-bodies intersecting this volume.
+     *
+     * @param force - The world-space force to apply at the center of mass of each rigid-body.
+     */
+    public setForce(force: Vector) {
+        let rawForce = VectorOps.intoRaw(force);
+        this.rawSet.setForce(this.id, rawForce);
+        rawForce.free();
+    }
+
+    /**
+     * The number of rigid-bodies currently affected by this volume.
+     */
+    public numAffectedBodies(): number {
+        return this.rawSet.numAffectedBodies(this.id);
+    }
+}
+
+/**
+ * The set of modifier volumes of a physics world.
+ */
+export class ModifierVolumeSet {
+    raw: RawModifierVolumeSet;
+    private volumes: Map<number, ModifierVolume>;
+
+    /**
+     * Release the WASM memory occupied by this set.
+     */
+    public free() {
+        if (!!this.raw) {
+            this.raw.free();
+        }
+        this.raw = undefined;
+        this.volumes = undefined;
+    }
+
+    constructor() {
+        this.raw = new RawModifierVolumeSet();
+        this.volumes = new Map();
+    }
+
+    /**
+     * Creates a new modifier volume defined by the given sensor collider.
+     *
+     * @param collider - The sensor collider defining the volume.
+     */
+    public createVolume(collider: Collider): ModifierVolume {
+        const id = this.raw.insert(collider.handle);
+        const volume = new ModifierVolume(this.raw, id, collider);
+        this.volumes.set(id, volume);
+        return volume;
+    }
+
+    /**
+     * Removes a modifier volume, restoring the original gravity scale and linear damping
+     * of the rigid-bodies it no longer affects.
+     *
+     * @param volume - The modifier volume to remove.
+     * @param bodies - The set of rigid-bodies affected by the volume.
+     */
+    public remove(volume: ModifierVolume, bodies: RigidBodySet) {
+        this.raw.remove(volume.id, bodies.raw);
+        this.volumes.delete(volume.id);
+    }
+
+    /**
+     * Applies the given closure to each modifier volume of this set.
+     *
+     * @param f - The closure to apply.
+     */
+    public forEach(f: (volume: ModifierVolume) => void) {
+        this.volumes.forEach(f);
+    }
+
+    /** @internal */
+    public reloadColliders(colliders: ColliderSet) {
+        this.volumes.forEach((volume) => {
+            volume._collider = colliders.get(this.raw.collider(volume.id));
+        });
+    }
+
+    /** @internal */
+    public apply(
+        dt: number,
+        bodies: RigidBodySet,
+        colliders: ColliderSet,
+        narrowPhase: NarrowPhase,
+    ) {
+        if (this.volumes.size > 0) {
+            this.raw.apply(dt, bodies.raw, colliders.raw, narrowPhase.raw);
+        }
+    }
+}
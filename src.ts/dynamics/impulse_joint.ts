@@ -70,6 +70,25 @@ export enum JointAxesMask {
     AngZ = 1 << 5,
 }
 
+/**
+ * An enum representing a single axis of a joint:
+ *
+ * - `LinX`, `LinY`, `LinZ`: the translation axes.
+ * - `AngX`, `AngY`, `AngZ`: the rotation axes.
+ */
+export enum JointAxis {
+    LinX,
+    LinY,
+    // #if DIM3
+    LinZ,
+    // #endif
+    AngX,
+    // #if DIM3
+    AngY,
+    AngZ,
+    // #endif
+}
+
 export class ImpulseJoint {
     protected rawSet: RawImpulseJointSet; // The ImpulseJoint won't need to free this.
     protected bodySet: RigidBodySet; // The ImpulseJoint won’t need to free this.
@@ -225,6 +244,92 @@ export class ImpulseJoint {
     public contactsEnabled(): boolean {
         return this.rawSet.jointContactsEnabled(this.handle);
     }
+
+    /**
+     * Enables or disables this joint.
+     *
+     * A disabled joint keeps its configuration but is ignored by the constraints solver,
+     * e.g., to temporarily release the constraints of a ragdoll blended with an animation.
+     */
+    public setEnabled(enabled: boolean) {
+        this.rawSet.jointSetEnabled(this.handle, enabled);
+    }
+
+    /**
+     * Is this joint enabled?
+     */
+    public isEnabled(): boolean {
+        return this.rawSet.jointIsEnabled(this.handle);
+    }
+
+    /**
+     * The axes which limits and motors are coupled.
+     */
+    public coupledAxes(): JointAxesMask {
+        return this.rawSet.jointCoupledAxes(this.handle);
+    }
+
+    /**
+     * Sets the axes which limits and motors are coupled.
+     *
+     * The limits and motors of coupled angular axes apply to the norm of the rotation
+     * around these axes, and are configured along the first coupled angular axis (see
+     * `setAxisLimits`). For example, coupling `AngY` and `AngZ` of a spherical joint,
+     * then limiting `AngY`, results in a circular cone limit instead of the square-shaped
+     * region obtained by limiting both axes independently.
+     *
+     * @param axes - The coupled axes. You can use logical OR to select these from the
+     *   `JointAxesMask` enum.
+     */
+    public setCoupledAxes(axes: JointAxesMask) {
+        this.rawSet.jointSetCoupledAxes(this.handle, axes);
+    }
+
+    /**
+     * Are the limits enabled along the given axis of this joint?
+     */
+    public axisLimitsEnabled(axis: JointAxis): boolean {
+        return this.rawSet.jointLimitsEnabled(
+            this.handle,
+            axis as number as RawJointAxis,
+        );
+    }
+
+    /**
+     * The min limit along the given axis of this joint.
+     */
+    public axisLimitsMin(axis: JointAxis): number {
+        return this.rawSet.jointLimitsMin(
+            this.handle,
+            axis as number as RawJointAxis,
+        );
+    }
+
+    /**
+     * The max limit along the given axis of this joint.
+     */
+    public axisLimitsMax(axis: JointAxis): number {
+        return this.rawSet.jointLimitsMax(
+            this.handle,
+            axis as number as RawJointAxis,
+        );
+    }
+
+    /**
+     * Sets the limits along the given axis of this joint.
+     *
+     * @param axis - The limited axis.
+     * @param min - The minimum bound of this joint’s coordinate along `axis`.
+     * @param max - The maximum bound of this joint’s coordinate along `axis`.
+     */
+    public setAxisLimits(axis: JointAxis, min: number, max: number) {
+        this.rawSet.jointSetLimits(
+            this.handle,
+            axis as number as RawJointAxis,
+            min,
+            max,
+        );
+    }
 }
 
 export class UnitImpulseJoint extends ImpulseJoint {
@@ -264,23 +369,88 @@ export class UnitImpulseJoint extends ImpulseJoint {
         this.rawSet.jointSetLimits(this.handle, this.rawAxis(), min, max);
     }
 
-    public configureMotorModel(model: MotorModel) {
-        this.rawSet.jointConfigureMotorModel(
+    /**
+     * The world-space direction of the axis left free by this joint, as seen from
+     * the first rigid-body it is attached to.
+     *
+     * For revolute joints, this is the rotation axis in 3D. In 2D, the rotation axis
+     * is orthogonal to the simulation plane so a zero vector is returned.
+     */
+    public axisWorld(): Vector {
+        return VectorOps.fromRaw(
+            this.rawSet.jointAxisWorld(
+                this.handle,
+                this.bodySet.raw,
+                this.rawAxis(),
+            ),
+        );
+    }
+
+    /**
+     * The rate of change of the coordinate left free by this joint: the relative
+     * linear velocity of the two rigid-bodies along the joint axis (measured at the
+     * anchors) for prismatic joints, or their relative angular velocity around the
+     * joint axis for revolute joints.
+     */
+    public axisVelocity(): number {
+        return this.rawSet.jointAxisVelocity(
             this.handle,
+            this.bodySet.raw,
             this.rawAxis(),
-            model as number as RawMotorModel,
         );
     }
 
-    public configureMotorVelocity(targetVel: number, factor: number) {
-        this.rawSet.jointConfigureMotorVelocity(
+    public configureMotorModel(model: MotorModel) {
+        this.rawSet.jointConfigureMotorModel(
             this.handle,
             this.rawAxis(),
-            targetVel,
-            factor,
+            model as number as RawMotorModel,
         );
     }
 
+    /**
+     * The target velocity currently applied to the motor of this joint.
+     *
+     * If the motor velocity is being ramped, this is the intermediate velocity
+     * applied during the last step.
+     */
+    public motorTargetVel(): number {
+        return this.rawSet.jointMotorTargetVel(this.handle, this.rawAxis());
+    }
+
+    /**
+     * Configures the motor of this joint to reach the given velocity.
+     *
+     * @param targetVel - The target velocity of the motor.
+     * @param factor - The motor's damping factor.
+     * @param maxAcceleration - If set, the motor's target velocity is not changed instantly and
+     *   instead ramps toward `targetVel` at each `World.step`, changing by at most
+     *   `maxAcceleration * dt` per step. The ramp is part of the joint set, so it is kept by
+     *   snapshots.
+     */
+    public configureMotorVelocity(
+        targetVel: number,
+        factor: number,
+        maxAcceleration?: number,
+    ) {
+        if (maxAcceleration == undefined || maxAcceleration == null) {
+            this.rawSet.jointConfigureMotorVelocity(
+                this.handle,
+                this.rawAxis(),
+                targetVel,
+                factor,
+            );
+        } else {
+            this.rawSet.jointConfigureMotorVelocityRamp(
+                this.handle,
+                this.rawAxis(),
+                targetVel,
+                factor,
+                maxAcceleration,
+            );
+        }
+    }
+
     public configureMotorPosition(
         targetPos: number,
         stiffness: number,
@@ -310,6 +480,64 @@ export class UnitImpulseJoint extends ImpulseJoint {
             damping,
         );
     }
+
+    /**
+     * Configures the motor of this joint to follow the given trajectory of target positions.
+     *
+     * At each `World.step`, the motor target position is set to the trajectory position
+     * reached at the end of that step (linearly interpolated between the given keyframes),
+     * and its target velocity to the trajectory velocity, so the motor doesn't lag behind
+     * even if the trajectory is sampled at a low rate. Once the end of the trajectory is
+     * reached, the motor keeps targeting its last position. The motor is configured once
+     * per step, not at each substep. The trajectory is part of the joint set, so it is kept
+     * by snapshots.
+     *
+     * Calling any other motor configuration method stops following the trajectory.
+     *
+     * @param times - The time of each keyframe, in seconds, relative to the next step. Must be
+     *   non-decreasing.
+     * @param positions - The target position of each keyframe.
+     * @param stiffness - The motor's stiffness.
+     * @param damping - The motor's damping.
+     */
+    public followMotorTrajectory(
+        times: number[],
+        positions: number[],
+        stiffness: number,
+        damping: number,
+    ) {
+        if (times.length == 0 || times.length != positions.length) {
+            throw new Error(
+                "A motor trajectory needs as many times as positions, and at least one keyframe.",
+            );
+        }
+        for (let i = 1; i < times.length; ++i) {
+            if (times[i] < times[i - 1]) {
+                throw new Error(
+                    "The motor trajectory times must be non-decreasing.",
+                );
+            }
+        }
+
+        this.rawSet.jointFollowMotorTrajectory(
+            this.handle,
+            this.rawAxis(),
+            new Float32Array(times),
+            new Float32Array(positions),
+            stiffness,
+            damping,
+        );
+    }
+
+    /**
+     * Is the motor of this joint currently following a trajectory set with `followMotorTrajectory`?
+     */
+    public isFollowingMotorTrajectory(): boolean {
+        return this.rawSet.jointIsFollowingMotorTrajectory(
+            this.handle,
+            this.rawAxis(),
+        );
+    }
 }
 
 export class FixedImpulseJoint extends ImpulseJoint {}
@@ -367,6 +595,7 @@ export class JointData {
     limitsEnabled: boolean;
     limits: Array<number>;
     axesMask: JointAxesMask;
+    coupledAxes: JointAxesMask;
     stiffness: number;
     damping: number;
     length: number;
@@ -676,6 +905,10 @@ export class JointData {
         rawA1.free();
         rawA2.free();
 
+        if (!!result && !!this.coupledAxes) {
+            result.setCoupledAxes(this.coupledAxes);
+        }
+
         return result;
     }
 }
@@ -6,6 +6,7 @@ import {
     FixedImpulseJoint,
     ImpulseJoint,
     ImpulseJointHandle,
+    JointAxesMask,
     JointData,
     JointType,
     PrismaticImpulseJoint,
@@ -16,6 +17,7 @@ import {
 import {IslandManager} from "./island_manager";
 import {RigidBodyHandle} from "./rigid_body";
 import {Collider, ColliderHandle} from "../geometry";
+import {Vector, VectorOps} from "../math";
 
 /**
  * A set of joints.
@@ -58,6 +60,19 @@ export class ImpulseJointSet {
         this.map.forEach((joint) => joint.finalizeDeserialization(bodies));
     }
 
+    /**
+     * Internal method, do not call this explicitly.
+     *
+     * Recreates the joint objects after the handles of the raw set changed. They
+     * must be finalized with `finalizeDeserialization` afterwards.
+     */
+    public reloadFromRaw() {
+        this.map.clear();
+        this.raw.forEachJointHandle((handle: ImpulseJointHandle) => {
+            this.map.set(handle, ImpulseJoint.newTyped(this.raw, null, handle));
+        });
+    }
+
     /**
      * Creates a new joint and return its integer handle.
      *
@@ -87,6 +102,98 @@ export class ImpulseJointSet {
         return joint;
     }
 
+    /**
+     * Creates a joint constraining the motion of a rigid-body relative to a world-space frame.
+     *
+     * The frame is centered at `origin` and its local X axis is aligned with `axis`. The
+     * constraint is anchored to a fixed rigid-body shared by all the world-space constraints
+     * of `bodies`, which isn't visible as one of its rigid-bodies.
+     *
+     * @param bodies - The set of rigid-bodies containing `body`.
+     * @param body - The handle of the constrained rigid-body.
+     * @param origin - The world-space origin of the constraint frame.
+     * @param axis - The world-space direction of the X axis of the constraint frame.
+     * @param lockedAxes - The axes of the constraint frame along which the motion is locked.
+     * @param wakeUp - Should the attached rigid-bodies be awakened?
+     * @returns The joint, or `null` if the rigid-body doesn't exist or if `axis` is zero.
+     */
+    public createWorldConstraint(
+        bodies: RigidBodySet,
+        body: RigidBodyHandle,
+        origin: Vector,
+        axis: Vector,
+        lockedAxes: JointAxesMask,
+        wakeUp: boolean,
+    ): ImpulseJoint | null {
+        let rawOrigin = VectorOps.intoRaw(origin);
+        let rawAxis = VectorOps.intoRaw(axis);
+        const handle = this.raw.createWorldConstraint(
+            bodies.raw,
+            body,
+            rawOrigin,
+            rawAxis,
+            lockedAxes,
+            wakeUp,
+        );
+        rawOrigin.free();
+        rawAxis.free();
+        if (handle === undefined) return null;
+
+        let joint = ImpulseJoint.newTyped(this.raw, bodies, handle);
+        this.map.set(handle, joint);
+        return joint;
+    }
+
+    /**
+     * Inserts a copy of the given joint into another joint set.
+     *
+     * @param handle - The handle of the joint to copy.
+     * @param target - The joint set the copy is inserted into.
+     * @param targetBodies - The rigid-body set associated to `target`.
+     * @param parent1 - The handle of the first rigid-body from `targetBodies` the copy is attached to,
+     *   or `null` to attach it to the anchor of the world-space constraints of `targetBodies`.
+     * @param parent2 - The handle of the second rigid-body from `targetBodies` the copy is attached to.
+     * @param wakeUp - If `true`, the rigid-bodies attached by the copy will be woken-up automatically.
+     * @returns The copy, or `null` if this set doesn't contain the given joint.
+     */
+    public cloneInto(
+        handle: ImpulseJointHandle,
+        target: ImpulseJointSet,
+        targetBodies: RigidBodySet,
+        parent1: RigidBodyHandle | null,
+        parent2: RigidBodyHandle,
+        wakeUp: boolean,
+    ): ImpulseJoint | null {
+        let copyHandle = this.raw.cloneInto(
+            handle,
+            target.raw,
+            targetBodies.raw,
+            parent1,
+            parent2,
+            wakeUp,
+        );
+        if (copyHandle === undefined) return null;
+
+        let joint = ImpulseJoint.newTyped(target.raw, targetBodies, copyHandle);
+        target.map.set(copyHandle, joint);
+        return joint;
+    }
+
+    /**
+     * Replaces the wrapper of a joint after its locked axes were changed, so that its
+     * class matches its new joint type.
+     *
+     * @internal
+     */
+    public retype(
+        bodies: RigidBodySet,
+        handle: ImpulseJointHandle,
+    ): ImpulseJoint {
+        let joint = ImpulseJoint.newTyped(this.raw, bodies, handle);
+        this.map.set(handle, joint);
+        return joint;
+    }
+
     /**
      * Remove a joint from this set.
      *
@@ -98,6 +205,19 @@ export class ImpulseJointSet {
         this.unmap(handle);
     }
 
+    /**
+     * Is the given joint a world-space constraint created with `this.createWorldConstraint`?
+     *
+     * @param bodies - The set of rigid-bodies the joint is attached to.
+     * @param handle - The handle of the joint.
+     */
+    public isWorldConstraint(
+        bodies: RigidBodySet,
+        handle: ImpulseJointHandle,
+    ): boolean {
+        return this.raw.isWorldConstraint(bodies.raw, handle);
+    }
+
     /**
      * Calls the given closure with the integer handle of each impulse joint attached to this rigid-body.
      *
@@ -110,6 +230,26 @@ export class ImpulseJointSet {
         this.raw.forEachJointAttachedToRigidBody(handle, f);
     }
 
+    /**
+     * Enables or disables several joints at once, without losing their configuration.
+     *
+     * @param handles - The integer handles of the joints to enable or disable.
+     * @param enabled - Whether the joints should be enabled.
+     */
+    public setEnabled(handles: ImpulseJointHandle[], enabled: boolean) {
+        this.raw.setEnabledBulk(new Float64Array(handles), enabled);
+    }
+
+    /**
+     * Enables or disables all the joints attached to the given rigid-body.
+     *
+     * @param handle - The integer handle of the rigid-body.
+     * @param enabled - Whether the joints should be enabled.
+     */
+    public setEnabledForRigidBody(handle: RigidBodyHandle, enabled: boolean) {
+        this.raw.setEnabledForRigidBody(handle, enabled);
+    }
+
     /**
      * Internal function, do not call directly.
      * @param handle
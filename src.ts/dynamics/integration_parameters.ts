@@ -68,6 +68,13 @@ export class IntegrationParameters {
         return this.raw.numInternalPgsIterations;
     }
 
+    /**
+     * Number of stabilization iterations run at each solver iteration (default: `1`).
+     */
+    get numInternalStabilizationIterations(): number {
+        return this.raw.numInternalStabilizationIterations;
+    }
+
     /**
      * Minimum number of dynamic bodies in each active island (default: `128`).
      */
@@ -82,6 +89,56 @@ export class IntegrationParameters {
         return this.raw.maxCcdSubsteps;
     }
 
+    /**
+     * The coefficient in `[0, 1]` applied to the impulses of the previous timestep to
+     * warmstart the constraints solver (default: `1.0`).
+     *
+     * Set this to `0.0` to disable warmstarting. This can be reset for a single contact
+     * pair with `ContactModificationContext.resetWarmstartImpulses`.
+     */
+    get warmstartCoefficient(): number {
+        return this.raw.warmstartCoefficient;
+    }
+
+    /**
+     * Amplitude, in radians per second, of a tiny deterministic perturbation of the angular
+     * velocity of resting dynamic rigid-bodies applied at the beginning of each step
+     * (default: `0.0`, i.e., disabled).
+     *
+     * This breaks the symmetry of perfectly aligned stacks, e.g., box towers, which would
+     * otherwise show synchronized micro-jitter. The perturbation only depends on the
+     * `restingJitterSeed` and on the current state of each rigid-body so the simulation
+     * remains deterministic. It is capped to half the angular sleep threshold of each
+     * rigid-body, and isn't applied to the rigid-bodies that are already slow enough to
+     * fall asleep, so it doesn't keep resting rigid-bodies awake.
+     */
+    get restingJitterAmplitude(): number {
+        return this.raw.restingJitterAmplitude;
+    }
+
+    /**
+     * The seed of the perturbation enabled by `restingJitterAmplitude` (default: `0`).
+     */
+    get restingJitterSeed(): number {
+        return this.raw.restingJitterSeed;
+    }
+
+    /**
+     * The minimum approach speed of a contact for it to bounce (default: `0.0`, i.e.,
+     * all contacts with a non-zero restitution bounce).
+     *
+     * Contacts approaching slower than this get no restitution, preventing slowly rolling
+     * or resting objects from micro-bouncing. It can be overridden for specific colliders
+     * with `Collider.setRestitutionThreshold`.
+     *
+     * The threshold is applied natively, without calling any JS hook, to the contacts of the
+     * colliders with a non-zero restitution only, so the colliders that can't bounce aren't
+     * slowed down by a contact modification.
+     */
+    get restitutionThreshold(): number {
+        return this.raw.restitutionThreshold;
+    }
+
     set dt(value: number) {
         this.raw.dt = value;
     }
@@ -116,6 +173,13 @@ export class IntegrationParameters {
         this.raw.numInternalPgsIterations = value;
     }
 
+    /**
+     * Sets the number of stabilization iterations run at each solver iteration (default: `1`).
+     */
+    set numInternalStabilizationIterations(value: number) {
+        this.raw.numInternalStabilizationIterations = value;
+    }
+
     set minIslandSize(value: number) {
         this.raw.minIslandSize = value;
     }
@@ -123,4 +187,20 @@ export class IntegrationParameters {
     set maxCcdSubsteps(value: number) {
         this.raw.maxCcdSubsteps = value;
     }
+
+    set warmstartCoefficient(value: number) {
+        this.raw.warmstartCoefficient = value;
+    }
+
+    set restingJitterAmplitude(value: number) {
+        this.raw.restingJitterAmplitude = value;
+    }
+
+    set restingJitterSeed(value: number) {
+        this.raw.restingJitterSeed = value;
+    }
+
+    set restitutionThreshold(value: number) {
+        this.raw.restitutionThreshold = value;
+    }
 }
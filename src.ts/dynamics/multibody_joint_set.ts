@@ -146,6 +146,46 @@ export class MultibodyJointSet {
         this.raw.forEachJointAttachedToRigidBody(handle, f);
     }
 
+    /**
+     * Is the given rigid-body a link of a multibody?
+     *
+     * @param body - The handle of the rigid-body.
+     */
+    public isMultibodyLink(body: RigidBodyHandle): boolean {
+        return this.raw.isMultibodyLink(body);
+    }
+
+    /**
+     * The index of the given rigid-body among the links of its multibody. The root of the
+     * multibody has the index `0`.
+     *
+     * @param body - The handle of the rigid-body.
+     * @returns The link index, or `undefined` if the rigid-body isn't a multibody link.
+     */
+    public linkIndex(body: RigidBodyHandle): number | undefined {
+        return this.raw.linkIndex(body);
+    }
+
+    /**
+     * The number of links of the multibody the given rigid-body is part of.
+     *
+     * @param body - The handle of the rigid-body.
+     * @returns The number of links, or `undefined` if the rigid-body isn't a multibody link.
+     */
+    public numLinks(body: RigidBodyHandle): number | undefined {
+        return this.raw.numLinks(body);
+    }
+
+    /**
+     * The handle of the rigid-body at the root of the multibody the given rigid-body is part of.
+     *
+     * @param body - The handle of the rigid-body.
+     * @returns The root handle, or `undefined` if the rigid-body isn't a multibody link.
+     */
+    public linkRootBody(body: RigidBodyHandle): RigidBodyHandle | undefined {
+        return this.raw.linkRootBody(body);
+    }
+
     /**
      * Gets all joints in the list.
      *
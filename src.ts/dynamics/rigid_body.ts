@@ -1,7 +1,13 @@
 import {RawRigidBodySet, RawRigidBodyType} from "../raw";
-import {Rotation, RotationOps, Vector, VectorOps} from "../math";
+import {
+    MassProperties,
+    Rotation,
+    RotationOps,
+    Vector,
+    VectorOps,
+} from "../math";
 // #if DIM3
-import {SdpMatrix3, SdpMatrix3Ops} from "../math";
+import {Quaternion, SdpMatrix3, SdpMatrix3Ops} from "../math";
 // #endif
 import {Collider, ColliderSet} from "../geometry";
 
@@ -43,6 +49,29 @@ export enum RigidBodyType {
     KinematicVelocityBased,
 }
 
+/**
+ * A snapshot of the main properties of a rigid-body, read in a single call
+ * with `RigidBody.state`.
+ */
+export interface RigidBodyState {
+    translation: Vector;
+    rotation: Rotation;
+    linvel: Vector;
+    // #if DIM2
+    angvel: number;
+    // #endif
+    // #if DIM3
+    angvel: Vector;
+    // #endif
+    mass: number;
+    bodyType: RigidBodyType;
+    isSleeping: boolean;
+    isEnabled: boolean;
+    gravityScale: number;
+    linearDamping: number;
+    angularDamping: number;
+}
+
 /**
  * A rigid-body.
  */
@@ -50,6 +79,7 @@ export class RigidBody {
     private rawSet: RawRigidBodySet; // The RigidBody won't need to free this.
     private colliderSet: ColliderSet;
     readonly handle: RigidBodyHandle;
+    private rotationLockStack: number[];
 
     /**
      * An arbitrary user-defined object associated with this rigid-body.
@@ -64,6 +94,7 @@ export class RigidBody {
         this.rawSet = rawSet;
         this.colliderSet = colliderSet;
         this.handle = handle;
+        this.rotationLockStack = [];
     }
 
     /** @internal */
@@ -79,6 +110,44 @@ export class RigidBody {
         return this.rawSet.contains(this.handle);
     }
 
+    /**
+     * Reads the pose, velocities, and main properties of this rigid-body in a single
+     * call to the WASM module, e.g., for editors and inspectors.
+     */
+    public state(): RigidBodyState {
+        let s = this.rawSet.bodyState(this.handle);
+        // #if DIM2
+        return {
+            translation: VectorOps.new(s[0], s[1]),
+            rotation: s[2],
+            linvel: VectorOps.new(s[3], s[4]),
+            angvel: s[5],
+            mass: s[6],
+            bodyType: s[7] as RigidBodyType,
+            isSleeping: s[8] != 0,
+            isEnabled: s[9] != 0,
+            gravityScale: s[10],
+            linearDamping: s[11],
+            angularDamping: s[12],
+        };
+        // #endif
+        // #if DIM3
+        return {
+            translation: VectorOps.new(s[0], s[1], s[2]),
+            rotation: new Quaternion(s[3], s[4], s[5], s[6]),
+            linvel: VectorOps.new(s[7], s[8], s[9]),
+            angvel: VectorOps.new(s[10], s[11], s[12]),
+            mass: s[13],
+            bodyType: s[14] as RigidBodyType,
+            isSleeping: s[15] != 0,
+            isEnabled: s[16] != 0,
+            gravityScale: s[17],
+            linearDamping: s[18],
+            angularDamping: s[19],
+        };
+        // #endif
+    }
+
     /**
      * Locks or unlocks the ability of this rigid-body to translate.
      *
@@ -99,6 +168,48 @@ export class RigidBody {
         return this.rawSet.rbLockRotations(this.handle, locked, wakeUp);
     }
 
+    /**
+     * Are all the rotational degrees of freedom of this rigid-body locked?
+     */
+    public isRotationLocked(): boolean {
+        return this.rawSet.rbIsRotationLocked(this.handle);
+    }
+
+    /**
+     * Locks or unlocks the rotations of this rigid-body, saving the previous locked axes
+     * (translational and rotational) so they can be restored later with `popRotationLock`.
+     *
+     * Pushes and pops can be nested, which lets independent pieces of gameplay code
+     * temporarily lock a rigid-body without clobbering each other's state.
+     *
+     * @param locked - If `true`, this rigid-body will no longer rotate due to torques and impulses.
+     * @param wakeUp - If `true`, this rigid-body will be automatically awaken if it is currently asleep.
+     */
+    public pushRotationLock(locked: boolean, wakeUp: boolean) {
+        this.rotationLockStack.push(this.rawSet.rbLockedAxes(this.handle));
+        this.lockRotations(locked, wakeUp);
+    }
+
+    /**
+     * Restores the locked axes saved by the last call to `pushRotationLock`.
+     *
+     * Returns `false` (and does nothing) if there is no saved state to restore.
+     *
+     * @param wakeUp - If `true`, this rigid-body will be automatically awaken if it is currently asleep.
+     */
+    public popRotationLock(wakeUp: boolean): boolean {
+        if (this.rotationLockStack.length == 0) {
+            return false;
+        }
+
+        this.rawSet.rbSetLockedAxes(
+            this.handle,
+            this.rotationLockStack.pop(),
+            wakeUp,
+        );
+        return true;
+    }
+
     // #if DIM2
     /**
      * Locks or unlocks the ability of this rigid-body to translation along individual coordinate axes.
@@ -238,6 +349,16 @@ export class RigidBody {
         this.rawSet.rbSetDominanceGroup(this.handle, group);
     }
 
+    /**
+     * The step at which this rigid-body was created, i.e., the value of `World.stepCount`
+     * when it was created.
+     *
+     * Returns `null` if this rigid-body was restored from a snapshot.
+     */
+    public creationStep(): number | null {
+        return this.rawSet.rbCreationStep(this.handle);
+    }
+
     /**
      * The number of additional solver iterations that will be run for this
      * rigid-body and everything that interacts with it directly or indirectly
@@ -262,6 +383,60 @@ export class RigidBody {
         this.rawSet.rbSetAdditionalSolverIterations(this.handle, iters);
     }
 
+    /**
+     * Scales the response of the contacts involving this rigid-body, to approximate a squishy
+     * object without simulating a soft-body.
+     *
+     * The penetration depth corrected by the constraints solver is multiplied by
+     * `stiffnessScale`: values smaller than `1` let the contacts sink in and recover more
+     * slowly. The restitution coefficient of each contact is multiplied by `restitutionScale`.
+     * If both rigid-bodies of a contact have a contact response, their scale factors are
+     * multiplied.
+     *
+     * This is handled natively during the simulation step: no physics hooks need to be given
+     * to `World.step`, and `ActiveHooks` don't need to be set on the colliders.
+     *
+     * @param stiffnessScale - The scale factor of the corrected penetration depth, in `[0, 1]`.
+     * @param restitutionScale - The scale factor of the restitution coefficients.
+     */
+    public setContactResponse(stiffnessScale: number, restitutionScale: number) {
+        this.rawSet.rbSetContactResponse(
+            this.handle,
+            this.colliderSet.raw,
+            stiffnessScale,
+            restitutionScale,
+        );
+    }
+
+    /**
+     * Removes the contact response scaling of this rigid-body.
+     */
+    public removeContactResponse() {
+        this.rawSet.rbRemoveContactResponse(this.handle, this.colliderSet.raw);
+    }
+
+    /**
+     * The scale factor applied to the penetration depth of the contacts of this rigid-body
+     * (default: `1`).
+     */
+    public contactStiffnessScale(): number {
+        return this.rawSet.rbContactStiffnessScale(
+            this.handle,
+            this.colliderSet.raw,
+        );
+    }
+
+    /**
+     * The scale factor applied to the restitution coefficient of the contacts of this
+     * rigid-body (default: `1`).
+     */
+    public contactRestitutionScale(): number {
+        return this.rawSet.rbContactRestitutionScale(
+            this.handle,
+            this.colliderSet.raw,
+        );
+    }
+
     /**
      * Enable or disable CCD (Continuous Collision Detection) for this rigid-body.
      *
@@ -426,6 +601,48 @@ export class RigidBody {
         this.rawSet.rbSetRotation(this.handle, angle, wakeUp);
     }
 
+    /**
+     * The rotation angle of this rigid-body, wrapped into the range `(-PI, PI]`.
+     */
+    public rotationNormalized(): number {
+        return RotationOps.normalizeAngle(this.rotation());
+    }
+
+    /**
+     * Teleports this rigid-body to the given rotation angle, following the shortest
+     * angular path from its current angle.
+     *
+     * The target angle may lie outside of `(-PI, PI]` (for example an angle accumulated
+     * over several turns): only its wrapped value matters. Returns the signed angle, in
+     * the range `(-PI, PI]`, by which this rigid-body was rotated.
+     *
+     * @param angle - The target rotation angle, in radians.
+     * @param wakeUp - Forces the rigid-body to wake-up so it is properly affected by forces if it
+     * wasn't moving before modifying its position.
+     */
+    public teleportRotation(angle: number, wakeUp: boolean): number {
+        let current = this.rotation();
+        let delta = RotationOps.angleDifference(current, angle);
+        this.setRotation(current + delta, wakeUp);
+        return delta;
+    }
+
+    /**
+     * Sets the next kinematic rotation of this rigid-body, following the shortest
+     * angular path from its current angle.
+     *
+     * Returns the signed angle, in the range `(-PI, PI]`, this rigid-body will rotate by
+     * during the next timestep.
+     *
+     * @param angle - The target kinematic rotation angle, in radians.
+     */
+    public setNextKinematicRotationShortestPath(angle: number): number {
+        let current = this.rotation();
+        let delta = RotationOps.angleDifference(current, angle);
+        this.setNextKinematicRotation(current + delta);
+        return delta;
+    }
+
     /**
      * Sets the angular velocity fo this rigid-body.
      *
@@ -935,6 +1152,56 @@ export class RigidBody {
 
     // #endif
 
+    /**
+     * The additional mass-properties of this rigid-body, set with `this.setAdditionalMass`,
+     * `this.setAdditionalMassProperties`, `RigidBodyDesc.setAdditionalMass`, or
+     * `RigidBodyDesc.setAdditionalMassProperties`.
+     *
+     * Returns `null` if this rigid-body has no additional mass-properties. If only an additional
+     * mass was set, the returned mass-properties have a zero center-of-mass and angular inertia.
+     */
+    public additionalMassProperties(): MassProperties | null {
+        return MassProperties.fromRaw(
+            this.rawSet.rbAdditionalMassProperties(this.handle),
+        );
+    }
+
+    /**
+     * Moves the center-of-mass of the additional mass-properties of this rigid-body, keeping
+     * their mass and angular inertia unchanged.
+     *
+     * This can be called at each frame to simulate, e.g., shifting cargo, without modifying any
+     * collider. If this rigid-body has no additional mass-properties, this does nothing.
+     *
+     * @param centerOfMass - The new local-space center-of-mass of the additional mass-properties.
+     * @param wakeUp - If `true` then the rigid-body will be woken up if it was put to sleep because it did not move for a while.
+     */
+    public setAdditionalLocalCenterOfMass(
+        centerOfMass: Vector,
+        wakeUp: boolean,
+    ) {
+        let props = this.additionalMassProperties();
+        if (!props) return;
+
+        // #if DIM2
+        this.setAdditionalMassProperties(
+            props.mass,
+            centerOfMass,
+            props.principalInertia,
+            wakeUp,
+        );
+        // #endif
+        // #if DIM3
+        this.setAdditionalMassProperties(
+            props.mass,
+            centerOfMass,
+            props.principalInertia,
+            props.principalInertiaFrame,
+            wakeUp,
+        );
+        // #endif
+    }
+
     /**
      * Sets the linear damping factor applied to this rigid-body.
      *
@@ -55,6 +55,19 @@ export class RigidBodySet {
         this.map.forEach((rb) => rb.finalizeDeserialization(colliderSet));
     }
 
+    /**
+     * Internal method, do not call this explicitly.
+     *
+     * Recreates the rigid-body objects after the handles of the raw set changed. They
+     * must be finalized with `finalizeDeserialization` afterwards.
+     */
+    public reloadFromRaw() {
+        this.map.clear();
+        this.raw.forEachRigidBodyHandle((handle: RigidBodyHandle) => {
+            this.map.set(handle, new RigidBody(this.raw, null, handle));
+        });
+    }
+
     /**
      * Creates a new rigid-body and return its integer handle.
      *
@@ -136,6 +149,38 @@ export class RigidBodySet {
         return body;
     }
 
+    /**
+     * Inserts a copy of the given rigid-body into another rigid-body set.
+     *
+     * The colliders and joints attached to the rigid-body are not copied, but its contact
+     * response is.
+     *
+     * @param handle - The handle of the rigid-body to copy.
+     * @param colliders - The collider set associated to this set.
+     * @param target - The rigid-body set the copy is inserted into.
+     * @param targetColliders - The collider set associated to `target`.
+     * @returns The copy, or `null` if this set doesn't contain the given rigid-body.
+     */
+    public cloneInto(
+        handle: RigidBodyHandle,
+        colliders: ColliderSet,
+        target: RigidBodySet,
+        targetColliders: ColliderSet,
+    ): RigidBody | null {
+        let copyHandle = this.raw.cloneInto(
+            handle,
+            colliders.raw,
+            target.raw,
+            targetColliders.raw,
+        );
+        if (copyHandle === undefined) return null;
+
+        const body = new RigidBody(target.raw, targetColliders, copyHandle);
+        body.userData = this.get(handle).userData;
+        target.map.set(copyHandle, body);
+        return body;
+    }
+
     /**
      * Removes a rigid-body from this set.
      *
@@ -177,6 +222,64 @@ export class RigidBodySet {
         this.map.delete(handle);
     }
 
+    /**
+     * Removes several rigid-bodies from this set at once.
+     *
+     * This takes a single call into the physics engine, e.g., when unloading a whole region
+     * of the scene, but each rigid-body is still removed individually from the islands,
+     * the broad-phase, and the joint graphs.
+     *
+     * @param handles - The handles of the rigid-bodies to remove. Handles of rigid-bodies
+     *                  that no longer exist are ignored.
+     * @param alsoColliders - If `true`, the colliders attached to the removed rigid-bodies
+     *                        are removed too. Otherwise, they are detached from them.
+     * @param alsoJoints - If `true`, the joints attached to the removed rigid-bodies are
+     *                     removed too. Otherwise, the rigid-bodies with joints attached are
+     *                     not removed, since joints can't outlive their rigid-bodies.
+     * @returns The handles of the rigid-bodies that were actually removed.
+     */
+    public removeBatch(
+        handles: RigidBodyHandle[],
+        islands: IslandManager,
+        colliders: ColliderSet,
+        impulseJoints: ImpulseJointSet,
+        multibodyJoints: MultibodyJointSet,
+        alsoColliders: boolean,
+        alsoJoints: boolean,
+    ): RigidBodyHandle[] {
+        let removed = this.raw.removeBatch(
+            new Float64Array(handles),
+            islands.raw,
+            colliders.raw,
+            impulseJoints.raw,
+            multibodyJoints.raw,
+            alsoColliders,
+            alsoJoints,
+        );
+
+        // Unmap the entities that were removed along with the rigid-bodies.
+        removed.colliders().forEach((handle) => colliders.unmap(handle));
+        removed
+            .impulseJoints()
+            .forEach((handle) => impulseJoints.unmap(handle));
+        removed
+            .multibodyJoints()
+            .forEach((handle) => multibodyJoints.unmap(handle));
+
+        let bodies = Array.from(removed.bodies());
+        bodies.forEach((handle) => this.map.delete(handle));
+        removed.free();
+        return bodies;
+    }
+
+    /**
+     * Internal function, do not call directly.
+     * @param handle
+     */
+    public unmap(handle: RigidBodyHandle) {
+        this.map.delete(handle);
+    }
+
     /**
      * The number of rigid-bodies on this set.
      */
@@ -227,6 +330,78 @@ export class RigidBodySet {
         });
     }
 
+    /**
+     * Copies the velocities of several rigid-bodies into the given buffers.
+     *
+     * The velocities are packed in `velocities` as `[linvel.x, linvel.y, angvel]` in 2D, and as
+     * `[linvel.x, linvel.y, linvel.z, angvel.x, angvel.y, angvel.z]` in 3D. The handle of the
+     * rigid-body each velocity belongs to is written at the same index in `handles`. The copy
+     * stops as soon as one of the buffers is full.
+     *
+     * @param islands - The island manager used to determine which rigid-bodies are active.
+     * @param activeOnly - If `true`, only the velocities of active rigid-bodies are copied.
+     * @param handles - The buffer where the rigid-body handles are written.
+     * @param velocities - The buffer where the velocities are written.
+     * @returns The number of rigid-bodies which velocities have been copied.
+     */
+    public copyVelocitiesToBuffer(
+        islands: IslandManager,
+        activeOnly: boolean,
+        handles: Float64Array,
+        velocities: Float32Array,
+    ): number {
+        return this.raw.copyVelocitiesToBuffer(
+            islands.raw,
+            activeOnly,
+            handles,
+            velocities,
+        );
+    }
+
+    /**
+     * Copies the world-space poses of several rigid-bodies into the given buffers.
+     *
+     * The poses are packed in `poses` as `[tx, ty, angle]` in 2D, and as
+     * `[tx, ty, tz, qx, qy, qz, qw]` in 3D. The handle of the rigid-body each pose belongs
+     * to is written at the same index in `handles`. The copy stops as soon as one of the
+     * buffers is full.
+     *
+     * @param islands - The island manager used to determine which rigid-bodies are active.
+     * @param activeOnly - If `true`, only the poses of active rigid-bodies are copied.
+     * @param handles - The buffer where the rigid-body handles are written.
+     * @param poses - The buffer where the poses are written.
+     * @returns The number of rigid-bodies which poses have been copied.
+     */
+    public copyPosesToBuffer(
+        islands: IslandManager,
+        activeOnly: boolean,
+        handles: Float64Array,
+        poses: Float32Array,
+    ): number {
+        return this.raw.copyPosesToBuffer(
+            islands.raw,
+            activeOnly,
+            handles,
+            poses,
+        );
+    }
+
+    /**
+     * Recomputes the mass-properties of several rigid-bodies based on their attached colliders.
+     *
+     * @param colliders - The set of colliders attached to these rigid-bodies.
+     * @param handles - The handles of the rigid-bodies to update.
+     */
+    public recomputeMassPropertiesFromColliders(
+        colliders: ColliderSet,
+        handles: RigidBodyHandle[],
+    ) {
+        this.raw.recomputeMassPropertiesFromColliders(
+            new Float64Array(handles),
+            colliders.raw,
+        );
+    }
+
     /**
      * Gets all rigid-bodies in the list.
      *
@@ -0,0 +1,62 @@
+import {Vector, VectorOps} from "../math";
+import {RawAabb, RawBoundingSphere} from "../raw";
+
+/**
+ * An axis-aligned bounding box.
+ */
+export class Aabb {
+    /**
+     * The point of the box with the smallest coordinates.
+     */
+    mins: Vector;
+    /**
+     * The point of the box with the largest coordinates.
+     */
+    maxs: Vector;
+
+    constructor(mins: Vector, maxs: Vector) {
+        this.mins = mins;
+        this.maxs = maxs;
+    }
+
+    public static fromRaw(raw: RawAabb): Aabb {
+        if (!raw) return null;
+
+        const result = new Aabb(
+            VectorOps.fromRaw(raw.mins()),
+            VectorOps.fromRaw(raw.maxs()),
+        );
+        raw.free();
+        return result;
+    }
+}
+
+/**
+ * A bounding sphere (a bounding circle in 2D).
+ */
+export class BoundingSphere {
+    /**
+     * The center of the bounding sphere.
+     */
+    center: Vector;
+    /**
+     * The radius of the bounding sphere.
+     */
+    radius: number;
+
+    constructor(center: Vector, radius: number) {
+        this.center = center;
+        this.radius = radius;
+    }
+
+    public static fromRaw(raw: RawBoundingSphere): BoundingSphere {
+        if (!raw) return null;
+
+        const result = new BoundingSphere(
+            VectorOps.fromRaw(raw.center()),
+            raw.radius(),
+        );
+        raw.free();
+        return result;
+    }
+}
@@ -1,4 +1,9 @@
-import {RawBroadPhase, RawRayColliderIntersection} from "../raw";
+import {
+    RawBroadPhase,
+    RawBroadPhaseOptimizationStrategy,
+    RawColliderShapeCastHit,
+    RawRayColliderIntersection,
+} from "../raw";
 import {RigidBodyHandle, RigidBodySet} from "../dynamics";
 import {ColliderSet} from "./collider_set";
 import {Ray, RayColliderHit, RayColliderIntersection} from "./ray";
@@ -8,9 +13,28 @@ import {Rotation, RotationOps, Vector, VectorOps} from "../math";
 import {Shape} from "./shape";
 import {PointColliderProjection} from "./point";
 import {ColliderShapeCastHit} from "./toi";
+import {PredictedTrajectory} from "./trajectory";
 import {QueryFilterFlags} from "../pipeline";
 import {NarrowPhase} from "./narrow_phase";
 
+/**
+ * The strategy used by the broad-phase to keep its bounding-volume hierarchy efficient
+ * as colliders move.
+ */
+export enum BroadPhaseOptimizationStrategy {
+    /**
+     * Incrementally rebuild the subtrees with the most moving colliders at each step.
+     * This is the default.
+     */
+    SubtreeOptimizer,
+    /**
+     * Never rebuild the hierarchy, only refit the bounding volumes of moving colliders.
+     * This is cheaper per step for worlds made of a huge number of mostly-static,
+     * similar-sized colliders, but queries may slow down if colliders travel far.
+     */
+    None,
+}
+
 /**
  * The broad-phase used for coarse collision-detection.
  *
@@ -34,6 +58,21 @@ export class BroadPhase {
         this.raw = raw || new RawBroadPhase();
     }
 
+    /**
+     * Creates a new broad-phase using the given optimization strategy.
+     *
+     * @param strategy - The strategy used to keep the broad-phase hierarchy efficient.
+     */
+    public static withOptimizationStrategy(
+        strategy: BroadPhaseOptimizationStrategy,
+    ): BroadPhase {
+        return new BroadPhase(
+            RawBroadPhase.withOptimizationStrategy(
+                strategy as number as RawBroadPhaseOptimizationStrategy,
+            ),
+        );
+    }
+
     /**
      * Find the closest intersection between a ray and a set of collider.
      *
@@ -439,6 +478,301 @@ export class BroadPhase {
         return result;
     }
 
+    /**
+     * Sweeps a shape between two poses, and returns the first collider it hits.
+     *
+     * The shape translates along a straight line from `startPos` to `endPos` while rotating
+     * uniformly, around its local origin, from `startRot` to `endRot`.
+     *
+     * @param colliders - The set of colliders taking part in this pipeline.
+     * @param shape - The shape to sweep.
+     * @param startPos - The initial position of the shape.
+     * @param startRot - The initial rotation of the shape.
+     * @param endPos - The final position of the shape.
+     * @param endRot - The final rotation of the shape.
+     * @param stopAtPenetration - If set to `false`, the sweep won’t immediately stop if
+     *   the shape is penetrating another shape at its starting pose **and** its motion is such
+     *   that it’s on a path to exit that penetration state.
+     * @returns The first hit, if any. Its time of impact is the fraction of the motion, between
+     *   `0` and `1`, traveled by the shape before the impact.
+     */
+    public sweepShapeBetweenPoses(
+        narrowPhase: NarrowPhase,
+        bodies: RigidBodySet,
+        colliders: ColliderSet,
+        shape: Shape,
+        startPos: Vector,
+        startRot: Rotation,
+        endPos: Vector,
+        endRot: Rotation,
+        stopAtPenetration: boolean,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: ColliderHandle,
+        filterExcludeRigidBody?: RigidBodyHandle,
+        filterPredicate?: (collider: ColliderHandle) => boolean,
+    ): ColliderShapeCastHit | null {
+        let rawShape = shape.intoRaw();
+        let rawStartPos = VectorOps.intoRaw(startPos);
+        let rawStartRot = RotationOps.intoRaw(startRot);
+        let rawEndPos = VectorOps.intoRaw(endPos);
+        let rawEndRot = RotationOps.intoRaw(endRot);
+
+        let result = ColliderShapeCastHit.fromRaw(
+            colliders,
+            this.raw.sweepShapeBetweenPoses(
+                narrowPhase.raw,
+                bodies.raw,
+                colliders.raw,
+                rawShape,
+                rawStartPos,
+                rawStartRot,
+                rawEndPos,
+                rawEndRot,
+                stopAtPenetration,
+                filterFlags,
+                filterGroups,
+                filterExcludeCollider,
+                filterExcludeRigidBody,
+                filterPredicate,
+            ),
+        );
+
+        rawShape.free();
+        rawStartPos.free();
+        rawStartRot.free();
+        rawEndPos.free();
+        rawEndRot.free();
+
+        return result;
+    }
+
+    /**
+     * Moves a rigid-body out of the colliders it penetrates, e.g., right after spawning it.
+     *
+     * At each iteration, the deepest penetration between one of the colliders attached to the
+     * rigid-body and the other colliders is resolved by translating the rigid-body along the
+     * contact normal. This stops once the rigid-body no longer penetrates anything, or after
+     * `maxIterations` iterations. Sensors are ignored.
+     *
+     * @param colliders - The set of colliders taking part in this pipeline.
+     * @param body - The handle of the rigid-body to move.
+     * @param maxIterations - The maximum number of penetrations resolved.
+     * @returns The translation applied to the rigid-body.
+     */
+    public depenetrate(
+        narrowPhase: NarrowPhase,
+        bodies: RigidBodySet,
+        colliders: ColliderSet,
+        body: RigidBodyHandle,
+        maxIterations: number,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: ColliderHandle,
+        filterPredicate?: (collider: ColliderHandle) => boolean,
+    ): Vector {
+        return VectorOps.fromRaw(
+            this.raw.depenetrate(
+                narrowPhase.raw,
+                bodies.raw,
+                colliders.raw,
+                body,
+                maxIterations,
+                filterFlags,
+                filterGroups,
+                filterExcludeCollider,
+                filterPredicate,
+            ),
+        );
+    }
+
+    /**
+     * Predicts the ballistic trajectory of a shape, up to the first fixed collider it hits.
+     *
+     * The velocity of the shape is integrated the same way the physics pipeline integrates
+     * the velocities of dynamic rigid-bodies. At each substep, the shape is cast along its
+     * displacement (without rotating) against fixed colliders only.
+     *
+     * @param colliders - The set of colliders taking part in this pipeline.
+     * @param shapePos - The initial position of the shape.
+     * @param shapeRot - The rotation of the shape.
+     * @param shapeVel - The initial linear velocity of the shape.
+     * @param shape - The shape of the projectile.
+     * @param gravity - The gravity of the physics world.
+     * @param gravityScale - The gravity scale of the projectile.
+     * @param linearDamping - The linear damping of the projectile.
+     * @param maxTime - The duration of the trajectory to predict.
+     * @param dt - The length of each substep.
+     */
+    public predictTrajectory(
+        narrowPhase: NarrowPhase,
+        bodies: RigidBodySet,
+        colliders: ColliderSet,
+        shapePos: Vector,
+        shapeRot: Rotation,
+        shapeVel: Vector,
+        shape: Shape,
+        gravity: Vector,
+        gravityScale: number,
+        linearDamping: number,
+        maxTime: number,
+        dt: number,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: ColliderHandle,
+        filterExcludeRigidBody?: RigidBodyHandle,
+        filterPredicate?: (collider: ColliderHandle) => boolean,
+    ): PredictedTrajectory {
+        let rawPos = VectorOps.intoRaw(shapePos);
+        let rawRot = RotationOps.intoRaw(shapeRot);
+        let rawVel = VectorOps.intoRaw(shapeVel);
+        let rawGravity = VectorOps.intoRaw(gravity);
+        let rawShape = shape.intoRaw();
+
+        let result = PredictedTrajectory.fromRaw(
+            colliders,
+            this.raw.predictTrajectory(
+                narrowPhase.raw,
+                bodies.raw,
+                colliders.raw,
+                rawPos,
+                rawRot,
+                rawVel,
+                rawShape,
+                rawGravity,
+                gravityScale,
+                linearDamping,
+                maxTime,
+                dt,
+                filterFlags,
+                filterGroups,
+                filterExcludeCollider,
+                filterExcludeRigidBody,
+                filterPredicate,
+            ),
+        );
+
+        rawPos.free();
+        rawRot.free();
+        rawVel.free();
+        rawGravity.free();
+        rawShape.free();
+
+        return result;
+    }
+
+    /**
+     * Casts a ray straight down (along `-Y`) from each of the given points, e.g., to place
+     * props on a terrain.
+     *
+     * The candidate colliders are found with a single traversal of the broad-phase for the
+     * whole batch, and bucketed into a horizontal grid so that each ray is only tested
+     * against the colliders above or below the grid cell containing its starting point.
+     *
+     * @param colliders - The set of colliders taking part in this pipeline.
+     * @param points - The starting points of the rays, packed as a flat array of coordinates.
+     * @param maxDrop - The maximum distance below each point where the ground is searched for.
+     * @returns The results for each point, packed as `[handle, height, normal]`, where `height`
+     *   is the `Y` coordinate of the ground below the point. The `handle` and `height` of a point
+     *   without ground below it are `NaN`, and its normal is zero. Each result takes 4 values
+     *   in 2D and 5 values in 3D.
+     */
+    public dropPointsToGround(
+        narrowPhase: NarrowPhase,
+        bodies: RigidBodySet,
+        colliders: ColliderSet,
+        points: Float32Array,
+        maxDrop: number,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: ColliderHandle,
+        filterExcludeRigidBody?: RigidBodyHandle,
+        filterPredicate?: (collider: ColliderHandle) => boolean,
+    ): Float64Array {
+        return this.raw.dropPointsToGround(
+            narrowPhase.raw,
+            bodies.raw,
+            colliders.raw,
+            points,
+            maxDrop,
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider,
+            filterExcludeRigidBody,
+            filterPredicate,
+        );
+    }
+
+    /**
+     * Casts a shape at a constant linear velocity and retrieve all the colliders it hits,
+     * sorted by increasing time-of-impact.
+     *
+     * @param colliders - The set of colliders taking part in this pipeline.
+     * @param shapePos - The initial position of the shape to cast.
+     * @param shapeRot - The initial rotation of the shape to cast.
+     * @param shapeVel - The constant velocity of the shape to cast (i.e. the cast direction).
+     * @param shape - The shape to cast.
+     * @param targetDistance − If the shape moves closer to this distance from a collider, a hit
+     *                       will be returned.
+     * @param maxToi - The maximum time-of-impact that can be reported by this cast. This effectively
+     *   limits the distance traveled by the shape to `shapeVel.norm() * maxToi`.
+     * @param stopAtPenetration - If set to `false`, the linear shape-cast won’t immediately stop if
+     *   the shape is penetrating another shape at its starting point **and** its trajectory is such
+     *   that it’s on a path to exit that penetration state.
+     * @param groups - The bit groups and filter associated to the shape to cast, in order to only
+     *   test on colliders with collision groups compatible with this group.
+     * @returns The hits, packed as `[handle, timeOfImpact, witness1, witness2, normal1, normal2]`
+     *   for each hit, i.e., 10 values per hit in 2D and 14 values per hit in 3D. The witnesses
+     *   and normals are expressed in the local-space of the cast shape and of the hit collider,
+     *   like those of `ColliderShapeCastHit`.
+     */
+    public castShapeAll(
+        narrowPhase: NarrowPhase,
+        bodies: RigidBodySet,
+        colliders: ColliderSet,
+        shapePos: Vector,
+        shapeRot: Rotation,
+        shapeVel: Vector,
+        shape: Shape,
+        targetDistance: number,
+        maxToi: number,
+        stopAtPenetration: boolean,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: ColliderHandle,
+        filterExcludeRigidBody?: RigidBodyHandle,
+        filterPredicate?: (collider: ColliderHandle) => boolean,
+    ): Float64Array {
+        let rawPos = VectorOps.intoRaw(shapePos);
+        let rawRot = RotationOps.intoRaw(shapeRot);
+        let rawVel = VectorOps.intoRaw(shapeVel);
+        let rawShape = shape.intoRaw();
+        let result = this.raw.castShapeAll(
+            narrowPhase.raw,
+            bodies.raw,
+            colliders.raw,
+            rawPos,
+            rawRot,
+            rawVel,
+            rawShape,
+            targetDistance,
+            maxToi,
+            stopAtPenetration,
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider,
+            filterExcludeRigidBody,
+            filterPredicate,
+        );
+
+        rawPos.free();
+        rawRot.free();
+        rawVel.free();
+        rawShape.free();
+
+        return result;
+    }
+
     /**
      * Retrieve all the colliders intersecting the given shape.
      *
@@ -488,6 +822,90 @@ export class BroadPhase {
         rawShape.free();
     }
 
+    /**
+     * Retrieve all the colliders intersecting the given cone, e.g., a vision cone.
+     *
+     * In 2D, the cone is a triangle.
+     *
+     * @param apex - The apex of the cone.
+     * @param dir - The direction of the axis of the cone, from its apex to its base.
+     * @param halfAngle - The half-aperture angle of the cone, in radians.
+     * @param maxDist - The distance between the apex of the cone and its flat base.
+     * @param callback - A function called with the handles of each collider intersecting the cone.
+     */
+    public intersectionsWithCone(
+        narrowPhase: NarrowPhase,
+        bodies: RigidBodySet,
+        colliders: ColliderSet,
+        apex: Vector,
+        dir: Vector,
+        halfAngle: number,
+        maxDist: number,
+        callback: (handle: ColliderHandle) => boolean,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: ColliderHandle,
+        filterExcludeRigidBody?: RigidBodyHandle,
+        filterPredicate?: (collider: ColliderHandle) => boolean,
+    ) {
+        let rawApex = VectorOps.intoRaw(apex);
+        let rawDir = VectorOps.intoRaw(dir);
+
+        this.raw.intersectionsWithCone(
+            narrowPhase.raw,
+            bodies.raw,
+            colliders.raw,
+            rawApex,
+            rawDir,
+            halfAngle,
+            maxDist,
+            callback,
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider,
+            filterExcludeRigidBody,
+            filterPredicate,
+        );
+
+        rawApex.free();
+        rawDir.free();
+    }
+
+    /**
+     * Retrieve all the colliders intersecting the convex region bounded by the given planes,
+     * e.g., a camera frustum.
+     *
+     * @param planes - The bounding planes, packed as `[nx, ny, (nz,) d]`. The region contains
+     *   the points `x` such that `dot(n, x) + d >= 0` for all the planes. Nothing is reported
+     *   if this region is empty or unbounded.
+     * @param callback - A function called with the handles of each collider intersecting the region.
+     */
+    public intersectionsWithFrustum(
+        narrowPhase: NarrowPhase,
+        bodies: RigidBodySet,
+        colliders: ColliderSet,
+        planes: number[] | Float32Array,
+        callback: (handle: ColliderHandle) => boolean,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: ColliderHandle,
+        filterExcludeRigidBody?: RigidBodyHandle,
+        filterPredicate?: (collider: ColliderHandle) => boolean,
+    ) {
+        this.raw.intersectionsWithFrustum(
+            narrowPhase.raw,
+            bodies.raw,
+            colliders.raw,
+            new Float32Array(planes),
+            callback,
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider,
+            filterExcludeRigidBody,
+            filterPredicate,
+        );
+    }
+
     /**
      * Finds the handles of all the colliders with an AABB intersecting the given AABB.
      *
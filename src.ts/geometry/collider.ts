@@ -1,5 +1,8 @@
 import {RawColliderSet} from "../raw";
 import {Rotation, RotationOps, Vector, VectorOps} from "../math";
+// #if DIM3
+import {Quaternion} from "../math";
+// #endif
 import {
     CoefficientCombineRule,
     RigidBody,
@@ -24,6 +27,8 @@ import {
     RoundCuboid,
     HalfSpace,
     TriMeshFlags,
+    Compound,
+    SharedShape,
     // #if DIM2
     ConvexPolygon,
     RoundConvexPolygon,
@@ -42,11 +47,17 @@ import {Ray, RayIntersection} from "./ray";
 import {PointProjection} from "./point";
 import {ColliderShapeCastHit, ShapeCastHit} from "./toi";
 import {ShapeContact} from "./contact";
+import {Aabb, BoundingSphere} from "./bounding_volume";
 import {ColliderSet} from "./collider_set";
 
 /**
  * Flags affecting whether collision-detection happens between two colliders
  * depending on the type of rigid-bodies they are attached to.
+ *
+ * A pair of colliders is tested if at least one of the two colliders enables the
+ * collision type matching the types of their rigid-bodies. In particular, with the
+ * `DEFAULT` flags, a sensor attached to a kinematic rigid-body won’t detect fixed
+ * colliders unless `KINEMATIC_FIXED` is enabled on the sensor.
  */
 export enum ActiveCollisionTypes {
     /**
@@ -103,6 +114,27 @@ export enum ActiveCollisionTypes {
  */
 export type ColliderHandle = number;
 
+/**
+ * A snapshot of the main properties of a collider, read in a single call
+ * with `Collider.state`.
+ */
+export interface ColliderState {
+    translation: Vector;
+    rotation: Rotation;
+    friction: number;
+    restitution: number;
+    density: number;
+    mass: number;
+    collisionGroups: InteractionGroups;
+    solverGroups: InteractionGroups;
+    isSensor: boolean;
+    isEnabled: boolean;
+    /**
+     * The handle of the parent rigid-body, or `null` if it doesn’t have any.
+     */
+    parent: RigidBodyHandle | null;
+}
+
 /**
  * A geometric entity that can be attached to a body so it can be affected
  * by contacts and proximity queries.
@@ -134,6 +166,11 @@ export class Collider {
         }
     }
 
+    /** @internal */
+    public updateParent(parent: RigidBody | null) {
+        this._parent = parent;
+    }
+
     private ensureShapeIsCached() {
         if (!this._shape)
             this._shape = Shape.fromRaw(this.colliderSet.raw, this.handle);
@@ -167,6 +204,38 @@ export class Collider {
         return this.colliderSet.raw.contains(this.handle);
     }
 
+    /**
+     * Reads the pose, material, and main properties of this collider in a single
+     * call to the WASM module, e.g., for editors and inspectors.
+     */
+    public state(): ColliderState {
+        let s = this.colliderSet.raw.colliderState(this.handle);
+        // #if DIM2
+        let translation = VectorOps.new(s[0], s[1]);
+        let rotation = s[2];
+        let i = 3;
+        // #endif
+        // #if DIM3
+        let translation = VectorOps.new(s[0], s[1], s[2]);
+        let rotation = new Quaternion(s[3], s[4], s[5], s[6]);
+        let i = 7;
+        // #endif
+
+        return {
+            translation,
+            rotation,
+            friction: s[i],
+            restitution: s[i + 1],
+            density: s[i + 2],
+            mass: s[i + 3],
+            collisionGroups: s[i + 4],
+            solverGroups: s[i + 5],
+            isSensor: s[i + 6] != 0,
+            isEnabled: s[i + 7] != 0,
+            parent: isNaN(s[i + 8]) ? null : s[i + 8],
+        };
+    }
+
     /**
      * The world-space translation of this collider.
      */
@@ -233,6 +302,28 @@ export class Collider {
         this._shape = shape;
     }
 
+    /**
+     * Replaces the shape of the collider, preserving its contacts where possible.
+     *
+     * If `preserveContacts` is `true` and the new shape has the same type as the previous one,
+     * the contacts between matching features keep their warmstart impulses. This avoids
+     * popping when a shape grows or shrinks progressively (e.g. growing projectiles). Otherwise,
+     * the warmstart impulses of the contacts of this collider are discarded during the next step.
+     *
+     * @param shape - The collider’s new shape.
+     * @param preserveContacts - Whether to keep the warmstart data of the existing contacts.
+     */
+    public replaceShape(shape: Shape, preserveContacts: boolean) {
+        let rawShape = shape.intoRaw();
+        this.colliderSet.raw.replaceShape(
+            this.handle,
+            rawShape,
+            preserveContacts,
+        );
+        rawShape.free();
+        this._shape = shape;
+    }
+
     /**
      * Sets whether this collider is enabled or not.
      *
@@ -242,6 +333,16 @@ export class Collider {
         this.colliderSet.raw.coSetEnabled(this.handle, enabled);
     }
 
+    /**
+     * The step at which this collider was created, i.e., the value of `World.stepCount`
+     * when it was created.
+     *
+     * Returns `null` if this collider was restored from a snapshot.
+     */
+    public creationStep(): number | null {
+        return this.colliderSet.raw.coCreationStep(this.handle);
+    }
+
     /**
      * Is this collider enabled?
      */
@@ -370,6 +471,80 @@ export class Collider {
         this.colliderSet.raw.coSetActiveHooks(this.handle, activeHooks);
     }
 
+    /**
+     * Makes this collider a one-way platform.
+     *
+     * Contacts with this collider are only kept if their normal, pointing towards the exterior
+     * of this collider and expressed in its local-space, makes an angle smaller than
+     * `allowedAngle` with `localNormal`. Contacts that started while forbidden stay forbidden
+     * until the colliders stop penetrating.
+     *
+     * This is handled natively during the simulation step: no physics hooks need to be
+     * given to `World.step`, and `ActiveHooks` don't need to be set. The contact's `userData`
+     * is used to keep track of the state of the platform, so it must not be modified by a
+     * `modifySolverContacts` hook for contacts involving this collider.
+     *
+     * @param localNormal - The allowed contact normal, in the local-space of this collider.
+     * @param allowedAngle - The maximum angle, in radians, between the contact normal and `localNormal`.
+     */
+    public setOnewayPlatform(localNormal: Vector, allowedAngle: number) {
+        let rawNormal = VectorOps.intoRaw(localNormal);
+        this.colliderSet.raw.coSetOnewayPlatform(
+            this.handle,
+            rawNormal,
+            allowedAngle,
+        );
+        rawNormal.free();
+    }
+
+    /**
+     * Removes the one-way platform behavior of this collider.
+     */
+    public removeOnewayPlatform() {
+        this.colliderSet.raw.coRemoveOnewayPlatform(this.handle);
+    }
+
+    /**
+     * Is this collider a one-way platform?
+     */
+    public isOnewayPlatform(): boolean {
+        return this.colliderSet.raw.coIsOnewayPlatform(this.handle);
+    }
+
+    /**
+     * Sets the minimum impact speed for the contacts of this collider to bounce, overriding
+     * `IntegrationParameters.restitutionThreshold`.
+     *
+     * Contacts approaching slower than this threshold get no restitution, which prevents slowly
+     * rolling or resting objects from micro-bouncing. If both colliders in contact have a
+     * threshold, the largest one is used. This is handled natively during the simulation step,
+     * without any physics hook.
+     *
+     * @param threshold - The minimum relative normal velocity for a contact to bounce.
+     */
+    public setRestitutionThreshold(threshold: number) {
+        this.colliderSet.raw.coSetRestitutionThreshold(this.handle, threshold);
+    }
+
+    /**
+     * Removes the restitution threshold override of this collider, so the one of the
+     * integration parameters applies again.
+     */
+    public removeRestitutionThreshold() {
+        this.colliderSet.raw.coRemoveRestitutionThreshold(this.handle);
+    }
+
+    /**
+     * The restitution threshold of this collider, or `null` if it uses the one of the
+     * integration parameters.
+     */
+    public restitutionThreshold(): number | null {
+        let threshold = this.colliderSet.raw.coRestitutionThreshold(
+            this.handle,
+        );
+        return threshold === undefined ? null : threshold;
+    }
+
     /**
      * The events active for this collider.
      */
@@ -414,6 +589,64 @@ export class Collider {
         return this.colliderSet.raw.coContactForceEventThreshold(this.handle);
     }
 
+    /**
+     * Enables or disables the accumulation of the contact impulses applied to this collider,
+     * e.g., to compute the wear of a tire or the damage caused by grinding against a surface
+     * without summing contact force events.
+     *
+     * The impulses are accumulated at the end of each timestep. Disabling the accumulation
+     * discards the impulses accumulated so far.
+     */
+    public setImpulseAccumulationEnabled(enabled: boolean) {
+        this.colliderSet.raw.coSetImpulseAccumulationEnabled(
+            this.handle,
+            enabled,
+        );
+    }
+
+    /**
+     * Are the contact impulses applied to this collider accumulated?
+     */
+    public isImpulseAccumulationEnabled(): boolean {
+        return this.colliderSet.raw.coIsImpulseAccumulationEnabled(this.handle);
+    }
+
+    /**
+     * The sum of the world-space normal impulses applied to this collider since the
+     * accumulation was enabled or last reset.
+     *
+     * Impulses in opposite directions cancel each other, so this tells the overall direction
+     * the collider has been pushed towards.
+     */
+    public accumulatedImpulse(): Vector {
+        return VectorOps.fromRaw(
+            this.colliderSet.raw.coAccumulatedImpulse(this.handle),
+        );
+    }
+
+    /**
+     * The sum of the magnitudes of the normal impulses applied to this collider since the
+     * accumulation was enabled or last reset.
+     */
+    public accumulatedNormalImpulse(): number {
+        return this.colliderSet.raw.coAccumulatedNormalImpulse(this.handle);
+    }
+
+    /**
+     * The sum of the magnitudes of the friction impulses applied to this collider since the
+     * accumulation was enabled or last reset.
+     */
+    public accumulatedFrictionImpulse(): number {
+        return this.colliderSet.raw.coAccumulatedFrictionImpulse(this.handle);
+    }
+
+    /**
+     * Resets the contact impulses accumulated for this collider.
+     */
+    public resetAccumulatedImpulse() {
+        this.colliderSet.raw.coResetAccumulatedImpulse(this.handle);
+    }
+
     /**
      * Set the collision types active for this collider.
      *
@@ -426,6 +659,39 @@ export class Collider {
         );
     }
 
+    /**
+     * Enables the given collision types for this collider, leaving its other active
+     * collision types unchanged.
+     *
+     * For example, a sensor attached to a kinematic rigid-body doesn’t detect fixed
+     * colliders by default. Calling `enableActiveCollisionTypes(ActiveCollisionTypes.KINEMATIC_FIXED)`
+     * on this sensor is enough to make it detect them: a pair of colliders is tested if
+     * at least one of them enables the corresponding collision type.
+     *
+     * @param activeCollisionTypes - The collision types to enable.
+     */
+    public enableActiveCollisionTypes(
+        activeCollisionTypes: ActiveCollisionTypes,
+    ) {
+        this.setActiveCollisionTypes(
+            this.activeCollisionTypes() | activeCollisionTypes,
+        );
+    }
+
+    /**
+     * Disables the given collision types for this collider, leaving its other active
+     * collision types unchanged.
+     *
+     * @param activeCollisionTypes - The collision types to disable.
+     */
+    public disableActiveCollisionTypes(
+        activeCollisionTypes: ActiveCollisionTypes,
+    ) {
+        this.setActiveCollisionTypes(
+            this.activeCollisionTypes() & ~activeCollisionTypes,
+        );
+    }
+
     /**
      * Sets the uniform density of this collider.
      *
@@ -532,8 +798,14 @@ export class Collider {
      * Does nothing if this collider isn't attached to a rigid-body.
      *
      * @param tra - The new translation of the collider relative to its parent.
+     * @param recomputeMassProperties - If `true`, the mass-properties of the parent rigid-body are
+     *   recomputed immediately. Otherwise, their update is deferred to the next call to
+     *   `World.recomputeMassProperties` or to the next physics step, whichever comes first.
      */
-    public setTranslationWrtParent(tra: Vector) {
+    public setTranslationWrtParent(
+        tra: Vector,
+        recomputeMassProperties: boolean = false,
+    ) {
         // #if DIM2
         this.colliderSet.raw.coSetTranslationWrtParent(
             this.handle,
@@ -549,6 +821,17 @@ export class Collider {
             tra.z,
         );
         // #endif
+        this.updateParentMassProperties(recomputeMassProperties);
+    }
+
+    private updateParentMassProperties(immediately: boolean) {
+        if (!this._parent) return;
+
+        if (immediately) {
+            this._parent.recomputeMassPropertiesFromColliders();
+        } else {
+            this.colliderSet.markMassPropertiesAsModified(this._parent.handle);
+        }
     }
 
     // #if DIM3
@@ -576,8 +859,14 @@ export class Collider {
      * attached to a rigid-body.
      *
      * @param rotation - The rotation to set.
+     * @param recomputeMassProperties - If `true`, the mass-properties of the parent rigid-body are
+     *   recomputed immediately. Otherwise, their update is deferred to the next call to
+     *   `World.recomputeMassProperties` or to the next physics step, whichever comes first.
      */
-    public setRotationWrtParent(rot: Rotation) {
+    public setRotationWrtParent(
+        rot: Rotation,
+        recomputeMassProperties: boolean = false,
+    ) {
         this.colliderSet.raw.coSetRotationWrtParent(
             this.handle,
             rot.x,
@@ -585,6 +874,7 @@ export class Collider {
             rot.z,
             rot.w,
         );
+        this.updateParentMassProperties(recomputeMassProperties);
     }
 
     // #endif
@@ -604,9 +894,16 @@ export class Collider {
      * Does nothing if this collider isn't attached to a rigid-body.
      *
      * @param angle - The rotation angle, in radians.
+     * @param recomputeMassProperties - If `true`, the mass-properties of the parent rigid-body are
+     *   recomputed immediately. Otherwise, their update is deferred to the next call to
+     *   `World.recomputeMassProperties` or to the next physics step, whichever comes first.
      */
-    public setRotationWrtParent(angle: number) {
+    public setRotationWrtParent(
+        angle: number,
+        recomputeMassProperties: boolean = false,
+    ) {
         this.colliderSet.raw.coSetRotationWrtParent(this.handle, angle);
+        this.updateParentMassProperties(recomputeMassProperties);
     }
 
     // #endif
@@ -839,6 +1136,70 @@ export class Collider {
         return this.colliderSet.raw.coHeightfieldHeights(this.handle);
     }
 
+    /**
+     * If this collider has a heightfield shape, this returns a read-only view of its
+     * heights aliasing the WASM memory, without copying them.
+     * In 3D, the height matrix is provided in column-major order.
+     *
+     * The view becomes invalid (detached or pointing to unrelated data) as soon as the
+     * WASM memory grows, which may happen during any call to the physics engine, and
+     * whenever the shape of this collider is replaced. It must thus be used immediately
+     * and never stored. The heights may be shared with other colliders and are not
+     * refreshed when modified through the view: use `setHeightfieldHeights` instead.
+     */
+    public heightfieldHeightsView(): Float32Array | undefined {
+        return this.colliderSet.raw.coHeightfieldHeightsView(this.handle);
+    }
+
+    /**
+     * If this collider has a triangle mesh shape, this returns a read-only view of its
+     * vertex buffer aliasing the WASM memory, without copying it.
+     *
+     * The view becomes invalid (detached or pointing to unrelated data) as soon as the
+     * WASM memory grows, which may happen during any call to the physics engine, and
+     * whenever the shape of this collider is replaced. It must thus be used immediately
+     * and never stored. The vertices may be shared with other colliders and are not
+     * refreshed when modified through the view: use `setTrimeshVertices` instead.
+     */
+    public trimeshVerticesView(): Float32Array | undefined {
+        return this.colliderSet.raw.coTrimeshVerticesView(this.handle);
+    }
+
+    /**
+     * If this collider has a heightfield shape, replaces its heights, keeping its
+     * dimensions, scale, and flags.
+     * In 3D, the height matrix is given in column-major order.
+     *
+     * If the shape is shared with other colliders (e.g. colliders created with
+     * `ColliderDesc.shared`), this collider gets its own copy of it first, so the other
+     * colliders are left unchanged.
+     *
+     * @param heights - The new heights, as many as the current ones.
+     * @returns `false` if the shape isn’t a heightfield, or if the number of heights
+     *   doesn’t match its dimensions.
+     */
+    public setHeightfieldHeights(heights: Float32Array): boolean {
+        this._shape = null;
+        return this.colliderSet.raw.coSetHeightfieldHeights(this.handle, heights);
+    }
+
+    /**
+     * If this collider has a triangle mesh shape, replaces its vertices, keeping its
+     * indices and flags.
+     *
+     * If the shape is shared with other colliders (e.g. colliders created with
+     * `ColliderDesc.shared`), this collider gets its own copy of it first, so the other
+     * colliders are left unchanged.
+     *
+     * @param vertices - The new coordinates of the vertices, as many as the current ones.
+     * @returns `false` if the shape isn’t a triangle mesh, if the number of vertices
+     *   changed, or if the modified triangle mesh is invalid.
+     */
+    public setTrimeshVertices(vertices: Float32Array): boolean {
+        this._shape = null;
+        return this.colliderSet.raw.coSetTrimeshVertices(this.handle, vertices);
+    }
+
     /**
      * If this collider has a heightfield shape, this returns the scale
      * applied to it.
@@ -902,6 +1263,32 @@ export class Collider {
         return this.colliderSet.raw.coMass(this.handle);
     }
 
+    /**
+     * The world-space axis-aligned bounding box of this collider.
+     */
+    public aabb(): Aabb {
+        return Aabb.fromRaw(this.colliderSet.raw.coAabb(this.handle));
+    }
+
+    /**
+     * The world-space bounding sphere of this collider.
+     */
+    public boundingSphere(): BoundingSphere {
+        return BoundingSphere.fromRaw(
+            this.colliderSet.raw.coBoundingSphere(this.handle),
+        );
+    }
+
+    /**
+     * The number of references to the shape of this collider.
+     *
+     * This is greater than 1 if the shape is shared (e.g. through a `SharedShape`)
+     * with other colliders or shapes.
+     */
+    public shapeRefCount(): number {
+        return this.colliderSet.raw.coShapeRefCount(this.handle);
+    }
+
     /**
      * The volume of this collider.
      */
@@ -1415,6 +1802,31 @@ export class ColliderDesc {
         return new ColliderDesc(shape);
     }
 
+    /**
+     * Creates a new collider descriptor with a compound shape.
+     *
+     * @param shapes - The sub-shapes of the compound shape.
+     * @param translations - The translation of each sub-shape relative to the compound shape.
+     * @param rotations - The rotation of each sub-shape relative to the compound shape.
+     */
+    public static compound(
+        shapes: Shape[],
+        translations: Vector[],
+        rotations: Rotation[],
+    ): ColliderDesc {
+        const shape = new Compound(shapes, translations, rotations);
+        return new ColliderDesc(shape);
+    }
+
+    /**
+     * Creates a new collider descriptor with a shape shared with other colliders.
+     *
+     * @param shape - The shared shape. Its internal data will not be duplicated.
+     */
+    public static shared(shape: SharedShape): ColliderDesc {
+        return new ColliderDesc(shape);
+    }
+
     // #if DIM2
     /**
      * Creates a new collider descriptor with a rectangular shape.
@@ -1643,12 +2055,41 @@ export class ColliderDesc {
      * to form a convex polyline (no convex-hull computation will be done).
      *
      * @param vertices - The vertices of the convex polyline.
+     * @param indices - The index buffer of the convex mesh.
+     * @param validate - If `true`, the convexity of the given mesh is checked, and the
+     *   collider creation fails if it isn't convex.
      */
     public static convexMesh(
         vertices: Float32Array,
         indices?: Uint32Array | null,
+        validate: boolean = false,
+    ): ColliderDesc | null {
+        const shape = new ConvexPolyhedron(vertices, indices, validate);
+        return new ColliderDesc(shape);
+    }
+
+    /**
+     * Creates a new collider descriptor that uses a convex polyhedron built from the
+     * given polygonal faces (no convex-hull computation will be done).
+     *
+     * @param vertices - The vertices of the convex polyhedron.
+     * @param faceIndices - The vertex indices of all the faces, concatenated. The vertices
+     *   of each face must be given in counter-clockwise order when seen from outside.
+     * @param faceSizes - The number of vertices of each face.
+     * @param validate - If `true`, `null` is returned if the faces don't describe a convex polyhedron.
+     */
+    public static convexMeshFromFaces(
+        vertices: Float32Array,
+        faceIndices: Uint32Array,
+        faceSizes: Uint32Array,
+        validate: boolean = true,
     ): ColliderDesc | null {
-        const shape = new ConvexPolyhedron(vertices, indices);
+        const shape = ConvexPolyhedron.fromFaces(
+            vertices,
+            faceIndices,
+            faceSizes,
+            validate,
+        );
         return new ColliderDesc(shape);
     }
 
@@ -1,9 +1,24 @@
 import {RawColliderSet} from "../raw";
 import {Coarena} from "../coarena";
 import {RotationOps, VectorOps} from "../math";
-import {Collider, ColliderDesc, ColliderHandle} from "./collider";
-import {ImpulseJointHandle, IslandManager, RigidBodyHandle} from "../dynamics";
+import {
+    ActiveCollisionTypes,
+    Collider,
+    ColliderDesc,
+    ColliderHandle,
+} from "./collider";
+import {
+    ImpulseJointHandle,
+    IslandManager,
+    RigidBody,
+    RigidBodyHandle,
+} from "../dynamics";
 import {RigidBodySet} from "../dynamics";
+import {ActiveHooks} from "../pipeline";
+// #if DIM3
+import {Vector} from "../math";
+import {WalkableSurfaces} from "./walkable_surfaces";
+// #endif
 
 /**
  * A set of rigid bodies that can be handled by a physics pipeline.
@@ -14,6 +29,7 @@ import {RigidBodySet} from "../dynamics";
 export class ColliderSet {
     raw: RawColliderSet;
     private map: Coarena<Collider>;
+    private pendingMassPropertiesUpdates: Set<RigidBodyHandle>;
 
     /**
      * Release the WASM memory occupied by this collider set.
@@ -33,6 +49,7 @@ export class ColliderSet {
     constructor(raw?: RawColliderSet) {
         this.raw = raw || new RawColliderSet();
         this.map = new Coarena<Collider>();
+        this.pendingMassPropertiesUpdates = new Set<RigidBodyHandle>();
         // Initialize the map with the existing elements, if any.
         if (raw) {
             raw.forEachColliderHandle((handle: ColliderHandle) => {
@@ -61,6 +78,19 @@ export class ColliderSet {
         );
     }
 
+    /**
+     * Internal method, do not call this explicitly.
+     *
+     * Recreates the collider objects after the handles of the raw set changed. They
+     * must be finalized with `finalizeDeserialization` afterwards.
+     */
+    public reloadFromRaw() {
+        this.map.clear();
+        this.raw.forEachColliderHandle((handle: ColliderHandle) => {
+            this.map.set(handle, new Collider(this, handle, null));
+        });
+    }
+
     /**
      * Creates a new collider and return its integer handle.
      *
@@ -143,6 +173,61 @@ export class ColliderSet {
         return collider;
     }
 
+    /**
+     * Inserts a copy of the given collider into another collider set.
+     *
+     * @param handle - The handle of the collider to copy.
+     * @param target - The collider set the copy is inserted into.
+     * @param targetBodies - The rigid-body set associated to `target`.
+     * @param parentHandle - The handle of the rigid-body from `targetBodies` the copy is
+     *   attached to, or `null` if the copy has no parent.
+     * @returns The copy, or `null` if this set doesn't contain the given collider.
+     */
+    public cloneInto(
+        handle: ColliderHandle,
+        target: ColliderSet,
+        targetBodies: RigidBodySet,
+        parentHandle: RigidBodyHandle | null,
+    ): Collider | null {
+        let copyHandle = this.raw.cloneInto(
+            handle,
+            target.raw,
+            targetBodies.raw,
+            parentHandle,
+        );
+        if (copyHandle === undefined) return null;
+
+        let parent =
+            parentHandle != null ? targetBodies.get(parentHandle) : null;
+        let collider = new Collider(target, copyHandle, parent);
+        target.map.set(copyHandle, collider);
+        return collider;
+    }
+
+    /**
+     * Attaches a collider to a new parent rigid-body, or detaches it from its parent.
+     *
+     * The mass properties of the previous and new parents are recomputed immediately. The
+     * parents can be links of a multibody, in which case the mass matrix of the multibody
+     * is updated at the next timestep.
+     *
+     * @param collider - The collider to attach.
+     * @param parent - The new parent rigid-body, or `null` to detach the collider.
+     * @param bodies - The set of rigid-bodies containing the previous and new parents.
+     */
+    public setParent(
+        collider: Collider,
+        parent: RigidBody | null,
+        bodies: RigidBodySet,
+    ) {
+        this.raw.setParent(
+            collider.handle,
+            parent ? parent.handle : null,
+            bodies.raw,
+        );
+        collider.updateParent(parent);
+    }
+
     /**
      * Remove a collider from this set.
      *
@@ -160,6 +245,34 @@ export class ColliderSet {
         this.unmap(handle);
     }
 
+    /**
+     * Removes several colliders from this set at once.
+     *
+     * This takes a single call into the physics engine, e.g., when unloading a whole region
+     * of the scene, and wakes up each affected rigid-body only once, but each collider is
+     * still removed individually from the broad-phase.
+     *
+     * @param handles - The handles of the colliders to remove. Handles of colliders that
+     *                  no longer exist are ignored.
+     * @param islands - The island manager of the world.
+     * @param bodies - The set of rigid-bodies containing the parents of the colliders.
+     * @param wakeUp - If `true`, the rigid-bodies the colliders are attached to are woken-up.
+     */
+    public removeBatch(
+        handles: ColliderHandle[],
+        islands: IslandManager,
+        bodies: RigidBodySet,
+        wakeUp: boolean,
+    ) {
+        this.raw.removeBatch(
+            new Float64Array(handles),
+            islands.raw,
+            bodies.raw,
+            wakeUp,
+        );
+        handles.forEach((handle) => this.unmap(handle));
+    }
+
     /**
      * Internal function, do not call directly.
      * @param handle
@@ -193,6 +306,90 @@ export class ColliderSet {
         return this.get(handle) != null;
     }
 
+    /**
+     * Internal method, do not call this explicitly.
+     *
+     * Marks the mass-properties of the given rigid-body as needing to be recomputed
+     * from its colliders.
+     */
+    public markMassPropertiesAsModified(parentHandle: RigidBodyHandle) {
+        this.pendingMassPropertiesUpdates.add(parentHandle);
+    }
+
+    /**
+     * Internal method, do not call this explicitly.
+     *
+     * Returns the handles of the rigid-bodies marked with `markMassPropertiesAsModified`
+     * and clears them.
+     */
+    public takePendingMassPropertiesUpdates(): RigidBodyHandle[] {
+        const result = Array.from(this.pendingMassPropertiesUpdates);
+        this.pendingMassPropertiesUpdates.clear();
+        return result;
+    }
+
+    /**
+     * Sets the physics hooks active for all the given colliders at once.
+     *
+     * @param handles - The handles of the colliders to modify.
+     * @param activeHooks - The hooks active for contact/intersection pairs involving these colliders.
+     */
+    public setActiveHooks(handles: ColliderHandle[], activeHooks: ActiveHooks) {
+        this.raw.coSetActiveHooksBulk(new Float64Array(handles), activeHooks);
+    }
+
+    /**
+     * Sets the collision types active for all the given colliders at once.
+     *
+     * @param handles - The handles of the colliders to modify.
+     * @param activeCollisionTypes - The new collision types of these colliders.
+     */
+    public setActiveCollisionTypes(
+        handles: ColliderHandle[],
+        activeCollisionTypes: ActiveCollisionTypes,
+    ) {
+        this.raw.coSetActiveCollisionTypesBulk(
+            new Float64Array(handles),
+            activeCollisionTypes,
+        );
+    }
+
+    /**
+     * Enables the given collision types for all the given colliders at once, leaving
+     * their other active collision types unchanged.
+     *
+     * @param handles - The handles of the colliders to modify.
+     * @param activeCollisionTypes - The collision types to enable.
+     */
+    public enableActiveCollisionTypes(
+        handles: ColliderHandle[],
+        activeCollisionTypes: ActiveCollisionTypes,
+    ) {
+        this.raw.coToggleActiveCollisionTypesBulk(
+            new Float64Array(handles),
+            activeCollisionTypes,
+            true,
+        );
+    }
+
+    /**
+     * Disables the given collision types for all the given colliders at once, leaving
+     * their other active collision types unchanged.
+     *
+     * @param handles - The handles of the colliders to modify.
+     * @param activeCollisionTypes - The collision types to disable.
+     */
+    public disableActiveCollisionTypes(
+        handles: ColliderHandle[],
+        activeCollisionTypes: ActiveCollisionTypes,
+    ) {
+        this.raw.coToggleActiveCollisionTypesBulk(
+            new Float64Array(handles),
+            activeCollisionTypes,
+            false,
+        );
+    }
+
     /**
      * Applies the given closure to each collider contained by this set.
      *
@@ -210,4 +407,41 @@ export class ColliderSet {
     public getAll(): Collider[] {
         return this.map.getAll();
     }
+
+    // #if DIM3
+    /**
+     * Extracts the triangles of the fixed colliders whose slope is small enough to be walked on.
+     *
+     * Colliders without parent, or attached to a fixed rigid-body, are tessellated, except
+     * sensors and disabled colliders. A triangle is walkable if the angle between its normal
+     * (given by its counter-clockwise winding) and `up` is at most `maxSlopeAngle`.
+     *
+     * @param bodies - The set of rigid-bodies the colliders are attached to.
+     * @param up - The unit vector pointing upward.
+     * @param maxSlopeAngle - The steepest walkable slope, in radians.
+     * @param weldDistance - The vertices closer than about this distance are merged. If zero,
+     *   only identical vertices are merged.
+     * @param subdivisions - The number of subdivisions used to tessellate curved shapes.
+     */
+    public walkableSurfaces(
+        bodies: RigidBodySet,
+        up: Vector,
+        maxSlopeAngle: number,
+        weldDistance: number,
+        subdivisions: number,
+    ): WalkableSurfaces {
+        let rawUp = VectorOps.intoRaw(up);
+        let result = WalkableSurfaces.fromRaw(
+            this.raw.walkableSurfaces(
+                bodies.raw,
+                rawUp,
+                maxSlopeAngle,
+                weldDistance,
+                subdivisions,
+            ),
+        );
+        rawUp.free();
+        return result;
+    }
+    // #endif
 }
@@ -9,3 +9,10 @@ export * from "./point";
 export * from "./toi";
 export * from "./interaction_groups";
 export * from "./contact";
+export * from "./bounding_volume";
+export * from "./trajectory";
+export * from "./query_batch";
+
+// #if DIM3
+export * from "./walkable_surfaces";
+// #endif
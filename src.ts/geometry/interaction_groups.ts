@@ -14,5 +14,12 @@
  * ```
  * ((a >> 16) & b) != 0 && ((b >> 16) & a) != 0
  * ```
+ *
+ * When given as the `filterGroups` of a scene query, the groups are tested on each collider
+ * whose bounding volume is reached by the broad-phase traversal, before the exact shape test
+ * and before calling any `filterPredicate`. The traversal itself is purely geometric: the
+ * bounding-volume hierarchy doesn't store group masks, so its nodes can't be skipped based
+ * on groups, and querying a sparse group still visits every region overlapping the query.
+ * `World.queryNumGroupTests` and `World.queryNumGroupRejections` count these tests.
  */
 export type InteractionGroups = number;
@@ -1,6 +1,10 @@
 import {RawNarrowPhase, RawContactManifold} from "../raw";
 import {ColliderHandle} from "./collider";
-import {Vector, VectorOps} from "../math";
+import {ColliderSet} from "./collider_set";
+import {RigidBodyHandle, RigidBodySet} from "../dynamics";
+import {ActiveHooks} from "../pipeline";
+import {FeatureType} from "./feature";
+import {Rotation, RotationOps, Vector, VectorOps} from "../math";
 
 /**
  * The narrow-phase used for precise collision-detection.
@@ -40,6 +44,49 @@ export class NarrowPhase {
         this.raw.contact_pairs_with(collider1, f);
     }
 
+    /**
+     * Iterates through all the contact manifolds between the colliders attached to two
+     * rigid-bodies.
+     *
+     * @param bodies - The set of rigid-bodies containing both rigid-bodies.
+     * @param body1 - The first rigid-body involved in the contacts.
+     * @param body2 - The second rigid-body involved in the contacts.
+     * @param f - Closure that will be called on each contact manifold between a collider
+     *            `collider1` attached to `body1` and a collider `collider2` attached to `body2`.
+     *            If the second argument passed to this closure is `true`, then the contact
+     *            manifold data is flipped, i.e., methods like `localNormal1` actually apply to
+     *            `collider2` and fields like `localNormal2` apply to `collider1`.
+     */
+    public contactsBetweenBodies(
+        bodies: RigidBodySet,
+        body1: RigidBodyHandle,
+        body2: RigidBodyHandle,
+        f: (
+            manifold: TempContactManifold,
+            flipped: boolean,
+            collider1: ColliderHandle,
+            collider2: ColliderHandle,
+        ) => void,
+    ) {
+        this.raw.contactsBetweenBodies(
+            bodies.raw,
+            body1,
+            body2,
+            (
+                rawManifold: RawContactManifold,
+                flipped: boolean,
+                collider1: ColliderHandle,
+                collider2: ColliderHandle,
+            ) => {
+                this.tempManifold.raw = rawManifold;
+                f(this.tempManifold, flipped, collider1, collider2);
+                // SAFETY: The RawContactManifold stores a raw pointer that will be invalidated
+                //         at the next timestep. So we must be sure to free it here.
+                this.tempManifold.free();
+            },
+        );
+    }
+
     /**
      * Enumerates all the colliders intersecting the given colliders, assuming one of them
      * is a sensor.
@@ -86,6 +133,78 @@ export class NarrowPhase {
         }
     }
 
+    /**
+     * Computes the contact manifolds between the given pair of colliders right now, without
+     * waiting for the next timestep, and independently from the contacts tracked by this
+     * narrow-phase.
+     *
+     * The computed manifolds only contain contact points: their solver contacts and impulses
+     * are empty.
+     *
+     * @param colliders - The set of colliders the two colliders belong to.
+     * @param collider1 - The first collider involved in the contact.
+     * @param collider2 - The second collider involved in the contact.
+     * @param prediction - The distance below which non-touching points are reported as contacts.
+     * @param f - Closure that will be called on each computed contact manifold.
+     * @param collider1Pos - If set, the contacts are computed as if `collider1` was at this
+     *   world-space translation instead of its current one.
+     * @param collider1Rot - If set, the contacts are computed as if `collider1` had this
+     *   world-space rotation instead of its current one.
+     * @returns `false` if one of the colliders doesn’t exist or contacts between their shapes
+     *   aren’t supported.
+     */
+    public computeContactNow(
+        colliders: ColliderSet,
+        collider1: ColliderHandle,
+        collider2: ColliderHandle,
+        prediction: number,
+        f: (manifold: TempContactManifold) => void,
+        collider1Pos?: Vector,
+        collider1Rot?: Rotation,
+    ): boolean {
+        let rawPair;
+        if (!!collider1Pos || collider1Rot != undefined) {
+            let co1 = colliders.get(collider1);
+            let rawPos = VectorOps.intoRaw(collider1Pos || co1.translation());
+            let rawRot = RotationOps.intoRaw(
+                collider1Rot != undefined ? collider1Rot : co1.rotation(),
+            );
+            rawPair = this.raw.computeContactNowAt(
+                colliders.raw,
+                collider1,
+                rawPos,
+                rawRot,
+                collider2,
+                prediction,
+            );
+            rawPos.free();
+            rawRot.free();
+        } else {
+            rawPair = this.raw.computeContactNow(
+                colliders.raw,
+                collider1,
+                collider2,
+                prediction,
+            );
+        }
+
+        if (!rawPair) {
+            return false;
+        }
+
+        for (let i = 0; i < rawPair.numContactManifolds(); ++i) {
+            this.tempManifold.raw = rawPair.contactManifold(i);
+            if (!!this.tempManifold.raw) {
+                f(this.tempManifold);
+            }
+            this.tempManifold.free();
+        }
+
+        // SAFETY: the manifolds are owned by the pair, so they must all be freed before it.
+        rawPair.free();
+        return true;
+    }
+
     /**
      * Returns `true` if `collider1` and `collider2` intersect and at least one of them is a sensor.
      * @param collider1 − The first collider involved in the intersection.
@@ -97,6 +216,25 @@ export class NarrowPhase {
     ): boolean {
         return this.raw.intersection_pair(collider1, collider2);
     }
+
+    /**
+     * The number of contact pairs for which the colliders involved request all the given hooks.
+     *
+     * This is useful to detect if some hooks (e.g. `ActiveHooks.MODIFY_SOLVER_CONTACTS`) are
+     * enabled on more pairs than expected.
+     *
+     * @param colliders - The set of colliders taking part in this narrow-phase.
+     * @param hooks - The hooks the contact pairs must request to be counted.
+     */
+    public numContactPairsWithActiveHooks(
+        colliders: ColliderSet,
+        hooks: ActiveHooks,
+    ): number {
+        return this.raw.num_contact_pairs_with_active_hooks(
+            colliders.raw,
+            hooks,
+        );
+    }
 }
 
 export class TempContactManifold {
@@ -125,10 +263,20 @@ export class TempContactManifold {
         return VectorOps.fromRaw(this.raw.local_n2());
     }
 
+    /**
+     * The index of the sub-shape of the first collider involved in this contact manifold.
+     *
+     * For triangle meshes and heightfields, this is the index of the triangle in contact.
+     */
     public subshape1(): number {
         return this.raw.subshape1();
     }
 
+    /**
+     * The index of the sub-shape of the second collider involved in this contact manifold.
+     *
+     * For triangle meshes and heightfields, this is the index of the triangle in contact.
+     */
     public subshape2(): number {
         return this.raw.subshape2();
     }
@@ -157,6 +305,36 @@ export class TempContactManifold {
         return this.raw.contact_fid2(i);
     }
 
+    /**
+     * The type of the feature (vertex, edge, or face) of the first sub-shape that generated
+     * the i-th contact.
+     */
+    public contactFeatureType1(i: number): FeatureType {
+        return this.raw.contact_feature_type1(i) as number as FeatureType;
+    }
+
+    /**
+     * The index of the feature of the first sub-shape that generated the i-th contact, if known.
+     */
+    public contactFeatureIndex1(i: number): number | undefined {
+        return this.raw.contact_feature_index1(i);
+    }
+
+    /**
+     * The type of the feature (vertex, edge, or face) of the second sub-shape that generated
+     * the i-th contact.
+     */
+    public contactFeatureType2(i: number): FeatureType {
+        return this.raw.contact_feature_type2(i) as number as FeatureType;
+    }
+
+    /**
+     * The index of the feature of the second sub-shape that generated the i-th contact, if known.
+     */
+    public contactFeatureIndex2(i: number): number | undefined {
+        return this.raw.contact_feature_index2(i);
+    }
+
     public contactImpulse(i: number): number {
         return this.raw.contact_impulse(i);
     }
@@ -200,4 +378,31 @@ export class TempContactManifold {
     public solverContactTangentVelocity(i: number): Vector {
         return VectorOps.fromRaw(this.raw.solver_contact_tangent_velocity(i));
     }
+
+    /**
+     * The velocity of the second rigid-body relative to the first one, at the location
+     * of the i-th solver contact. Colliders without parent are considered static.
+     *
+     * @param i - The index of the solver contact.
+     * @param bodies - The set of rigid-bodies the colliders of this manifold are attached to.
+     */
+    public relativeVelocityAt(i: number, bodies: RigidBodySet): Vector | null {
+        return VectorOps.fromRaw(this.raw.relative_velocity_at(i, bodies.raw));
+    }
+
+    /**
+     * The first friction direction used by the constraints solver for this manifold.
+     */
+    public tangent1(): Vector {
+        return VectorOps.fromRaw(this.raw.tangent1());
+    }
+
+    // #if DIM3
+    /**
+     * The second friction direction used by the constraints solver for this manifold.
+     */
+    public tangent2(): Vector {
+        return VectorOps.fromRaw(this.raw.tangent2());
+    }
+    // #endif
 }
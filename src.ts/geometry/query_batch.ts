@@ -0,0 +1,282 @@
+import {RawRayCastBatch, RawShapeIntersectionBatch} from "../raw";
+import {RigidBodyHandle, RigidBodySet} from "../dynamics";
+import {Rotation, Vector} from "../math";
+import {QueryFilterFlags} from "../pipeline";
+import {BroadPhase} from "./broad_phase";
+import {ColliderHandle} from "./collider";
+import {ColliderSet} from "./collider_set";
+import {InteractionGroups} from "./interaction_groups";
+import {NarrowPhase} from "./narrow_phase";
+import {Ray, RayColliderIntersection} from "./ray";
+import {Shape} from "./shape";
+
+/**
+ * A batch of ray-casts that can be spread across several frames.
+ *
+ * Each call to `resume` casts the next rays of the batch until the given budget is
+ * exhausted, and the batch remembers where to continue from.
+ *
+ * To avoid leaking WASM resources, this MUST be freed manually with `batch.free()`
+ * once you are done using it.
+ */
+export class RayCastBatch {
+    raw: RawRayCastBatch;
+
+    /**
+     * Release the WASM memory occupied by this batch.
+     */
+    public free() {
+        if (!!this.raw) {
+            this.raw.free();
+        }
+        this.raw = undefined;
+    }
+
+    /**
+     * Creates a new batch of ray-casts.
+     *
+     * @param rays - The rays to cast.
+     * @param maxToi - The maximum time-of-impact of the rays.
+     * @param solid - If `false` then the ray will attempt to hit the boundary of a shape, even if its
+     *   origin already lies inside of a shape. In other terms, `true` implies that all shapes are plain,
+     *   whereas `false` implies that all shapes are hollow for this ray-cast.
+     */
+    constructor(rays: Ray[], maxToi: number, solid: boolean) {
+        let origins = [];
+        let dirs = [];
+        rays.forEach((ray) => {
+            // #if DIM2
+            origins.push(ray.origin.x, ray.origin.y);
+            dirs.push(ray.dir.x, ray.dir.y);
+            // #endif
+            // #if DIM3
+            origins.push(ray.origin.x, ray.origin.y, ray.origin.z);
+            dirs.push(ray.dir.x, ray.dir.y, ray.dir.z);
+            // #endif
+        });
+
+        this.raw = new RawRayCastBatch(
+            new Float32Array(origins),
+            new Float32Array(dirs),
+            maxToi,
+            solid,
+        );
+    }
+
+    /**
+     * The number of rays in this batch.
+     */
+    public len(): number {
+        return this.raw.len();
+    }
+
+    /**
+     * The index of the next ray to cast.
+     */
+    public cursor(): number {
+        return this.raw.cursor();
+    }
+
+    /**
+     * Have all the rays of this batch been cast?
+     */
+    public isComplete(): boolean {
+        return this.raw.isComplete();
+    }
+
+    /**
+     * Forgets all the results, so the batch can be cast again from its first ray.
+     */
+    public reset() {
+        this.raw.reset();
+    }
+
+    /**
+     * Casts the next rays of this batch, until `maxRays` rays were cast or `maxTimeMs`
+     * milliseconds elapsed. At least one ray is cast by each call.
+     *
+     * @param maxRays - The maximum number of rays to cast, or `null` for no limit.
+     * @param maxTimeMs - The maximum duration of this call, in milliseconds, or `null` for no limit.
+     * @returns `true` if all the rays of the batch have been cast.
+     */
+    public resume(
+        broadPhase: BroadPhase,
+        narrowPhase: NarrowPhase,
+        bodies: RigidBodySet,
+        colliders: ColliderSet,
+        maxRays?: number,
+        maxTimeMs?: number,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: ColliderHandle,
+        filterExcludeRigidBody?: RigidBodyHandle,
+        filterPredicate?: (collider: ColliderHandle) => boolean,
+    ): boolean {
+        return this.raw.resume(
+            broadPhase.raw,
+            narrowPhase.raw,
+            bodies.raw,
+            colliders.raw,
+            maxRays,
+            maxTimeMs,
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider,
+            filterExcludeRigidBody,
+            filterPredicate,
+        );
+    }
+
+    /**
+     * The hit of the `i`-th ray, or `null` if it hasn't been cast yet or didn't hit anything.
+     *
+     * @param colliders - The set of colliders the ray was cast against.
+     * @param i - The index of the ray.
+     */
+    public hit(
+        colliders: ColliderSet,
+        i: number,
+    ): RayColliderIntersection | null {
+        return RayColliderIntersection.fromRaw(colliders, this.raw.hit(i));
+    }
+
+    /**
+     * The results of all the rays cast so far, packed as `[handle, toi, normal]` for each ray.
+     *
+     * The `handle` and `toi` of a ray that didn't hit anything are `NaN`, and its normal is zero.
+     * Each result takes 4 values in 2D and 5 values in 3D.
+     */
+    public hitsPacked(): Float64Array {
+        return this.raw.hitsPacked();
+    }
+}
+
+/**
+ * A batch of intersection tests between a shape, at several poses, and the colliders, that
+ * can be spread across several frames.
+ *
+ * Each call to `resume` tests the next poses of the batch until the given budget is
+ * exhausted, and the batch remembers where to continue from.
+ *
+ * To avoid leaking WASM resources, this MUST be freed manually with `batch.free()`
+ * once you are done using it.
+ */
+export class ShapeIntersectionBatch {
+    raw: RawShapeIntersectionBatch;
+
+    /**
+     * Release the WASM memory occupied by this batch.
+     */
+    public free() {
+        if (!!this.raw) {
+            this.raw.free();
+        }
+        this.raw = undefined;
+    }
+
+    /**
+     * Creates a new batch of intersection tests.
+     *
+     * @param shape - The shape to test.
+     * @param positions - The positions of the shape to test.
+     * @param rotations - The orientations of the shape to test, one for each position.
+     */
+    constructor(shape: Shape, positions: Vector[], rotations: Rotation[]) {
+        let rawPositions = [];
+        let rawRotations = [];
+        positions.forEach((pos, i) => {
+            let rot = rotations[i];
+            // #if DIM2
+            rawPositions.push(pos.x, pos.y);
+            rawRotations.push(rot);
+            // #endif
+            // #if DIM3
+            rawPositions.push(pos.x, pos.y, pos.z);
+            rawRotations.push(rot.x, rot.y, rot.z, rot.w);
+            // #endif
+        });
+
+        let rawShape = shape.intoRaw();
+        this.raw = new RawShapeIntersectionBatch(
+            rawShape,
+            new Float32Array(rawPositions),
+            new Float32Array(rawRotations),
+        );
+        rawShape.free();
+    }
+
+    /**
+     * The number of poses in this batch.
+     */
+    public len(): number {
+        return this.raw.len();
+    }
+
+    /**
+     * The index of the next pose to test.
+     */
+    public cursor(): number {
+        return this.raw.cursor();
+    }
+
+    /**
+     * Have all the poses of this batch been tested?
+     */
+    public isComplete(): boolean {
+        return this.raw.isComplete();
+    }
+
+    /**
+     * Forgets all the results, so the batch can be tested again from its first pose.
+     */
+    public reset() {
+        this.raw.reset();
+    }
+
+    /**
+     * Tests the next poses of this batch, until `maxTests` poses were tested or `maxTimeMs`
+     * milliseconds elapsed. At least one pose is tested by each call.
+     *
+     * @param maxTests - The maximum number of poses to test, or `null` for no limit.
+     * @param maxTimeMs - The maximum duration of this call, in milliseconds, or `null` for no limit.
+     * @returns `true` if all the poses of the batch have been tested.
+     */
+    public resume(
+        broadPhase: BroadPhase,
+        narrowPhase: NarrowPhase,
+        bodies: RigidBodySet,
+        colliders: ColliderSet,
+        maxTests?: number,
+        maxTimeMs?: number,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: ColliderHandle,
+        filterExcludeRigidBody?: RigidBodyHandle,
+        filterPredicate?: (collider: ColliderHandle) => boolean,
+    ): boolean {
+        return this.raw.resume(
+            broadPhase.raw,
+            narrowPhase.raw,
+            bodies.raw,
+            colliders.raw,
+            maxTests,
+            maxTimeMs,
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider,
+            filterExcludeRigidBody,
+            filterPredicate,
+        );
+    }
+
+    /**
+     * The handles of the colliders intersecting the shape at the `i`-th pose.
+     *
+     * Empty if this pose hasn't been tested yet.
+     *
+     * @param i - The index of the pose.
+     */
+    public intersections(i: number): ColliderHandle[] {
+        return Array.from(this.raw.intersections(i));
+    }
+}
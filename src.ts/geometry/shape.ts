@@ -5,6 +5,7 @@ import {PointProjection} from "./point";
 import {Ray, RayIntersection} from "./ray";
 import {ShapeCastHit} from "./toi";
 import {ColliderHandle} from "./collider";
+import {Aabb, BoundingSphere} from "./bounding_volume";
 
 export abstract class Shape {
     public abstract intoRaw(): RawShape;
@@ -197,6 +198,21 @@ export abstract class Shape {
                 return new RoundCone(halfHeight, radius, borderRadius);
             // #endif
 
+            case RawShapeType.Compound:
+                const parts = rawSet.coCompoundParts(handle);
+                const shapes: Shape[] = [];
+                const translations: Vector[] = [];
+                const rotations: Rotation[] = [];
+                parts.forEachColliderHandle((part: ColliderHandle) => {
+                    shapes.push(Shape.fromRaw(parts, part));
+                    translations.push(
+                        VectorOps.fromRaw(parts.coTranslation(part)),
+                    );
+                    rotations.push(RotationOps.fromRaw(parts.coRotation(part)));
+                });
+                parts.free();
+                return new Compound(shapes, translations, rotations);
+
             default:
                 throw new Error("unknown shape type: " + rawType);
         }
@@ -365,6 +381,26 @@ export abstract class Shape {
         return result;
     }
 
+    /**
+     * Computes the axis-aligned bounding box of this shape in its local-space.
+     */
+    public localAabb(): Aabb {
+        let rawShape = this.intoRaw();
+        let result = Aabb.fromRaw(rawShape.localAabb());
+        rawShape.free();
+        return result;
+    }
+
+    /**
+     * Computes the bounding sphere of this shape in its local-space.
+     */
+    public localBoundingSphere(): BoundingSphere {
+        let rawShape = this.intoRaw();
+        let result = BoundingSphere.fromRaw(rawShape.localBoundingSphere());
+        rawShape.free();
+        return result;
+    }
+
     containsPoint(
         shapePos: Vector,
         shapeRot: Rotation,
@@ -515,7 +551,7 @@ export enum ShapeType {
     Triangle = 5,
     TriMesh = 6,
     HeightField = 7,
-    // Compound = 8,
+    Compound = 8,
     ConvexPolygon = 9,
     RoundCuboid = 10,
     RoundTriangle = 11,
@@ -540,7 +576,7 @@ export enum ShapeType {
     Triangle = 5,
     TriMesh = 6,
     HeightField = 7,
-    // Compound = 8,
+    Compound = 8,
     ConvexPolyhedron = 9,
     Cylinder = 10,
     Cone = 11,
@@ -1108,6 +1144,145 @@ export class TriMesh extends Shape {
     }
 }
 
+/**
+ * A shape made of several other shapes, each with its own relative position.
+ */
+export class Compound extends Shape {
+    readonly type = ShapeType.Compound;
+
+    /**
+     * The sub-shapes of this compound shape.
+     */
+    shapes: Shape[];
+
+    /**
+     * The translation of each sub-shape relative to the compound shape.
+     */
+    translations: Vector[];
+
+    /**
+     * The rotation of each sub-shape relative to the compound shape.
+     */
+    rotations: Rotation[];
+
+    /**
+     * Creates a new compound shape.
+     *
+     * Use `SharedShape` sub-shapes to avoid duplicating their data when they are also
+     * used elsewhere. The sub-shapes must not be composite shapes themselves (compounds,
+     * triangle meshes, polylines, heightfields, or voxels).
+     *
+     * @param shapes - The sub-shapes of the compound shape.
+     * @param translations - The translation of each sub-shape relative to the compound shape.
+     * @param rotations - The rotation of each sub-shape relative to the compound shape.
+     */
+    constructor(
+        shapes: Shape[],
+        translations: Vector[],
+        rotations: Rotation[],
+    ) {
+        super();
+        this.shapes = shapes;
+        this.translations = translations;
+        this.rotations = rotations;
+    }
+
+    public intoRaw(): RawShape {
+        // #if DIM2
+        const poses = new Float32Array(this.shapes.length * 3);
+        this.shapes.forEach((_, i) => {
+            poses[i * 3] = this.translations[i].x;
+            poses[i * 3 + 1] = this.translations[i].y;
+            poses[i * 3 + 2] = this.rotations[i];
+        });
+        // #endif
+
+        // #if DIM3
+        const poses = new Float32Array(this.shapes.length * 7);
+        this.shapes.forEach((_, i) => {
+            poses[i * 7] = this.translations[i].x;
+            poses[i * 7 + 1] = this.translations[i].y;
+            poses[i * 7 + 2] = this.translations[i].z;
+            poses[i * 7 + 3] = this.rotations[i].x;
+            poses[i * 7 + 4] = this.rotations[i].y;
+            poses[i * 7 + 5] = this.rotations[i].z;
+            poses[i * 7 + 6] = this.rotations[i].w;
+        });
+        // #endif
+
+        // NOTE: the raw sub-shapes are consumed by `RawShape.compound`.
+        const rawShapes = this.shapes.map((shape) => shape.intoRaw());
+        const raw = RawShape.compound(rawShapes, poses);
+
+        if (!raw) {
+            throw new Error(
+                "invalid compound shape: it must have at least one " +
+                    "sub-shape, one translation and rotation per sub-shape, " +
+                    "and no composite sub-shapes (compounds, triangle " +
+                    "meshes, polylines, heightfields, or voxels)",
+            );
+        }
+
+        return raw;
+    }
+}
+
+/**
+ * A shape which internal WASM data is built only once and shared by all
+ * the colliders and compound shapes it is used for.
+ *
+ * This avoids duplicating the memory of large shapes (e.g. triangle meshes)
+ * used by several colliders.
+ *
+ * To avoid leaking WASM resources, this MUST be freed manually with `sharedShape.free()`
+ * once you are done using it. Colliders already created with this shape are not affected.
+ */
+export class SharedShape extends Shape {
+    raw: RawShape;
+
+    /**
+     * The shape this shared shape was built from.
+     */
+    readonly shape: Shape;
+
+    /**
+     * Builds the WASM data of the given shape so it can be shared.
+     *
+     * @param shape - The shape to share.
+     */
+    constructor(shape: Shape) {
+        super();
+        this.shape = shape;
+        this.raw = shape.intoRaw();
+    }
+
+    public get type(): ShapeType {
+        return this.shape.type;
+    }
+
+    /**
+     * Release the WASM memory occupied by this shared shape.
+     */
+    public free() {
+        if (!!this.raw) {
+            this.raw.free();
+        }
+        this.raw = undefined;
+    }
+
+    /**
+     * The number of references to the internal data of this shape, including this
+     * `SharedShape` and all the colliders and compound shapes using it.
+     */
+    public refCount(): number {
+        return this.raw.shapeRefCount();
+    }
+
+    public intoRaw(): RawShape {
+        return this.raw.shallowClone();
+    }
+}
+
 // #if DIM2
 /**
  * A shape that is a convex polygon.
@@ -1256,6 +1431,12 @@ export class ConvexPolyhedron extends Shape {
      */
     indices?: Uint32Array | null;
 
+    /**
+     * If `true`, the convexity of the mesh given by `indices` is checked when
+     * creating the shape, and the shape creation fails if it isn't convex.
+     */
+    validate: boolean;
+
     /**
      * Creates a new convex polygon shape.
      *
@@ -1264,16 +1445,63 @@ export class ConvexPolyhedron extends Shape {
      *   or `undefined`, the convex-hull of the input vertices will be computed
      *   automatically. Otherwise, it will be assumed that the mesh you provide
      *   is already convex.
+     * @param validate - If `true`, check that the mesh given by `indices` is
+     *   actually convex instead of assuming it is.
      */
-    constructor(vertices: Float32Array, indices?: Uint32Array | null) {
+    constructor(
+        vertices: Float32Array,
+        indices?: Uint32Array | null,
+        validate: boolean = false,
+    ) {
         super();
         this.vertices = vertices;
         this.indices = indices;
+        this.validate = validate;
+    }
+
+    /**
+     * Creates a convex polyhedron from a list of polygonal faces, preserving these
+     * faces instead of recomputing the convex-hull of the vertices.
+     *
+     * Each face is triangulated as a fan around its first vertex.
+     *
+     * @param vertices - The coordinates of the convex polyhedron's vertices.
+     * @param faceIndices - The vertex indices of all the faces, concatenated. The vertices
+     *   of each face must be given in counter-clockwise order when seen from outside.
+     * @param faceSizes - The number of vertices of each face.
+     * @param validate - If `true`, check that the faces actually describe a convex polyhedron.
+     */
+    public static fromFaces(
+        vertices: Float32Array,
+        faceIndices: Uint32Array,
+        faceSizes: Uint32Array,
+        validate: boolean = true,
+    ): ConvexPolyhedron {
+        let numTriangles = 0;
+        faceSizes.forEach((size) => (numTriangles += Math.max(size - 2, 0)));
+
+        let indices = new Uint32Array(numTriangles * 3);
+        let faceStart = 0;
+        let k = 0;
+        faceSizes.forEach((size) => {
+            for (let j = 1; j + 1 < size; ++j) {
+                indices[k++] = faceIndices[faceStart];
+                indices[k++] = faceIndices[faceStart + j];
+                indices[k++] = faceIndices[faceStart + j + 1];
+            }
+            faceStart += size;
+        });
+
+        return new ConvexPolyhedron(vertices, indices, validate);
     }
 
     public intoRaw(): RawShape {
         if (!!this.indices) {
-            return RawShape.convexMesh(this.vertices, this.indices);
+            return RawShape.convexMesh(
+                this.vertices,
+                this.indices,
+                this.validate,
+            );
         } else {
             return RawShape.convexHull(this.vertices);
         }
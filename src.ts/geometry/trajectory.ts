@@ -0,0 +1,78 @@
+import {Collider} from "./collider";
+import {Vector, VectorOps} from "../math";
+import {RawPredictedTrajectory} from "../raw";
+import {ColliderSet} from "./collider_set";
+
+/**
+ * The predicted path of a projectile, and the first collider it hits.
+ */
+export class PredictedTrajectory {
+    /**
+     * The positions sampled along the path. The first point is the starting position
+     * of the projectile. If it hit something, the last point is its position at the
+     * time of impact.
+     */
+    points: Vector[];
+    /**
+     * The collider hit by the projectile, or `null` if it didn't hit anything.
+     */
+    hitCollider: Collider | null;
+    /**
+     * The time, since the beginning of the trajectory, when the projectile hit `hitCollider`.
+     */
+    hitTime: number | null;
+    /**
+     * The position of the projectile at the time of impact.
+     */
+    hitPosition: Vector | null;
+    /**
+     * The world-space normal of `hitCollider` at the time of impact.
+     */
+    hitNormal: Vector | null;
+
+    constructor(
+        points: Vector[],
+        hitCollider: Collider | null,
+        hitTime: number | null,
+        hitPosition: Vector | null,
+        hitNormal: Vector | null,
+    ) {
+        this.points = points;
+        this.hitCollider = hitCollider;
+        this.hitTime = hitTime;
+        this.hitPosition = hitPosition;
+        this.hitNormal = hitNormal;
+    }
+
+    public static fromRaw(
+        colliderSet: ColliderSet,
+        raw: RawPredictedTrajectory,
+    ): PredictedTrajectory {
+        if (!raw) return null;
+
+        let coords = raw.points();
+        let points = [];
+        // #if DIM2
+        for (let i = 0; i < coords.length; i += 2) {
+            points.push(VectorOps.new(coords[i], coords[i + 1]));
+        }
+        // #endif
+        // #if DIM3
+        for (let i = 0; i < coords.length; i += 3) {
+            points.push(VectorOps.new(coords[i], coords[i + 1], coords[i + 2]));
+        }
+        // #endif
+
+        let result = raw.hasHit()
+            ? new PredictedTrajectory(
+                  points,
+                  colliderSet.get(raw.hitColliderHandle()),
+                  raw.hitTime(),
+                  VectorOps.fromRaw(raw.hitPosition()),
+                  VectorOps.fromRaw(raw.hitNormal()),
+              )
+            : new PredictedTrajectory(points, null, null, null, null);
+        raw.free();
+        return result;
+    }
+}
@@ -0,0 +1,29 @@
+import {RawWalkableSurfaces} from "../raw";
+
+/**
+ * The triangles of the fixed colliders that are flat enough to be walked on, merged into
+ * a single mesh, e.g., to be given to a navigation mesh baker.
+ */
+export class WalkableSurfaces {
+    /**
+     * The world-space vertices of the mesh, packed as a flat array of coordinates.
+     */
+    vertices: Float32Array;
+    /**
+     * The indices of the vertices of each triangle of the mesh, packed as a flat array.
+     */
+    indices: Uint32Array;
+
+    constructor(vertices: Float32Array, indices: Uint32Array) {
+        this.vertices = vertices;
+        this.indices = indices;
+    }
+
+    public static fromRaw(raw: RawWalkableSurfaces): WalkableSurfaces {
+        if (!raw) return null;
+
+        let result = new WalkableSurfaces(raw.vertices(), raw.indices());
+        raw.free();
+        return result;
+    }
+}
@@ -1,4 +1,4 @@
-import {RawVector, RawRotation} from "./raw";
+import {RawMassProperties, RawVector, RawRotation} from "./raw";
 // #if DIM3
 import {RawSdpMatrix3} from "./raw";
 // #endif
@@ -71,6 +71,31 @@ export class RotationOps {
     public static intoRaw(angle: Rotation): RawRotation {
         return RawRotation.fromAngle(angle);
     }
+
+    /**
+     * Wraps the given angle into the range `(-PI, PI]`.
+     *
+     * @param angle - The angle to normalize, in radians.
+     */
+    public static normalizeAngle(angle: Rotation): Rotation {
+        let res = angle % (2.0 * Math.PI);
+        if (res <= -Math.PI) {
+            res += 2.0 * Math.PI;
+        } else if (res > Math.PI) {
+            res -= 2.0 * Math.PI;
+        }
+        return res;
+    }
+
+    /**
+     * The signed angle of the shortest rotation from `from` to `to`, in the range `(-PI, PI]`.
+     *
+     * @param from - The starting angle, in radians.
+     * @param to - The target angle, in radians.
+     */
+    public static angleDifference(from: Rotation, to: Rotation): Rotation {
+        return RotationOps.normalizeAngle(to - from);
+    }
 }
 
 // #endif
@@ -261,3 +286,259 @@ export class SdpMatrix3Ops {
 }
 
 // #endif
+
+/**
+ * The mass-properties of a rigid-body, a collider, or any part of them.
+ *
+ * These can be computed, transformed, and combined without creating any collider, e.g.,
+ * for computing the explicit mass-properties given to `RigidBody.setAdditionalMassProperties`.
+ */
+export class MassProperties {
+    /**
+     * The mass.
+     */
+    mass: number;
+    /**
+     * The center-of-mass, in the local-space of these mass-properties.
+     */
+    centerOfMass: Vector;
+    // #if DIM2
+    /**
+     * The angular inertia relative to the center-of-mass.
+     */
+    principalInertia: number;
+    // #endif
+    // #if DIM3
+    /**
+     * The angular inertia along the principal inertia axes, relative to the center-of-mass.
+     */
+    principalInertia: Vector;
+    /**
+     * The local-space rotation of the principal inertia axes.
+     */
+    principalInertiaFrame: Rotation;
+    // #endif
+
+    // #if DIM2
+    constructor(mass: number, centerOfMass: Vector, principalInertia: number) {
+        this.mass = mass;
+        this.centerOfMass = centerOfMass;
+        this.principalInertia = principalInertia;
+    }
+    // #endif
+
+    // #if DIM3
+    constructor(
+        mass: number,
+        centerOfMass: Vector,
+        principalInertia: Vector,
+        principalInertiaFrame: Rotation,
+    ) {
+        this.mass = mass;
+        this.centerOfMass = centerOfMass;
+        this.principalInertia = principalInertia;
+        this.principalInertiaFrame = principalInertiaFrame;
+    }
+    // #endif
+
+    public static fromRaw(raw: RawMassProperties): MassProperties {
+        if (!raw) return null;
+
+        // #if DIM2
+        let res = new MassProperties(
+            raw.mass(),
+            VectorOps.fromRaw(raw.localCom()),
+            raw.principalInertia(),
+        );
+        // #endif
+        // #if DIM3
+        let res = new MassProperties(
+            raw.mass(),
+            VectorOps.fromRaw(raw.localCom()),
+            VectorOps.fromRaw(raw.principalInertia()),
+            RotationOps.fromRaw(raw.principalInertiaFrame()),
+        );
+        // #endif
+        raw.free();
+        return res;
+    }
+
+    public intoRaw(): RawMassProperties {
+        let rawCom = VectorOps.intoRaw(this.centerOfMass);
+        // #if DIM2
+        let res = new RawMassProperties(
+            rawCom,
+            this.mass,
+            this.principalInertia,
+        );
+        // #endif
+        // #if DIM3
+        let rawInertia = VectorOps.intoRaw(this.principalInertia);
+        let rawFrame = RotationOps.intoRaw(this.principalInertiaFrame);
+        let res = new RawMassProperties(
+            rawCom,
+            this.mass,
+            rawInertia,
+            rawFrame,
+        );
+        rawInertia.free();
+        rawFrame.free();
+        // #endif
+        rawCom.free();
+        return res;
+    }
+
+    /**
+     * The mass-properties of a ball.
+     *
+     * @param density - The density of the ball.
+     * @param radius - The radius of the ball.
+     */
+    public static ball(density: number, radius: number): MassProperties {
+        return MassProperties.fromRaw(RawMassProperties.ball(density, radius));
+    }
+
+    /**
+     * The mass-properties of a cuboid centered at the origin.
+     *
+     * @param density - The density of the cuboid.
+     * @param halfExtents - The half-extents of the cuboid.
+     */
+    public static cuboid(density: number, halfExtents: Vector): MassProperties {
+        let rawHalfExtents = VectorOps.intoRaw(halfExtents);
+        let res = MassProperties.fromRaw(
+            RawMassProperties.cuboid(density, rawHalfExtents),
+        );
+        rawHalfExtents.free();
+        return res;
+    }
+
+    /**
+     * The mass-properties of a capsule centered at the origin and aligned with the `y` axis.
+     *
+     * @param density - The density of the capsule.
+     * @param halfHeight - The half-height of the capsule, without its round caps.
+     * @param radius - The radius of the capsule.
+     */
+    public static capsule(
+        density: number,
+        halfHeight: number,
+        radius: number,
+    ): MassProperties {
+        return MassProperties.fromRaw(
+            RawMassProperties.capsule(density, halfHeight, radius),
+        );
+    }
+
+    // #if DIM3
+    /**
+     * The mass-properties of a cylinder centered at the origin and aligned with the `y` axis.
+     *
+     * @param density - The density of the cylinder.
+     * @param halfHeight - The half-height of the cylinder.
+     * @param radius - The radius of the cylinder.
+     */
+    public static cylinder(
+        density: number,
+        halfHeight: number,
+        radius: number,
+    ): MassProperties {
+        return MassProperties.fromRaw(
+            RawMassProperties.cylinder(density, halfHeight, radius),
+        );
+    }
+
+    /**
+     * The mass-properties of a cone centered at the origin and aligned with the `y` axis.
+     *
+     * @param density - The density of the cone.
+     * @param halfHeight - The half-height of the cone.
+     * @param radius - The radius of the cone's base.
+     */
+    public static cone(
+        density: number,
+        halfHeight: number,
+        radius: number,
+    ): MassProperties {
+        return MassProperties.fromRaw(
+            RawMassProperties.cone(density, halfHeight, radius),
+        );
+    }
+
+    /**
+     * The full angular inertia tensor relative to the center-of-mass, in the local-space
+     * of these mass-properties.
+     */
+    public angularInertia(): SdpMatrix3 {
+        let raw = this.intoRaw();
+        let res = SdpMatrix3Ops.fromRaw(raw.angularInertia());
+        raw.free();
+        return res;
+    }
+    // #endif
+
+    /**
+     * These mass-properties expressed in another frame.
+     *
+     * @param translation - The translation of the part described by these mass-properties, in the new frame.
+     * @param rotation - The rotation of the part described by these mass-properties, in the new frame.
+     */
+    public transformBy(
+        translation: Vector,
+        rotation: Rotation,
+    ): MassProperties {
+        let raw = this.intoRaw();
+        let rawTra = VectorOps.intoRaw(translation);
+        let rawRot = RotationOps.intoRaw(rotation);
+        let res = MassProperties.fromRaw(raw.transformBy(rawTra, rawRot));
+        raw.free();
+        rawTra.free();
+        rawRot.free();
+        return res;
+    }
+
+    /**
+     * The mass-properties of the union of the parts described by `this` and `other`.
+     *
+     * Both mass-properties must be expressed in the same frame.
+     *
+     * @param other - The mass-properties to add to these.
+     */
+    public add(other: MassProperties): MassProperties {
+        return MassProperties.sum([this, other]);
+    }
+
+    /**
+     * The mass-properties of the union of all the given parts.
+     *
+     * All the mass-properties must be expressed in the same frame.
+     *
+     * @param parts - The mass-properties of each part.
+     */
+    public static sum(parts: MassProperties[]): MassProperties {
+        if (parts.length == 0) {
+            // #if DIM2
+            return new MassProperties(0.0, VectorOps.zeros(), 0.0);
+            // #endif
+            // #if DIM3
+            return new MassProperties(
+                0.0,
+                VectorOps.zeros(),
+                VectorOps.zeros(),
+                RotationOps.identity(),
+            );
+            // #endif
+        }
+
+        let acc = parts[0].intoRaw();
+        for (let i = 1; i < parts.length; ++i) {
+            let part = parts[i].intoRaw();
+            let next = acc.add(part);
+            acc.free();
+            part.free();
+            acc = next;
+        }
+
+        return MassProperties.fromRaw(acc);
+    }
+}
@@ -0,0 +1,98 @@
+import {RawContactMaterialTable} from "../raw";
+import {ColliderHandle} from "../geometry";
+
+/**
+ * A table resolving a tag (e.g. an impact sound identifier) for each contact force event,
+ * from the materials of the two colliders involved and the magnitude of the contact force.
+ *
+ * The tags are computed while draining the events with `EventQueue.drainContactForceEvents`,
+ * and read with `TempContactForceEvent.materialTag`.
+ *
+ * To avoid leaking WASM resources, this MUST be freed manually with `table.free()`
+ * once you are done using it, unless it is the table owned by a `World`.
+ */
+export class ContactMaterialTable {
+    raw: RawContactMaterialTable;
+
+    /**
+     * Release the WASM memory occupied by this table.
+     */
+    public free() {
+        if (!!this.raw) {
+            this.raw.free();
+        }
+        this.raw = undefined;
+    }
+
+    constructor(raw?: RawContactMaterialTable) {
+        this.raw = raw || new RawContactMaterialTable();
+    }
+
+    /**
+     * Sets the material identifier of the given collider.
+     *
+     * @param collider - The handle of the collider.
+     * @param material - The material identifier.
+     */
+    public setColliderMaterial(collider: ColliderHandle, material: number) {
+        this.raw.setColliderMaterial(collider, material);
+    }
+
+    /**
+     * The material identifier of the given collider, or `undefined` if none was set.
+     *
+     * @param collider - The handle of the collider.
+     */
+    public colliderMaterial(collider: ColliderHandle): number | undefined {
+        return this.raw.colliderMaterial(collider);
+    }
+
+    /**
+     * Removes the material identifier of the given collider.
+     *
+     * @param collider - The handle of the collider.
+     */
+    public removeColliderMaterial(collider: ColliderHandle) {
+        this.raw.removeColliderMaterial(collider);
+    }
+
+    /**
+     * Sets the tags given to the contact force events between colliders with the two given
+     * materials (in any order).
+     *
+     * The tag `tags[i]` is given to events with a total force magnitude greater than or equal
+     * to `thresholds[i]`, and smaller than the next threshold. Events with a force magnitude
+     * smaller than all the thresholds, or between colliders without material, are given the
+     * tag `0`.
+     *
+     * @param material1 - The first material identifier.
+     * @param material2 - The second material identifier.
+     * @param thresholds - The lowest force magnitude of each bucket.
+     * @param tags - The tag of each bucket.
+     * @returns `false` (and does nothing) if `thresholds` and `tags` don't have the same length.
+     */
+    public setTags(
+        material1: number,
+        material2: number,
+        thresholds: number[],
+        tags: number[],
+    ): boolean {
+        return this.raw.setTags(
+            material1,
+            material2,
+            new Float32Array(thresholds),
+            new Uint32Array(tags),
+        );
+    }
+
+    /**
+     * Removes the tags given to the contact force events between colliders with the two
+     * given materials.
+     *
+     * @param material1 - The first material identifier.
+     * @param material2 - The second material identifier.
+     */
+    public removeTags(material1: number, material2: number) {
+        this.raw.removeTags(material1, material2);
+    }
+}
@@ -1,7 +1,8 @@
 import {RawContactForceEvent, RawEventQueue} from "../raw";
 import {RigidBodyHandle} from "../dynamics";
-import {Collider, ColliderHandle} from "../geometry";
+import {Collider, ColliderHandle, ColliderSet} from "../geometry";
 import {Vector, VectorOps} from "../math";
+import {ContactMaterialTable} from "./contact_material_table";
 
 /**
  * Flags indicating what events are enabled for colliders.
@@ -80,12 +81,43 @@ export class TempContactForceEvent {
     public maxForceMagnitude(): number {
         return this.raw.max_force_magnitude();
     }
+
+    /**
+     * The tag resolved by the `ContactMaterialTable` given to `EventQueue.drainContactForceEvents`,
+     * or `0` if no table was given or none of its entries matched.
+     */
+    public materialTag(): number {
+        return this.raw.material_tag();
+    }
+
+    /**
+     * The index of the step, since the last drain of the contact force events, that
+     * generated this event. Always `0` if the queue is automatically drained.
+     */
+    public stepIndex(): number {
+        return this.raw.step_index();
+    }
+
+    /**
+     * The sequence number of this event, see `EventQueue`.
+     */
+    public sequenceNumber(): number {
+        return this.raw.sequence_number();
+    }
 }
 
 /**
  * A structure responsible for collecting events generated
  * by the physics engine.
  *
+ * Each collision and contact force event is given a sequence number when it is collected
+ * at the end of a step. Sequence numbers are shared by both kinds of events, and increase
+ * monotonically over the lifetime of the queue: they are not reset when the queue is
+ * drained or cleared. Within one step, the collision events are numbered before the contact
+ * force events. Events are always drained by increasing sequence number, so they can be
+ * merged back into a single stable order, e.g., for deterministic replays. Coalesced
+ * collision events and cleared events leave gaps in the sequence.
+ *
  * To avoid leaking WASM resources, this MUST be freed manually with `eventQueue.free()`
  * once you are done using it.
  */
@@ -114,39 +146,157 @@ export class EventQueue {
         this.raw = undefined;
     }
 
+    /**
+     * Sets whether a collision that starts and stops, or stops and starts again, between two
+     * drains of this queue is reported as two events, or removed from the queue.
+     *
+     * Collisions are detected again after each CCD substep (see `World.maxCcdSubsteps`), so a
+     * fast rigid-body can start and stop touching a collider during different substeps of a
+     * single step: these events are coalesced whether the queue is automatically drained or
+     * not. If `autoDrain` is `false`, coalescing also spans all the steps since the last drain,
+     * e.g., when stepping the world several times per frame: contacts lasting less than a
+     * frame are then not reported at all.
+     *
+     * @param coalesce - If `true`, collisions that start and stop, or stop and start again,
+     *   between two drains are removed.
+     */
+    public setCoalesceCollisionEvents(coalesce: boolean) {
+        this.raw.setCoalesceCollisionEvents(coalesce);
+    }
+
+    /**
+     * Whether collisions that start and stop, or stop and start again, between two drains of
+     * this queue are removed (default: `false`).
+     */
+    public coalesceCollisionEvents(): boolean {
+        return this.raw.coalesceCollisionEvents();
+    }
+
     /**
      * Applies the given javascript closure on each collision event of this collector, then clear
      * the internal collision event buffer.
      *
+     * The substep index of an event is the index of the CCD substep, within its step, that
+     * generated it: `0` for the collisions detected at the beginning of the step, then
+     * increasing with each later substep. Substeps are told apart by the motion of the active
+     * CCD-enabled rigid-bodies, and of the rigid-bodies involved in the previous events of the
+     * step, so a substep that moved none of them and generated no event isn't counted.
+     *
      * @param f - JavaScript closure applied to each collision event. The
-     * closure must take three arguments: two integers representing the handles of the colliders
-     * involved in the collision, and a boolean indicating if the collision started (true) or stopped
-     * (false).
+     * closure must take six arguments: two integers representing the handles of the colliders
+     * involved in the collision, a boolean indicating if the collision started (true) or stopped
+     * (false), the index of the step, since the last drain, that generated the event, the
+     * sequence number of the event, and the index of its CCD substep. The step index is always
+     * `0` if the queue is automatically drained.
      */
     public drainCollisionEvents(
         f: (
             handle1: ColliderHandle,
             handle2: ColliderHandle,
             started: boolean,
+            stepIndex: number,
+            seq: number,
+            substepIndex: number,
         ) => void,
     ) {
         this.raw.drainCollisionEvents(f);
     }
 
+    /**
+     * Applies the given javascript closure on each collision event of this collector, with
+     * additional information about the colliders involved, then clear the internal collision
+     * event buffer.
+     *
+     * If only one of the two colliders is a sensor, it is always given as the first collider,
+     * so intersection events with a sensor can be handled without any extra getter call.
+     *
+     * @param colliders - The set of colliders the events were generated for.
+     * @param f - JavaScript closure applied to each collision event. It is given the handles of the
+     *   two colliders, whether the collision started (`true`) or stopped (`false`), whether each
+     *   collider is a sensor, the handles of the rigid-bodies the colliders are attached to (or
+     *   `null`), whether the event was caused by the removal of one of the colliders, the index
+     *   of the step, since the last drain, that generated the event, the sequence number of the
+     *   event, and the index of its CCD substep (see `drainCollisionEvents`). The sensor flags are only set if the event involved a sensor when it was
+     *   generated, so intersection events of removed sensors are still reported as such. The
+     *   parents of removed colliders are reported as `null`.
+     */
+    public drainCollisionEventsWithColliderInfo(
+        colliders: ColliderSet,
+        f: (
+            handle1: ColliderHandle,
+            handle2: ColliderHandle,
+            started: boolean,
+            sensor1: boolean,
+            sensor2: boolean,
+            body1: RigidBodyHandle | null,
+            body2: RigidBodyHandle | null,
+            removed: boolean,
+            stepIndex: number,
+            seq: number,
+            substepIndex: number,
+        ) => void,
+    ) {
+        this.raw.drainCollisionEventsWithColliderInfo(colliders.raw, f);
+    }
+
     /**
      * Applies the given javascript closure on each contact force event of this collector, then clear
      * the internal collision event buffer.
      *
      * @param f - JavaScript closure applied to each collision event. The
      *            closure must take one `TempContactForceEvent` argument.
+     * @param materialTable - If provided, the tag of each event is resolved from this table
+     *            and can be read with `TempContactForceEvent.materialTag`.
      */
-    public drainContactForceEvents(f: (event: TempContactForceEvent) => void) {
+    public drainContactForceEvents(
+        f: (event: TempContactForceEvent) => void,
+        materialTable?: ContactMaterialTable,
+    ) {
         let event = new TempContactForceEvent();
-        this.raw.drainContactForceEvents((raw: RawContactForceEvent) => {
+        let callback = (raw: RawContactForceEvent) => {
             event.raw = raw;
             f(event);
             event.free();
-        });
+        };
+
+        if (!!materialTable) {
+            this.raw.drainContactForceEventsWithMaterialTags(
+                materialTable.raw,
+                callback,
+            );
+        } else {
+            this.raw.drainContactForceEvents(callback);
+        }
+    }
+
+    /**
+     * The number of rigid-bodies that woke up since the last drain of the sleep events.
+     */
+    public numBodiesWokenUp(): number {
+        return this.raw.numBodiesWokenUp();
+    }
+
+    /**
+     * The number of rigid-bodies that fell asleep since the last drain of the sleep events.
+     */
+    public numBodiesFellAsleep(): number {
+        return this.raw.numBodiesFellAsleep();
+    }
+
+    /**
+     * Applies the given javascript closure on each rigid-body that fell asleep or woke up
+     * during a simulation step, then clear the internal sleep event buffer.
+     *
+     * Rigid-bodies created since the previous step aren't reported as woken up when they
+     * become active for the first time.
+     *
+     * @param f - JavaScript closure applied to each sleep event. It is given the handle of
+     *   the rigid-body, and `true` if it fell asleep or `false` if it woke up.
+     */
+    public drainSleepEvents(
+        f: (handle: RigidBodyHandle, sleeping: boolean) => void,
+    ) {
+        this.raw.drainSleepEvents(f);
     }
 
     /**
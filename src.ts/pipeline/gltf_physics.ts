@@ -0,0 +1,1128 @@
+import {RawJointAxis, RawMotorModel} from "../raw";
+import {Quaternion, Rotation, Vector, Vector3} from "../math";
+import {
+    CoefficientCombineRule,
+    ImpulseJoint,
+    JointAxesMask,
+    JointData,
+    RigidBody,
+    RigidBodyDesc,
+    RigidBodyType,
+} from "../dynamics";
+import {
+    Ball,
+    Capsule,
+    Collider,
+    ColliderDesc,
+    Cone,
+    ConvexPolyhedron,
+    Cuboid,
+    Cylinder,
+    InteractionGroups,
+    Shape,
+    ShapeType,
+    TriMesh,
+} from "../geometry";
+import {World} from "./world";
+
+const RIGID_BODIES_EXT = "KHR_physics_rigid_bodies";
+const IMPLICIT_SHAPES_EXT = "KHR_implicit_shapes";
+
+const GLB_MAGIC = 0x46546c67;
+const GLB_CHUNK_JSON = 0x4e4f534a;
+const GLB_CHUNK_BIN = 0x004e4942;
+const GL_FLOAT = 5126;
+const GL_UNSIGNED_BYTE = 5121;
+const GL_UNSIGNED_SHORT = 5123;
+const GL_UNSIGNED_INT = 5125;
+
+const LIN_AXES = JointAxesMask.LinX | JointAxesMask.LinY | JointAxesMask.LinZ;
+const ALL_GROUPS = 0xffffffff;
+// The largest finite 32-bits float, used for the unbounded side of joint limits.
+const UNBOUNDED = 3.4e38;
+
+interface Pose {
+    translation: Vector;
+    rotation: Rotation;
+}
+
+function rotate(q: Rotation, v: Vector): Vector {
+    let tx = 2.0 * (q.y * v.z - q.z * v.y);
+    let ty = 2.0 * (q.z * v.x - q.x * v.z);
+    let tz = 2.0 * (q.x * v.y - q.y * v.x);
+    return new Vector3(
+        v.x + q.w * tx + (q.y * tz - q.z * ty),
+        v.y + q.w * ty + (q.z * tx - q.x * tz),
+        v.z + q.w * tz + (q.x * ty - q.y * tx),
+    );
+}
+
+function mulRotations(a: Rotation, b: Rotation): Rotation {
+    return new Quaternion(
+        a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+        a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+        a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+        a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+    );
+}
+
+function mulPoses(a: Pose, b: Pose): Pose {
+    let t = rotate(a.rotation, b.translation);
+    return {
+        translation: new Vector3(
+            a.translation.x + t.x,
+            a.translation.y + t.y,
+            a.translation.z + t.z,
+        ),
+        rotation: mulRotations(a.rotation, b.rotation),
+    };
+}
+
+function invPose(a: Pose): Pose {
+    let r = a.rotation;
+    let inv = new Quaternion(-r.x, -r.y, -r.z, r.w);
+    let t = rotate(inv, a.translation);
+    return {
+        translation: new Vector3(-t.x, -t.y, -t.z),
+        rotation: inv,
+    };
+}
+
+function vec(v: Vector): number[] {
+    return [v.x, v.y, v.z];
+}
+
+function quat(q: Rotation): number[] {
+    return [q.x, q.y, q.z, q.w];
+}
+
+function align4(len: number): number {
+    return (len + 3) & ~3;
+}
+
+const COMBINE_RULES = ["average", "minimum", "multiply", "maximum"];
+
+function combineRuleName(rule: CoefficientCombineRule): string {
+    return COMBINE_RULES[rule];
+}
+
+function combineRule(name: string | undefined): CoefficientCombineRule {
+    let i = COMBINE_RULES.indexOf(name);
+    return i >= 0
+        ? (i as CoefficientCombineRule)
+        : CoefficientCombineRule.Average;
+}
+
+/**
+ * Builds the glTF document and binary buffer describing a physics world.
+ */
+class GltfPhysicsExporter {
+    nodes: any[] = [];
+    roots: number[] = [];
+    shapes: any[] = [];
+    meshes: any[] = [];
+    accessors: any[] = [];
+    bufferViews: any[] = [];
+    materials: any[] = [];
+    filters: any[] = [];
+    joints: any[] = [];
+    binParts: Uint8Array[] = [];
+    binLength = 0;
+    materialIds = new Map<string, number>();
+    filterIds = new Map<number, number>();
+
+    addNode(node: any, parent: number | null): number {
+        let id = this.nodes.length;
+        this.nodes.push(node);
+        if (parent === null) {
+            this.roots.push(id);
+        } else {
+            let p = this.nodes[parent];
+            p.children = p.children || [];
+            p.children.push(id);
+        }
+        return id;
+    }
+
+    addBufferView(data: Uint8Array, target: number): number {
+        let id = this.bufferViews.length;
+        this.bufferViews.push({
+            buffer: 0,
+            byteOffset: this.binLength,
+            byteLength: data.byteLength,
+            target,
+        });
+        let padded = new Uint8Array(align4(data.byteLength));
+        padded.set(data);
+        this.binParts.push(padded);
+        this.binLength += padded.byteLength;
+        return id;
+    }
+
+    addMesh(vertices: Float32Array, indices: Uint32Array | null): number {
+        let min = [Infinity, Infinity, Infinity];
+        let max = [-Infinity, -Infinity, -Infinity];
+        for (let i = 0; i < vertices.length; ++i) {
+            min[i % 3] = Math.min(min[i % 3], vertices[i]);
+            max[i % 3] = Math.max(max[i % 3], vertices[i]);
+        }
+
+        let positions = this.accessors.length;
+        this.accessors.push({
+            bufferView: this.addBufferView(
+                new Uint8Array(
+                    vertices.buffer,
+                    vertices.byteOffset,
+                    vertices.byteLength,
+                ),
+                34962,
+            ),
+            componentType: GL_FLOAT,
+            count: vertices.length / 3,
+            type: "VEC3",
+            min,
+            max,
+        });
+
+        let primitive: any = {attributes: {POSITION: positions}};
+        if (!!indices) {
+            primitive.indices = this.accessors.length;
+            primitive.mode = 4;
+            this.accessors.push({
+                bufferView: this.addBufferView(
+                    new Uint8Array(
+                        indices.buffer,
+                        indices.byteOffset,
+                        indices.byteLength,
+                    ),
+                    34963,
+                ),
+                componentType: GL_UNSIGNED_INT,
+                count: indices.length,
+                type: "SCALAR",
+            });
+        } else {
+            // Only the vertices of a convex hull are needed.
+            primitive.mode = 0;
+        }
+
+        this.meshes.push({primitives: [primitive]});
+        return this.meshes.length - 1;
+    }
+
+    addShape(shape: any): number {
+        this.shapes.push(shape);
+        return this.shapes.length - 1;
+    }
+
+    /**
+     * The geometry of a collider node, or `null` if its shape can't be represented.
+     */
+    geometry(shape: Shape, node: any, nodeId: number): any {
+        switch (shape.type) {
+            case ShapeType.Ball:
+                return {
+                    shape: this.addShape({
+                        type: "sphere",
+                        sphere: {radius: (shape as Ball).radius},
+                    }),
+                };
+            case ShapeType.Cuboid:
+                let he = (shape as Cuboid).halfExtents;
+                return {
+                    shape: this.addShape({
+                        type: "box",
+                        box: {size: [he.x * 2.0, he.y * 2.0, he.z * 2.0]},
+                    }),
+                };
+            case ShapeType.Capsule:
+                let capsule = shape as Capsule;
+                return {
+                    shape: this.addShape({
+                        type: "capsule",
+                        capsule: {
+                            height: capsule.halfHeight * 2.0,
+                            radiusTop: capsule.radius,
+                            radiusBottom: capsule.radius,
+                        },
+                    }),
+                };
+            case ShapeType.Cylinder:
+            case ShapeType.Cone:
+                let cylinder = shape as Cylinder | Cone;
+                return {
+                    shape: this.addShape({
+                        type: "cylinder",
+                        cylinder: {
+                            height: cylinder.halfHeight * 2.0,
+                            radiusTop:
+                                shape.type == ShapeType.Cone
+                                    ? 0.0
+                                    : cylinder.radius,
+                            radiusBottom: cylinder.radius,
+                        },
+                    }),
+                };
+            case ShapeType.TriMesh:
+                let trimesh = shape as TriMesh;
+                node.mesh = this.addMesh(trimesh.vertices, trimesh.indices);
+                return {node: nodeId, convexHull: false};
+            case ShapeType.ConvexPolyhedron:
+                let convex = shape as ConvexPolyhedron;
+                node.mesh = this.addMesh(convex.vertices, null);
+                return {node: nodeId, convexHull: true};
+            default:
+                return null;
+        }
+    }
+
+    material(collider: Collider): number {
+        let friction = collider.friction();
+        let restitution = collider.restitution();
+        let frictionCombine = collider.frictionCombineRule();
+        let restitutionCombine = collider.restitutionCombineRule();
+        let key = [
+            friction,
+            restitution,
+            frictionCombine,
+            restitutionCombine,
+        ].join("/");
+
+        if (!this.materialIds.has(key)) {
+            this.materialIds.set(key, this.materials.length);
+            this.materials.push({
+                staticFriction: friction,
+                dynamicFriction: friction,
+                restitution,
+                frictionCombine: combineRuleName(frictionCombine),
+                restitutionCombine: combineRuleName(restitutionCombine),
+            });
+        }
+
+        return this.materialIds.get(key);
+    }
+
+    filter(groups: InteractionGroups): number | undefined {
+        groups = groups >>> 0;
+        if (groups == ALL_GROUPS) {
+            return undefined;
+        }
+
+        if (!this.filterIds.has(groups)) {
+            let systems = (bits: number) => {
+                let names: string[] = [];
+                for (let i = 0; i < 16; ++i) {
+                    if ((bits & (1 << i)) != 0) {
+                        names.push("group" + i);
+                    }
+                }
+                return names;
+            };
+
+            this.filterIds.set(groups, this.filters.length);
+            this.filters.push({
+                collisionSystems: systems(groups >>> 16),
+                collideWithSystems: systems(groups & 0xffff),
+            });
+        }
+
+        return this.filterIds.get(groups);
+    }
+
+    addBody(body: RigidBody): number {
+        let node: any = {
+            name: "body" + body.handle,
+            translation: vec(body.translation()),
+            rotation: quat(body.rotation()),
+        };
+
+        if (body.bodyType() != RigidBodyType.Fixed) {
+            node.extensions = {
+                [RIGID_BODIES_EXT]: {
+                    motion: {
+                        isKinematic: body.isKinematic(),
+                        mass: body.mass(),
+                        centerOfMass: vec(body.localCom()),
+                        inertiaDiagonal: vec(body.principalInertia()),
+                        inertiaOrientation: quat(
+                            body.principalInertiaLocalFrame(),
+                        ),
+                        linearVelocity: vec(body.linvel()),
+                        angularVelocity: vec(body.angvel()),
+                        gravityFactor: body.gravityScale(),
+                    },
+                },
+            };
+        }
+
+        return this.addNode(node, null);
+    }
+
+    addCollider(collider: Collider, parent: number | null) {
+        let node: any = {name: "collider" + collider.handle};
+        if (parent === null) {
+            node.translation = vec(collider.translation());
+            node.rotation = quat(collider.rotation());
+        } else {
+            node.translation = vec(collider.translationWrtParent());
+            node.rotation = quat(collider.rotationWrtParent());
+        }
+
+        let nodeId = this.addNode(node, parent);
+        let geometry = this.geometry(collider.shape, node, nodeId);
+        if (!geometry) {
+            return;
+        }
+
+        let collisionFilter = this.filter(collider.collisionGroups());
+        node.extensions = {
+            [RIGID_BODIES_EXT]: collider.isSensor()
+                ? {trigger: {geometry, collisionFilter}}
+                : {
+                      collider: {
+                          geometry,
+                          physicsMaterial: this.material(collider),
+                          collisionFilter,
+                      },
+                  },
+        };
+    }
+
+    addJoint(
+        world: World,
+        joint: ImpulseJoint,
+        bodyNodes: Map<number, number>,
+    ) {
+        let raw = world.impulseJoints.raw;
+        let handle = joint.handle;
+        let locked = raw.jointLockedAxes(handle);
+        let coupled = raw.jointCoupledAxes(handle);
+        let limits: any[] = [];
+        let drives: any[] = [];
+
+        let linear = (mask: number) =>
+            [0, 1, 2].filter((i) => (mask & (1 << i)) != 0);
+        let angular = (mask: number) =>
+            [0, 1, 2].filter((i) => (mask & (1 << (i + 3))) != 0);
+
+        if (linear(locked).length > 0) {
+            limits.push({linearAxes: linear(locked), min: 0.0, max: 0.0});
+        }
+        if (angular(locked).length > 0) {
+            limits.push({angularAxes: angular(locked), min: 0.0, max: 0.0});
+        }
+
+        for (let i = 0; i < 6; ++i) {
+            let axis = i as RawJointAxis;
+            let bit = 1 << i;
+            if ((locked & bit) != 0 || ((coupled & bit) != 0 && i != 0)) {
+                continue;
+            }
+
+            let axes =
+                (coupled & bit) != 0
+                    ? {linearAxes: linear(coupled)}
+                    : i < 3
+                    ? {linearAxes: [i]}
+                    : {angularAxes: [i - 3]};
+
+            if (raw.jointLimitsEnabled(handle, axis)) {
+                limits.push({
+                    ...axes,
+                    min: raw.jointLimitsMin(handle, axis),
+                    max: raw.jointLimitsMax(handle, axis),
+                });
+            }
+
+            if (raw.jointMotorEnabled(handle, axis)) {
+                let targetPos = raw.jointMotorTargetPos(handle, axis);
+                if ((coupled & bit) != 0) {
+                    // A spring joint.
+                    limits.push({
+                        ...axes,
+                        min: targetPos,
+                        max: targetPos,
+                        stiffness: raw.jointMotorStiffness(handle, axis),
+                        damping: raw.jointMotorDamping(handle, axis),
+                    });
+                } else {
+                    let maxForce = raw.jointMotorMaxForce(handle, axis);
+                    drives.push({
+                        type: i < 3 ? "linear" : "angular",
+                        mode:
+                            raw.jointMotorModel(handle, axis) ==
+                            RawMotorModel.ForceBased
+                                ? "force"
+                                : "acceleration",
+                        axis: i % 3,
+                        maxForce: isFinite(maxForce) ? maxForce : undefined,
+                        positionTarget: targetPos,
+                        velocityTarget: raw.jointMotorTargetVel(handle, axis),
+                        stiffness: raw.jointMotorStiffness(handle, axis),
+                        damping: raw.jointMotorDamping(handle, axis),
+                    });
+                }
+            }
+        }
+
+        this.joints.push({limits, drives});
+        let frame1 = this.addNode(
+            {
+                name: "joint" + handle,
+                translation: vec(joint.anchor1()),
+                rotation: quat(joint.frameX1()),
+            },
+            // World-space constraints are attached to a hidden anchor at the origin.
+            world.impulseJoints.isWorldConstraint(world.bodies, handle)
+                ? null
+                : bodyNodes.get(joint.body1().handle),
+        );
+        let frame2 = this.addNode(
+            {
+                name: "joint" + handle + "_connected",
+                translation: vec(joint.anchor2()),
+                rotation: quat(joint.frameX2()),
+            },
+            bodyNodes.get(joint.body2().handle),
+        );
+        this.nodes[frame1].extensions = {
+            [RIGID_BODIES_EXT]: {
+                joint: {
+                    connectedNode: frame2,
+                    joint: this.joints.length - 1,
+                    enableCollision: joint.contactsEnabled(),
+                },
+            },
+        };
+    }
+
+    document(): any {
+        let doc: any = {
+            asset: {version: "2.0", generator: "rapier.js"},
+            extensionsUsed: [RIGID_BODIES_EXT, IMPLICIT_SHAPES_EXT],
+            extensions: {
+                [IMPLICIT_SHAPES_EXT]: {shapes: this.shapes},
+                [RIGID_BODIES_EXT]: {
+                    physicsMaterials: this.materials,
+                    collisionFilters: this.filters,
+                    physicsJoints: this.joints,
+                },
+            },
+            scene: 0,
+            scenes: [{nodes: this.roots}],
+            nodes: this.nodes,
+        };
+
+        if (this.meshes.length > 0) {
+            doc.meshes = this.meshes;
+            doc.accessors = this.accessors;
+            doc.bufferViews = this.bufferViews;
+            doc.buffers = [{byteLength: this.binLength}];
+        }
+
+        return doc;
+    }
+
+    glb(): Uint8Array {
+        let json = new TextEncoder().encode(JSON.stringify(this.document()));
+        let jsonLength = align4(json.byteLength);
+        let binChunkLength = this.binLength > 0 ? 8 + this.binLength : 0;
+        let totalLength = 12 + 8 + jsonLength + binChunkLength;
+
+        let result = new Uint8Array(totalLength);
+        let view = new DataView(result.buffer);
+        view.setUint32(0, GLB_MAGIC, true);
+        view.setUint32(4, 2, true);
+        view.setUint32(8, totalLength, true);
+        view.setUint32(12, jsonLength, true);
+        view.setUint32(16, GLB_CHUNK_JSON, true);
+        result.set(json, 20);
+        // The JSON chunk must be padded with spaces.
+        result.fill(0x20, 20 + json.byteLength, 20 + jsonLength);
+
+        if (this.binLength > 0) {
+            let offset = 20 + jsonLength;
+            view.setUint32(offset, this.binLength, true);
+            view.setUint32(offset + 4, GLB_CHUNK_BIN, true);
+            offset += 8;
+            for (let part of this.binParts) {
+                result.set(part, offset);
+                offset += part.byteLength;
+            }
+        }
+
+        return result;
+    }
+}
+
+/**
+ * Exports the rigid-bodies, colliders, and impulse joints of a world as a binary glTF
+ * (GLB) buffer using the `KHR_physics_rigid_bodies` and `KHR_implicit_shapes` extensions.
+ *
+ * Balls, cuboids, capsules, cylinders and cones are exported as implicit shapes. Triangle
+ * meshes and convex polyhedra are exported as meshes. Colliders with any other shape, and
+ * multibody joints, are not exported.
+ */
+export function exportGltfPhysics(world: World): Uint8Array {
+    let exporter = new GltfPhysicsExporter();
+    let bodyNodes = new Map<number, number>();
+
+    world.bodies.forEach((body) => {
+        bodyNodes.set(body.handle, exporter.addBody(body));
+    });
+    world.colliders.forEach((collider) => {
+        let parent = collider.parent();
+        exporter.addCollider(
+            collider,
+            !!parent ? bodyNodes.get(parent.handle) : null,
+        );
+    });
+    world.impulseJoints.forEach((joint) => {
+        exporter.addJoint(world, joint, bodyNodes);
+    });
+
+    return exporter.glb();
+}
+
+/**
+ * Creates the rigid-bodies, colliders, and joints of a glTF document into a world.
+ */
+class GltfPhysicsImporter {
+    doc: any;
+    bin: Uint8Array | null;
+    world: World;
+    poses: Pose[] = [];
+    parents: number[] = [];
+    bodies = new Map<number, RigidBody>();
+    fullMassProps = new Set<number>();
+    fixedBody: RigidBody | null = null;
+    systemBits = new Map<string, number>();
+
+    constructor(doc: any, bin: Uint8Array | null, world: World) {
+        this.doc = doc;
+        this.bin = bin;
+        this.world = world;
+    }
+
+    ext(node: number): any {
+        let extensions = this.doc.nodes[node].extensions;
+        return !!extensions ? extensions[RIGID_BODIES_EXT] : undefined;
+    }
+
+    docExt(name: string): any {
+        return (!!this.doc.extensions && this.doc.extensions[name]) || {};
+    }
+
+    /**
+     * Computes the world-space pose of each node. Node scales are ignored.
+     */
+    computePoses() {
+        let nodes: any[] = this.doc.nodes || [];
+        let isChild = new Set<number>();
+        nodes.forEach((node, i) => {
+            (node.children || []).forEach((child: number) => {
+                this.parents[child] = i;
+                isChild.add(child);
+            });
+        });
+
+        let scenes = this.doc.scenes;
+        let roots: number[] =
+            !!scenes && scenes.length > 0
+                ? scenes[this.doc.scene || 0].nodes || []
+                : nodes.map((_, i) => i).filter((i) => !isChild.has(i));
+
+        let visit = (i: number, parentPose: Pose) => {
+            let node = nodes[i];
+            let t = node.translation || [0.0, 0.0, 0.0];
+            let r = node.rotation || [0.0, 0.0, 0.0, 1.0];
+            let pose = mulPoses(parentPose, {
+                translation: new Vector3(t[0], t[1], t[2]),
+                rotation: new Quaternion(r[0], r[1], r[2], r[3]),
+            });
+            this.poses[i] = pose;
+            (node.children || []).forEach((child: number) =>
+                visit(child, pose),
+            );
+        };
+
+        let identity = {
+            translation: new Vector3(0.0, 0.0, 0.0),
+            rotation: new Quaternion(0.0, 0.0, 0.0, 1.0),
+        };
+        roots.forEach((i) => visit(i, identity));
+    }
+
+    /**
+     * The closest node with a rigid-body, among the given node and its ancestors.
+     */
+    owner(node: number): number | undefined {
+        for (let i = node; i !== undefined; i = this.parents[i]) {
+            if (this.bodies.has(i)) {
+                return i;
+            }
+        }
+        return undefined;
+    }
+
+    /**
+     * The pose of a node relative to its owner rigid-body, and that rigid-body.
+     */
+    localPose(node: number): [Pose, RigidBody | null] {
+        let owner = this.owner(node);
+        if (owner === undefined) {
+            return [this.poses[node], null];
+        }
+        return [
+            mulPoses(invPose(this.poses[owner]), this.poses[node]),
+            this.bodies.get(owner),
+        ];
+    }
+
+    createBodies() {
+        this.poses.forEach((pose, i) => {
+            let ext = this.ext(i);
+            if (!ext || !ext.motion) {
+                return;
+            }
+
+            let motion = ext.motion;
+            let desc = !!motion.isKinematic
+                ? RigidBodyDesc.kinematicVelocityBased()
+                : RigidBodyDesc.dynamic();
+            let linvel = motion.linearVelocity || [0.0, 0.0, 0.0];
+            let angvel = motion.angularVelocity || [0.0, 0.0, 0.0];
+            desc.setTranslation(
+                pose.translation.x,
+                pose.translation.y,
+                pose.translation.z,
+            )
+                .setRotation(pose.rotation)
+                .setLinvel(linvel[0], linvel[1], linvel[2])
+                .setAngvel(new Vector3(angvel[0], angvel[1], angvel[2]))
+                .setGravityScale(
+                    motion.gravityFactor !== undefined
+                        ? motion.gravityFactor
+                        : 1.0,
+                );
+
+            if (motion.mass !== undefined && !!motion.inertiaDiagonal) {
+                let com = motion.centerOfMass || [0.0, 0.0, 0.0];
+                let inertia = motion.inertiaDiagonal;
+                let frame = motion.inertiaOrientation || [0.0, 0.0, 0.0, 1.0];
+                desc.setAdditionalMassProperties(
+                    motion.mass,
+                    new Vector3(com[0], com[1], com[2]),
+                    new Vector3(inertia[0], inertia[1], inertia[2]),
+                    new Quaternion(frame[0], frame[1], frame[2], frame[3]),
+                );
+                this.fullMassProps.add(i);
+            }
+
+            this.bodies.set(i, this.world.createRigidBody(desc));
+        });
+    }
+
+    groups(filterId: number | undefined): InteractionGroups {
+        let filters = this.docExt(RIGID_BODIES_EXT).collisionFilters || [];
+        let filter = filterId !== undefined ? filters[filterId] : undefined;
+        if (!filter) {
+            return ALL_GROUPS;
+        }
+
+        let bits = (names: string[] | undefined) => {
+            let result = 0;
+            (names || []).forEach((name) => {
+                if (!this.systemBits.has(name)) {
+                    let match = /^group(\d+)$/.exec(name);
+                    let bit =
+                        !!match && +match[1] < 16
+                            ? +match[1]
+                            : this.systemBits.size % 16;
+                    this.systemBits.set(name, bit);
+                }
+                result |= 1 << this.systemBits.get(name);
+            });
+            return result;
+        };
+
+        let memberships = !!filter.collisionSystems
+            ? bits(filter.collisionSystems)
+            : 0xffff;
+        let collideWith = !!filter.collideWithSystems
+            ? bits(filter.collideWithSystems)
+            : !!filter.notCollideWithSystems
+            ? ~bits(filter.notCollideWithSystems) & 0xffff
+            : 0xffff;
+        return ((memberships << 16) | collideWith) >>> 0;
+    }
+
+    accessor(id: number): Float32Array | Uint32Array {
+        let accessor = this.doc.accessors[id];
+        let view = this.doc.bufferViews[accessor.bufferView];
+        let components = accessor.type == "VEC3" ? 3 : 1;
+        let componentSize =
+            accessor.componentType == GL_UNSIGNED_BYTE
+                ? 1
+                : accessor.componentType == GL_UNSIGNED_SHORT
+                ? 2
+                : 4;
+        let stride = view.byteStride || components * componentSize;
+        let offset = (view.byteOffset || 0) + (accessor.byteOffset || 0);
+        let data = new DataView(
+            this.bin.buffer,
+            this.bin.byteOffset,
+            this.bin.byteLength,
+        );
+
+        let result =
+            accessor.componentType == GL_FLOAT
+                ? new Float32Array(accessor.count * components)
+                : new Uint32Array(accessor.count * components);
+        for (let i = 0; i < accessor.count; ++i) {
+            for (let k = 0; k < components; ++k) {
+                let at = offset + i * stride + k * componentSize;
+                result[i * components + k] =
+                    accessor.componentType == GL_FLOAT
+                        ? data.getFloat32(at, true)
+                        : componentSize == 1
+                        ? data.getUint8(at)
+                        : componentSize == 2
+                        ? data.getUint16(at, true)
+                        : data.getUint32(at, true);
+            }
+        }
+        return result;
+    }
+
+    /**
+     * The collider descriptor of the given geometry, expressed in the frame of `node`.
+     */
+    colliderDesc(geometry: any, node: number): ColliderDesc | null {
+        if (!geometry) {
+            return null;
+        }
+
+        if (geometry.shape !== undefined) {
+            let shape = this.docExt(IMPLICIT_SHAPES_EXT).shapes[geometry.shape];
+            switch (shape.type) {
+                case "sphere":
+                    return ColliderDesc.ball(shape.sphere.radius);
+                case "box":
+                    let size = shape.box.size;
+                    return ColliderDesc.cuboid(
+                        size[0] / 2.0,
+                        size[1] / 2.0,
+                        size[2] / 2.0,
+                    );
+                case "capsule":
+                    return ColliderDesc.capsule(
+                        shape.capsule.height / 2.0,
+                        Math.max(
+                            shape.capsule.radiusTop,
+                            shape.capsule.radiusBottom,
+                        ),
+                    );
+                case "cylinder":
+                    let cylinder = shape.cylinder;
+                    if (
+                        cylinder.radiusTop == 0.0 &&
+                        cylinder.radiusBottom > 0.0
+                    ) {
+                        return ColliderDesc.cone(
+                            cylinder.height / 2.0,
+                            cylinder.radiusBottom,
+                        );
+                    }
+                    return ColliderDesc.cylinder(
+                        cylinder.height / 2.0,
+                        Math.max(cylinder.radiusTop, cylinder.radiusBottom),
+                    );
+                default:
+                    return null;
+            }
+        }
+
+        if (geometry.node === undefined || !this.bin) {
+            return null;
+        }
+
+        let meshId = this.doc.nodes[geometry.node].mesh;
+        if (meshId === undefined) {
+            return null;
+        }
+
+        // Express the vertices in the frame of the collider node.
+        let toCollider = mulPoses(
+            invPose(this.poses[node]),
+            this.poses[geometry.node],
+        );
+        let vertices: number[] = [];
+        let indices: number[] = [];
+        this.doc.meshes[meshId].primitives.forEach((primitive: any) => {
+            let base = vertices.length / 3;
+            let positions = this.accessor(primitive.attributes.POSITION);
+            for (let i = 0; i < positions.length; i += 3) {
+                let p = mulPoses(toCollider, {
+                    translation: new Vector3(
+                        positions[i],
+                        positions[i + 1],
+                        positions[i + 2],
+                    ),
+                    rotation: new Quaternion(0.0, 0.0, 0.0, 1.0),
+                }).translation;
+                vertices.push(p.x, p.y, p.z);
+            }
+
+            if (primitive.indices !== undefined) {
+                this.accessor(primitive.indices).forEach((i: number) =>
+                    indices.push(base + i),
+                );
+            } else {
+                for (let i = base; i < vertices.length / 3; ++i) {
+                    indices.push(i);
+                }
+            }
+        });
+
+        return !!geometry.convexHull
+            ? ColliderDesc.convexHull(new Float32Array(vertices))
+            : ColliderDesc.trimesh(
+                  new Float32Array(vertices),
+                  new Uint32Array(indices),
+              );
+    }
+
+    createColliders() {
+        let materials = this.docExt(RIGID_BODIES_EXT).physicsMaterials || [];
+        let pending: [ColliderDesc, RigidBody | null, number | undefined][] =
+            [];
+        let numColliders = new Map<number, number>();
+
+        this.poses.forEach((_, i) => {
+            let ext = this.ext(i);
+            let spec = !!ext ? ext.collider || ext.trigger : undefined;
+            let desc = !!spec ? this.colliderDesc(spec.geometry, i) : null;
+            if (!desc) {
+                return;
+            }
+
+            let [pose, body] = this.localPose(i);
+            desc.setTranslation(
+                pose.translation.x,
+                pose.translation.y,
+                pose.translation.z,
+            )
+                .setRotation(pose.rotation)
+                .setSensor(!ext.collider)
+                .setCollisionGroups(this.groups(spec.collisionFilter));
+
+            let material =
+                spec.physicsMaterial !== undefined
+                    ? materials[spec.physicsMaterial]
+                    : undefined;
+            if (!!material) {
+                let friction =
+                    material.dynamicFriction !== undefined
+                        ? material.dynamicFriction
+                        : material.staticFriction;
+                if (friction !== undefined) {
+                    desc.setFriction(friction);
+                }
+                if (material.restitution !== undefined) {
+                    desc.setRestitution(material.restitution);
+                }
+                desc.setFrictionCombineRule(
+                    combineRule(material.frictionCombine),
+                );
+                desc.setRestitutionCombineRule(
+                    combineRule(material.restitutionCombine),
+                );
+            }
+
+            let owner = this.owner(i);
+            if (owner !== undefined) {
+                numColliders.set(owner, (numColliders.get(owner) || 0) + 1);
+            }
+            pending.push([desc, body, owner]);
+        });
+
+        pending.forEach(([desc, body, owner]) => {
+            if (owner !== undefined && !desc.isSensor) {
+                let motion = this.ext(owner).motion;
+                if (this.fullMassProps.has(owner)) {
+                    desc.setDensity(0.0);
+                } else if (motion.mass !== undefined) {
+                    // Spread the mass of the rigid-body between its colliders.
+                    desc.setMass(motion.mass / numColliders.get(owner));
+                }
+            }
+            this.world.createCollider(desc, body);
+        });
+    }
+
+    jointBody(body: RigidBody | null): RigidBody {
+        if (!!body) {
+            return body;
+        }
+        if (!this.fixedBody) {
+            this.fixedBody = this.world.createRigidBody(RigidBodyDesc.fixed());
+        }
+        return this.fixedBody;
+    }
+
+    configureJoint(joint: ImpulseJoint, spec: any) {
+        let raw = this.world.impulseJoints.raw;
+        let handle = joint.handle;
+        let locked = 0;
+        let coupled = 0;
+
+        (spec.limits || []).forEach((limit: any) => {
+            let axes = (limit.linearAxes || [])
+                .map((i: number) => i)
+                .concat((limit.angularAxes || []).map((i: number) => i + 3));
+            let mask = axes.reduce((m: number, i: number) => m | (1 << i), 0);
+            let min = limit.min !== undefined ? limit.min : -UNBOUNDED;
+            let max = limit.max !== undefined ? limit.max : UNBOUNDED;
+
+            if (min == 0.0 && max == 0.0 && !limit.stiffness) {
+                locked |= mask;
+            } else if (axes.length == 1) {
+                raw.jointSetLimits(handle, axes[0] as RawJointAxis, min, max);
+            } else if (mask == LIN_AXES) {
+                // A limit on the distance between the joint frames.
+                coupled |= LIN_AXES;
+                if (!!limit.stiffness) {
+                    raw.jointConfigureMotorModel(
+                        handle,
+                        RawJointAxis.LinX,
+                        RawMotorModel.ForceBased,
+                    );
+                    raw.jointConfigureMotor(
+                        handle,
+                        RawJointAxis.LinX,
+                        limit.max !== undefined ? max : Math.max(min, 0.0),
+                        0.0,
+                        limit.stiffness,
+                        limit.damping || 0.0,
+                    );
+                } else {
+                    raw.jointSetLimits(
+                        handle,
+                        RawJointAxis.LinX,
+                        Math.max(min, 0.0),
+                        max,
+                    );
+                }
+            }
+        });
+
+        raw.jointSetLockedAxes(handle, locked);
+        raw.jointSetCoupledAxes(handle, coupled);
+
+        (spec.drives || []).forEach((drive: any) => {
+            let axis = (drive.axis +
+                (drive.type == "angular" ? 3 : 0)) as RawJointAxis;
+            raw.jointConfigureMotorModel(
+                handle,
+                axis,
+                drive.mode == "acceleration"
+                    ? RawMotorModel.AccelerationBased
+                    : RawMotorModel.ForceBased,
+            );
+            raw.jointConfigureMotor(
+                handle,
+                axis,
+                drive.positionTarget || 0.0,
+                drive.velocityTarget || 0.0,
+                drive.stiffness || 0.0,
+                drive.damping || 0.0,
+            );
+            if (drive.maxForce !== undefined) {
+                raw.jointSetMotorMaxForce(handle, axis, drive.maxForce);
+            }
+        });
+    }
+
+    createJoints() {
+        let joints = this.docExt(RIGID_BODIES_EXT).physicsJoints || [];
+        this.poses.forEach((_, i) => {
+            let ext = this.ext(i);
+            let spec =
+                !!ext && !!ext.joint ? joints[ext.joint.joint] : undefined;
+            if (!spec || this.poses[ext.joint.connectedNode] === undefined) {
+                return;
+            }
+
+            let [frame1, body1] = this.localPose(i);
+            let [frame2, body2] = this.localPose(ext.joint.connectedNode);
+            let data = JointData.fixed(
+                frame1.translation,
+                frame1.rotation,
+                frame2.translation,
+                frame2.rotation,
+            );
+            let joint = this.world.createImpulseJoint(
+                data,
+                this.jointBody(body1),
+                this.jointBody(body2),
+                true,
+            );
+            this.configureJoint(joint, spec);
+            joint = this.world.impulseJoints.retype(
+                this.world.bodies,
+                joint.handle,
+            );
+            joint.setContactsEnabled(!!ext.joint.enableCollision);
+        });
+    }
+}
+
+/**
+ * Parses a binary glTF (GLB) buffer, or a glTF JSON document without external buffers.
+ */
+function parseGltf(data: Uint8Array): [any, Uint8Array | null] {
+    let view = new DataView(data.buffer, data.byteOffset, data.byteLength);
+    let decoder = new TextDecoder();
+    if (data.byteLength < 12 || view.getUint32(0, true) != GLB_MAGIC) {
+        return [JSON.parse(decoder.decode(data)), null];
+    }
+
+    let doc = null;
+    let bin = null;
+    let offset = 12;
+    while (offset + 8 <= data.byteLength) {
+        let length = view.getUint32(offset, true);
+        let type = view.getUint32(offset + 4, true);
+        let chunk = data.subarray(offset + 8, offset + 8 + length);
+        if (type == GLB_CHUNK_JSON) {
+            doc = JSON.parse(decoder.decode(chunk));
+        } else if (type == GLB_CHUNK_BIN && !bin) {
+            bin = chunk;
+        }
+        offset += 8 + align4(length);
+    }
+
+    if (!doc) {
+        throw new Error("Invalid GLB file: it doesn't contain any JSON chunk.");
+    }
+
+    return [doc, bin];
+}
+
+/**
+ * Creates the rigid-bodies, colliders, and joints described by a glTF document using the
+ * `KHR_physics_rigid_bodies` and `KHR_implicit_shapes` extensions into the given world.
+ *
+ * Node scales are ignored. Joint limits that are neither locks, single-axis limits, nor
+ * limits on the distance between the joint frames are ignored. Throws an error if `data`
+ * is a GLB file without any JSON chunk.
+ */
+export function importGltfPhysics(data: Uint8Array, world: World) {
+    let [doc, bin] = parseGltf(data);
+    let importer = new GltfPhysicsImporter(doc, bin, world);
+    importer.computePoses();
+    importer.createBodies();
+    importer.createColliders();
+    importer.createJoints();
+}
@@ -5,3 +5,11 @@ export * from "./event_queue";
 export * from "./physics_hooks";
 export * from "./debug_render_pipeline";
 export * from "./query_pipeline";
+export * from "./pose_buffer";
+export * from "./contact_material_table";
+export * from "./mini_simulator";
+export * from "./simulation_lod";
+
+// #if DIM3
+export * from "./gltf_physics";
+// #endif
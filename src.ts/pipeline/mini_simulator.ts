@@ -0,0 +1,209 @@
+import {RawMiniSimulator} from "../raw";
+import {Vector, VectorOps} from "../math";
+import {RigidBody, RigidBodyDesc, RigidBodySet} from "../dynamics";
+import {Collider, ColliderDesc, ColliderSet} from "../geometry";
+import {World, WorldCloneMapping} from "./world";
+
+/**
+ * A lightweight simulator for a handful of rigid-bodies and colliders, e.g., for
+ * inverse-kinematics reach tests, trajectory previews, or UI physics.
+ *
+ * Unlike a `World`, the island manager, broad-phase, narrow-phase, and CCD solver
+ * are kept internal to the WASM module, so a full step only takes a single call.
+ * Joints, events, hooks, and scene queries are not supported. Use `MiniSimulator.cloneFrom`
+ * to simulate a copy of some of the objects of a `World`, e.g., to preview their motion.
+ *
+ * To avoid leaking WASM resources, this MUST be freed manually with `simulator.free()`
+ * once you are done using it.
+ */
+export class MiniSimulator {
+    raw: RawMiniSimulator;
+    bodies: RigidBodySet;
+    colliders: ColliderSet;
+    /**
+     * The gravity applied to the dynamic rigid-bodies of this simulator.
+     */
+    gravity: Vector;
+
+    /**
+     * Release the WASM memory occupied by this simulator.
+     */
+    public free() {
+        this.raw.free();
+        this.bodies.free();
+        this.colliders.free();
+        this.raw = undefined;
+        this.bodies = undefined;
+        this.colliders = undefined;
+    }
+
+    constructor(gravity: Vector) {
+        this.raw = new RawMiniSimulator();
+        this.bodies = new RigidBodySet();
+        this.colliders = new ColliderSet();
+        this.gravity = gravity;
+    }
+
+    /**
+     * Creates a simulator containing copies of some of the rigid-bodies and colliders of a
+     * physics world, with the same gravity.
+     *
+     * See `MiniSimulator.copyFrom` for the objects copied.
+     *
+     * @param world - The physics world the objects are copied from.
+     * @param bodyFilter - If provided, only the rigid-bodies for which this closure returns `true`
+     *   are copied.
+     * @param colliderFilter - If provided, only the colliders for which this closure returns `true`
+     *   are copied.
+     */
+    public static cloneFrom(
+        world: World,
+        bodyFilter?: (body: RigidBody) => boolean,
+        colliderFilter?: (collider: Collider) => boolean,
+    ): MiniSimulator {
+        let gravity = VectorOps.zeros();
+        VectorOps.copy(gravity, world.gravity);
+        let simulator = new MiniSimulator(gravity);
+        simulator.copyFrom(world, bodyFilter, colliderFilter);
+        return simulator;
+    }
+
+    /**
+     * Copies some of the rigid-bodies and colliders of a physics world into this simulator.
+     *
+     * Objects are copied as-is, including their current velocities, as with `World.cloneInto`.
+     * Colliders without parent are copied if they pass `colliderFilter`, whereas colliders
+     * attached to a rigid-body are only copied if their rigid-body is copied too. Joints are
+     * not copied.
+     *
+     * @param world - The physics world the objects are copied from.
+     * @param bodyFilter - If provided, only the rigid-bodies for which this closure returns `true`
+     *   are copied.
+     * @param colliderFilter - If provided, only the colliders for which this closure returns `true`
+     *   are copied.
+     * @returns The mapping from the handles of the copied objects to the handles of their copies.
+     */
+    public copyFrom(
+        world: World,
+        bodyFilter?: (body: RigidBody) => boolean,
+        colliderFilter?: (collider: Collider) => boolean,
+    ): WorldCloneMapping {
+        let mapping = new WorldCloneMapping();
+
+        world.bodies.forEach((body) => {
+            if (!bodyFilter || bodyFilter(body)) {
+                let copy = world.bodies.cloneInto(
+                    body.handle,
+                    world.colliders,
+                    this.bodies,
+                    this.colliders,
+                );
+                mapping.bodies.set(body.handle, copy.handle);
+            }
+        });
+
+        world.colliders.forEach((collider) => {
+            let parent = collider.parent();
+            let copyParent = !!parent
+                ? mapping.bodies.get(parent.handle)
+                : null;
+            if (copyParent === undefined) {
+                return;
+            }
+
+            if (!colliderFilter || colliderFilter(collider)) {
+                let copy = world.colliders.cloneInto(
+                    collider.handle,
+                    this.colliders,
+                    this.bodies,
+                    copyParent,
+                );
+                mapping.colliders.set(collider.handle, copy.handle);
+            }
+        });
+
+        return mapping;
+    }
+
+    /**
+     * Creates a new rigid-body from the given rigid-body descriptor.
+     *
+     * @param body - The description of the rigid-body to create.
+     */
+    public createRigidBody(body: RigidBodyDesc): RigidBody {
+        return this.bodies.createRigidBody(this.colliders, body);
+    }
+
+    /**
+     * Creates a new collider.
+     *
+     * @param desc - The description of the collider.
+     * @param parent - The rigid-body this collider is attached to.
+     */
+    public createCollider(desc: ColliderDesc, parent?: RigidBody): Collider {
+        let parentHandle = parent ? parent.handle : undefined;
+        return this.colliders.createCollider(this.bodies, desc, parentHandle);
+    }
+
+    /**
+     * Removes the given rigid-body, and the colliders attached to it, from this simulator.
+     *
+     * @param body - The rigid-body to remove.
+     */
+    public removeRigidBody(body: RigidBody) {
+        for (let i = 0; i < body.numColliders(); i += 1) {
+            this.colliders.unmap(body.collider(i).handle);
+        }
+
+        this.raw.removeRigidBody(
+            body.handle,
+            this.bodies.raw,
+            this.colliders.raw,
+        );
+        this.bodies.unmap(body.handle);
+    }
+
+    /**
+     * Removes the given collider from this simulator.
+     *
+     * @param collider - The collider to remove.
+     */
+    public removeCollider(collider: Collider) {
+        this.raw.removeCollider(
+            collider.handle,
+            this.bodies.raw,
+            this.colliders.raw,
+        );
+        this.colliders.unmap(collider.handle);
+    }
+
+    /**
+     * Advances the simulation by `dt` seconds.
+     *
+     * @param dt - The timestep length, in seconds.
+     */
+    public step(dt: number) {
+        let rawGravity = VectorOps.intoRaw(this.gravity);
+        this.raw.step(rawGravity, dt, this.bodies.raw, this.colliders.raw);
+        rawGravity.free();
+    }
+
+    /**
+     * Steps the simulation `numSteps` times with a timestep of `dt` seconds each,
+     * in a single call to the WASM module.
+     *
+     * @param dt - The timestep length, in seconds.
+     * @param numSteps - The number of steps to perform.
+     */
+    public advance(dt: number, numSteps: number) {
+        let rawGravity = VectorOps.intoRaw(this.gravity);
+        this.raw.advance(
+            rawGravity,
+            dt,
+            numSteps,
+            this.bodies.raw,
+            this.colliders.raw,
+        );
+        rawGravity.free();
+    }
+}
@@ -1,4 +1,8 @@
-import { RawContactManifold, RawContactModificationContext } from "../raw";
+import {
+    RawContactManifold,
+    RawContactModificationContext,
+    RawContactReductionStrategy,
+} from "../raw";
 import { RigidBodyHandle } from "../dynamics";
 import { ColliderHandle } from "../geometry";
 import { Vector, VectorOps } from "../math";
@@ -15,6 +19,23 @@ export enum SolverFlags {
     COMPUTE_IMPULSE = 0b001,
 }
 
+/**
+ * The strategy used to select the solver contacts kept by
+ * `ContactModificationContext.reduceSolverContacts`.
+ */
+export enum ContactReductionStrategy {
+    /**
+     * Keep the most penetrating solver contacts.
+     */
+    Deepest,
+    /**
+     * Keep the most penetrating solver contact, then the solver contacts farthest
+     * from the ones already kept. This preserves the area covered by the contacts,
+     * which generally yields more stable stacking of large flat shapes.
+     */
+    Spread,
+}
+
 export class ContactModificationContext {
     raw: RawContactModificationContext;
     constructor(raw: RawContactModificationContext) {
@@ -85,6 +106,23 @@ export class ContactModificationContext {
         this.raw.clear_solver_contacts();
     }
 
+    /**
+     * Reduces the number of solver contacts in this contact modification context.
+     *
+     * @param maxContacts - The maximum number of solver contacts to keep.
+     * @param strategy - The strategy used to select the solver contacts to keep.
+     * @returns The number of solver contacts removed. This is zero if no reduction occurred.
+     */
+    reduceSolverContacts(
+        maxContacts: number,
+        strategy: ContactReductionStrategy = ContactReductionStrategy.Spread,
+    ): number {
+        return this.raw.reduce_solver_contacts(
+            maxContacts,
+            strategy as number as RawContactReductionStrategy,
+        );
+    }
+
     /**
      * Removes the solver contact at the given index. The last solver contact
      * will be moved to the given index.
@@ -256,6 +294,18 @@ export class ContactModificationContext {
         this.raw.set_solver_contact_warmstart_twist_impulse(index, impulse);
     }
 
+    /**
+     * Sets the warmstart impulses of all the solver contacts of this pair to zero, so
+     * the constraints solver starts from scratch for this pair during this timestep.
+     *
+     * This is useful to prevent stale impulses from polluting the response of contacts
+     * right after a large external impulse (e.g. an explosion). To disable warmstarting
+     * globally, set `IntegrationParameters.warmstartCoefficient` to `0.0` instead.
+     */
+    resetWarmstartImpulses(): void {
+        this.raw.reset_warmstart_impulses();
+    }
+
     /**
      * @param index - The index of the solver contact.
      * @returns Whether this contact existed during the last timestep.
@@ -349,8 +399,37 @@ export interface PhysicsHooks {
      * involved in the contact contains the `ActiveHooks::MODIFY_SOLVER_CONTACTS` flags
      * in its physics hooks flags.
      *
+     * The context, and its contact manifold, are only valid during this call: using them after
+     * this function returns throws an error.
+     *
      * @param context - The raw context providing information and access to the contacts to modify.
      * Can be used with ContactModificationContext for easier use.
      */
     modifySolverContacts?(context: RawContactModificationContext): void;
+
+    /**
+     * Batched version of `filterContactPair`, evaluating many contact pairs in a single call.
+     *
+     * If set, this is called once at the beginning of each timestep with all the existing contact
+     * pairs involving a collider with the `ActiveHooks.FILTER_CONTACT_PAIRS` flag, instead of calling
+     * `filterContactPair` once per pair. Only these persisting pairs are batched: pairs that start
+     * overlapping during the timestep are evaluated by calling this function with arrays of length 1,
+     * and `filterContactPair` is never called.
+     *
+     * If this function throws, or doesn't return one value per pair, the pairs it was called with are
+     * filtered out and the error is thrown by `World.step` once the timestep is complete.
+     *
+     * @param colliders1 - Handles of the first colliders of each pair.
+     * @param colliders2 - Handles of the second colliders of each pair.
+     * @param bodies1 - Handles of the first bodies of each pair, or `NaN` if the collider has no parent.
+     * @param bodies2 - Handles of the second bodies of each pair, or `NaN` if the collider has no parent.
+     * @returns The `SolverFlags` of each pair, in the same order. A negative or `NaN` value means that
+     *          no contact should be computed for this pair.
+     */
+    filterContactPairs?(
+        colliders1: Float64Array,
+        colliders2: Float64Array,
+        bodies1: Float64Array,
+        bodies2: Float64Array,
+    ): ArrayLike<number>;
 }
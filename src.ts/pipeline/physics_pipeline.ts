@@ -65,6 +65,7 @@ export class PhysicsPipeline {
                 !!hooks ? hooks.filterContactPair : null,
                 !!hooks ? hooks.filterIntersectionPair : null,
                 !!hooks ? hooks.modifySolverContacts : null,
+                !!hooks ? hooks.filterContactPairs : null,
             );
         } else {
             this.raw.step(
@@ -0,0 +1,142 @@
+import {
+    IslandManager,
+    RigidBody,
+    RigidBodyHandle,
+    RigidBodySet,
+} from "../dynamics";
+import {Rotation, Vector, VectorOps} from "../math";
+// #if DIM3
+import {Quaternion} from "../math";
+// #endif
+
+/**
+ * A snapshot of the world-space poses of all the rigid-bodies of a physics world.
+ *
+ * The poses are packed in `poses` as `[tx, ty, angle]` in 2D, and as
+ * `[tx, ty, tz, qx, qy, qz, qw]` in 3D. The handle of the rigid-body each pose
+ * belongs to is stored at the same index in `handles`.
+ */
+export class PoseBuffer {
+    // #if DIM2
+    /**
+     * The number of floats used to store one pose.
+     */
+    public static readonly STRIDE = 3;
+    // #endif
+    // #if DIM3
+    /**
+     * The number of floats used to store one pose.
+     */
+    public static readonly STRIDE = 7;
+    // #endif
+
+    /**
+     * The handles of the rigid-bodies which poses are stored in this buffer.
+     */
+    public handles: Float64Array;
+    /**
+     * The packed rigid-body poses.
+     */
+    public poses: Float32Array;
+    /**
+     * The number of poses actually stored in this buffer.
+     */
+    public len: number;
+    private indices: Map<RigidBodyHandle, number>;
+
+    constructor() {
+        this.handles = new Float64Array(0);
+        this.poses = new Float32Array(0);
+        this.len = 0;
+        this.indices = null;
+    }
+
+    /**
+     * Overwrites this buffer with the current poses of all the rigid-bodies from `bodies`.
+     *
+     * @param bodies - The set of rigid-bodies to read the poses from.
+     * @param islands - The island manager of the physics world.
+     */
+    public update(bodies: RigidBodySet, islands: IslandManager) {
+        let count = bodies.len();
+        if (this.handles.length < count) {
+            let capacity = Math.max(count, this.handles.length * 2);
+            this.handles = new Float64Array(capacity);
+            this.poses = new Float32Array(capacity * PoseBuffer.STRIDE);
+        }
+
+        this.len = bodies.copyPosesToBuffer(
+            islands,
+            false,
+            this.handles,
+            this.poses,
+        );
+        this.indices = null;
+    }
+
+    /**
+     * The index, in this buffer, of the pose of the given rigid-body, or `-1` if
+     * this buffer doesn't contain it.
+     *
+     * @param body - The rigid-body (or its handle) to look for.
+     */
+    public indexOf(body: RigidBody | RigidBodyHandle): number {
+        if (!this.indices) {
+            this.indices = new Map();
+            for (let i = 0; i < this.len; ++i) {
+                this.indices.set(this.handles[i], i);
+            }
+        }
+
+        let handle = typeof body === "number" ? body : body.handle;
+        let index = this.indices.get(handle);
+        return index === undefined ? -1 : index;
+    }
+
+    /**
+     * The world-space translation of the given rigid-body stored in this buffer,
+     * or `null` if this buffer doesn't contain it.
+     *
+     * @param body - The rigid-body (or its handle) to look for.
+     */
+    public translation(body: RigidBody | RigidBodyHandle): Vector | null {
+        let i = this.indexOf(body);
+        if (i < 0) return null;
+
+        let offset = i * PoseBuffer.STRIDE;
+        // #if DIM2
+        return VectorOps.new(this.poses[offset], this.poses[offset + 1]);
+        // #endif
+        // #if DIM3
+        return VectorOps.new(
+            this.poses[offset],
+            this.poses[offset + 1],
+            this.poses[offset + 2],
+        );
+        // #endif
+    }
+
+    /**
+     * The world-space rotation of the given rigid-body stored in this buffer,
+     * or `null` if this buffer doesn't contain it.
+     *
+     * @param body - The rigid-body (or its handle) to look for.
+     */
+    public rotation(body: RigidBody | RigidBodyHandle): Rotation | null {
+        let i = this.indexOf(body);
+        if (i < 0) return null;
+
+        let offset = i * PoseBuffer.STRIDE;
+        // #if DIM2
+        return this.poses[offset + 2];
+        // #endif
+        // #if DIM3
+        return new Quaternion(
+            this.poses[offset + 3],
+            this.poses[offset + 4],
+            this.poses[offset + 5],
+            this.poses[offset + 6],
+        );
+        // #endif
+    }
+}
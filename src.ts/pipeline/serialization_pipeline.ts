@@ -9,6 +9,10 @@ import {
 } from "../dynamics";
 import {BroadPhase, ColliderSet, NarrowPhase} from "../geometry";
 import {World} from "./world";
+import {SimulationLod} from "./simulation_lod";
+// #if DIM3
+import {exportGltfPhysics, importGltfPhysics} from "./gltf_physics";
+// #endif
 
 /**
  * A pipeline for serializing the physics scene.
@@ -33,6 +37,16 @@ export class SerializationPipeline {
         this.raw = raw || new RawSerializationPipeline();
     }
 
+    /**
+     * Can snapshots be compressed with this build of Rapier?
+     *
+     * Compression is only available if the WASM module was built with the `compression`
+     * feature, which is enabled by default.
+     */
+    public static compressionSupported(): boolean {
+        return RawSerializationPipeline.compressionSupported();
+    }
+
     /**
      * Serialize a complete physics state into a single byte array.
      * @param gravity - The current gravity affecting the simulation.
@@ -43,6 +57,10 @@ export class SerializationPipeline {
      * @param colliders - The colliders taking part into the simulation.
      * @param impulseJoints - The impulse joints taking part into the simulation.
      * @param multibodyJoints - The multibody joints taking part into the simulation.
+     * @param compress - If `true`, the result is compressed with LZ4. In that case, `null` is
+     *   returned if compression isn't supported by this build.
+     * @param simulationLod - The simulation tiers of the rigid-bodies. If not set, all the
+     *   rigid-bodies are saved in the `Full` tier.
      */
     public serializeAll(
         gravity: Vector,
@@ -54,8 +72,11 @@ export class SerializationPipeline {
         colliders: ColliderSet,
         impulseJoints: ImpulseJointSet,
         multibodyJoints: MultibodyJointSet,
+        compress: boolean = false,
+        simulationLod?: SimulationLod,
     ): Uint8Array {
         let rawGra = VectorOps.intoRaw(gravity);
+        let lod = !!simulationLod ? simulationLod : new SimulationLod();
 
         const res = this.raw.serializeAll(
             rawGra,
@@ -67,8 +88,13 @@ export class SerializationPipeline {
             colliders.raw,
             impulseJoints.raw,
             multibodyJoints.raw,
+            lod.raw,
+            compress,
         );
         rawGra.free();
+        if (!simulationLod) {
+            lod.free();
+        }
 
         return res;
     }
@@ -77,8 +103,134 @@ export class SerializationPipeline {
      * Deserialize the complete physics state from a single byte array.
      *
      * @param data - The byte array to deserialize.
+     * @param compressed - Must be `true` if `data` was serialized with compression enabled.
+     */
+    public deserializeAll(
+        data: Uint8Array,
+        compressed: boolean = false,
+    ): World {
+        return World.fromRaw(this.raw.deserializeAll(data, compressed));
+    }
+
+    /**
+     * Serialize a complete physics state into a single byte array, except for the fixed
+     * rigid-bodies and the static colliders.
+     *
+     * A collider is static if it isn't attached to any rigid-body, or if it is attached to a
+     * fixed rigid-body. Only the handles of these rigid-bodies and colliders are recorded: they
+     * are left out of the snapshot along with their broad-phase data, and with the narrow-phase
+     * data of the static colliders that only interact with other static colliders. They are
+     * retrieved from existing sets with `deserializeAllWithStaticGeometry`. The contacts between
+     * static and non-static colliders are saved, since they are part of the state of the
+     * non-static rigid-bodies.
+     *
+     * @param gravity - The current gravity affecting the simulation.
+     * @param integrationParameters - The integration parameters of the simulation.
+     * @param broadPhase - The broad-phase of the simulation.
+     * @param narrowPhase - The narrow-phase of the simulation.
+     * @param bodies - The rigid-bodies taking part into the simulation.
+     * @param colliders - The colliders taking part into the simulation.
+     * @param impulseJoints - The impulse joints taking part into the simulation.
+     * @param multibodyJoints - The multibody joints taking part into the simulation.
+     * @param compress - If `true`, the result is compressed with LZ4. In that case, `null` is
+     *   returned if compression isn't supported by this build.
+     * @param simulationLod - The simulation tiers of the rigid-bodies. If not set, all the
+     *   rigid-bodies are saved in the `Full` tier.
+     */
+    public serializeAllWithoutStaticGeometry(
+        gravity: Vector,
+        integrationParameters: IntegrationParameters,
+        islands: IslandManager,
+        broadPhase: BroadPhase,
+        narrowPhase: NarrowPhase,
+        bodies: RigidBodySet,
+        colliders: ColliderSet,
+        impulseJoints: ImpulseJointSet,
+        multibodyJoints: MultibodyJointSet,
+        compress: boolean = false,
+        simulationLod?: SimulationLod,
+    ): Uint8Array {
+        let rawGra = VectorOps.intoRaw(gravity);
+        let lod = !!simulationLod ? simulationLod : new SimulationLod();
+
+        const res = this.raw.serializeAllWithoutStaticGeometry(
+            rawGra,
+            integrationParameters.raw,
+            islands.raw,
+            broadPhase.raw,
+            narrowPhase.raw,
+            bodies.raw,
+            colliders.raw,
+            impulseJoints.raw,
+            multibodyJoints.raw,
+            lod.raw,
+            compress,
+        );
+        rawGra.free();
+        if (!simulationLod) {
+            lod.free();
+        }
+
+        return res;
+    }
+
+    /**
+     * Deserialize a physics state serialized with `serializeAllWithoutStaticGeometry`.
+     *
+     * @param data - The byte array to deserialize.
+     * @param staticBodies - The rigid-bodies the fixed rigid-bodies are copied from. Each fixed
+     *   rigid-body is a copy of the rigid-body with the same handle in this set.
+     * @param staticColliders - The colliders the static colliders are copied from. Each static
+     *   collider is a copy of the collider with the same handle in this set.
+     * @param compressed - Must be `true` if `data` was serialized with compression enabled.
+     * @returns The deserialized world, or `null` if one of the fixed rigid-bodies or static
+     *   colliders couldn't be found in `staticBodies` or `staticColliders`.
+     */
+    public deserializeAllWithStaticGeometry(
+        data: Uint8Array,
+        staticBodies: RigidBodySet,
+        staticColliders: ColliderSet,
+        compressed: boolean = false,
+    ): World {
+        return World.fromRaw(
+            this.raw.deserializeAllWithStaticGeometry(
+                data,
+                staticBodies.raw,
+                staticColliders.raw,
+                compressed,
+            ),
+        );
+    }
+
+    // #if DIM3
+    /**
+     * Exports the rigid-bodies, colliders, and impulse joints of a world as a binary glTF (GLB)
+     * buffer, using the `KHR_physics_rigid_bodies` and `KHR_implicit_shapes` extensions.
+     *
+     * Balls, cuboids, capsules, cylinders and cones are exported as implicit shapes, while
+     * triangle meshes and convex polyhedra are exported as meshes. Colliders with any other
+     * shape, and multibody joints, are not exported.
+     *
+     * @param world - The world to export.
+     */
+    public serializeGltf(world: World): Uint8Array {
+        return exportGltfPhysics(world);
+    }
+
+    /**
+     * Creates a new world from the rigid-bodies, colliders, and joints described by a glTF
+     * document using the `KHR_physics_rigid_bodies` and `KHR_implicit_shapes` extensions.
+     *
+     * Node scales are ignored, as well as joint limits that are neither locks, single-axis
+     * limits, nor limits on the distance between the two joint frames.
+     *
+     * @param data - A binary glTF (GLB) buffer, or a glTF JSON document without external buffers.
+     * @param gravity - The gravity of the new world.
      */
-    public deserializeAll(data: Uint8Array): World {
-        return World.fromRaw(this.raw.deserializeAll(data));
+    public deserializeGltf(data: Uint8Array, gravity: Vector): World {
+        let world = new World(gravity);
+        importGltfPhysics(data, world);
+        return world;
     }
+    // #endif
 }
@@ -0,0 +1,168 @@
+import {RawSimulationLod, RawSimulationTier} from "../raw";
+import {
+    IntegrationParameters,
+    RigidBodyHandle,
+    RigidBodySet,
+} from "../dynamics";
+
+/**
+ * The level of detail of the simulation of a rigid-body.
+ */
+export enum SimulationTier {
+    /**
+     * The rigid-body is simulated normally, with `IntegrationParameters.numSolverIterations`
+     * solver iterations.
+     */
+    Full = 0,
+    /**
+     * The rigid-body is simulated with `SimulationLod.reducedTierSolverIterations` solver
+     * iterations, unless it touches a `Full` rigid-body, and its sleep thresholds are scaled
+     * by `SimulationLod.reducedTierSleepThresholdScale` so it falls asleep sooner.
+     */
+    Reduced,
+    /**
+     * The rigid-body is locked in place as a fixed rigid-body: it is no longer simulated, but
+     * the other rigid-bodies still collide with it. Its type and velocities are restored when
+     * leaving this tier, unless its type was changed while it was frozen.
+     */
+    Frozen,
+}
+
+/**
+ * Assigns a simulation tier to rigid-bodies, e.g., to simulate distant debris more cheaply
+ * without removing them from the world.
+ *
+ * Bodies are in the `Full` tier unless assigned another tier. While some bodies are in the
+ * `Reduced` tier, each `World.step` runs the solver with `reducedTierSolverIterations`
+ * iterations, and gives the dynamic `Full` bodies (including the sleeping ones, which may
+ * be woken up during the step) the additional iterations needed to keep
+ * `IntegrationParameters.numSolverIterations` iterations. The solver iterations are chosen
+ * per island, so the islands containing only `Reduced` bodies are the ones solved more
+ * cheaply. The configured iterations, and the additional iterations set with
+ * `RigidBody.setAdditionalSolverIterations`, are restored after the step.
+ *
+ * All the islands are integrated with the same timestep, so the `Reduced` tier can't use a
+ * larger one. It tolerates more motion before falling asleep instead, with scaled sleep
+ * thresholds, so that resting debris stops being simulated sooner.
+ *
+ * The simulation tiers are saved in the snapshots of the world.
+ *
+ * To avoid leaking WASM resources, this MUST be freed manually with `lod.free()`
+ * once you are done using it, unless it is the one owned by a `World`.
+ */
+export class SimulationLod {
+    raw: RawSimulationLod;
+
+    /**
+     * Release the WASM memory occupied by this LOD controller.
+     */
+    public free() {
+        if (!!this.raw) {
+            this.raw.free();
+        }
+        this.raw = undefined;
+    }
+
+    constructor(raw?: RawSimulationLod) {
+        this.raw = raw || new RawSimulationLod();
+    }
+
+    /**
+     * The number of solver iterations of the rigid-bodies of the `Reduced` tier (default: `1`).
+     */
+    public reducedTierSolverIterations(): number {
+        return this.raw.reducedTierSolverIterations();
+    }
+
+    /**
+     * Sets the number of solver iterations of the rigid-bodies of the `Reduced` tier.
+     *
+     * This has no effect if it isn't smaller than `IntegrationParameters.numSolverIterations`.
+     *
+     * @param iters - The number of solver iterations, at least `1`.
+     */
+    public setReducedTierSolverIterations(iters: number) {
+        this.raw.setReducedTierSolverIterations(iters);
+    }
+
+    /**
+     * The factor applied to the sleep thresholds of the rigid-bodies of the `Reduced` tier
+     * (default: `2`).
+     */
+    public reducedTierSleepThresholdScale(): number {
+        return this.raw.reducedTierSleepThresholdScale();
+    }
+
+    /**
+     * Sets the factor applied to the sleep thresholds of the rigid-bodies of the `Reduced`
+     * tier, including the ones already in that tier.
+     *
+     * The sleep thresholds a rigid-body had before entering the `Reduced` tier are restored
+     * when it leaves the tier.
+     *
+     * @param bodies - The set containing the rigid-bodies.
+     * @param scale - The non-negative factor applied to the sleep thresholds.
+     */
+    public setReducedTierSleepThresholdScale(
+        bodies: RigidBodySet,
+        scale: number,
+    ) {
+        this.raw.setReducedTierSleepThresholdScale(bodies.raw, scale);
+    }
+
+    /**
+     * The simulation tier of the given rigid-body.
+     *
+     * @param handle - The handle of the rigid-body.
+     */
+    public simulationTier(handle: RigidBodyHandle): SimulationTier {
+        return this.raw.simulationTier(handle) as number as SimulationTier;
+    }
+
+    /**
+     * Assigns a simulation tier to the given rigid-body.
+     *
+     * @param bodies - The set containing the rigid-body.
+     * @param handle - The handle of the rigid-body.
+     * @param tier - The new simulation tier of the rigid-body.
+     */
+    public setSimulationTier(
+        bodies: RigidBodySet,
+        handle: RigidBodyHandle,
+        tier: SimulationTier,
+    ) {
+        this.raw.setSimulationTier(
+            bodies.raw,
+            handle,
+            tier as number as RawSimulationTier,
+        );
+    }
+
+    /**
+     * The number of rigid-bodies explicitly assigned to the given tier.
+     *
+     * @param tier - The simulation tier.
+     */
+    public numBodiesInTier(tier: SimulationTier): number {
+        return this.raw.numBodiesInTier(tier as number as RawSimulationTier);
+    }
+
+    /**
+     * Internal method, do not call this explicitly.
+     *
+     * Lowers the solver iterations for the next step if some rigid-bodies are in the
+     * `Reduced` tier.
+     */
+    public beginStep(params: IntegrationParameters, bodies: RigidBodySet) {
+        this.raw.beginStep(params.raw, bodies.raw);
+    }
+
+    /**
+     * Internal method, do not call this explicitly.
+     *
+     * Restores the solver iterations modified by `beginStep`.
+     */
+    public endStep(params: IntegrationParameters, bodies: RigidBodySet) {
+        this.raw.endStep(params.raw, bodies.raw);
+    }
+}
@@ -1,5 +1,6 @@
 import {
     RawBroadPhase,
+    RawBroadPhaseOptimizationStrategy,
     RawCCDSolver,
     RawColliderSet,
     RawDeserializedWorld,
@@ -16,6 +17,7 @@ import {
 
 import {
     BroadPhase,
+    BroadPhaseOptimizationStrategy,
     Collider,
     ColliderDesc,
     ColliderHandle,
@@ -28,6 +30,9 @@ import {
     RayColliderHit,
     Shape,
     ColliderShapeCastHit,
+    PredictedTrajectory,
+    RayCastBatch,
+    ShapeIntersectionBatch,
     TempContactManifold,
 } from "../geometry";
 import {
@@ -36,6 +41,7 @@ import {
     IslandManager,
     ImpulseJoint,
     ImpulseJointHandle,
+    JointAxesMask,
     MultibodyJoint,
     MultibodyJointHandle,
     JointData,
@@ -51,10 +57,15 @@ import {PhysicsPipeline} from "./physics_pipeline";
 import {QueryFilterFlags} from "./query_pipeline";
 import {SerializationPipeline} from "./serialization_pipeline";
 import {EventQueue} from "./event_queue";
-import {PhysicsHooks} from "./physics_hooks";
+import {ActiveHooks, PhysicsHooks} from "./physics_hooks";
 import {DebugRenderBuffers, DebugRenderPipeline} from "./debug_render_pipeline";
+import {PoseBuffer} from "./pose_buffer";
+import {ContactMaterialTable} from "./contact_material_table";
+import {SimulationLod, SimulationTier} from "./simulation_lod";
 import {
     KinematicCharacterController,
+    ModifierVolume,
+    ModifierVolumeSet,
     PidAxesMask,
     PidController,
 } from "../control";
@@ -62,14 +73,113 @@ import {Coarena} from "../coarena";
 
 // #if DIM3
 import {DynamicRayCastVehicleController} from "../control";
+import {WalkableSurfaces} from "../geometry";
 
 // #endif
 
+/**
+ * The mapping from the handles of objects copied by `World.cloneInto` to the
+ * handles of their copies.
+ */
+export class WorldCloneMapping {
+    /**
+     * The handles of the copied rigid-bodies, mapped to the handles of their copies.
+     */
+    public bodies: Map<RigidBodyHandle, RigidBodyHandle>;
+    /**
+     * The handles of the copied colliders, mapped to the handles of their copies.
+     */
+    public colliders: Map<ColliderHandle, ColliderHandle>;
+    /**
+     * The handles of the copied impulse joints, mapped to the handles of their copies.
+     */
+    public impulseJoints: Map<ImpulseJointHandle, ImpulseJointHandle>;
+
+    constructor() {
+        this.bodies = new Map();
+        this.colliders = new Map();
+        this.impulseJoints = new Map();
+    }
+}
+
+/**
+ * The sets of objects simulated by a physics world, detached from any world.
+ *
+ * Use `World.detachSets` to detach the sets of a world, e.g., to keep them alive across
+ * the rebuild of the world, and `World.fromSets` to create a world simulating them. The
+ * island manager, broad-phase, and narrow-phase hold the state of the simulation of the
+ * rigid-bodies and colliders, so they are kept along with the sets.
+ *
+ * To avoid leaking WASM resources, this MUST be freed manually with `sets.free()`
+ * unless it is given to `World.fromSets`.
+ */
+export class WorldSets {
+    public islands: IslandManager;
+    public broadPhase: BroadPhase;
+    public narrowPhase: NarrowPhase;
+    public bodies: RigidBodySet;
+    public colliders: ColliderSet;
+    public impulseJoints: ImpulseJointSet;
+    public multibodyJoints: MultibodyJointSet;
+
+    constructor(
+        islands: IslandManager,
+        broadPhase: BroadPhase,
+        narrowPhase: NarrowPhase,
+        bodies: RigidBodySet,
+        colliders: ColliderSet,
+        impulseJoints: ImpulseJointSet,
+        multibodyJoints: MultibodyJointSet,
+    ) {
+        this.islands = islands;
+        this.broadPhase = broadPhase;
+        this.narrowPhase = narrowPhase;
+        this.bodies = bodies;
+        this.colliders = colliders;
+        this.impulseJoints = impulseJoints;
+        this.multibodyJoints = multibodyJoints;
+    }
+
+    /**
+     * Release the WASM memory occupied by these sets.
+     */
+    public free() {
+        this.islands.free();
+        this.broadPhase.free();
+        this.narrowPhase.free();
+        this.bodies.free();
+        this.colliders.free();
+        this.impulseJoints.free();
+        this.multibodyJoints.free();
+        this.islands = undefined;
+        this.broadPhase = undefined;
+        this.narrowPhase = undefined;
+        this.bodies = undefined;
+        this.colliders = undefined;
+        this.impulseJoints = undefined;
+        this.multibodyJoints = undefined;
+    }
+}
+
 /**
  * The physics world.
  *
  * This contains all the data-structures necessary for creating and simulating
  * bodies with contacts, joints, and external forces.
+ *
+ * The internal iteration order of the simulation (bodies, colliders, joints, and
+ * contact pairs) follows the order in which objects were inserted and removed. There is
+ * no seed controlling this order since it is tied to the object handles. Two worlds are
+ * only guaranteed to yield identical simulations if they are built with the exact same
+ * sequence of insertions and removals, so clients requiring cross-client determinism
+ * should either sort their scene (e.g. by a stable identifier) before inserting it, or
+ * call `World.canonicalizeOrdering` once it is built.
+ *
+ * There is no per-collider priority for the order in which contacts are solved. The
+ * constraints solver processes the contacts of each island in the order of the contact
+ * graph, which is not exposed and cannot be reordered from outside of the physics
+ * pipeline. Jitter on a specific body is best reduced by giving it additional solver
+ * iterations (see `RigidBody.setAdditionalSolverIterations`).
  */
 export class World {
     public gravity: Vector;
@@ -87,6 +197,13 @@ export class World {
     debugRenderPipeline: DebugRenderPipeline;
     characterControllers: Set<KinematicCharacterController>;
     pidControllers: Set<PidController>;
+    modifierVolumes: ModifierVolumeSet;
+    contactMaterials: ContactMaterialTable;
+    simulationLod: SimulationLod;
+    private displayedPoseBuffer: PoseBuffer;
+    private latestPoseBuffer: PoseBuffer;
+    private asyncStepPending: boolean;
+    private accumulatedTime: number;
 
     // #if DIM3
     vehicleControllers: Set<DynamicRayCastVehicleController>;
@@ -98,6 +215,7 @@ export class World {
      *
      * All the fields of this physics world will be freed as well,
      * so there is no need to call their `.free()` methods individually.
+     * A step still pending after `stepAsync` is cancelled.
      */
     public free() {
         this.integrationParameters.free();
@@ -114,6 +232,9 @@ export class World {
         this.debugRenderPipeline.free();
         this.characterControllers.forEach((controller) => controller.free());
         this.pidControllers.forEach((controller) => controller.free());
+        this.modifierVolumes.free();
+        this.contactMaterials.free();
+        this.simulationLod.free();
 
         // #if DIM3
         this.vehicleControllers.forEach((controller) => controller.free());
@@ -133,6 +254,11 @@ export class World {
         this.debugRenderPipeline = undefined;
         this.characterControllers = undefined;
         this.pidControllers = undefined;
+        this.modifierVolumes = undefined;
+        this.contactMaterials = undefined;
+        this.simulationLod = undefined;
+        this.displayedPoseBuffer = undefined;
+        this.latestPoseBuffer = undefined;
 
         // #if DIM3
         this.vehicleControllers = undefined;
@@ -175,6 +301,13 @@ export class World {
         );
         this.characterControllers = new Set<KinematicCharacterController>();
         this.pidControllers = new Set<PidController>();
+        this.modifierVolumes = new ModifierVolumeSet();
+        this.contactMaterials = new ContactMaterialTable();
+        this.simulationLod = new SimulationLod();
+        this.displayedPoseBuffer = null;
+        this.latestPoseBuffer = null;
+        this.asyncStepPending = false;
+        this.accumulatedTime = 0.0;
 
         // #if DIM3
         this.vehicleControllers = new Set<DynamicRayCastVehicleController>();
@@ -185,10 +318,100 @@ export class World {
         this.colliders.finalizeDeserialization(this.bodies);
     }
 
+    /**
+     * Creates a new physics world using a broad-phase with the given optimization strategy.
+     *
+     * @param gravity - The gravity applied to the dynamic rigid-bodies of this world.
+     * @param strategy - The strategy used to keep the broad-phase hierarchy efficient.
+     */
+    public static withBroadPhaseOptimizationStrategy(
+        gravity: Vector,
+        strategy: BroadPhaseOptimizationStrategy,
+    ): World {
+        let broadPhase = BroadPhase.withOptimizationStrategy(strategy);
+        return new World(gravity, undefined, undefined, broadPhase.raw);
+    }
+
+    /**
+     * Creates a new physics world simulating the given sets.
+     *
+     * The sets are owned by the new world afterwards: they are freed by `World.free`, unless
+     * they are detached again with `World.detachSets`. The rigid-bodies, colliders, and
+     * joints keep their handles.
+     *
+     * @param gravity - The gravity applied to the dynamic rigid-bodies of this world.
+     * @param sets - The sets to simulate, e.g., detached from another world.
+     * @param integrationParameters - If set, the integration parameters of the new world.
+     *   They are owned by the new world afterwards.
+     */
+    public static fromSets(
+        gravity: Vector,
+        sets: WorldSets,
+        integrationParameters?: IntegrationParameters,
+    ): World {
+        let world = new World(
+            gravity,
+            !!integrationParameters ? integrationParameters.raw : undefined,
+        );
+        world.currentSets().free();
+        world.attachSets(sets);
+        return world;
+    }
+
+    /**
+     * Detaches the sets simulated by this physics world, and replaces them with empty ones.
+     *
+     * The detached sets are no longer freed by `World.free`. They can be given to
+     * `World.fromSets` to be simulated by another world, or freed with `sets.free()`.
+     */
+    public detachSets(): WorldSets {
+        let sets = this.currentSets();
+        this.attachSets(
+            new WorldSets(
+                new IslandManager(),
+                new BroadPhase(),
+                new NarrowPhase(),
+                new RigidBodySet(),
+                new ColliderSet(),
+                new ImpulseJointSet(),
+                new MultibodyJointSet(),
+            ),
+        );
+        return sets;
+    }
+
+    private currentSets(): WorldSets {
+        return new WorldSets(
+            this.islands,
+            this.broadPhase,
+            this.narrowPhase,
+            this.bodies,
+            this.colliders,
+            this.impulseJoints,
+            this.multibodyJoints,
+        );
+    }
+
+    private attachSets(sets: WorldSets) {
+        this.islands = sets.islands;
+        this.broadPhase = sets.broadPhase;
+        this.narrowPhase = sets.narrowPhase;
+        this.bodies = sets.bodies;
+        this.colliders = sets.colliders;
+        this.impulseJoints = sets.impulseJoints;
+        this.multibodyJoints = sets.multibodyJoints;
+
+        // The poses of the previous sets are meaningless for the new ones.
+        if (this.isPoseDoubleBufferingEnabled()) {
+            this.enablePoseDoubleBuffering(false);
+            this.enablePoseDoubleBuffering(true);
+        }
+    }
+
     public static fromRaw(raw: RawDeserializedWorld): World {
         if (!raw) return null;
 
-        return new World(
+        let world = new World(
             VectorOps.fromRaw(raw.takeGravity()),
             raw.takeIntegrationParameters(),
             raw.takeIslandManager(),
@@ -199,6 +422,9 @@ export class World {
             raw.takeImpulseJoints(),
             raw.takeMultibodyJoints(),
         );
+        world.simulationLod.free();
+        world.simulationLod = new SimulationLod(raw.takeSimulationLod());
+        return world;
     }
 
     /**
@@ -206,8 +432,42 @@ export class World {
      *
      * Use `World.restoreSnapshot` to create a new physics world with a state identical to
      * the state when `.takeSnapshot()` is called.
-     */
-    public takeSnapshot(): Uint8Array {
+     *
+     * The snapshot includes the state handled natively on top of Rapier: the one-way platforms,
+     * contact responses and restitution thresholds, the step counter and creation steps, the
+     * resting jitter, the impulses accumulated for watched colliders, the joint motor
+     * velocity ramps and trajectories, and the simulation tiers. It doesn't include the objects living outside of the
+     * world, like event queues, physics hooks, or the user data of rigid-bodies and colliders.
+     *
+     * @param excludeStaticGeometry - If `true`, the fixed rigid-bodies and the colliders that are
+     *   not attached to any rigid-body, or attached to a fixed rigid-body, are not saved. Only
+     *   their handles are recorded, and the snapshot must be restored with `World.restoreSnapshot`
+     *   given a world containing the same fixed rigid-bodies and static colliders (with the same
+     *   handles).
+     * @param compress - If `true`, the snapshot is compressed with LZ4, and must be restored with
+     *   `compressed` set to `true`. Returns `null` if compression isn't supported by this build,
+     *   see `SerializationPipeline.compressionSupported`.
+     */
+    public takeSnapshot(
+        excludeStaticGeometry: boolean = false,
+        compress: boolean = false,
+    ): Uint8Array {
+        if (excludeStaticGeometry) {
+            return this.serializationPipeline.serializeAllWithoutStaticGeometry(
+                this.gravity,
+                this.integrationParameters,
+                this.islands,
+                this.broadPhase,
+                this.narrowPhase,
+                this.bodies,
+                this.colliders,
+                this.impulseJoints,
+                this.multibodyJoints,
+                compress,
+                this.simulationLod,
+            );
+        }
+
         return this.serializationPipeline.serializeAll(
             this.gravity,
             this.integrationParameters,
@@ -218,6 +478,8 @@ export class World {
             this.colliders,
             this.impulseJoints,
             this.multibodyJoints,
+            compress,
+            this.simulationLod,
         );
     }
 
@@ -225,10 +487,216 @@ export class World {
      * Creates a new physics world from a snapshot.
      *
      * This new physics world will be an identical copy of the snapshoted physics world.
-     */
-    public static restoreSnapshot(data: Uint8Array): World {
+     * The snapshots taken with previous versions of this library can still be restored, but
+     * without the state handled natively on top of Rapier (see `takeSnapshot`).
+     *
+     * @param data - The snapshot to restore.
+     * @param staticWorld - Required if the snapshot was taken with `excludeStaticGeometry` set
+     *   to `true`: the world the fixed rigid-bodies and static colliders are copied from. This
+     *   world is left unchanged.
+     * @param compressed - Must be `true` if the snapshot was taken with `compress` set to `true`.
+     */
+    public static restoreSnapshot(
+        data: Uint8Array,
+        staticWorld?: World,
+        compressed: boolean = false,
+    ): World {
         let deser = new SerializationPipeline();
-        return deser.deserializeAll(data);
+        let result = !!staticWorld
+            ? deser.deserializeAllWithStaticGeometry(
+                  data,
+                  staticWorld.bodies,
+                  staticWorld.colliders,
+                  compressed,
+              )
+            : deser.deserializeAll(data, compressed);
+        deser.free();
+        return result;
+    }
+
+    /**
+     * Copies some of the rigid-bodies, colliders, and impulse joints of this physics world
+     * into another physics world, e.g., for client-side prediction or "what if" simulations.
+     *
+     * Objects are copied as-is, including their current velocities and internal states, which
+     * is both faster and more faithful than recreating them from descriptors. Colliders without
+     * parent are copied if they pass `colliderFilter`, whereas colliders attached to a rigid-body
+     * are only copied if their rigid-body is copied too. Impulse joints are copied if both the
+     * rigid-bodies they are attached to are copied, and world-space constraints if their
+     * rigid-body is copied. Multibody joints are not copied.
+     *
+     * @param target - The physics world the objects are copied into. Must be different from `this`.
+     * @param bodyFilter - If provided, only the rigid-bodies for which this closure returns `true`
+     *   are copied.
+     * @param colliderFilter - If provided, only the colliders for which this closure returns `true`
+     *   are copied.
+     * @returns The mapping from the handles of the copied objects to the handles of their copies.
+     */
+    public cloneInto(
+        target: World,
+        bodyFilter?: (body: RigidBody) => boolean,
+        colliderFilter?: (collider: Collider) => boolean,
+    ): WorldCloneMapping {
+        let mapping = new WorldCloneMapping();
+
+        this.bodies.forEach((body) => {
+            if (!bodyFilter || bodyFilter(body)) {
+                let copy = this.bodies.cloneInto(
+                    body.handle,
+                    this.colliders,
+                    target.bodies,
+                    target.colliders,
+                );
+                mapping.bodies.set(body.handle, copy.handle);
+            }
+        });
+
+        this.colliders.forEach((collider) => {
+            let parent = collider.parent();
+            let copyParent = !!parent
+                ? mapping.bodies.get(parent.handle)
+                : null;
+            if (copyParent === undefined) {
+                return;
+            }
+
+            if (!colliderFilter || colliderFilter(collider)) {
+                let copy = this.colliders.cloneInto(
+                    collider.handle,
+                    target.colliders,
+                    target.bodies,
+                    copyParent,
+                );
+                mapping.colliders.set(collider.handle, copy.handle);
+            }
+        });
+
+        this.impulseJoints.forEach((joint) => {
+            // World-space constraints are attached to the hidden anchor of the target.
+            let copyBody1 = this.impulseJoints.isWorldConstraint(
+                this.bodies,
+                joint.handle,
+            )
+                ? null
+                : mapping.bodies.get(joint.body1().handle);
+            let copyBody2 = mapping.bodies.get(joint.body2().handle);
+            if (copyBody1 !== undefined && copyBody2 !== undefined) {
+                let copy = this.impulseJoints.cloneInto(
+                    joint.handle,
+                    target.impulseJoints,
+                    target.bodies,
+                    copyBody1,
+                    copyBody2,
+                    false,
+                );
+                mapping.impulseJoints.set(joint.handle, copy.handle);
+            }
+        });
+
+        return mapping;
+    }
+
+    /**
+     * Inserts all the rigid-bodies, colliders, and impulse joints of this world again, sorted
+     * by the given keys, so that the simulation no longer depends on the order they were
+     * created and removed in.
+     *
+     * Two worlds containing the same objects with the same keys yield identical simulations
+     * after this, whatever the order their scenes were built in. All the objects get new
+     * handles: the `RigidBody`, `Collider`, and `ImpulseJoint` objects obtained before must
+     * be fetched again. The modifier volumes, simulation tiers, and contact materials of this
+     * world are updated to the new handles, but the handles stored elsewhere (e.g., by vehicle
+     * controllers) must be updated with the returned mapping. The contacts are computed from scratch during the next timestep, so
+     * this is meant to be called once the scene is built, before simulating it.
+     *
+     * Throws if there are multibody joints, or if several objects have the same key.
+     *
+     * @param bodyKey - A key unique to each rigid-body, e.g., a stable identifier.
+     * @param colliderKey - A key unique to each collider.
+     * @param jointKey - A key unique to each impulse joint. By default, the impulse joints are
+     *   sorted by the keys of the rigid-bodies they are attached to, world-space constraints
+     *   first.
+     * @param strategy - The optimization strategy of the broad-phase created again.
+     * @returns The mapping from the previous handles of the objects to their new handles.
+     */
+    public canonicalizeOrdering(
+        bodyKey: (body: RigidBody) => number | string,
+        colliderKey: (collider: Collider) => number | string,
+        jointKey?: (joint: ImpulseJoint) => number | string,
+        strategy = BroadPhaseOptimizationStrategy.SubtreeOptimizer,
+    ): WorldCloneMapping {
+        let bodies: RigidBody[] = [];
+        let colliders: Collider[] = [];
+        let joints: ImpulseJoint[] = [];
+        this.bodies.forEach((body) => bodies.push(body));
+        this.colliders.forEach((collider) => colliders.push(collider));
+        this.impulseJoints.forEach((joint) => joints.push(joint));
+
+        let defaultJointKey = (joint: ImpulseJoint) =>
+            this.impulseJoints.isWorldConstraint(this.bodies, joint.handle)
+                ? [0, bodyKey(joint.body2())]
+                : [1, bodyKey(joint.body1()), bodyKey(joint.body2())];
+
+        bodies = sortByKey(bodies, (body) => [bodyKey(body)], "rigid-body");
+        colliders = sortByKey(
+            colliders,
+            (collider) => [colliderKey(collider)],
+            "collider",
+        );
+        joints = sortByKey(
+            joints,
+            !!jointKey ? (joint) => [jointKey(joint)] : defaultJointKey,
+            "impulse joint",
+        );
+
+        let handles = this.bodies.raw.canonicalizeOrdering(
+            this.islands.raw,
+            this.broadPhase.raw,
+            this.narrowPhase.raw,
+            this.colliders.raw,
+            this.impulseJoints.raw,
+            this.multibodyJoints.raw,
+            this.modifierVolumes.raw,
+            this.simulationLod.raw,
+            this.contactMaterials.raw,
+            strategy as number as RawBroadPhaseOptimizationStrategy,
+            new Float64Array(bodies.map((body) => body.handle)),
+            new Float64Array(colliders.map((collider) => collider.handle)),
+            new Float64Array(joints.map((joint) => joint.handle)),
+        );
+
+        let mapping = new WorldCloneMapping();
+        let i = 0;
+        bodies.forEach((body) => mapping.bodies.set(body.handle, handles[i++]));
+        colliders.forEach((collider) =>
+            mapping.colliders.set(collider.handle, handles[i++]),
+        );
+        joints.forEach((joint) =>
+            mapping.impulseJoints.set(joint.handle, handles[i++]),
+        );
+
+        this.bodies.reloadFromRaw();
+        this.colliders.reloadFromRaw();
+        this.impulseJoints.reloadFromRaw();
+        this.impulseJoints.finalizeDeserialization(this.bodies);
+        this.bodies.finalizeDeserialization(this.colliders);
+        this.colliders.finalizeDeserialization(this.bodies);
+        this.modifierVolumes.reloadColliders(this.colliders);
+        this.colliders.takePendingMassPropertiesUpdates().forEach((handle) => {
+            if (mapping.bodies.has(handle)) {
+                this.colliders.markMassPropertiesAsModified(
+                    mapping.bodies.get(handle),
+                );
+            }
+        });
+
+        // The poses of the previous handles are meaningless for the new ones.
+        if (this.isPoseDoubleBufferingEnabled()) {
+            this.enablePoseDoubleBuffering(false);
+            this.enablePoseDoubleBuffering(true);
+        }
+
+        return mapping;
     }
 
     /**
@@ -266,19 +734,216 @@ export class World {
      *   events generated by the physics engine.
      */
     public step(eventQueue?: EventQueue, hooks?: PhysicsHooks) {
-        this.physicsPipeline.step(
-            this.gravity,
-            this.integrationParameters,
-            this.islands,
-            this.broadPhase,
-            this.narrowPhase,
+        if (!this.asyncStepPending) {
+            this.swapPoseBuffers();
+        }
+
+        // The physics step recomputes the modified mass-properties anyway.
+        this.colliders.takePendingMassPropertiesUpdates();
+        this.modifierVolumes.apply(
+            this.integrationParameters.dt,
             this.bodies,
             this.colliders,
-            this.impulseJoints,
-            this.multibodyJoints,
-            this.ccdSolver,
-            eventQueue,
-            hooks,
+            this.narrowPhase,
+        );
+        this.simulationLod.beginStep(this.integrationParameters, this.bodies);
+        try {
+            this.physicsPipeline.step(
+                this.gravity,
+                this.integrationParameters,
+                this.islands,
+                this.broadPhase,
+                this.narrowPhase,
+                this.bodies,
+                this.colliders,
+                this.impulseJoints,
+                this.multibodyJoints,
+                this.ccdSolver,
+                eventQueue,
+                hooks,
+            );
+        } finally {
+            this.simulationLod.endStep(this.integrationParameters, this.bodies);
+        }
+
+        if (!!this.latestPoseBuffer) {
+            this.latestPoseBuffer.update(this.bodies, this.islands);
+        }
+    }
+
+    /**
+     * Advance the simulation by the given amount of real time, using fixed time steps.
+     *
+     * The elapsed time is accumulated, and as many steps of `integrationParameters.dt`
+     * seconds as fit in the accumulated time are performed. The time left over is kept for
+     * the next call, and `interpolationAlpha` gives its fraction of a time step, e.g., to
+     * interpolate between `displayedPoses` and `latestPoses` when rendering.
+     *
+     * If more than `maxSteps` steps would be needed (e.g., after the application was
+     * suspended, or if the steps take longer to compute than the time they simulate), only
+     * `maxSteps` steps are performed and the accumulated time exceeding them is dropped, so
+     * the simulation never falls further and further behind.
+     *
+     * @param realDt - The real time elapsed since the last call, in seconds.
+     * @param maxSteps - The maximum number of steps performed by this call.
+     * @param eventQueue - (optional) structure responsible for collecting
+     *   events generated by the physics engine during all the steps.
+     * @param hooks - (optional) the physics hooks called during all the steps.
+     * @returns The number of steps performed.
+     */
+    public advance(
+        realDt: number,
+        maxSteps: number = 8,
+        eventQueue?: EventQueue,
+        hooks?: PhysicsHooks,
+    ): number {
+        let dt = this.integrationParameters.dt;
+        if (dt <= 0.0) {
+            return 0;
+        }
+
+        this.accumulatedTime += Math.max(realDt, 0.0);
+
+        let numSteps = 0;
+        while (this.accumulatedTime >= dt && numSteps < maxSteps) {
+            this.step(eventQueue, hooks);
+            this.accumulatedTime -= dt;
+            numSteps += 1;
+        }
+
+        if (this.accumulatedTime >= dt) {
+            // Drop the whole steps that couldn't be performed, keeping the fraction.
+            this.accumulatedTime %= dt;
+        }
+
+        return numSteps;
+    }
+
+    /**
+     * The fraction, in `[0, 1)`, of a time step accumulated by `advance` but not simulated
+     * yet.
+     *
+     * The poses to render are best obtained by interpolating between the poses before the
+     * last step and the poses after it, with this factor.
+     */
+    public interpolationAlpha(): number {
+        return Math.min(
+            this.accumulatedTime / this.integrationParameters.dt,
+            1.0,
+        );
+    }
+
+    /**
+     * Discards the time accumulated by `advance` but not simulated yet.
+     */
+    public resetAccumulatedTime() {
+        this.accumulatedTime = 0.0;
+    }
+
+    /**
+     * Advance the simulation by one time step, once the current synchronous code completed.
+     *
+     * The step is scheduled as a microtask, so the current code, e.g., a render callback, runs
+     * before it, without the delay a timer would add. When pose double-buffering is enabled
+     * (see `enablePoseDoubleBuffering`), the poses computed by the previous step become the
+     * displayed poses as soon as this method is called. They can be read for rendering until
+     * this step runs, and the poses it computes are written to the latest pose buffer. Only one
+     * asynchronous step may be pending at a time. If the world is freed before the step runs,
+     * the step is skipped and the promise is rejected.
+     *
+     * @param EventQueue - (optional) structure responsible for collecting
+     *   events generated by the physics engine.
+     * @returns A promise resolved once the step completed.
+     */
+    public stepAsync(
+        eventQueue?: EventQueue,
+        hooks?: PhysicsHooks,
+    ): Promise<void> {
+        if (this.asyncStepPending) {
+            return Promise.reject(
+                new Error("An asynchronous world step is already pending."),
+            );
+        }
+
+        this.swapPoseBuffers();
+        this.asyncStepPending = true;
+        return Promise.resolve().then(() => {
+            try {
+                if (!this.physicsPipeline) {
+                    throw new Error(
+                        "The world was freed before its asynchronous step ran.",
+                    );
+                }
+                this.step(eventQueue, hooks);
+            } finally {
+                this.asyncStepPending = false;
+            }
+        });
+    }
+
+    /**
+     * Enables or disables pose double-buffering.
+     *
+     * When enabled, each step writes the resulting rigid-body poses into the latest pose
+     * buffer, while the poses of the step before remain available in the displayed pose
+     * buffer. This lets a renderer read a consistent set of poses while the next step runs.
+     *
+     * @param enabled - Whether pose double-buffering should be enabled.
+     */
+    public enablePoseDoubleBuffering(enabled: boolean) {
+        if (enabled && !this.latestPoseBuffer) {
+            this.displayedPoseBuffer = new PoseBuffer();
+            this.latestPoseBuffer = new PoseBuffer();
+            this.displayedPoseBuffer.update(this.bodies, this.islands);
+            this.latestPoseBuffer.update(this.bodies, this.islands);
+        } else if (!enabled) {
+            this.displayedPoseBuffer = null;
+            this.latestPoseBuffer = null;
+        }
+    }
+
+    /**
+     * Is pose double-buffering enabled?
+     */
+    public isPoseDoubleBufferingEnabled(): boolean {
+        return !!this.latestPoseBuffer;
+    }
+
+    /**
+     * The rigid-body poses meant to be displayed, i.e., the poses computed by the step before
+     * the last one, or `null` if pose double-buffering is disabled.
+     */
+    public displayedPoses(): PoseBuffer | null {
+        return this.displayedPoseBuffer;
+    }
+
+    /**
+     * The rigid-body poses computed by the last completed step, or `null` if pose
+     * double-buffering is disabled.
+     */
+    public latestPoses(): PoseBuffer | null {
+        return this.latestPoseBuffer;
+    }
+
+    private swapPoseBuffers() {
+        if (!!this.latestPoseBuffer) {
+            let displayed = this.displayedPoseBuffer;
+            this.displayedPoseBuffer = this.latestPoseBuffer;
+            this.latestPoseBuffer = displayed;
+        }
+    }
+
+    /**
+     * Recomputes the mass-properties of all the rigid-bodies which colliders were moved
+     * relative to them with a deferred mass-properties update (see `Collider.setTranslationWrtParent`
+     * and `Collider.setRotationWrtParent`).
+     *
+     * This is done automatically at the next physics step if this method isn't called.
+     */
+    public recomputeMassProperties() {
+        this.bodies.recomputeMassPropertiesFromColliders(
+            this.colliders,
+            this.colliders.takePendingMassPropertiesUpdates(),
         );
     }
 
@@ -306,6 +971,15 @@ export class World {
     //     this.queryPipeline.update(this.colliders);
     // }
 
+    /**
+     * The number of steps simulated since this world was created.
+     *
+     * This is not saved by snapshots, so it restarts from zero after a snapshot is restored.
+     */
+    get stepCount(): number {
+        return this.bodies.raw.numSteps();
+    }
+
     /**
      * The current simulation timestep.
      */
@@ -395,6 +1069,25 @@ export class World {
         this.integrationParameters.numInternalPgsIterations = niter;
     }
 
+    /**
+     * Number of stabilization iterations run at each solver iteration (default: `1`).
+     */
+    get numInternalStabilizationIterations(): number {
+        return this.integrationParameters.numInternalStabilizationIterations;
+    }
+
+    /**
+     * Sets the number of stabilization iterations run at each solver iteration (default: `1`).
+     *
+     * Stabilization iterations correct the penetrations and joint drift. Decreasing this value
+     * speeds up the solver, at the cost of slower penetration recovery.
+     *
+     * @param niter - The new number of stabilization iterations.
+     */
+    set numInternalStabilizationIterations(niter: number) {
+        this.integrationParameters.numInternalStabilizationIterations = niter;
+    }
+
     /**
      * The number of substeps continuous collision-detection can run (default: `1`).
      */
@@ -416,6 +1109,26 @@ export class World {
         this.integrationParameters.maxCcdSubsteps = substeps;
     }
 
+    /**
+     * Assigns a simulation tier to the given rigid-body, e.g., to simulate distant
+     * debris more cheaply. See `SimulationLod` for details.
+     *
+     * @param body - The rigid-body.
+     * @param tier - The new simulation tier of the rigid-body.
+     */
+    public setSimulationTier(body: RigidBody, tier: SimulationTier) {
+        this.simulationLod.setSimulationTier(this.bodies, body.handle, tier);
+    }
+
+    /**
+     * The simulation tier of the given rigid-body.
+     *
+     * @param body - The rigid-body.
+     */
+    public simulationTier(body: RigidBody): SimulationTier {
+        return this.simulationLod.simulationTier(body.handle);
+    }
+
     /**
      * Creates a new rigid-body from the given rigid-body descriptor.
      *
@@ -498,6 +1211,28 @@ export class World {
         controller.free();
     }
 
+    /**
+     * Creates a new modifier volume, applying an effect to all the dynamic rigid-bodies
+     * intersecting the given sensor collider at each step.
+     *
+     * @param collider - The sensor collider defining the volume.
+     */
+    public createModifierVolume(collider: Collider): ModifierVolume {
+        return this.modifierVolumes.createVolume(collider);
+    }
+
+    /**
+     * Removes a modifier volume from this world.
+     *
+     * The original gravity scale and linear damping of the rigid-bodies affected by the volume
+     * are restored, unless they are still affected by other volumes.
+     *
+     * @param volume - The modifier volume to remove.
+     */
+    public removeModifierVolume(volume: ModifierVolume) {
+        this.modifierVolumes.remove(volume, this.bodies);
+    }
+
     // #if DIM3
     /**
      * Creates a new vehicle controller.
@@ -568,6 +1303,121 @@ export class World {
         );
     }
 
+    /**
+     * Constrains the motion of a rigid-body relative to a world-space frame.
+     *
+     * The frame is centered at `origin` and its local X axis is aligned with `axis`. For
+     * example, locking the `AngY` and `AngZ` axes only lets the body rotate around `axis`.
+     * The constraint is anchored to a fixed rigid-body at the world origin, shared by all
+     * the world-space constraints and hidden from the rigid-bodies of this world.
+     *
+     * @param body - The rigid-body to constrain.
+     * @param origin - The world-space origin of the constraint frame.
+     * @param axis - The world-space direction of the X axis of the constraint frame.
+     * @param lockedAxes - The axes of the constraint frame along which the motion is locked.
+     * @param wakeUp - Should the constrained rigid-body be awakened?
+     */
+    public createWorldConstraint(
+        body: RigidBody,
+        origin: Vector,
+        axis: Vector,
+        lockedAxes: JointAxesMask,
+        wakeUp: boolean = true,
+    ): ImpulseJoint | null {
+        return this.impulseJoints.createWorldConstraint(
+            this.bodies,
+            body.handle,
+            origin,
+            axis,
+            lockedAxes,
+            wakeUp,
+        );
+    }
+
+    /**
+     * Constrains the translation of a rigid-body to a world-space plane (a line in 2D) of
+     * arbitrary orientation, e.g., for a tilted elevator or a slider.
+     *
+     * @param body - The rigid-body to constrain.
+     * @param point - A point on the plane.
+     * @param normal - The normal of the plane.
+     * @param lockRotations - If `true`, the rotations of the body are locked too.
+     * @param wakeUp - Should the constrained rigid-body be awakened?
+     */
+    public createPlaneConstraint(
+        body: RigidBody,
+        point: Vector,
+        normal: Vector,
+        lockRotations: boolean = false,
+        wakeUp: boolean = true,
+    ): ImpulseJoint | null {
+        let lockedAxes = JointAxesMask.LinX;
+        if (lockRotations) {
+            // #if DIM2
+            lockedAxes |= JointAxesMask.AngX;
+            // #endif
+            // #if DIM3
+            lockedAxes |=
+                JointAxesMask.AngX | JointAxesMask.AngY | JointAxesMask.AngZ;
+            // #endif
+        }
+
+        return this.createWorldConstraint(
+            body,
+            point,
+            normal,
+            lockedAxes,
+            wakeUp,
+        );
+    }
+
+    /**
+     * Constrains the translation of a rigid-body to a world-space line of arbitrary
+     * orientation, e.g., for a rail.
+     *
+     * @param body - The rigid-body to constrain.
+     * @param point - A point on the line.
+     * @param dir - The direction of the line.
+     * @param lockRotations - If `true`, the rotations of the body are locked too.
+     * @param wakeUp - Should the constrained rigid-body be awakened?
+     */
+    public createLineConstraint(
+        body: RigidBody,
+        point: Vector,
+        dir: Vector,
+        lockRotations: boolean = false,
+        wakeUp: boolean = true,
+    ): ImpulseJoint | null {
+        // #if DIM2
+        let lockedAxes = JointAxesMask.LinY;
+        if (lockRotations) lockedAxes |= JointAxesMask.AngX;
+        // #endif
+        // #if DIM3
+        let lockedAxes = JointAxesMask.LinY | JointAxesMask.LinZ;
+        if (lockRotations) {
+            lockedAxes |=
+                JointAxesMask.AngX | JointAxesMask.AngY | JointAxesMask.AngZ;
+        }
+        // #endif
+
+        return this.createWorldConstraint(body, point, dir, lockedAxes, wakeUp);
+    }
+
+    /**
+     * Removes a constraint created with `this.createWorldConstraint`, `this.createPlaneConstraint`,
+     * or `this.createLineConstraint`.
+     *
+     * @param joint - The constraint to remove.
+     * @param wakeUp - Should the constrained rigid-body be awakened?
+     */
+    public removeWorldConstraint(joint: ImpulseJoint, wakeUp: boolean = true) {
+        if (!this.impulseJoints.isWorldConstraint(this.bodies, joint.handle)) {
+            throw new Error("the joint isn't a world-space constraint");
+        }
+
+        this.impulseJoints.remove(joint.handle, wakeUp);
+    }
+
     /**
      * Creates a new multibody joint from the given joint descriptor.
      *
@@ -647,9 +1497,39 @@ export class World {
     }
 
     /**
-     * Removes the given collider from this physics world.
+     * Removes several rigid-bodies from this physics world at once.
      *
-     * @param collider - The collider to remove.
+     * This takes a single call into the physics engine instead of one per rigid-body, e.g.,
+     * when unloading a whole region of the scene. See `RigidBodySet.removeBatch`.
+     *
+     * @param bodies - The rigid-bodies to remove.
+     * @param alsoColliders - If `true`, the colliders attached to the removed rigid-bodies
+     *                        are removed too. Otherwise, they are detached from them.
+     * @param alsoJoints - If `true`, the joints attached to the removed rigid-bodies are
+     *                     removed too. Otherwise, the rigid-bodies with joints attached are
+     *                     not removed.
+     * @returns The handles of the rigid-bodies that were actually removed.
+     */
+    public removeRigidBodies(
+        bodies: RigidBody[],
+        alsoColliders: boolean = true,
+        alsoJoints: boolean = true,
+    ): RigidBodyHandle[] {
+        return this.bodies.removeBatch(
+            bodies.map((body) => body.handle),
+            this.islands,
+            this.colliders,
+            this.impulseJoints,
+            this.multibodyJoints,
+            alsoColliders,
+            alsoJoints,
+        );
+    }
+
+    /**
+     * Removes the given collider from this physics world.
+     *
+     * @param collider - The collider to remove.
      * @param wakeUp - If set to `true`, the rigid-body this collider is attached to will be awaken.
      */
     public removeCollider(collider: Collider, wakeUp: boolean) {
@@ -660,9 +1540,40 @@ export class World {
                 this.bodies,
                 wakeUp,
             );
+            this.contactMaterials.removeColliderMaterial(collider.handle);
         }
     }
 
+    /**
+     * Removes several colliders from this physics world at once.
+     *
+     * This takes a single call into the physics engine instead of one per collider. See
+     * `ColliderSet.removeBatch`.
+     *
+     * @param colliders - The colliders to remove.
+     * @param wakeUp - If set to `true`, the rigid-bodies these colliders are attached to will be awaken.
+     */
+    public removeColliders(colliders: Collider[], wakeUp: boolean) {
+        let handles = colliders.map((collider) => collider.handle);
+        this.colliders.removeBatch(handles, this.islands, this.bodies, wakeUp);
+        handles.forEach((handle) =>
+            this.contactMaterials.removeColliderMaterial(handle),
+        );
+    }
+
+    /**
+     * Attaches the given collider to a new parent rigid-body, or detaches it from its parent.
+     *
+     * This works with rigid-bodies that are links of a multibody: their mass properties are
+     * recomputed immediately, and the mass matrix of the multibody at the next timestep.
+     *
+     * @param collider - The collider to attach.
+     * @param parent - The new parent rigid-body, or `null` to detach the collider.
+     */
+    public setColliderParent(collider: Collider, parent: RigidBody | null) {
+        this.colliders.setParent(collider, parent, this.bodies);
+    }
+
     /**
      * Removes the given impulse joint from this physics world.
      *
@@ -718,6 +1629,32 @@ export class World {
         this.bodies.forEachActiveRigidBody(this.islands, f);
     }
 
+    /**
+     * Copies the velocities of the rigid-bodies managed by this physics world into the given buffers.
+     *
+     * The velocities are packed in `velocities` as `[linvel.x, linvel.y, angvel]` in 2D, and as
+     * `[linvel.x, linvel.y, linvel.z, angvel.x, angvel.y, angvel.z]` in 3D. The handle of the
+     * rigid-body each velocity belongs to is written at the same index in `handles`. The copy
+     * stops as soon as one of the buffers is full.
+     *
+     * @param handles - The buffer where the rigid-body handles are written.
+     * @param velocities - The buffer where the velocities are written.
+     * @param activeOnly - If `true`, only the velocities of active rigid-bodies are copied.
+     * @returns The number of rigid-bodies which velocities have been copied.
+     */
+    public copyVelocitiesToBuffer(
+        handles: Float64Array,
+        velocities: Float32Array,
+        activeOnly: boolean = false,
+    ): number {
+        return this.bodies.copyVelocitiesToBuffer(
+            this.islands,
+            activeOnly,
+            handles,
+            velocities,
+        );
+    }
+
     /**
      * Find the closest intersection between a ray and the physics world.
      *
@@ -1014,6 +1951,277 @@ export class World {
         );
     }
 
+    /**
+     * Sweeps a shape between two poses, and returns the first collider it hits.
+     *
+     * The shape translates along a straight line from `startPos` to `endPos` while rotating
+     * uniformly, around its local origin, from `startRot` to `endRot`. This can be used to
+     * check that a teleportation between two poses doesn't go through any collider.
+     *
+     * @param shape - The shape to sweep.
+     * @param startPos - The initial position of the shape.
+     * @param startRot - The initial rotation of the shape.
+     * @param endPos - The final position of the shape.
+     * @param endRot - The final rotation of the shape.
+     * @param stopAtPenetration - If set to `false`, the sweep won’t immediately stop if
+     *   the shape is penetrating another shape at its starting pose **and** its motion is such
+     *   that it’s on a path to exit that penetration state.
+     * @param filterFlags - Flags for excluding whole sets of colliders from the query.
+     * @param filterGroups - The bit groups and filter associated to the shape, in order to only
+     *   test on colliders with collision groups compatible with this group.
+     * @param filterExcludeCollider - A collider excluded from the query.
+     * @param filterExcludeRigidBody - The colliders attached to this rigid-body are excluded from the query.
+     * @param filterPredicate - Any collider for which this closure returns `false` is excluded.
+     * @returns The first hit, if any. Its time of impact is the fraction of the motion, between
+     *   `0` and `1`, traveled by the shape before the impact.
+     */
+    public sweepShapeBetweenPoses(
+        shape: Shape,
+        startPos: Vector,
+        startRot: Rotation,
+        endPos: Vector,
+        endRot: Rotation,
+        stopAtPenetration: boolean,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: Collider,
+        filterExcludeRigidBody?: RigidBody,
+        filterPredicate?: (collider: Collider) => boolean,
+    ): ColliderShapeCastHit | null {
+        return this.broadPhase.sweepShapeBetweenPoses(
+            this.narrowPhase,
+            this.bodies,
+            this.colliders,
+            shape,
+            startPos,
+            startRot,
+            endPos,
+            endRot,
+            stopAtPenetration,
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider ? filterExcludeCollider.handle : null,
+            filterExcludeRigidBody ? filterExcludeRigidBody.handle : null,
+            this.colliders.castClosure(filterPredicate),
+        );
+    }
+
+    /**
+     * Casts a shape at a constant linear velocity and retrieve all the colliders it hits,
+     * sorted by increasing time-of-impact.
+     *
+     * @param shapePos - The initial position of the shape to cast.
+     * @param shapeRot - The initial rotation of the shape to cast.
+     * @param shapeVel - The constant velocity of the shape to cast (i.e. the cast direction).
+     * @param shape - The shape to cast.
+     * @param targetDistance − If the shape moves closer to this distance from a collider, a hit
+     *                         will be returned.
+     * @param maxToi - The maximum time-of-impact that can be reported by this cast. This effectively
+     *   limits the distance traveled by the shape to `shapeVel.norm() * maxToi`.
+     * @param stopAtPenetration - If set to `false`, the linear shape-cast won’t immediately stop if
+     *   the shape is penetrating another shape at its starting point **and** its trajectory is such
+     *   that it’s on a path to exit that penetration state.
+     * @param groups - The bit groups and filter associated to the shape to cast, in order to only
+     *   test on colliders with collision groups compatible with this group.
+     * @returns The hits, packed as `[handle, timeOfImpact, witness1, witness2, normal1, normal2]`
+     *   for each hit, i.e., 10 values per hit in 2D and 14 values per hit in 3D. The witnesses
+     *   and normals are expressed in the local-space of the cast shape and of the hit collider,
+     *   like those of `ColliderShapeCastHit`.
+     */
+    public castShapeAll(
+        shapePos: Vector,
+        shapeRot: Rotation,
+        shapeVel: Vector,
+        shape: Shape,
+        targetDistance: number,
+        maxToi: number,
+        stopAtPenetration: boolean,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: Collider,
+        filterExcludeRigidBody?: RigidBody,
+        filterPredicate?: (collider: Collider) => boolean,
+    ): Float64Array {
+        return this.broadPhase.castShapeAll(
+            this.narrowPhase,
+            this.bodies,
+            this.colliders,
+            shapePos,
+            shapeRot,
+            shapeVel,
+            shape,
+            targetDistance,
+            maxToi,
+            stopAtPenetration,
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider ? filterExcludeCollider.handle : null,
+            filterExcludeRigidBody ? filterExcludeRigidBody.handle : null,
+            this.colliders.castClosure(filterPredicate),
+        );
+    }
+
+    /**
+     * Finds the ground straight below (along `-Y`) each of the given points, e.g., to place
+     * foliage or props on a terrain.
+     *
+     * @param points - The points to drop, packed as a flat array of coordinates.
+     * @param maxDrop - The maximum distance below each point where the ground is searched for.
+     * @returns The results for each point, packed as `[handle, height, normal]`, where `height`
+     *   is the `Y` coordinate of the ground below the point. The `handle` and `height` of a point
+     *   without ground below it are `NaN`, and its normal is zero. Each result takes 4 values
+     *   in 2D and 5 values in 3D.
+     */
+    public dropPointsToGround(
+        points: Float32Array,
+        maxDrop: number,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: Collider,
+        filterExcludeRigidBody?: RigidBody,
+        filterPredicate?: (collider: Collider) => boolean,
+    ): Float64Array {
+        return this.broadPhase.dropPointsToGround(
+            this.narrowPhase,
+            this.bodies,
+            this.colliders,
+            points,
+            maxDrop,
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider ? filterExcludeCollider.handle : null,
+            filterExcludeRigidBody ? filterExcludeRigidBody.handle : null,
+            this.colliders.castClosure(filterPredicate),
+        );
+    }
+
+    /**
+     * Moves a rigid-body out of the colliders it penetrates, e.g., right after spawning it
+     * inside of a wall.
+     *
+     * The rigid-body is translated along the normal of its deepest penetration, until it no
+     * longer penetrates anything, or after `maxIterations` iterations. Its velocities are left
+     * unchanged. Sensors are ignored.
+     *
+     * @param body - The rigid-body to move.
+     * @param maxIterations - The maximum number of penetrations resolved.
+     * @returns The translation applied to the rigid-body.
+     */
+    public depenetrate(
+        body: RigidBody,
+        maxIterations: number = 10,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: Collider,
+        filterPredicate?: (collider: Collider) => boolean,
+    ): Vector {
+        return this.broadPhase.depenetrate(
+            this.narrowPhase,
+            this.bodies,
+            this.colliders,
+            body.handle,
+            maxIterations,
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider ? filterExcludeCollider.handle : null,
+            this.colliders.castClosure(filterPredicate),
+        );
+    }
+
+    /**
+     * Predicts the ballistic trajectory of a projectile against the fixed colliders of this
+     * world, without stepping the simulation.
+     *
+     * The velocity of the projectile is integrated the same way `World.step` integrates the
+     * velocities of dynamic rigid-bodies (taking this world’s gravity into account), and its
+     * path is sampled every `dt` seconds, until it hits a fixed collider or `maxTime` is reached.
+     *
+     * @param shapePos - The initial position of the projectile.
+     * @param shapeRot - The rotation of the projectile (it doesn’t rotate along the trajectory).
+     * @param shapeVel - The initial linear velocity of the projectile.
+     * @param shape - The shape of the projectile.
+     * @param maxTime - The duration of the trajectory to predict.
+     * @param dt - The sampling interval, e.g., the timestep of this world.
+     * @param gravityScale - The gravity scale of the projectile.
+     * @param linearDamping - The linear damping of the projectile.
+     * @param filterFlags - Additional flags for filtering the colliders the projectile can hit.
+     *   Colliders attached to non-fixed rigid-bodies are always excluded.
+     */
+    public predictTrajectory(
+        shapePos: Vector,
+        shapeRot: Rotation,
+        shapeVel: Vector,
+        shape: Shape,
+        maxTime: number,
+        dt: number,
+        gravityScale: number = 1.0,
+        linearDamping: number = 0.0,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: Collider,
+        filterExcludeRigidBody?: RigidBody,
+        filterPredicate?: (collider: Collider) => boolean,
+    ): PredictedTrajectory {
+        return this.broadPhase.predictTrajectory(
+            this.narrowPhase,
+            this.bodies,
+            this.colliders,
+            shapePos,
+            shapeRot,
+            shapeVel,
+            shape,
+            this.gravity,
+            gravityScale,
+            linearDamping,
+            maxTime,
+            dt,
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider ? filterExcludeCollider.handle : null,
+            filterExcludeRigidBody ? filterExcludeRigidBody.handle : null,
+            this.colliders.castClosure(filterPredicate),
+        );
+    }
+
+    /**
+     * Predicts the ballistic trajectory of a rigid-body against the fixed colliders of this
+     * world, without stepping the simulation.
+     *
+     * The trajectory starts from the current position and linear velocity of the rigid-body,
+     * uses its gravity scale and linear damping, and the shape of its first collider.
+     *
+     * @param body - The rigid-body to predict the trajectory of. It must have at least one collider.
+     * @param maxTime - The duration of the trajectory to predict.
+     * @param dt - The sampling interval, e.g., the timestep of this world.
+     * @param initialVel - If set, overrides the current linear velocity of the rigid-body.
+     */
+    public predictBodyTrajectory(
+        body: RigidBody,
+        maxTime: number,
+        dt: number,
+        initialVel?: Vector,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterPredicate?: (collider: Collider) => boolean,
+    ): PredictedTrajectory {
+        let collider = body.collider(0);
+        return this.predictTrajectory(
+            collider.translation(),
+            collider.rotation(),
+            initialVel || body.linvel(),
+            collider.shape,
+            maxTime,
+            dt,
+            body.gravityScale(),
+            body.linearDamping(),
+            filterFlags,
+            filterGroups,
+            null,
+            body,
+            filterPredicate,
+        );
+    }
+
     /**
      * Retrieve all the colliders intersecting the given shape.
      *
@@ -1051,6 +2259,147 @@ export class World {
         );
     }
 
+    /**
+     * Retrieve all the colliders intersecting the given cone, e.g., a vision cone.
+     *
+     * In 2D, the cone is a triangle.
+     *
+     * @param apex - The apex of the cone.
+     * @param dir - The direction of the axis of the cone, from its apex to its base.
+     * @param halfAngle - The half-aperture angle of the cone, in radians.
+     * @param maxDist - The distance between the apex of the cone and its flat base.
+     * @param callback - A function called with each collider intersecting the cone.
+     */
+    public intersectionsWithCone(
+        apex: Vector,
+        dir: Vector,
+        halfAngle: number,
+        maxDist: number,
+        callback: (collider: Collider) => boolean,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: Collider,
+        filterExcludeRigidBody?: RigidBody,
+        filterPredicate?: (collider: Collider) => boolean,
+    ) {
+        this.broadPhase.intersectionsWithCone(
+            this.narrowPhase,
+            this.bodies,
+            this.colliders,
+            apex,
+            dir,
+            halfAngle,
+            maxDist,
+            this.colliders.castClosure(callback),
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider ? filterExcludeCollider.handle : null,
+            filterExcludeRigidBody ? filterExcludeRigidBody.handle : null,
+            this.colliders.castClosure(filterPredicate),
+        );
+    }
+
+    /**
+     * Retrieve all the colliders intersecting the convex region bounded by the given planes,
+     * e.g., a camera frustum.
+     *
+     * @param planes - The bounding planes, packed as `[nx, ny, (nz,) d]`. The region contains
+     *   the points `x` such that `dot(n, x) + d >= 0` for all the planes.
+     * @param callback - A function called with each collider intersecting the region.
+     */
+    public intersectionsWithFrustum(
+        planes: number[] | Float32Array,
+        callback: (collider: Collider) => boolean,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: Collider,
+        filterExcludeRigidBody?: RigidBody,
+        filterPredicate?: (collider: Collider) => boolean,
+    ) {
+        this.broadPhase.intersectionsWithFrustum(
+            this.narrowPhase,
+            this.bodies,
+            this.colliders,
+            planes,
+            this.colliders.castClosure(callback),
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider ? filterExcludeCollider.handle : null,
+            filterExcludeRigidBody ? filterExcludeRigidBody.handle : null,
+            this.colliders.castClosure(filterPredicate),
+        );
+    }
+
+    /**
+     * Casts the next rays of the given batch against this world, until `maxRays` rays were
+     * cast or `maxTimeMs` milliseconds elapsed, e.g., to spread a large batch over several
+     * frames. At least one ray is cast by each call.
+     *
+     * @param batch - The batch of rays to cast.
+     * @param maxRays - The maximum number of rays to cast, or `null` for no limit.
+     * @param maxTimeMs - The maximum duration of this call, in milliseconds, or `null` for no limit.
+     * @returns `true` if all the rays of the batch have been cast.
+     */
+    public resumeRayCastBatch(
+        batch: RayCastBatch,
+        maxRays?: number,
+        maxTimeMs?: number,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: Collider,
+        filterExcludeRigidBody?: RigidBody,
+        filterPredicate?: (collider: Collider) => boolean,
+    ): boolean {
+        return batch.resume(
+            this.broadPhase,
+            this.narrowPhase,
+            this.bodies,
+            this.colliders,
+            maxRays,
+            maxTimeMs,
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider ? filterExcludeCollider.handle : null,
+            filterExcludeRigidBody ? filterExcludeRigidBody.handle : null,
+            this.colliders.castClosure(filterPredicate),
+        );
+    }
+
+    /**
+     * Tests the next poses of the given batch against this world, until `maxTests` poses were
+     * tested or `maxTimeMs` milliseconds elapsed, e.g., to spread a large batch over several
+     * frames. At least one pose is tested by each call.
+     *
+     * @param batch - The batch of intersection tests.
+     * @param maxTests - The maximum number of poses to test, or `null` for no limit.
+     * @param maxTimeMs - The maximum duration of this call, in milliseconds, or `null` for no limit.
+     * @returns `true` if all the poses of the batch have been tested.
+     */
+    public resumeShapeIntersectionBatch(
+        batch: ShapeIntersectionBatch,
+        maxTests?: number,
+        maxTimeMs?: number,
+        filterFlags?: QueryFilterFlags,
+        filterGroups?: InteractionGroups,
+        filterExcludeCollider?: Collider,
+        filterExcludeRigidBody?: RigidBody,
+        filterPredicate?: (collider: Collider) => boolean,
+    ): boolean {
+        return batch.resume(
+            this.broadPhase,
+            this.narrowPhase,
+            this.bodies,
+            this.colliders,
+            maxTests,
+            maxTimeMs,
+            filterFlags,
+            filterGroups,
+            filterExcludeCollider ? filterExcludeCollider.handle : null,
+            filterExcludeRigidBody ? filterExcludeRigidBody.handle : null,
+            this.colliders.castClosure(filterPredicate),
+        );
+    }
+
     /**
      * Finds the handles of all the colliders with an AABB intersecting the given AABB.
      *
@@ -1121,6 +2470,76 @@ export class World {
         this.narrowPhase.contactPair(collider1.handle, collider2.handle, f);
     }
 
+    /**
+     * Iterates through all the contact manifolds between the colliders attached to two
+     * rigid-bodies.
+     *
+     * @param body1 - The first rigid-body involved in the contacts.
+     * @param body2 - The second rigid-body involved in the contacts.
+     * @param f - Closure that will be called on each contact manifold between a collider
+     *            `collider1` attached to `body1` and a collider `collider2` attached to `body2`.
+     *            If the second argument passed to this closure is `true`, then the contact
+     *            manifold data is flipped, i.e., methods like `localNormal1` actually apply to
+     *            `collider2` and fields like `localNormal2` apply to `collider1`.
+     */
+    public contactsBetweenBodies(
+        body1: RigidBody,
+        body2: RigidBody,
+        f: (
+            manifold: TempContactManifold,
+            flipped: boolean,
+            collider1: Collider,
+            collider2: Collider,
+        ) => void,
+    ) {
+        this.narrowPhase.contactsBetweenBodies(
+            this.bodies,
+            body1.handle,
+            body2.handle,
+            (manifold, flipped, collider1, collider2) =>
+                f(
+                    manifold,
+                    flipped,
+                    this.colliders.get(collider1),
+                    this.colliders.get(collider2),
+                ),
+        );
+    }
+
+    /**
+     * Computes the contact manifolds between the given pair of colliders right now, without
+     * stepping the simulation, e.g., to check whether a piece would overlap its surroundings
+     * at another position.
+     *
+     * @param collider1 - The first collider involved in the contact.
+     * @param collider2 - The second collider involved in the contact.
+     * @param prediction - The distance below which non-touching points are reported as contacts.
+     * @param f - Closure that will be called on each computed contact manifold.
+     * @param collider1Pos - If set, the contacts are computed as if `collider1` was at this
+     *   world-space translation instead of its current one.
+     * @param collider1Rot - If set, the contacts are computed as if `collider1` had this
+     *   world-space rotation instead of its current one.
+     * @returns `false` if contacts between the shapes of these colliders aren’t supported.
+     */
+    public computeContactNow(
+        collider1: Collider,
+        collider2: Collider,
+        prediction: number,
+        f: (manifold: TempContactManifold) => void,
+        collider1Pos?: Vector,
+        collider1Rot?: Rotation,
+    ): boolean {
+        return this.narrowPhase.computeContactNow(
+            this.colliders,
+            collider1.handle,
+            collider2.handle,
+            prediction,
+            f,
+            collider1Pos,
+            collider1Rot,
+        );
+    }
+
     /**
      * Returns `true` if `collider1` and `collider2` intersect and at least one of them is a sensor.
      * @param collider1 − The first collider involved in the intersection.
@@ -1133,6 +2552,65 @@ export class World {
         );
     }
 
+    /**
+     * The number of contact pairs for which the colliders involved request all the given hooks.
+     *
+     * This can be used to detect an accidental activation of `ActiveHooks.MODIFY_SOLVER_CONTACTS`
+     * on a large number of pairs, which can significantly impact performances.
+     *
+     * @param hooks - The hooks the contact pairs must request to be counted.
+     */
+    public numContactPairsWithActiveHooks(hooks: ActiveHooks): number {
+        return this.narrowPhase.numContactPairsWithActiveHooks(
+            this.colliders,
+            hooks,
+        );
+    }
+
+    // #if DIM3
+    /**
+     * Extracts the triangles of the fixed colliders of this world that are flat enough to be
+     * walked on, merged into a single welded mesh, e.g., to be given to a navigation mesh baker.
+     *
+     * Colliders without parent, or attached to a fixed rigid-body, are tessellated, except
+     * sensors and disabled colliders. The normals of triangle meshes are given by the
+     * counter-clockwise winding of their triangles.
+     *
+     * @param maxSlopeAngle - The steepest walkable slope, in radians.
+     * @param up - The unit vector pointing upward.
+     * @param weldDistance - The vertices closer than about this distance are merged. If zero,
+     *   only identical vertices are merged.
+     * @param subdivisions - The number of subdivisions used to tessellate curved shapes.
+     */
+    public walkableSurfaces(
+        maxSlopeAngle: number,
+        up: Vector = VectorOps.new(0.0, 1.0, 0.0),
+        weldDistance: number = 1.0e-3,
+        subdivisions: number = 16,
+    ): WalkableSurfaces {
+        return this.colliders.walkableSurfaces(
+            this.bodies,
+            up,
+            maxSlopeAngle,
+            weldDistance,
+            subdivisions,
+        );
+    }
+    // #endif
+
+    /**
+     * Sets the physics hooks active for all the given colliders at once.
+     *
+     * @param colliders - The colliders to modify.
+     * @param activeHooks - The hooks active for contact/intersection pairs involving these colliders.
+     */
+    public setActiveHooks(colliders: Collider[], activeHooks: ActiveHooks) {
+        this.colliders.setActiveHooks(
+            colliders.map((collider) => collider.handle),
+            activeHooks,
+        );
+    }
+
     /**
      * Sets whether internal performance profiling is enabled (default: false).
      *
@@ -1309,4 +2787,131 @@ export class World {
     public timingUserChanges(): number {
         return this.physicsPipeline.raw.timing_user_changes();
     }
+
+    /**
+     * The total time spent in the JS callbacks of the physics hooks during the last timestep.
+     *
+     * This is reset at each timestep, and is zero if the last timestep was run without hooks.
+     */
+    public timingHooks(): number {
+        return this.physicsPipeline.raw.timing_hooks();
+    }
+
+    /**
+     * The number of contact pairs rejected by `PhysicsHooks.filterContactPair` during the last timestep.
+     */
+    public hooksNumContactPairsFiltered(): number {
+        return this.physicsPipeline.raw.hooks_num_contact_pairs_filtered();
+    }
+
+    /**
+     * The number of contact pairs accepted by `PhysicsHooks.filterContactPair` during the last timestep.
+     */
+    public hooksNumContactPairsPassed(): number {
+        return this.physicsPipeline.raw.hooks_num_contact_pairs_passed();
+    }
+
+    /**
+     * The number of intersection pairs rejected by `PhysicsHooks.filterIntersectionPair` during the
+     * last timestep.
+     */
+    public hooksNumIntersectionPairsFiltered(): number {
+        return this.physicsPipeline.raw.hooks_num_intersection_pairs_filtered();
+    }
+
+    /**
+     * The number of intersection pairs accepted by `PhysicsHooks.filterIntersectionPair` during the
+     * last timestep.
+     */
+    public hooksNumIntersectionPairsPassed(): number {
+        return this.physicsPipeline.raw.hooks_num_intersection_pairs_passed();
+    }
+
+    /**
+     * The number of contact pairs given to `PhysicsHooks.modifySolverContacts` during the last timestep.
+     */
+    public hooksNumContactPairsModified(): number {
+        return this.physicsPipeline.raw.hooks_num_contact_pairs_modified();
+    }
+
+    /**
+     * The number of colliders which `filterGroups` were tested by the scene queries of this
+     * world since the last call to `resetQueryCounters`.
+     */
+    public queryNumGroupTests(): number {
+        return this.broadPhase.raw.queryNumGroupTests();
+    }
+
+    /**
+     * The number of colliders rejected by the scene queries of this world because of their
+     * `filterGroups` since the last call to `resetQueryCounters`.
+     */
+    public queryNumGroupRejections(): number {
+        return this.broadPhase.raw.queryNumGroupRejections();
+    }
+
+    /**
+     * The number of colliders accepted by the filters of the scene queries of this world, then
+     * tested against the exact query shape, since the last call to `resetQueryCounters`.
+     */
+    public queryNumAcceptedColliders(): number {
+        return this.broadPhase.raw.queryNumAcceptedColliders();
+    }
+
+    /**
+     * Resets the `queryNum*` counters, e.g., before benchmarking some scene queries.
+     */
+    public resetQueryCounters() {
+        this.broadPhase.raw.resetQueryStats();
+    }
+
+    /**
+     * The largest number of solver iterations configured for an island during the last
+     * timestep.
+     *
+     * This only reports the configuration: Rapier doesn't report the iterations its solver
+     * actually ran. Each island is configured with `numSolverIterations` iterations, plus the
+     * largest `additionalSolverIterations` of its active rigid-bodies. This is zero if no
+     * dynamic rigid-body was active.
+     */
+    public solverMaxConfiguredIterations(): number {
+        return this.physicsPipeline.raw.solver_max_configured_iterations();
+    }
+
+    /**
+     * The number of active dynamic rigid-bodies configured with additional solver iterations
+     * during the last timestep. Each of them increases the number of iterations configured for
+     * its whole island.
+     */
+    public solverNumBodiesWithAdditionalIterations(): number {
+        return this.physicsPipeline.raw.solver_num_bodies_with_additional_iterations();
+    }
+}
+
+/**
+ * Sorts the given objects by their keys, compared element by element.
+ *
+ * Throws if two objects have the same key.
+ */
+function sortByKey<T>(
+    objects: T[],
+    key: (object: T) => (number | string)[],
+    name: string,
+): T[] {
+    let keyed = objects.map((object) => ({object, key: key(object)}));
+    let compare = (a: (number | string)[], b: (number | string)[]) => {
+        for (let i = 0; i < Math.min(a.length, b.length); ++i) {
+            if (a[i] < b[i]) return -1;
+            if (a[i] > b[i]) return 1;
+        }
+        return a.length - b.length;
+    };
+
+    keyed.sort((a, b) => compare(a.key, b.key));
+    for (let i = 1; i < keyed.length; ++i) {
+        if (compare(keyed[i - 1].key, keyed[i].key) == 0) {
+            throw Error(`Each ${name} must have a unique key.`);
+        }
+    }
+    return keyed.map((entry) => entry.object);
 }
@@ -8,7 +8,7 @@ use rapier::control::{
     KinematicCharacterController,
 };
 use rapier::geometry::{ColliderHandle, ShapeCastHit};
-use rapier::math::{Point, Real, Vector};
+use rapier::math::{Point, Real, Vector, DIM};
 use rapier::parry::query::ShapeCastStatus;
 use rapier::pipeline::{QueryFilter, QueryFilterFlags};
 use wasm_bindgen::prelude::*;
@@ -18,6 +18,8 @@ pub struct RawKinematicCharacterController {
     controller: KinematicCharacterController,
     result: EffectiveCharacterMovement,
     events: Vec<CharacterCollision>,
+    max_substep_length: Option<Real>,
+    max_substeps: u32,
 }
 
 fn length_value(length: CharacterLength) -> Real {
@@ -45,6 +47,8 @@ impl RawKinematicCharacterController {
                 is_sliding_down_slope: false,
             },
             events: vec![],
+            max_substep_length: None,
+            max_substeps: 0,
         }
     }
 
@@ -140,6 +144,34 @@ impl RawKinematicCharacterController {
         self.controller.snap_to_ground.is_some()
     }
 
+    /// The maximum length of each sub-movement, if tunneling protection is enabled.
+    pub fn tunnelingProtectionMaxSubstepLength(&self) -> Option<Real> {
+        self.max_substep_length
+    }
+
+    /// The maximum number of sub-movements, if tunneling protection is enabled.
+    pub fn tunnelingProtectionMaxSubsteps(&self) -> Option<u32> {
+        self.max_substep_length.map(|_| self.max_substeps)
+    }
+
+    pub fn tunnelingProtectionEnabled(&self) -> bool {
+        self.max_substep_length.is_some()
+    }
+
+    /// Splits desired translations longer than `maxSubstepLength` into equal sub-movements
+    /// of at most that length, each resolved with its own shape-casts.
+    ///
+    /// At most `maxSubsteps` sub-movements are performed, so the desired translation is
+    /// clamped to `maxSubstepLength * maxSubsteps`.
+    pub fn enableTunnelingProtection(&mut self, maxSubstepLength: Real, maxSubsteps: u32) {
+        self.max_substep_length = Some(maxSubstepLength.max(Real::EPSILON));
+        self.max_substeps = maxSubsteps.max(1);
+    }
+
+    pub fn disableTunnelingProtection(&mut self) {
+        self.max_substep_length = None;
+    }
+
     pub fn computeColliderMovement(
         &mut self,
         dt: Real,
@@ -156,7 +188,7 @@ impl RawKinematicCharacterController {
         filter_predicate: &js_sys::Function,
     ) {
         let handle = crate::utils::collider_handle(collider_handle);
-        if let Some(collider) = colliders.0.get(handle) {
+        if let Some(collider) = colliders.set.get(handle) {
             let collider_pose = *collider.position();
             let collider_shape = collider.shared_shape().clone();
             let collider_parent = collider.parent();
@@ -174,28 +206,72 @@ impl RawKinematicCharacterController {
                 let character_mass = character_mass
                     .or_else(|| {
                         collider_parent
-                            .and_then(|h| bodies.0.get(h))
+                            .and_then(|h| bodies.set.get(h))
                             .map(|b| b.mass())
                     })
                     .unwrap_or(0.0);
 
-                let mut query_pipeline = broad_phase.0.as_query_pipeline_mut(
-                    narrow_phase.0.query_dispatcher(),
-                    &mut bodies.0,
-                    &mut colliders.0,
+                let mut query_pipeline = broad_phase.broad_phase.as_query_pipeline_mut(
+                    narrow_phase.narrow_phase.query_dispatcher(),
+                    &mut bodies.set,
+                    &mut colliders.set,
                     query_filter,
                 );
 
                 self.events.clear();
                 let events = &mut self.events;
-                self.result = self.controller.move_shape(
-                    dt,
-                    &query_pipeline.as_ref(),
-                    &*collider_shape,
-                    &collider_pose,
-                    desired_translation_delta.0,
-                    |event| events.push(event),
-                );
+                let desired = desired_translation_delta.0;
+                let length = desired.norm();
+
+                match self.max_substep_length {
+                    Some(max_length) if length > max_length => {
+                        // Clamp the desired translation, then split it evenly so the
+                        // sub-movements add up to exactly the clamped translation.
+                        let clamped_length = length.min(max_length * self.max_substeps as Real);
+                        let num_substeps = ((clamped_length / max_length).ceil() as u32)
+                            .max(1)
+                            .min(self.max_substeps);
+                        let substep = desired * (clamped_length / (length * num_substeps as Real));
+                        let mut pose = collider_pose;
+                        let mut translation = Vector::zeros();
+                        let mut result = None;
+
+                        for _ in 0..num_substeps {
+                            let substep_result = self.controller.move_shape(
+                                dt,
+                                &query_pipeline.as_ref(),
+                                &*collider_shape,
+                                &pose,
+                                substep,
+                                |event| events.push(event),
+                            );
+                            pose.translation.vector += substep_result.translation;
+                            translation += substep_result.translation;
+                            let blocked =
+                                substep_result.translation.norm_squared() <= Real::EPSILON;
+                            result = Some(substep_result);
+
+                            if blocked {
+                                break;
+                            }
+                        }
+
+                        if let Some(result) = result {
+                            self.result = result;
+                        }
+                        self.result.translation = translation;
+                    }
+                    _ => {
+                        self.result = self.controller.move_shape(
+                            dt,
+                            &query_pipeline.as_ref(),
+                            &*collider_shape,
+                            &collider_pose,
+                            desired,
+                            |event| events.push(event),
+                        );
+                    }
+                }
 
                 if apply_impulses_to_dynamic_bodies {
                     self.controller.solve_character_collision_impulses(
@@ -231,6 +307,26 @@ impl RawKinematicCharacterController {
 
         i < self.events.len()
     }
+
+    /// All the collisions detected by the last call to `computeColliderMovement`, packed
+    /// as a flat array.
+    ///
+    /// Each collision is packed as `[handle, toi, witness, normal, applied, remaining]`
+    /// where `witness` and `normal` are the world-space contact point and outward normal on
+    /// the obstacle, and `applied` and `remaining` are the translation deltas applied before
+    /// and remaining after this collision.
+    pub fn computedCollisionsPacked(&self) -> Vec<f64> {
+        let mut out = Vec::with_capacity(self.events.len() * (2 + 4 * DIM));
+        for coll in &self.events {
+            out.push(utils::flat_handle(coll.handle.0));
+            out.push(coll.hit.time_of_impact as f64);
+            out.extend(coll.hit.witness1.coords.iter().map(|x| *x as f64));
+            out.extend(coll.hit.normal1.iter().map(|x| *x as f64));
+            out.extend(coll.translation_applied.iter().map(|x| *x as f64));
+            out.extend(coll.translation_remaining.iter().map(|x| *x as f64));
+        }
+        out
+    }
 }
 
 #[wasm_bindgen]
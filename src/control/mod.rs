@@ -1,10 +1,12 @@
 pub use self::character_controller::RawKinematicCharacterController;
+pub use self::modifier_volume::RawModifierVolumeSet;
 pub use self::pid_controller::RawPidController;
 
 #[cfg(feature = "dim3")]
 pub use self::ray_cast_vehicle_controller::RawDynamicRayCastVehicleController;
 
 mod character_controller;
+mod modifier_volume;
 mod pid_controller;
 
 #[cfg(feature = "dim3")]
@@ -0,0 +1,242 @@
+use crate::dynamics::RawRigidBodySet;
+use crate::geometry::{RawColliderSet, RawNarrowPhase};
+use crate::math::RawVector;
+use crate::utils::{self, FlatHandle};
+use rapier::dynamics::RigidBodyHandle;
+use rapier::geometry::ColliderHandle;
+use rapier::math::{Real, Vector};
+use std::collections::{BTreeMap, HashMap};
+use wasm_bindgen::prelude::*;
+
+/// A sensor volume applying an effect to all the dynamic rigid-bodies intersecting it.
+struct ModifierVolume {
+    collider: ColliderHandle,
+    gravity_scale: Option<Real>,
+    linear_damping: Option<Real>,
+    force: Vector<Real>,
+    // The bodies affected by this volume during the last timestep.
+    affected: Vec<RigidBodyHandle>,
+}
+
+/// The gravity scale and linear damping of a rigid-body before it entered any modifier volume.
+struct BaseValues {
+    gravity_scale: Real,
+    linear_damping: Real,
+}
+
+/// The modifier volumes of a physics world.
+///
+/// The original gravity scale and linear damping of each affected rigid-body are kept once
+/// for all the volumes, and the values applied to the rigid-body are recomputed from all the
+/// volumes it intersects. When several of these volumes set the same property, the most
+/// recently created one wins.
+#[wasm_bindgen]
+pub struct RawModifierVolumeSet {
+    volumes: BTreeMap<u32, ModifierVolume>,
+    next_id: u32,
+    base_values: HashMap<RigidBodyHandle, BaseValues>,
+}
+
+impl RawModifierVolumeSet {
+    fn volume(&self, id: u32) -> &ModifierVolume {
+        self.volumes
+            .get(&id)
+            .expect("Invalid ModifierVolume reference. It may have been removed from the World.")
+    }
+
+    fn volume_mut(&mut self, id: u32) -> &mut ModifierVolume {
+        self.volumes
+            .get_mut(&id)
+            .expect("Invalid ModifierVolume reference. It may have been removed from the World.")
+    }
+
+    /// Replaces the handles of the rigid-bodies and colliders by the ones they are mapped to.
+    ///
+    /// The rigid-bodies that aren't mapped are forgotten, as if they were removed.
+    pub(crate) fn remap(
+        &mut self,
+        bodies: &HashMap<RigidBodyHandle, RigidBodyHandle>,
+        colliders: &HashMap<ColliderHandle, ColliderHandle>,
+    ) {
+        for volume in self.volumes.values_mut() {
+            if let Some(collider) = colliders.get(&volume.collider) {
+                volume.collider = *collider;
+            }
+            volume.affected = volume
+                .affected
+                .iter()
+                .filter_map(|handle| bodies.get(handle).copied())
+                .collect();
+        }
+        utils::remap_keys(&mut self.base_values, bodies);
+    }
+
+    /// Sets the gravity scale and linear damping of the affected rigid-bodies from all the
+    /// volumes affecting them, and restores those of the rigid-bodies no longer affected.
+    fn update_bodies(&mut self, bodies: &mut RawRigidBodySet) {
+        let mut overrides: HashMap<RigidBodyHandle, (Option<Real>, Option<Real>)> = HashMap::new();
+        for volume in self.volumes.values() {
+            for handle in &volume.affected {
+                let entry = overrides.entry(*handle).or_default();
+                entry.0 = volume.gravity_scale.or(entry.0);
+                entry.1 = volume.linear_damping.or(entry.1);
+            }
+        }
+
+        self.base_values.retain(|handle, base| {
+            if overrides.contains_key(handle) {
+                return true;
+            }
+
+            if let Some(rb) = bodies.set.get_mut(*handle) {
+                rb.set_gravity_scale(base.gravity_scale, true);
+                rb.set_linear_damping(base.linear_damping);
+            }
+            false
+        });
+
+        for (handle, (gravity_scale, linear_damping)) in overrides {
+            let rb = match bodies.set.get_mut(handle) {
+                Some(rb) => rb,
+                None => continue,
+            };
+
+            let base = self
+                .base_values
+                .entry(handle)
+                .or_insert_with(|| BaseValues {
+                    gravity_scale: rb.gravity_scale(),
+                    linear_damping: rb.linear_damping(),
+                });
+
+            let gravity_scale = gravity_scale.unwrap_or(base.gravity_scale);
+            if rb.gravity_scale() != gravity_scale {
+                rb.set_gravity_scale(gravity_scale, true);
+            }
+
+            let linear_damping = linear_damping.unwrap_or(base.linear_damping);
+            if rb.linear_damping() != linear_damping {
+                rb.set_linear_damping(linear_damping);
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl RawModifierVolumeSet {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            volumes: BTreeMap::new(),
+            next_id: 0,
+            base_values: HashMap::new(),
+        }
+    }
+
+    /// Adds a modifier volume defined by the given sensor collider, and returns its identifier.
+    pub fn insert(&mut self, collider: FlatHandle) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.volumes.insert(
+            id,
+            ModifierVolume {
+                collider: utils::collider_handle(collider),
+                gravity_scale: None,
+                linear_damping: None,
+                force: Vector::zeros(),
+                affected: vec![],
+            },
+        );
+        id
+    }
+
+    /// Removes a modifier volume.
+    ///
+    /// The gravity scale and linear damping of the rigid-bodies it affected are recomputed from
+    /// the remaining volumes, or restored if they aren't affected by any other volume.
+    pub fn remove(&mut self, id: u32, bodies: &mut RawRigidBodySet) {
+        if self.volumes.remove(&id).is_some() {
+            self.update_bodies(bodies);
+        }
+    }
+
+    pub fn collider(&self, id: u32) -> FlatHandle {
+        utils::flat_handle(self.volume(id).collider.0)
+    }
+
+    pub fn gravityScale(&self, id: u32) -> Option<f32> {
+        self.volume(id).gravity_scale
+    }
+
+    pub fn setGravityScale(&mut self, id: u32, scale: Option<f32>) {
+        self.volume_mut(id).gravity_scale = scale;
+    }
+
+    pub fn linearDamping(&self, id: u32) -> Option<f32> {
+        self.volume(id).linear_damping
+    }
+
+    pub fn setLinearDamping(&mut self, id: u32, damping: Option<f32>) {
+        self.volume_mut(id).linear_damping = damping;
+    }
+
+    pub fn force(&self, id: u32) -> RawVector {
+        self.volume(id).force.into()
+    }
+
+    pub fn setForce(&mut self, id: u32, force: &RawVector) {
+        self.volume_mut(id).force = force.0;
+    }
+
+    /// The number of rigid-bodies currently affected by a volume.
+    pub fn numAffectedBodies(&self, id: u32) -> usize {
+        self.volume(id).affected.len()
+    }
+
+    /// Applies the effect of all the volumes to the dynamic rigid-bodies intersecting them.
+    ///
+    /// The intersections are the ones computed by the narrow-phase during the last timestep.
+    /// The original gravity scale and linear damping of a rigid-body are restored once it
+    /// stops intersecting all the volumes.
+    pub fn apply(
+        &mut self,
+        dt: f32,
+        bodies: &mut RawRigidBodySet,
+        colliders: &RawColliderSet,
+        narrow_phase: &RawNarrowPhase,
+    ) {
+        for volume in self.volumes.values_mut() {
+            volume.affected.clear();
+            for (h1, h2, intersecting) in narrow_phase
+                .narrow_phase
+                .intersection_pairs_with(volume.collider)
+            {
+                if !intersecting {
+                    continue;
+                }
+
+                let other = if h1 == volume.collider { h2 } else { h1 };
+                if let Some(parent) = colliders.set.get(other).and_then(|co| co.parent()) {
+                    let is_dynamic = bodies.set.get(parent).is_some_and(|rb| rb.is_dynamic());
+                    if is_dynamic && !volume.affected.contains(&parent) {
+                        volume.affected.push(parent);
+                    }
+                }
+            }
+        }
+
+        self.update_bodies(bodies);
+
+        for volume in self.volumes.values() {
+            if volume.force == Vector::zeros() {
+                continue;
+            }
+
+            for handle in &volume.affected {
+                if let Some(rb) = bodies.set.get_mut(*handle) {
+                    rb.apply_impulse(volume.force * dt, true);
+                }
+            }
+        }
+    }
+}
@@ -86,10 +86,10 @@ impl RawDynamicRayCastVehicleController {
                 exclude_collider: None,
             };
 
-            let query_pipeline = broad_phase.0.as_query_pipeline_mut(
-                narrow_phase.0.query_dispatcher(),
-                &mut bodies.0,
-                &mut colliders.0,
+            let query_pipeline = broad_phase.broad_phase.as_query_pipeline_mut(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &mut bodies.set,
+                &mut colliders.set,
                 query_filter,
             );
 
@@ -1,7 +1,12 @@
-use crate::dynamics::{RawImpulseJointSet, RawJointAxis, RawJointType, RawMotorModel};
+use crate::dynamics::{
+    MotorTarget, RawImpulseJointSet, RawJointAxis, RawJointType, RawMotorModel, RawRigidBodySet,
+};
 use crate::math::{RawRotation, RawVector};
 use crate::utils::{self, FlatHandle};
-use rapier::dynamics::JointAxis;
+use rapier::dynamics::{
+    ImpulseJoint, JointAxesMask, JointAxis, MotorModel, RigidBody, RigidBodySet,
+};
+use rapier::math::{Isometry, Real, Vector};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -73,6 +78,20 @@ impl RawImpulseJointSet {
         });
     }
 
+    /// Is this joint enabled?
+    ///
+    /// A disabled joint keeps its configuration but is ignored by the constraints solver.
+    pub fn jointIsEnabled(&self, handle: FlatHandle) -> bool {
+        self.map(handle, |j| j.data.is_enabled())
+    }
+
+    /// Enables or disables this joint, without losing its configuration.
+    pub fn jointSetEnabled(&mut self, handle: FlatHandle, enabled: bool) {
+        self.map_mut(handle, |j| {
+            j.data.set_enabled(enabled);
+        });
+    }
+
     /// Are the limits for this joint enabled?
     pub fn jointLimitsEnabled(&self, handle: FlatHandle, axis: RawJointAxis) -> bool {
         self.map(handle, |j| {
@@ -97,6 +116,83 @@ impl RawImpulseJointSet {
         });
     }
 
+    /// The axes locked by this joint, as a bit mask of `JointAxesMask`.
+    pub fn jointLockedAxes(&self, handle: FlatHandle) -> u8 {
+        self.map(handle, |j| j.data.locked_axes.bits())
+    }
+
+    /// Sets the axes locked by this joint, as a bit mask of `JointAxesMask`.
+    pub fn jointSetLockedAxes(&mut self, handle: FlatHandle, lockedAxes: u8) {
+        self.map_mut(handle, |j| {
+            j.data.locked_axes = JointAxesMask::from_bits_truncate(lockedAxes);
+        })
+    }
+
+    /// The axes which limits and motors are coupled, as a bit mask of `JointAxesMask`.
+    ///
+    /// The limits and motors of coupled linear axes apply to the distance between the two
+    /// anchors, and are configured along `LinX`. This is how rope and spring joints work.
+    /// The limits and motors of coupled angular axes apply to the norm of the rotation
+    /// around these axes, and are configured along the first coupled angular axis. For
+    /// example, coupling `AngY` and `AngZ` results in a circular cone limit.
+    pub fn jointCoupledAxes(&self, handle: FlatHandle) -> u8 {
+        self.map(handle, |j| j.data.coupled_axes.bits())
+    }
+
+    /// Sets the axes which limits and motors are coupled, as a bit mask of `JointAxesMask`.
+    pub fn jointSetCoupledAxes(&mut self, handle: FlatHandle, coupledAxes: u8) {
+        self.map_mut(handle, |j| {
+            j.data.coupled_axes = JointAxesMask::from_bits_truncate(coupledAxes);
+        })
+    }
+
+    /// Is the motor along the given joint axis enabled?
+    pub fn jointMotorEnabled(&self, handle: FlatHandle, axis: RawJointAxis) -> bool {
+        self.map(handle, |j| {
+            j.data.motor_axes.contains(JointAxis::from(axis).into())
+        })
+    }
+
+    /// The target position of the motor along the given joint axis.
+    pub fn jointMotorTargetPos(&self, handle: FlatHandle, axis: RawJointAxis) -> f32 {
+        self.map(handle, |j| j.data.motors[axis as usize].target_pos)
+    }
+
+    /// The target velocity of the motor along the given joint axis.
+    pub fn jointMotorTargetVel(&self, handle: FlatHandle, axis: RawJointAxis) -> f32 {
+        self.map(handle, |j| j.data.motors[axis as usize].target_vel)
+    }
+
+    /// The stiffness of the motor along the given joint axis.
+    pub fn jointMotorStiffness(&self, handle: FlatHandle, axis: RawJointAxis) -> f32 {
+        self.map(handle, |j| j.data.motors[axis as usize].stiffness)
+    }
+
+    /// The damping of the motor along the given joint axis.
+    pub fn jointMotorDamping(&self, handle: FlatHandle, axis: RawJointAxis) -> f32 {
+        self.map(handle, |j| j.data.motors[axis as usize].damping)
+    }
+
+    /// The maximum force the motor along the given joint axis can deliver.
+    pub fn jointMotorMaxForce(&self, handle: FlatHandle, axis: RawJointAxis) -> f32 {
+        self.map(handle, |j| j.data.motors[axis as usize].max_force)
+    }
+
+    /// Sets the maximum force the motor along the given joint axis can deliver.
+    pub fn jointSetMotorMaxForce(&mut self, handle: FlatHandle, axis: RawJointAxis, maxForce: f32) {
+        self.map_mut(handle, |j| {
+            j.data.set_motor_max_force(axis.into(), maxForce);
+        })
+    }
+
+    /// The model of the motor along the given joint axis.
+    pub fn jointMotorModel(&self, handle: FlatHandle, axis: RawJointAxis) -> RawMotorModel {
+        self.map(handle, |j| match j.data.motors[axis as usize].model {
+            MotorModel::AccelerationBased => RawMotorModel::AccelerationBased,
+            MotorModel::ForceBased => RawMotorModel::ForceBased,
+        })
+    }
+
     pub fn jointConfigureMotorModel(
         &mut self,
         handle: FlatHandle,
@@ -210,6 +306,157 @@ impl RawImpulseJointSet {
         self.map_mut(handle, |j| {
             j.data
                 .set_motor(axis.into(), targetPos, targetVel, stiffness, damping);
+        });
+        self.motor_targets
+            .remove(&(utils::impulse_joint_handle(handle), axis as usize));
+    }
+
+    /// Ramps the target velocity of the motor along the given joint axis toward `targetVel`,
+    /// changing it by at most `maxAcceleration * dt` at the beginning of each timestep.
+    pub fn jointConfigureMotorVelocityRamp(
+        &mut self,
+        handle: FlatHandle,
+        axis: RawJointAxis,
+        targetVel: f32,
+        factor: f32,
+        maxAcceleration: f32,
+    ) {
+        let current_vel = self.jointMotorTargetVel(handle, axis);
+        self.jointConfigureMotorVelocity(handle, axis, current_vel, factor);
+        self.motor_targets.insert(
+            (utils::impulse_joint_handle(handle), axis as usize),
+            MotorTarget::VelocityRamp {
+                target_vel: targetVel,
+                factor,
+                max_acceleration: maxAcceleration,
+            },
+        );
+    }
+
+    /// Makes the motor along the given joint axis follow a trajectory of target positions.
+    ///
+    /// At the beginning of each timestep, the motor target position is set to the trajectory
+    /// position reached at the end of that timestep, and its target velocity to the trajectory
+    /// velocity. `times` must be non-decreasing, relative to the next timestep, and have as
+    /// many elements as `positions`. Does nothing if the joint doesn't exist or if the
+    /// trajectory is empty.
+    pub fn jointFollowMotorTrajectory(
+        &mut self,
+        handle: FlatHandle,
+        axis: RawJointAxis,
+        times: Vec<f32>,
+        positions: Vec<f32>,
+        stiffness: f32,
+        damping: f32,
+    ) {
+        if !self.contains(handle) || times.is_empty() || times.len() != positions.len() {
+            return;
+        }
+
+        self.motor_targets.insert(
+            (utils::impulse_joint_handle(handle), axis as usize),
+            MotorTarget::Trajectory {
+                times,
+                positions,
+                stiffness,
+                damping,
+                elapsed: 0.0,
+            },
+        );
+    }
+
+    /// Is the motor along the given joint axis following a trajectory?
+    pub fn jointIsFollowingMotorTrajectory(&self, handle: FlatHandle, axis: RawJointAxis) -> bool {
+        matches!(
+            self.motor_targets
+                .get(&(utils::impulse_joint_handle(handle), axis as usize)),
+            Some(MotorTarget::Trajectory { .. })
+        )
+    }
+
+    /// The world-space direction of the given axis of this joint, as seen from the frame
+    /// attached to its first rigid-body.
+    ///
+    /// In 2D, the angular axis is orthogonal to the simulation plane so a zero vector is
+    /// returned for `AngX`.
+    pub fn jointAxisWorld(
+        &self,
+        handle: FlatHandle,
+        bodies: &RawRigidBodySet,
+        axis: RawJointAxis,
+    ) -> RawVector {
+        self.map(handle, |j| {
+            let (frame1, _) = world_frames(j, &bodies.set);
+            RawVector(axis_direction(&frame1, axis))
         })
     }
+
+    /// The relative velocity of the second rigid-body with respect to the first one along
+    /// the given axis of this joint, i.e., the time derivative of the joint coordinate
+    /// along this axis.
+    ///
+    /// This is a linear velocity for linear axes (measured at the joint anchors), and an
+    /// angular velocity for angular axes.
+    pub fn jointAxisVelocity(
+        &self,
+        handle: FlatHandle,
+        bodies: &RawRigidBodySet,
+        axis: RawJointAxis,
+    ) -> f32 {
+        self.map(handle, |j| {
+            let rb1 = &bodies.set[j.body1];
+            let rb2 = &bodies.set[j.body2];
+            let (frame1, frame2) = world_frames(j, &bodies.set);
+            let dir = axis_direction(&frame1, axis);
+
+            match JointAxis::from(axis) {
+                JointAxis::LinX | JointAxis::LinY => {
+                    linear_velocity(rb1, rb2, &frame1, &frame2, &dir)
+                }
+                #[cfg(feature = "dim3")]
+                JointAxis::LinZ => linear_velocity(rb1, rb2, &frame1, &frame2, &dir),
+                #[cfg(feature = "dim2")]
+                _ => rb2.angvel() - rb1.angvel(),
+                #[cfg(feature = "dim3")]
+                _ => (rb2.angvel() - rb1.angvel()).dot(&dir),
+            }
+        })
+    }
+}
+
+/// The world-space frames of the joint attached to its first and second rigid-bodies.
+fn world_frames(joint: &ImpulseJoint, bodies: &RigidBodySet) -> (Isometry<Real>, Isometry<Real>) {
+    let frame1 = bodies[joint.body1].position() * joint.data.local_frame1;
+    let frame2 = bodies[joint.body2].position() * joint.data.local_frame2;
+    (frame1, frame2)
+}
+
+fn axis_direction(frame: &Isometry<Real>, axis: RawJointAxis) -> Vector<Real> {
+    let i = match JointAxis::from(axis) {
+        JointAxis::LinX => 0,
+        JointAxis::LinY => 1,
+        #[cfg(feature = "dim3")]
+        JointAxis::LinZ => 2,
+        #[cfg(feature = "dim2")]
+        JointAxis::AngX => return Vector::zeros(),
+        #[cfg(feature = "dim3")]
+        JointAxis::AngX => 0,
+        #[cfg(feature = "dim3")]
+        JointAxis::AngY => 1,
+        #[cfg(feature = "dim3")]
+        JointAxis::AngZ => 2,
+    };
+    frame.rotation * Vector::ith(i, 1.0)
+}
+
+fn linear_velocity(
+    rb1: &RigidBody,
+    rb2: &RigidBody,
+    frame1: &Isometry<Real>,
+    frame2: &Isometry<Real>,
+    dir: &Vector<Real>,
+) -> Real {
+    let v1 = rb1.velocity_at_point(&frame1.translation.vector.into());
+    let v2 = rb2.velocity_at_point(&frame2.translation.vector.into());
+    (v2 - v1).dot(dir)
 }
@@ -1,14 +1,159 @@
-use crate::dynamics::RawGenericJoint;
+use crate::dynamics::{RawGenericJoint, RawRigidBodySet};
+use crate::math::RawVector;
 use crate::utils::{self, FlatHandle};
-use rapier::dynamics::{ImpulseJoint, ImpulseJointSet};
+use na::Unit;
+use rapier::dynamics::{
+    GenericJoint, GenericJointBuilder, ImpulseJoint, ImpulseJointHandle, ImpulseJointSet,
+    JointAxesMask, JointAxis, RigidBodyHandle,
+};
+use rapier::math::{Isometry, Real, Rotation, Vector};
+use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 
+/// A motor configuration updated by the joint set at the beginning of each timestep.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) enum MotorTarget {
+    /// Ramps the target velocity of the motor toward `target_vel`, changing it by at most
+    /// `max_acceleration * dt` per timestep.
+    VelocityRamp {
+        target_vel: Real,
+        factor: Real,
+        max_acceleration: Real,
+    },
+    /// Follows a trajectory of target positions, linearly interpolated between keyframes.
+    Trajectory {
+        times: Vec<Real>,
+        positions: Vec<Real>,
+        stiffness: Real,
+        damping: Real,
+        elapsed: Real,
+    },
+}
+
+impl MotorTarget {
+    /// Configures the motor of `joint` along `axis` for a timestep of length `dt`.
+    ///
+    /// Returns `false` once the target is reached and no longer needs to be updated.
+    fn update(&mut self, joint: &mut GenericJoint, axis: usize, dt: Real) -> bool {
+        match self {
+            MotorTarget::VelocityRamp {
+                target_vel,
+                factor,
+                max_acceleration,
+            } => {
+                let current_vel = joint.motors[axis].target_vel;
+                let max_delta = *max_acceleration * dt;
+                let (vel, done) = if *target_vel - current_vel > max_delta {
+                    (current_vel + max_delta, false)
+                } else if current_vel - *target_vel > max_delta {
+                    (current_vel - max_delta, false)
+                } else {
+                    (*target_vel, true)
+                };
+
+                joint.set_motor(joint_axis(axis), 0.0, vel, 0.0, *factor);
+                !done
+            }
+            MotorTarget::Trajectory {
+                times,
+                positions,
+                stiffness,
+                damping,
+                elapsed,
+            } => {
+                *elapsed += dt;
+                let t = *elapsed;
+                let last = times.len() - 1;
+                let (pos, vel, done) = if t >= times[last] {
+                    (positions[last], 0.0, true)
+                } else if t <= times[0] {
+                    (positions[0], 0.0, false)
+                } else {
+                    let i = times.iter().position(|time| *time >= t).unwrap_or(last);
+                    let duration = times[i] - times[i - 1];
+                    let alpha = (t - times[i - 1]) / duration;
+                    let delta = positions[i] - positions[i - 1];
+                    (positions[i - 1] + delta * alpha, delta / duration, false)
+                };
+
+                joint.set_motor(joint_axis(axis), pos, vel, *stiffness, *damping);
+                !done
+            }
+        }
+    }
+}
+
+#[cfg(feature = "dim2")]
+fn joint_axis(axis: usize) -> JointAxis {
+    [JointAxis::LinX, JointAxis::LinY, JointAxis::AngX][axis]
+}
+
+#[cfg(feature = "dim3")]
+fn joint_axis(axis: usize) -> JointAxis {
+    [
+        JointAxis::LinX,
+        JointAxis::LinY,
+        JointAxis::LinZ,
+        JointAxis::AngX,
+        JointAxis::AngY,
+        JointAxis::AngZ,
+    ][axis]
+}
+
 #[wasm_bindgen]
-pub struct RawImpulseJointSet(pub(crate) ImpulseJointSet);
+pub struct RawImpulseJointSet {
+    pub(crate) set: ImpulseJointSet,
+    /// The motors ramping their velocity or following a trajectory, indexed by joint and axis.
+    pub(crate) motor_targets: HashMap<(ImpulseJointHandle, usize), MotorTarget>,
+}
 
 impl RawImpulseJointSet {
+    /// Advances the motor velocity ramps and trajectories by a timestep of length `dt`.
+    ///
+    /// The motors are configured once per timestep, not at each of its substeps.
+    pub(crate) fn update_motor_targets(&mut self, dt: Real) {
+        let set = &mut self.set;
+        self.motor_targets
+            .retain(|(handle, axis), target| match set.get_mut(*handle, true) {
+                Some(joint) => target.update(&mut joint.data, *axis, dt),
+                None => false,
+            });
+    }
+
+    /// Inserts the joints again into an empty joint set in the given order, attached to the
+    /// rigid-bodies `body_map` maps their bodies to, and updates their motor targets.
+    ///
+    /// Returns the previous handles of the joints mapped to their new handles.
+    pub(crate) fn rebuild(
+        &mut self,
+        order: &[ImpulseJointHandle],
+        body_map: &HashMap<RigidBodyHandle, RigidBodyHandle>,
+    ) -> HashMap<ImpulseJointHandle, ImpulseJointHandle> {
+        let old = std::mem::replace(&mut self.set, ImpulseJointSet::new());
+        let mut map = HashMap::new();
+        // Handles of removed joints are skipped, and their motor targets dropped.
+        for (handle, joint) in order.iter().filter_map(|h| old.get(*h).map(|j| (h, j))) {
+            let copy = self.set.insert(
+                body_map[&joint.body1],
+                body_map[&joint.body2],
+                joint.data.clone(),
+                false,
+            );
+            map.insert(*handle, copy);
+        }
+
+        self.motor_targets = self
+            .motor_targets
+            .drain()
+            .filter_map(|((handle, axis), target)| {
+                map.get(&handle).map(|handle| ((*handle, axis), target))
+            })
+            .collect();
+        map
+    }
+
     pub(crate) fn map<T>(&self, handle: FlatHandle, f: impl FnOnce(&ImpulseJoint) -> T) -> T {
-        let body = self.0.get(utils::impulse_joint_handle(handle)).expect(
+        let body = self.set.get(utils::impulse_joint_handle(handle)).expect(
             "Invalid ImpulseJoint reference. It may have been removed from the physics World.",
         );
         f(body)
@@ -20,7 +165,7 @@ impl RawImpulseJointSet {
         f: impl FnOnce(&mut ImpulseJoint) -> T,
     ) -> T {
         let body = self
-            .0
+            .set
             .get_mut(utils::impulse_joint_handle(handle), true)
             .expect(
                 "Invalid ImpulseJoint reference. It may have been removed from the physics World.",
@@ -33,7 +178,10 @@ impl RawImpulseJointSet {
 impl RawImpulseJointSet {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
-        RawImpulseJointSet(ImpulseJointSet::new())
+        RawImpulseJointSet {
+            set: ImpulseJointSet::new(),
+            motor_targets: HashMap::new(),
+        }
     }
 
     pub fn createJoint(
@@ -44,7 +192,7 @@ impl RawImpulseJointSet {
         wake_up: bool,
     ) -> FlatHandle {
         utils::flat_handle(
-            self.0
+            self.set
                 .insert(
                     utils::body_handle(parent1),
                     utils::body_handle(parent2),
@@ -55,17 +203,129 @@ impl RawImpulseJointSet {
         )
     }
 
+    /// Constrains the motion of a rigid-body relative to a world-space frame, e.g., to keep
+    /// it on a tilted plane or along a rail.
+    ///
+    /// The frame is centered at `origin` and its local X axis is aligned with `axis`. The
+    /// constraint is a joint locking the `lockedAxes` of that frame between `body` and a fixed
+    /// rigid-body at the world origin. This anchor is shared by all the world-space constraints
+    /// of the rigid-body set, and hidden from its rigid-bodies. Returns the handle of this
+    /// joint, or `None` if `body` doesn't exist or if `axis` is zero.
+    pub fn createWorldConstraint(
+        &mut self,
+        bodies: &mut RawRigidBodySet,
+        body: FlatHandle,
+        origin: &RawVector,
+        axis: &RawVector,
+        lockedAxes: u8,
+        wakeUp: bool,
+    ) -> Option<FlatHandle> {
+        let body = utils::body_handle(body);
+        if bodies.is_world_anchor(body) {
+            return None;
+        }
+        let body_pos = *bodies.set.get(body)?.position();
+        let axis = Unit::try_new(axis.0, 1.0e-6)?;
+        let anchor = bodies.world_anchor();
+
+        #[cfg(feature = "dim2")]
+        let rot = Rotation::rotation_between(&Vector::x(), &axis.into_inner());
+        #[cfg(feature = "dim3")]
+        let rot = Rotation::rotation_between(&Vector::x(), &axis.into_inner())
+            .unwrap_or_else(|| Rotation::from_axis_angle(&Vector::y_axis(), std::f32::consts::PI));
+        let frame = Isometry::from_parts(origin.0.into(), rot);
+
+        let mut data =
+            GenericJointBuilder::new(JointAxesMask::from_bits_truncate(lockedAxes)).build();
+        data.local_frame1 = frame;
+        data.local_frame2 = body_pos.inverse() * frame;
+
+        let handle = self.set.insert(anchor, body, data, wakeUp);
+        Some(utils::flat_handle(handle.0))
+    }
+
+    /// Is the given joint a world-space constraint created with `createWorldConstraint`?
+    pub fn isWorldConstraint(&self, bodies: &RawRigidBodySet, handle: FlatHandle) -> bool {
+        self.set
+            .get(utils::impulse_joint_handle(handle))
+            .is_some_and(|joint| bodies.is_world_anchor(joint.body1))
+    }
+
+    /// Inserts a copy of the given joint into another joint set, attached to the rigid-bodies
+    /// `body1` and `body2` of `targetBodies`.
+    ///
+    /// If `body1` is `None`, the copy is attached to the anchor of the world-space constraints
+    /// of `targetBodies`, e.g., to copy a joint created with `createWorldConstraint`. The motor
+    /// velocity ramps and trajectories of the joint are copied too. Returns the handle of the
+    /// copy, or `None` if the joint doesn't exist.
+    pub fn cloneInto(
+        &self,
+        handle: FlatHandle,
+        target: &mut RawImpulseJointSet,
+        targetBodies: &mut RawRigidBodySet,
+        body1: Option<FlatHandle>,
+        body2: FlatHandle,
+        wakeUp: bool,
+    ) -> Option<FlatHandle> {
+        let handle = utils::impulse_joint_handle(handle);
+        let joint = self.set.get(handle)?;
+        let body1 = match body1 {
+            Some(body1) => utils::body_handle(body1),
+            None => targetBodies.world_anchor(),
+        };
+        let copy = target
+            .set
+            .insert(body1, utils::body_handle(body2), joint.data.clone(), wakeUp);
+
+        for ((joint, axis), motor_target) in &self.motor_targets {
+            if *joint == handle {
+                target
+                    .motor_targets
+                    .insert((copy, *axis), motor_target.clone());
+            }
+        }
+
+        Some(utils::flat_handle(copy.0))
+    }
+
     pub fn remove(&mut self, handle: FlatHandle, wakeUp: bool) {
         let handle = utils::impulse_joint_handle(handle);
-        self.0.remove(handle, wakeUp);
+        self.set.remove(handle, wakeUp);
+        self.motor_targets.retain(|(joint, _), _| *joint != handle);
+    }
+
+    /// Enables or disables several joints at once, without losing their configuration.
+    ///
+    /// Handles of joints that no longer exist are ignored.
+    pub fn setEnabledBulk(&mut self, handles: Vec<FlatHandle>, enabled: bool) {
+        for handle in handles {
+            if let Some(joint) = self.set.get_mut(utils::impulse_joint_handle(handle), true) {
+                joint.data.set_enabled(enabled);
+            }
+        }
+    }
+
+    /// Enables or disables all the joints attached to the given rigid-body.
+    pub fn setEnabledForRigidBody(&mut self, body: FlatHandle, enabled: bool) {
+        let handles: Vec<_> = self
+            .set
+            .attached_joints(utils::body_handle(body))
+            .map(|(_, _, handle, _)| handle)
+            .collect();
+
+        for handle in handles {
+            if let Some(joint) = self.set.get_mut(handle, true) {
+                joint.data.set_enabled(enabled);
+            }
+        }
     }
 
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.set.len()
     }
 
     pub fn contains(&self, handle: FlatHandle) -> bool {
-        self.0.get(utils::impulse_joint_handle(handle)).is_some()
+        self.set.get(utils::impulse_joint_handle(handle)).is_some()
     }
 
     /// Applies the given JavaScript function to the integer handle of each joint managed by this physics world.
@@ -74,7 +334,7 @@ impl RawImpulseJointSet {
     /// - `f(handle)`: the function to apply to the integer handle of each joint managed by this set. Called as `f(collider)`.
     pub fn forEachJointHandle(&self, f: &js_sys::Function) {
         let this = JsValue::null();
-        for (handle, _) in self.0.iter() {
+        for (handle, _) in self.set.iter() {
             let _ = f.call1(&this, &JsValue::from(utils::flat_handle(handle.0)));
         }
     }
@@ -85,7 +345,7 @@ impl RawImpulseJointSet {
     /// - `f(handle)`: the function to apply to the integer handle of each joint attached to the rigid-body. Called as `f(collider)`.
     pub fn forEachJointAttachedToRigidBody(&self, body: FlatHandle, f: &js_sys::Function) {
         let this = JsValue::null();
-        for (_, _, handle, _) in self.0.attached_joints(utils::body_handle(body)) {
+        for (_, _, handle, _) in self.set.attached_joints(utils::body_handle(body)) {
             let _ = f.call1(&this, &JsValue::from(utils::flat_handle(handle.0)));
         }
     }
@@ -1,101 +1,240 @@
-use rapier::dynamics::IntegrationParameters;
+use rapier::dynamics::{IntegrationParameters, IslandManager, RigidBodySet};
+use rapier::math::Real;
 use wasm_bindgen::prelude::*;
 
+/// A tiny deterministic perturbation of the angular velocity of resting rigid-bodies,
+/// breaking the symmetry of perfectly aligned stacks.
+#[derive(Copy, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct RestingJitter {
+    pub(crate) amplitude: Real,
+    pub(crate) seed: u32,
+}
+
+impl RestingJitter {
+    /// Rigid-bodies with a linear velocity smaller than this (multiplied by the length unit)
+    /// are considered resting.
+    const RESTING_SPEED: Real = 0.1;
+
+    pub(crate) fn apply(
+        &self,
+        bodies: &mut RigidBodySet,
+        islands: &IslandManager,
+        length_unit: Real,
+    ) {
+        if self.amplitude <= 0.0 {
+            return;
+        }
+
+        for handle in islands.active_bodies() {
+            let rb = match bodies.get_mut(*handle) {
+                Some(rb) => rb,
+                None => continue,
+            };
+
+            // Bodies that are already sleep candidates are left alone so they can fall asleep.
+            let activation = rb.activation();
+            if !rb.is_dynamic()
+                || activation.time_since_can_sleep > 0.0
+                || rb.linvel().norm() >= Self::RESTING_SPEED * length_unit
+            {
+                continue;
+            }
+            // Keep the perturbation below the angular sleep threshold so it can't keep the body
+            // awake by itself.
+            let max_delta = 0.5 * activation.angular_threshold.abs();
+
+            // Derive the perturbation from the current pose of the body so the result
+            // only depends on the simulation state (and survives snapshots).
+            let (index, generation) = handle.into_raw_parts();
+            let mut state = ((self.seed as u64) << 32) ^ ((generation as u64) << 24) ^ index as u64;
+            for x in rb.translation().iter() {
+                state = splitmix64(state ^ x.to_bits() as u64);
+            }
+
+            #[cfg(feature = "dim2")]
+            let delta = (self.amplitude * unit_random(&mut state)).clamp(-max_delta, max_delta);
+            #[cfg(feature = "dim3")]
+            let delta = (rapier::math::Vector::new(
+                unit_random(&mut state),
+                unit_random(&mut state),
+                unit_random(&mut state),
+            ) * self.amplitude)
+                .cap_magnitude(max_delta);
+
+            let angvel = rb.angvel() + delta;
+            rb.set_angvel(angvel, false);
+        }
+    }
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// A pseudo-random number in `[-1, 1]`.
+fn unit_random(state: &mut u64) -> Real {
+    *state = splitmix64(*state);
+    (*state >> 40) as Real / (1u64 << 23) as Real - 1.0
+}
+
+/// The integration parameters, along with the parameters of the behaviors implemented on top
+/// of Rapier: the resting jitter, and the restitution velocity threshold.
 #[wasm_bindgen]
-pub struct RawIntegrationParameters(pub(crate) IntegrationParameters);
+pub struct RawIntegrationParameters {
+    pub(crate) params: IntegrationParameters,
+    pub(crate) resting_jitter: RestingJitter,
+    pub(crate) restitution_threshold: Real,
+}
 
 #[wasm_bindgen]
 impl RawIntegrationParameters {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
-        RawIntegrationParameters(IntegrationParameters::default())
+        RawIntegrationParameters {
+            params: IntegrationParameters::default(),
+            resting_jitter: RestingJitter::default(),
+            restitution_threshold: 0.0,
+        }
     }
 
     #[wasm_bindgen(getter)]
     pub fn dt(&self) -> f32 {
-        self.0.dt
+        self.params.dt
     }
 
     #[wasm_bindgen(getter)]
     pub fn contact_erp(&self) -> f32 {
-        self.0.contact_erp()
+        self.params.contact_erp()
     }
 
     #[wasm_bindgen(getter)]
     pub fn normalizedAllowedLinearError(&self) -> f32 {
-        self.0.normalized_allowed_linear_error
+        self.params.normalized_allowed_linear_error
     }
 
     #[wasm_bindgen(getter)]
     pub fn normalizedPredictionDistance(&self) -> f32 {
-        self.0.normalized_prediction_distance
+        self.params.normalized_prediction_distance
     }
 
     #[wasm_bindgen(getter)]
     pub fn numSolverIterations(&self) -> usize {
-        self.0.num_solver_iterations
+        self.params.num_solver_iterations
     }
 
     #[wasm_bindgen(getter)]
     pub fn numInternalPgsIterations(&self) -> usize {
-        self.0.num_internal_pgs_iterations
+        self.params.num_internal_pgs_iterations
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn numInternalStabilizationIterations(&self) -> usize {
+        self.params.num_internal_stabilization_iterations
     }
 
     #[wasm_bindgen(getter)]
     pub fn minIslandSize(&self) -> usize {
-        self.0.min_island_size
+        self.params.min_island_size
     }
 
     #[wasm_bindgen(getter)]
     pub fn maxCcdSubsteps(&self) -> usize {
-        self.0.max_ccd_substeps
+        self.params.max_ccd_substeps
     }
 
     #[wasm_bindgen(getter)]
     pub fn lengthUnit(&self) -> f32 {
-        self.0.length_unit
+        self.params.length_unit
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn warmstartCoefficient(&self) -> f32 {
+        self.params.warmstart_coefficient
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn restingJitterAmplitude(&self) -> f32 {
+        self.resting_jitter.amplitude
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn restingJitterSeed(&self) -> u32 {
+        self.resting_jitter.seed
+    }
+
+    /// The minimum approach speed of a contact for it to bounce.
+    #[wasm_bindgen(getter)]
+    pub fn restitutionThreshold(&self) -> f32 {
+        self.restitution_threshold
     }
 
     #[wasm_bindgen(setter)]
     pub fn set_dt(&mut self, value: f32) {
-        self.0.dt = value;
+        self.params.dt = value;
     }
 
     #[wasm_bindgen(setter)]
     pub fn set_contact_natural_frequency(&mut self, value: f32) {
-        self.0.contact_natural_frequency = value
+        self.params.contact_natural_frequency = value
     }
 
     #[wasm_bindgen(setter)]
     pub fn set_normalizedAllowedLinearError(&mut self, value: f32) {
-        self.0.normalized_allowed_linear_error = value
+        self.params.normalized_allowed_linear_error = value
     }
 
     #[wasm_bindgen(setter)]
     pub fn set_normalizedPredictionDistance(&mut self, value: f32) {
-        self.0.normalized_prediction_distance = value
+        self.params.normalized_prediction_distance = value
     }
 
     #[wasm_bindgen(setter)]
     pub fn set_numSolverIterations(&mut self, value: usize) {
-        self.0.num_solver_iterations = value;
+        self.params.num_solver_iterations = value;
     }
     #[wasm_bindgen(setter)]
     pub fn set_numInternalPgsIterations(&mut self, value: usize) {
-        self.0.num_internal_pgs_iterations = value;
+        self.params.num_internal_pgs_iterations = value;
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_numInternalStabilizationIterations(&mut self, value: usize) {
+        self.params.num_internal_stabilization_iterations = value;
     }
     #[wasm_bindgen(setter)]
     pub fn set_minIslandSize(&mut self, value: usize) {
-        self.0.min_island_size = value
+        self.params.min_island_size = value
     }
 
     #[wasm_bindgen(setter)]
     pub fn set_maxCcdSubsteps(&mut self, value: usize) {
-        self.0.max_ccd_substeps = value
+        self.params.max_ccd_substeps = value
     }
 
     #[wasm_bindgen(setter)]
     pub fn set_lengthUnit(&mut self, value: f32) {
-        self.0.length_unit = value
+        self.params.length_unit = value
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_warmstartCoefficient(&mut self, value: f32) {
+        self.params.warmstart_coefficient = value
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_restingJitterAmplitude(&mut self, value: f32) {
+        self.resting_jitter.amplitude = value
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_restingJitterSeed(&mut self, value: u32) {
+        self.resting_jitter.seed = value
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_restitutionThreshold(&mut self, value: f32) {
+        self.restitution_threshold = value
     }
 }
@@ -311,4 +311,9 @@ impl RawGenericJoint {
                 .into(),
         ))
     }
+
+    /// Sets the axes which limits and motors are coupled, as a bit mask of `JointAxesMask`.
+    pub fn setCoupledAxes(&mut self, coupledAxes: u8) {
+        self.0.coupled_axes = JointAxesMask::from_bits_truncate(coupledAxes);
+    }
 }
@@ -82,4 +82,35 @@ impl RawMultibodyJointSet {
             let _ = f.call1(&this, &JsValue::from(utils::flat_handle(handle.0)));
         }
     }
+
+    /// Is the given rigid-body a link of a multibody?
+    pub fn isMultibodyLink(&self, body: FlatHandle) -> bool {
+        self.0.rigid_body_link(utils::body_handle(body)).is_some()
+    }
+
+    /// The index of the given rigid-body among the links of its multibody.
+    ///
+    /// The root of the multibody has the index `0`. Returns `None` if the rigid-body isn't
+    /// a multibody link.
+    pub fn linkIndex(&self, body: FlatHandle) -> Option<usize> {
+        self.0
+            .rigid_body_link(utils::body_handle(body))
+            .map(|link| link.id)
+    }
+
+    /// The number of links of the multibody the given rigid-body is part of.
+    pub fn numLinks(&self, body: FlatHandle) -> Option<usize> {
+        let link = self.0.rigid_body_link(utils::body_handle(body))?;
+        self.0
+            .get_multibody(link.multibody)
+            .map(|multibody| multibody.num_links())
+    }
+
+    /// The handle of the rigid-body at the root of the multibody the given rigid-body is part of.
+    pub fn linkRootBody(&self, body: FlatHandle) -> Option<FlatHandle> {
+        let link = self.0.rigid_body_link(utils::body_handle(body))?;
+        self.0
+            .get_multibody(link.multibody)
+            .map(|multibody| utils::flat_handle(multibody.root().rigid_body_handle().0))
+    }
 }
@@ -2,10 +2,12 @@ use crate::dynamics::{RawRigidBodySet, RawRigidBodyType};
 use crate::geometry::RawColliderSet;
 #[cfg(feature = "dim3")]
 use crate::math::RawSdpMatrix3;
-use crate::math::{RawRotation, RawVector};
+use crate::math::{RawMassProperties, RawRotation, RawVector};
+use crate::pipeline::ContactResponse;
 use crate::utils::{self, FlatHandle};
 use na::Point;
-use rapier::dynamics::MassProperties;
+use rapier::dynamics::{LockedAxes, MassProperties, RigidBodyAdditionalMassProps};
+use rapier::math::Real;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -235,7 +237,7 @@ impl RawRigidBodySet {
         colliders: &RawColliderSet,
     ) {
         self.map_mut(handle, |rb| {
-            rb.recompute_mass_properties_from_colliders(&colliders.0)
+            rb.recompute_mass_properties_from_colliders(&colliders.set)
         })
     }
 
@@ -281,6 +283,25 @@ impl RawRigidBodySet {
         })
     }
 
+    /// The additional mass-properties of this rigid-body, set with `rbSetAdditionalMass` or
+    /// `rbSetAdditionalMassProperties`.
+    ///
+    /// If only an additional mass was set, the returned mass-properties have a zero
+    /// center-of-mass and angular inertia.
+    pub fn rbAdditionalMassProperties(&self, handle: FlatHandle) -> Option<RawMassProperties> {
+        self.map(handle, |rb| {
+            rb.mass_properties()
+                .additional_local_mprops
+                .as_ref()
+                .map(|props| match **props {
+                    RigidBodyAdditionalMassProps::MassProps(props) => RawMassProperties(props),
+                    RigidBodyAdditionalMassProps::Mass(mass) => RawMassProperties(
+                        MassProperties::new(Point::origin(), mass, Default::default()),
+                    ),
+                })
+        })
+    }
+
     /// The linear velocity of this rigid-body.
     pub fn rbLinvel(&self, handle: FlatHandle) -> RawVector {
         self.map(handle, |rb| RawVector(*rb.linvel()))
@@ -340,6 +361,25 @@ impl RawRigidBodySet {
         self.map_mut(handle, |rb| rb.lock_rotations(locked, wake_up))
     }
 
+    /// Are all the rotational degrees of freedom of this rigid-body locked?
+    pub fn rbIsRotationLocked(&self, handle: FlatHandle) -> bool {
+        self.map(handle, |rb| {
+            rb.locked_axes().contains(LockedAxes::ROTATION_LOCKED)
+        })
+    }
+
+    /// The bitmask of the locked translational and rotational axes of this rigid-body.
+    pub fn rbLockedAxes(&self, handle: FlatHandle) -> u8 {
+        self.map(handle, |rb| rb.locked_axes().bits())
+    }
+
+    /// Sets the bitmask of the locked translational and rotational axes of this rigid-body.
+    pub fn rbSetLockedAxes(&mut self, handle: FlatHandle, lockedAxes: u8, wakeUp: bool) {
+        self.map_mut(handle, |rb| {
+            rb.set_locked_axes(LockedAxes::from_bits_truncate(lockedAxes), wakeUp)
+        })
+    }
+
     #[cfg(feature = "dim3")]
     pub fn rbSetEnabledRotations(
         &mut self,
@@ -706,6 +746,70 @@ impl RawRigidBodySet {
         })
     }
 
+    /// The value of `numSteps` when this rigid-body was created.
+    ///
+    /// Returns `None` if the rigid-body was restored from a snapshot.
+    pub fn rbCreationStep(&self, handle: FlatHandle) -> Option<u32> {
+        self.lifetimes
+            .creation_steps
+            .get(&utils::body_handle(handle))
+            .copied()
+    }
+
+    /// Scales the response of the contacts involving this rigid-body, to make them softer.
+    ///
+    /// The penetration depth corrected by the constraints solver is multiplied by
+    /// `stiffnessScale`, and the restitution coefficient of each contact by `restitutionScale`.
+    /// This is applied natively during contact modification, without calling any JS hook.
+    pub fn rbSetContactResponse(
+        &self,
+        handle: FlatHandle,
+        colliders: &mut RawColliderSet,
+        stiffnessScale: Real,
+        restitutionScale: Real,
+    ) {
+        let handle = utils::body_handle(handle);
+        colliders.native_hooks.contact_responses.insert(
+            handle,
+            ContactResponse {
+                stiffness_scale: stiffnessScale,
+                restitution_scale: restitutionScale,
+            },
+        );
+        self.update_native_hooks(handle, colliders);
+    }
+
+    /// Removes the contact response scaling of this rigid-body.
+    pub fn rbRemoveContactResponse(&self, handle: FlatHandle, colliders: &mut RawColliderSet) {
+        let handle = utils::body_handle(handle);
+        colliders.native_hooks.contact_responses.remove(&handle);
+        self.update_native_hooks(handle, colliders);
+    }
+
+    /// The scale factor applied to the penetration depth of the contacts of this rigid-body.
+    pub fn rbContactStiffnessScale(&self, handle: FlatHandle, colliders: &RawColliderSet) -> Real {
+        colliders
+            .native_hooks
+            .contact_responses
+            .get(&utils::body_handle(handle))
+            .map(|response| response.stiffness_scale)
+            .unwrap_or(1.0)
+    }
+
+    /// The scale factor applied to the restitution coefficient of the contacts of this rigid-body.
+    pub fn rbContactRestitutionScale(
+        &self,
+        handle: FlatHandle,
+        colliders: &RawColliderSet,
+    ) -> Real {
+        colliders
+            .native_hooks
+            .contact_responses
+            .get(&utils::body_handle(handle))
+            .map(|response| response.restitution_scale)
+            .unwrap_or(1.0)
+    }
+
     pub fn rbAdditionalSolverIterations(&self, handle: FlatHandle) -> usize {
         self.map(handle, |rb| rb.additional_solver_iterations())
     }
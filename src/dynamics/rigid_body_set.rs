@@ -1,8 +1,18 @@
+use crate::control::RawModifierVolumeSet;
 use crate::dynamics::{RawImpulseJointSet, RawIslandManager, RawMultibodyJointSet};
-use crate::geometry::RawColliderSet;
+use crate::geometry::{
+    RawBroadPhase, RawBroadPhaseOptimizationStrategy, RawColliderSet, RawNarrowPhase,
+};
 use crate::math::{RawRotation, RawVector};
+use crate::pipeline::{RawContactMaterialTable, RawSimulationLod};
 use crate::utils::{self, FlatHandle};
-use rapier::dynamics::{MassProperties, RigidBody, RigidBodyBuilder, RigidBodySet, RigidBodyType};
+use rapier::dynamics::{
+    IslandManager, MassProperties, RigidBody, RigidBodyBuilder, RigidBodyHandle, RigidBodySet,
+    RigidBodyType,
+};
+use rapier::geometry::NarrowPhase;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -35,12 +45,55 @@ impl Into<RawRigidBodyType> for RigidBodyType {
     }
 }
 
+/// The number of steps simulated with a rigid-body set, and the step at which each of its
+/// rigid-bodies was created.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct BodyLifetimes {
+    pub(crate) num_steps: u32,
+    pub(crate) creation_steps: HashMap<RigidBodyHandle, u32>,
+}
+
+/// The handles of the entities removed by `RawRigidBodySet.removeBatch`.
+#[wasm_bindgen]
+pub struct RawRemovedEntities {
+    bodies: Vec<FlatHandle>,
+    colliders: Vec<FlatHandle>,
+    impulse_joints: Vec<FlatHandle>,
+    multibody_joints: Vec<FlatHandle>,
+}
+
+#[wasm_bindgen]
+impl RawRemovedEntities {
+    pub fn bodies(&self) -> Vec<FlatHandle> {
+        self.bodies.clone()
+    }
+
+    pub fn colliders(&self) -> Vec<FlatHandle> {
+        self.colliders.clone()
+    }
+
+    pub fn impulseJoints(&self) -> Vec<FlatHandle> {
+        self.impulse_joints.clone()
+    }
+
+    pub fn multibodyJoints(&self) -> Vec<FlatHandle> {
+        self.multibody_joints.clone()
+    }
+}
+
 #[wasm_bindgen]
-pub struct RawRigidBodySet(pub(crate) RigidBodySet);
+pub struct RawRigidBodySet {
+    pub(crate) set: RigidBodySet,
+    pub(crate) lifetimes: BodyLifetimes,
+    /// The fixed rigid-body anchoring all the world-space constraints.
+    ///
+    /// It is created on demand and hidden from the rigid-bodies enumerated by this set.
+    pub(crate) world_anchor: Option<RigidBodyHandle>,
+}
 
 impl RawRigidBodySet {
     pub(crate) fn map<T>(&self, handle: FlatHandle, f: impl FnOnce(&RigidBody) -> T) -> T {
-        let body = self.0.get(utils::body_handle(handle)).expect(
+        let body = self.set.get(utils::body_handle(handle)).expect(
             "Invalid RigidBody reference. It may have been removed from the physics World.",
         );
         f(body)
@@ -51,18 +104,92 @@ impl RawRigidBodySet {
         handle: FlatHandle,
         f: impl FnOnce(&mut RigidBody) -> T,
     ) -> T {
-        let body = self.0.get_mut(utils::body_handle(handle)).expect(
+        let body = self.set.get_mut(utils::body_handle(handle)).expect(
             "Invalid RigidBody reference. It may have been removed from the physics World.",
         );
         f(body)
     }
+
+    /// The fixed rigid-body anchoring the world-space constraints, created if needed.
+    pub(crate) fn world_anchor(&mut self) -> RigidBodyHandle {
+        match self.world_anchor {
+            Some(handle) if self.set.contains(handle) => handle,
+            _ => {
+                let handle = self.set.insert(RigidBodyBuilder::fixed());
+                self.world_anchor = Some(handle);
+                handle
+            }
+        }
+    }
+
+    pub(crate) fn is_world_anchor(&self, handle: RigidBodyHandle) -> bool {
+        self.world_anchor == Some(handle)
+    }
+
+    /// The rigid-bodies of this set, excluding the world anchor.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (RigidBodyHandle, &RigidBody)> {
+        self.set
+            .iter()
+            .filter(move |(handle, _)| !self.is_world_anchor(*handle))
+    }
+
+    /// Calls `write(i, handle, rb)` for the first `capacity` rigid-bodies, or the first
+    /// `capacity` active ones if `active_only` is `true`, where `i` is the index of the
+    /// rigid-body in the copy. Returns the number of rigid-bodies copied.
+    fn copy_to_buffers(
+        &self,
+        islands: &RawIslandManager,
+        active_only: bool,
+        capacity: usize,
+        mut write: impl FnMut(usize, RigidBodyHandle, &RigidBody),
+    ) -> usize {
+        let mut count = 0;
+        let mut copy = |handle, rb: &RigidBody| {
+            write(count, handle, rb);
+            count += 1;
+        };
+
+        if active_only {
+            islands
+                .0
+                .active_bodies()
+                .iter()
+                .filter_map(|handle| self.set.get(*handle).map(|rb| (*handle, rb)))
+                .take(capacity)
+                .for_each(|(handle, rb)| copy(handle, rb));
+        } else {
+            self.iter()
+                .take(capacity)
+                .for_each(|(handle, rb)| copy(handle, rb));
+        }
+
+        count
+    }
+
+    /// Updates the physics hooks of the colliders of a rigid-body after the native contact
+    /// modifications applying to it changed.
+    pub(crate) fn update_native_hooks(
+        &self,
+        handle: RigidBodyHandle,
+        colliders: &mut RawColliderSet,
+    ) {
+        if let Some(rb) = self.set.get(handle) {
+            for collider in rb.colliders() {
+                colliders.update_native_hooks(*collider);
+            }
+        }
+    }
 }
 
 #[wasm_bindgen]
 impl RawRigidBodySet {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
-        RawRigidBodySet(RigidBodySet::new())
+        RawRigidBodySet {
+            set: RigidBodySet::new(),
+            lifetimes: BodyLifetimes::default(),
+            world_anchor: None,
+        }
     }
 
     #[cfg(feature = "dim3")]
@@ -130,7 +257,11 @@ impl RawRigidBodySet {
             rigid_body.additional_mass_properties(props)
         };
 
-        utils::flat_handle(self.0.insert(rigid_body.build()).0)
+        let handle = self.set.insert(rigid_body.build());
+        self.lifetimes
+            .creation_steps
+            .insert(handle, self.lifetimes.num_steps);
+        utils::flat_handle(handle.0)
     }
 
     #[cfg(feature = "dim2")]
@@ -187,7 +318,67 @@ impl RawRigidBodySet {
             rigid_body = rigid_body.lock_rotations();
         }
 
-        utils::flat_handle(self.0.insert(rigid_body.build()).0)
+        let handle = self.set.insert(rigid_body.build());
+        self.lifetimes
+            .creation_steps
+            .insert(handle, self.lifetimes.num_steps);
+        utils::flat_handle(handle.0)
+    }
+
+    /// Inserts a copy of the given rigid-body into another rigid-body set.
+    ///
+    /// The colliders and joints attached to the rigid-body are not copied, but its contact
+    /// response is. Returns the handle of the copy, or `None` if the rigid-body doesn't exist.
+    pub fn cloneInto(
+        &self,
+        handle: FlatHandle,
+        colliders: &RawColliderSet,
+        target: &mut RawRigidBodySet,
+        targetColliders: &mut RawColliderSet,
+    ) -> Option<FlatHandle> {
+        let source = utils::body_handle(handle);
+        let body = self.set.get(source)?.clone();
+        let handle = target.set.insert(body);
+        target
+            .lifetimes
+            .creation_steps
+            .insert(handle, target.lifetimes.num_steps);
+        if let Some(response) = colliders.native_hooks.contact_responses.get(&source) {
+            targetColliders
+                .native_hooks
+                .contact_responses
+                .insert(handle, *response);
+        }
+        Some(utils::flat_handle(handle.0))
+    }
+
+    /// The state of the given rigid-body, packed in a single array, for inspecting its
+    /// properties in a single call.
+    ///
+    /// The array contains, in this order: the world-space translation, the world-space
+    /// rotation (angle in 2D, quaternion `[x, y, z, w]` in 3D), the linear velocity, the
+    /// angular velocity (scalar in 2D, vector in 3D), the mass, the body type (as a
+    /// `RawRigidBodyType`), whether it is sleeping (`0` or `1`), whether it is enabled
+    /// (`0` or `1`), the gravity scale, the linear damping, and the angular damping.
+    pub fn bodyState(&self, handle: FlatHandle) -> Vec<f64> {
+        self.map(handle, |rb| {
+            let mut state = Vec::with_capacity(20);
+            utils::push_isometry(&mut state, rb.position());
+            state.extend(rb.linvel().iter().map(|x| *x as f64));
+            #[cfg(feature = "dim2")]
+            state.push(rb.angvel() as f64);
+            #[cfg(feature = "dim3")]
+            state.extend(rb.angvel().iter().map(|x| *x as f64));
+            state.push(rb.mass() as f64);
+            let body_type: RawRigidBodyType = rb.body_type().into();
+            state.push(body_type as u32 as f64);
+            state.push(rb.is_sleeping() as u32 as f64);
+            state.push(rb.is_enabled() as u32 as f64);
+            state.push(rb.gravity_scale() as f64);
+            state.push(rb.linear_damping() as f64);
+            state.push(rb.angular_damping() as f64);
+            state
+        })
     }
 
     pub fn remove(
@@ -199,24 +390,239 @@ impl RawRigidBodySet {
         articulations: &mut RawMultibodyJointSet,
     ) {
         let handle = utils::body_handle(handle);
-        self.0.remove(
+        if let Some(rb) = self.set.get(handle) {
+            for collider in rb.colliders() {
+                colliders.forget(collider);
+            }
+        }
+
+        self.set.remove(
             handle,
             &mut islands.0,
-            &mut colliders.0,
-            &mut joints.0,
+            &mut colliders.set,
+            &mut joints.set,
             &mut articulations.0,
             true,
         );
+        self.lifetimes.creation_steps.remove(&handle);
+        colliders.native_hooks.contact_responses.remove(&handle);
+    }
+
+    /// Removes several rigid-bodies with a single call.
+    ///
+    /// This is equivalent to calling `remove` for each rigid-body: there is no consolidated
+    /// cleanup of the islands or of the joint graphs, each rigid-body is removed from them
+    /// individually. If `alsoColliders` is `true`, the colliders attached to the removed rigid-bodies are
+    /// removed too. Otherwise, they are detached and kept in the collider set. Because joints
+    /// can't outlive the rigid-bodies they are attached to, the rigid-bodies with joints
+    /// attached are left untouched if `alsoJoints` is `false`. Handles of rigid-bodies that no
+    /// longer exist are ignored.
+    ///
+    /// As with `remove`, the broad-phase proxies of the removed colliders are cleaned up at the
+    /// beginning of the next timestep.
+    pub fn removeBatch(
+        &mut self,
+        handles: Vec<FlatHandle>,
+        islands: &mut RawIslandManager,
+        colliders: &mut RawColliderSet,
+        joints: &mut RawImpulseJointSet,
+        articulations: &mut RawMultibodyJointSet,
+        alsoColliders: bool,
+        alsoJoints: bool,
+    ) -> RawRemovedEntities {
+        let mut removed = RawRemovedEntities {
+            bodies: vec![],
+            colliders: vec![],
+            impulse_joints: vec![],
+            multibody_joints: vec![],
+        };
+
+        for handle in handles {
+            let handle = utils::body_handle(handle);
+            let rb = match self.set.get(handle) {
+                Some(rb) => rb,
+                None => continue,
+            };
+
+            let impulse_joints: Vec<_> = joints
+                .set
+                .attached_joints(handle)
+                .map(|(_, _, joint, _)| utils::flat_handle(joint.0))
+                .collect();
+            let multibody_joints: Vec<_> = articulations
+                .0
+                .attached_joints(handle)
+                .map(|(_, _, joint)| utils::flat_handle(joint.0))
+                .collect();
+            if !alsoJoints && (!impulse_joints.is_empty() || !multibody_joints.is_empty()) {
+                continue;
+            }
+
+            if alsoColliders {
+                for collider in rb.colliders() {
+                    colliders.forget(collider);
+                    removed.colliders.push(utils::flat_handle(collider.0));
+                }
+            }
+
+            self.set.remove(
+                handle,
+                &mut islands.0,
+                &mut colliders.set,
+                &mut joints.set,
+                &mut articulations.0,
+                alsoColliders,
+            );
+            self.lifetimes.creation_steps.remove(&handle);
+            colliders.native_hooks.contact_responses.remove(&handle);
+
+            removed.bodies.push(utils::flat_handle(handle.0));
+            removed.impulse_joints.extend(impulse_joints);
+            removed.multibody_joints.extend(multibody_joints);
+        }
+
+        removed
+    }
+
+    /// Inserts all the rigid-bodies, colliders, and impulse joints again in the given orders, so
+    /// that their handles and the internal order of the simulation only depend on these orders.
+    ///
+    /// `bodyOrder`, `colliderOrder`, and `jointOrder` must contain the handles of all the
+    /// rigid-bodies, colliders, and impulse joints, each exactly once. The island manager, the
+    /// broad-phase (created again with the given optimization strategy), and the narrow-phase
+    /// are reset, so the contacts are computed from scratch during the next timestep. The
+    /// handles stored by the modifier volumes, the simulation tiers, and the contact materials
+    /// are replaced by the new ones.
+    ///
+    /// Returns the new handles of the rigid-bodies, then of the colliders, then of the impulse
+    /// joints, in the given orders. Throws without modifying anything if an order is invalid,
+    /// or if there are multibody joints.
+    pub fn canonicalizeOrdering(
+        &mut self,
+        islands: &mut RawIslandManager,
+        broadPhase: &mut RawBroadPhase,
+        narrowPhase: &mut RawNarrowPhase,
+        colliders: &mut RawColliderSet,
+        joints: &mut RawImpulseJointSet,
+        articulations: &RawMultibodyJointSet,
+        modifierVolumes: &mut RawModifierVolumeSet,
+        simulationLod: &mut RawSimulationLod,
+        contactMaterials: &mut RawContactMaterialTable,
+        strategy: RawBroadPhaseOptimizationStrategy,
+        bodyOrder: Vec<FlatHandle>,
+        colliderOrder: Vec<FlatHandle>,
+        jointOrder: Vec<FlatHandle>,
+    ) -> Result<Vec<FlatHandle>, JsValue> {
+        fn check_order<H: Copy + Eq + Hash>(
+            order: &[H],
+            len: usize,
+            contains: impl Fn(H) -> bool,
+            objects: &str,
+        ) -> Result<(), JsValue> {
+            let unique: HashSet<_> = order.iter().copied().collect();
+            if order.len() == len && unique.len() == len && order.iter().all(|h| contains(*h)) {
+                Ok(())
+            } else {
+                Err(js_sys::Error::new(&format!(
+                    "The order of the {} must contain each of them exactly once.",
+                    objects
+                ))
+                .into())
+            }
+        }
+
+        if articulations.0.iter().next().is_some() {
+            return Err(js_sys::Error::new(
+                "The ordering of a world with multibody joints can't be canonicalized.",
+            )
+            .into());
+        }
+
+        let body_order: Vec<_> = bodyOrder.into_iter().map(utils::body_handle).collect();
+        let collider_order: Vec<_> = colliderOrder
+            .into_iter()
+            .map(utils::collider_handle)
+            .collect();
+        let joint_order: Vec<_> = jointOrder
+            .into_iter()
+            .map(utils::impulse_joint_handle)
+            .collect();
+        check_order(
+            &body_order,
+            self.len(),
+            |h| self.set.contains(h) && !self.is_world_anchor(h),
+            "rigid-bodies",
+        )?;
+        check_order(
+            &collider_order,
+            colliders.set.len(),
+            |h| colliders.set.contains(h),
+            "colliders",
+        )?;
+        check_order(
+            &joint_order,
+            joints.set.len(),
+            |h| joints.set.contains(h),
+            "impulse joints",
+        )?;
+
+        let old = std::mem::replace(&mut self.set, RigidBodySet::new());
+        let anchor = self.world_anchor.filter(|h| old.contains(*h));
+        let mut body_map = HashMap::new();
+        // The world anchor is inserted first, as if it was created before all the rigid-bodies.
+        for handle in anchor.iter().chain(&body_order) {
+            body_map.insert(*handle, self.set.insert(old[*handle].clone()));
+        }
+        self.world_anchor = anchor.map(|h| body_map[&h]);
+        utils::remap_keys(&mut self.lifetimes.creation_steps, &body_map);
+
+        let collider_map = colliders.rebuild(&collider_order, &mut self.set, &body_map);
+        let joint_map = joints.rebuild(&joint_order, &body_map);
+        modifierVolumes.remap(&body_map, &collider_map);
+        simulationLod.remap(&body_map);
+        contactMaterials.remap(&collider_map);
+
+        islands.0 = IslandManager::new();
+        broadPhase.broad_phase = RawBroadPhase::withOptimizationStrategy(strategy).broad_phase;
+        narrowPhase.invalidate_borrows();
+        narrowPhase.narrow_phase = NarrowPhase::new();
+
+        let mut handles: Vec<_> = body_order
+            .iter()
+            .map(|h| utils::flat_handle(body_map[h].0))
+            .collect();
+        handles.extend(
+            collider_order
+                .iter()
+                .map(|h| utils::flat_handle(collider_map[h].0)),
+        );
+        handles.extend(
+            joint_order
+                .iter()
+                .map(|h| utils::flat_handle(joint_map[h].0)),
+        );
+        Ok(handles)
+    }
+
+    /// The number of steps simulated with this set since its creation.
+    ///
+    /// This is not saved by snapshots, so it restarts from zero after a snapshot is restored.
+    pub fn numSteps(&self) -> u32 {
+        self.lifetimes.num_steps
     }
 
     /// The number of rigid-bodies on this set.
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.set.len()
+            - self
+                .world_anchor
+                .map_or(0, |h| self.set.contains(h) as usize)
     }
 
     /// Checks if a rigid-body with the given integer handle exists.
     pub fn contains(&self, handle: FlatHandle) -> bool {
-        self.0.get(utils::body_handle(handle)).is_some()
+        let handle = utils::body_handle(handle);
+        self.set.get(handle).is_some() && !self.is_world_anchor(handle)
     }
 
     /// Applies the given JavaScript function to the integer handle of each rigid-body managed by this set.
@@ -225,13 +631,125 @@ impl RawRigidBodySet {
     /// - `f(handle)`: the function to apply to the integer handle of each rigid-body managed by this set. Called as `f(collider)`.
     pub fn forEachRigidBodyHandle(&self, f: &js_sys::Function) {
         let this = JsValue::null();
-        for (handle, _) in self.0.iter() {
+        for (handle, _) in self.iter() {
             let _ = f.call1(&this, &JsValue::from(utils::flat_handle(handle.0)));
         }
     }
 
+    /// Copies the velocities of several rigid-bodies into the given buffers.
+    ///
+    /// The velocities are packed in `velocities` as `[linvel.x, linvel.y, angvel]` in 2D, and as
+    /// `[linvel.x, linvel.y, linvel.z, angvel.x, angvel.y, angvel.z]` in 3D. The integer handle
+    /// of the rigid-body each velocity belongs to is written at the same index in `handles`.
+    /// The copy stops as soon as one of the buffers is full.
+    ///
+    /// # Parameters
+    /// - `islands`: the island manager used to determine which rigid-bodies are active.
+    /// - `activeOnly`: if `true`, only the velocities of active rigid-bodies are copied.
+    /// - `handles`: the buffer where the rigid-body handles are written.
+    /// - `velocities`: the buffer where the velocities are written.
+    ///
+    /// Returns the number of rigid-bodies which velocities have been copied.
+    pub fn copyVelocitiesToBuffer(
+        &self,
+        islands: &RawIslandManager,
+        activeOnly: bool,
+        handles: &mut [f64],
+        velocities: &mut [f32],
+    ) -> usize {
+        #[cfg(feature = "dim2")]
+        const STRIDE: usize = 3;
+        #[cfg(feature = "dim3")]
+        const STRIDE: usize = 6;
+
+        let capacity = handles.len().min(velocities.len() / STRIDE);
+        let write = |i: usize, handle: RigidBodyHandle, rb: &RigidBody| {
+            let linvel = rb.linvel();
+            let angvel = rb.angvel();
+            let out = &mut velocities[i * STRIDE..(i + 1) * STRIDE];
+            handles[i] = utils::flat_handle(handle.0);
+
+            #[cfg(feature = "dim2")]
+            {
+                out[0] = linvel.x;
+                out[1] = linvel.y;
+                out[2] = angvel;
+            }
+            #[cfg(feature = "dim3")]
+            {
+                out[0] = linvel.x;
+                out[1] = linvel.y;
+                out[2] = linvel.z;
+                out[3] = angvel.x;
+                out[4] = angvel.y;
+                out[5] = angvel.z;
+            }
+        };
+
+        self.copy_to_buffers(islands, activeOnly, capacity, write)
+    }
+
+    /// Copies the world-space poses of several rigid-bodies into the given buffers.
+    ///
+    /// Each pose is packed as `[tx, ty, angle]` in 2D and `[tx, ty, tz, qx, qy, qz, qw]` in 3D.
+    /// The handle of each rigid-body is written at the same index in `handles`. Returns the
+    /// number of rigid-bodies copied.
+    pub fn copyPosesToBuffer(
+        &self,
+        islands: &RawIslandManager,
+        activeOnly: bool,
+        handles: &mut [f64],
+        poses: &mut [f32],
+    ) -> usize {
+        #[cfg(feature = "dim2")]
+        const STRIDE: usize = 3;
+        #[cfg(feature = "dim3")]
+        const STRIDE: usize = 7;
+
+        let capacity = handles.len().min(poses.len() / STRIDE);
+        let write = |i: usize, handle: RigidBodyHandle, rb: &RigidBody| {
+            let pos = rb.position();
+            let out = &mut poses[i * STRIDE..(i + 1) * STRIDE];
+            handles[i] = utils::flat_handle(handle.0);
+
+            #[cfg(feature = "dim2")]
+            {
+                out[0] = pos.translation.x;
+                out[1] = pos.translation.y;
+                out[2] = pos.rotation.angle();
+            }
+            #[cfg(feature = "dim3")]
+            {
+                out[0] = pos.translation.x;
+                out[1] = pos.translation.y;
+                out[2] = pos.translation.z;
+                out[3] = pos.rotation.i;
+                out[4] = pos.rotation.j;
+                out[5] = pos.rotation.k;
+                out[6] = pos.rotation.w;
+            }
+        };
+
+        self.copy_to_buffers(islands, activeOnly, capacity, write)
+    }
+
+    /// Recomputes the mass-properties of several rigid-bodies based on their attached colliders.
+    ///
+    /// Handles of rigid-bodies that no longer exist are ignored.
+    pub fn recomputeMassPropertiesFromColliders(
+        &mut self,
+        handles: Vec<FlatHandle>,
+        colliders: &RawColliderSet,
+    ) {
+        for handle in handles {
+            if let Some(rb) = self.set.get_mut(utils::body_handle(handle)) {
+                rb.recompute_mass_properties_from_colliders(&colliders.set);
+            }
+        }
+    }
+
     pub fn propagateModifiedBodyPositionsToColliders(&mut self, colliders: &mut RawColliderSet) {
-        self.0
-            .propagate_modified_body_positions_to_colliders(&mut colliders.0);
+        self.set
+            .propagate_modified_body_positions_to_colliders(&mut colliders.set);
     }
 }
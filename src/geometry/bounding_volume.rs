@@ -0,0 +1,31 @@
+use crate::math::RawVector;
+use rapier::parry::bounding_volume::{Aabb, BoundingSphere};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct RawAabb(pub(crate) Aabb);
+
+#[wasm_bindgen]
+impl RawAabb {
+    pub fn mins(&self) -> RawVector {
+        self.0.mins.coords.into()
+    }
+
+    pub fn maxs(&self) -> RawVector {
+        self.0.maxs.coords.into()
+    }
+}
+
+#[wasm_bindgen]
+pub struct RawBoundingSphere(pub(crate) BoundingSphere);
+
+#[wasm_bindgen]
+impl RawBoundingSphere {
+    pub fn center(&self) -> RawVector {
+        self.0.center.coords.into()
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.0.radius
+    }
+}
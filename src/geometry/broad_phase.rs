@@ -1,63 +1,298 @@
 use crate::dynamics::RawRigidBodySet;
+use crate::geometry::shape::SharedShapeUtility;
 use crate::geometry::{
     RawColliderSet, RawColliderShapeCastHit, RawNarrowPhase, RawPointColliderProjection,
-    RawRayColliderHit, RawRayColliderIntersection, RawShape,
+    RawPredictedTrajectory, RawRayColliderHit, RawRayColliderIntersection, RawShape, TrajectoryHit,
 };
 use crate::math::{RawRotation, RawVector};
 use crate::utils::{self, FlatHandle};
-use rapier::geometry::DefaultBroadPhase;
-use rapier::geometry::{Aabb, ColliderHandle, Ray};
-use rapier::math::{Isometry, Point};
-use rapier::parry::query::ShapeCastOptions;
+use na::Unit;
+#[cfg(feature = "dim2")]
+use na::{Matrix2, UnitComplex};
+#[cfg(feature = "dim3")]
+use na::{Matrix3, UnitQuaternion};
+use rapier::geometry::{Aabb, BoundingVolume, Collider, ColliderHandle, Ray};
+use rapier::geometry::{BvhOptimizationStrategy, DefaultBroadPhase};
+use rapier::math::{Isometry, Point, Real, Vector, DIM};
+use rapier::parry::query::{self, NonlinearRigidMotion, ShapeCastOptions};
+#[cfg(feature = "dim3")]
+use rapier::parry::shape::Cone;
+#[cfg(feature = "dim2")]
+use rapier::parry::shape::Triangle;
+use rapier::parry::shape::{Shape, SharedShape};
 use rapier::pipeline::{QueryFilter, QueryFilterFlags};
 use rapier::prelude::FeatureId;
+use std::cell::Cell;
 use wasm_bindgen::prelude::*;
 
+/// The strategy used by the broad-phase to keep its bounding-volume hierarchy efficient.
 #[wasm_bindgen]
-pub struct RawBroadPhase(pub(crate) DefaultBroadPhase);
+#[derive(Copy, Clone)]
+pub enum RawBroadPhaseOptimizationStrategy {
+    /// Incrementally rebuild the subtrees with the most moving colliders at each step.
+    SubtreeOptimizer,
+    /// Never rebuild the hierarchy, only refit the bounding volumes of moving colliders.
+    None,
+}
 
 #[wasm_bindgen]
+pub struct RawBroadPhase {
+    pub(crate) broad_phase: DefaultBroadPhase,
+    pub(crate) query_stats: QueryStats,
+}
+
+/// The number of colliders tested by the filters of the scene queries of a broad-phase.
+#[derive(Default)]
+pub(crate) struct QueryStats {
+    group_tests: Cell<u32>,
+    group_rejections: Cell<u32>,
+    accepted: Cell<u32>,
+}
+
+impl QueryStats {
+    fn increment(counter: &Cell<u32>) {
+        counter.set(counter.get().saturating_add(1));
+    }
+}
+
+/// A uniform grid over the horizontal axes of an AABB, listing the indices of the
+/// candidate colliders whose AABB overlaps each column of cells.
+struct ColumnGrid {
+    mins: Point<Real>,
+    cell_size: Vector<Real>,
+    cells_per_axis: usize,
+    cells: Vec<Vec<u32>>,
+}
+
+impl ColumnGrid {
+    #[cfg(feature = "dim2")]
+    const HORIZONTAL_AXES: &'static [usize] = &[0];
+    #[cfg(feature = "dim3")]
+    const HORIZONTAL_AXES: &'static [usize] = &[0, 2];
+    const MAX_CELLS: usize = 1 << 16;
+
+    /// Builds a grid with about one cell per point.
+    fn new<'a>(aabb: &Aabb, num_points: usize, candidates: impl Iterator<Item = &'a Aabb>) -> Self {
+        let num_axes = Self::HORIZONTAL_AXES.len() as u32;
+        let num_cells = num_points.min(Self::MAX_CELLS) as Real;
+        let cells_per_axis = (num_cells.powf(1.0 / num_axes as Real).ceil() as usize).max(1);
+        let mut grid = Self {
+            mins: aabb.mins,
+            cell_size: aabb.extents() / cells_per_axis as Real,
+            cells_per_axis,
+            cells: vec![vec![]; cells_per_axis.pow(num_axes)],
+        };
+
+        for (i, candidate) in candidates.enumerate() {
+            let mins = grid.cell_coords(&candidate.mins);
+            let maxs = grid.cell_coords(&candidate.maxs);
+            for x in mins[0]..=maxs[0] {
+                for z in mins[1]..=maxs[1] {
+                    let cell = grid.cell_index([x, z]);
+                    grid.cells[cell].push(i as u32);
+                }
+            }
+        }
+
+        grid
+    }
+
+    /// The coordinates, along each horizontal axis, of the cell containing the given point.
+    ///
+    /// The second coordinate is always zero in 2D.
+    fn cell_coords(&self, point: &Point<Real>) -> [usize; 2] {
+        let mut coords = [0; 2];
+        for (coord, axis) in coords.iter_mut().zip(Self::HORIZONTAL_AXES) {
+            if self.cell_size[*axis] > 0.0 {
+                let cell = ((point[*axis] - self.mins[*axis]) / self.cell_size[*axis]).floor();
+                *coord = (cell.max(0.0) as usize).min(self.cells_per_axis - 1);
+            }
+        }
+        coords
+    }
+
+    fn cell_index(&self, coords: [usize; 2]) -> usize {
+        coords[0]
+            * self
+                .cells_per_axis
+                .pow(Self::HORIZONTAL_AXES.len() as u32 - 1)
+            + coords[1]
+    }
+
+    /// The indices of the candidates whose AABB may contain the vertical line through the
+    /// given point.
+    fn candidates_at(&self, point: &Point<Real>) -> &[u32] {
+        &self.cells[self.cell_index(self.cell_coords(point))]
+    }
+}
+
 impl RawBroadPhase {
-    #[wasm_bindgen(constructor)]
-    pub fn new() -> Self {
-        RawBroadPhase(DefaultBroadPhase::new())
+    /// Calls `f` with the predicate of a scene query, testing the given interaction groups, then
+    /// `filter_predicate`, on each collider reached by the broad-phase traversal before its exact
+    /// shape test, and counting these tests in the query stats.
+    ///
+    /// The groups are tested by this predicate, so they must not be set on the `QueryFilter` too.
+    pub(crate) fn with_counted_filter<T>(
+        &self,
+        filter_groups: Option<u32>,
+        filter_predicate: &js_sys::Function,
+        f: impl FnOnce(Option<&dyn Fn(ColliderHandle, &Collider) -> bool>) -> T,
+    ) -> T {
+        let groups = filter_groups.map(crate::geometry::unpack_interaction_groups);
+        let stats = &self.query_stats;
+        utils::with_filter(filter_predicate, |predicate| {
+            let counted_predicate = |handle: ColliderHandle, co: &Collider| {
+                if let Some(groups) = groups {
+                    QueryStats::increment(&stats.group_tests);
+                    if !co.collision_groups().test(groups) {
+                        QueryStats::increment(&stats.group_rejections);
+                        return false;
+                    }
+                }
+
+                let accepted = predicate.is_none_or(|predicate| predicate(handle, co));
+                if accepted {
+                    QueryStats::increment(&stats.accepted);
+                }
+                accepted
+            };
+
+            f(Some(&counted_predicate))
+        })
     }
 
-    pub fn castRay(
+    fn intersections_with_shape(
         &self,
         narrow_phase: &RawNarrowPhase,
         bodies: &RawRigidBodySet,
         colliders: &RawColliderSet,
-        rayOrig: &RawVector,
-        rayDir: &RawVector,
-        maxToi: f32,
-        solid: bool,
+        pos: Isometry<Real>,
+        shape: &dyn Shape,
+        callback: &js_sys::Function,
         filter_flags: u32,
         filter_groups: Option<u32>,
         filter_exclude_collider: Option<FlatHandle>,
         filter_exclude_rigid_body: Option<FlatHandle>,
         filter_predicate: &js_sys::Function,
-    ) -> Option<RawRayColliderHit> {
-        let (handle, timeOfImpact) = utils::with_filter(filter_predicate, |predicate| {
+    ) {
+        self.with_counted_filter(filter_groups, filter_predicate, |predicate| {
             let query_filter = QueryFilter {
                 flags: QueryFilterFlags::from_bits(filter_flags)
                     .unwrap_or(QueryFilterFlags::empty()),
-                groups: filter_groups.map(crate::geometry::unpack_interaction_groups),
+                groups: None,
                 exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
                 exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
                 predicate,
             };
 
-            let query_pipeline = self.0.as_query_pipeline(
-                narrow_phase.0.query_dispatcher(),
-                &bodies.0,
-                &colliders.0,
+            let query_pipeline = self.broad_phase.as_query_pipeline(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &bodies.set,
+                &colliders.set,
                 query_filter,
             );
 
-            let ray = Ray::new(rayOrig.0.into(), rayDir.0);
-            query_pipeline.cast_ray(&ray, maxToi, solid)
-        })?;
+            let rcallback = |handle: ColliderHandle| match callback.call1(
+                &JsValue::null(),
+                &JsValue::from(utils::flat_handle(handle.0)),
+            ) {
+                Err(_) => true,
+                Ok(val) => val.as_bool().unwrap_or(true),
+            };
+
+            for (handle, _) in query_pipeline.intersect_shape(pos, shape) {
+                if !rcallback(handle) {
+                    break;
+                }
+            }
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl RawBroadPhase {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        RawBroadPhase {
+            broad_phase: DefaultBroadPhase::new(),
+            query_stats: QueryStats::default(),
+        }
+    }
+
+    /// The number of colliders which interaction groups were tested by scene queries since
+    /// the last call to `resetQueryStats`.
+    pub fn queryNumGroupTests(&self) -> u32 {
+        self.query_stats.group_tests.get()
+    }
+
+    /// The number of colliders rejected by scene queries because of their interaction groups
+    /// since the last call to `resetQueryStats`.
+    pub fn queryNumGroupRejections(&self) -> u32 {
+        self.query_stats.group_rejections.get()
+    }
+
+    /// The number of colliders accepted by the filters of scene queries, and tested against
+    /// their exact shape, since the last call to `resetQueryStats`.
+    pub fn queryNumAcceptedColliders(&self) -> u32 {
+        self.query_stats.accepted.get()
+    }
+
+    /// Resets the counters of the colliders tested by scene queries.
+    pub fn resetQueryStats(&self) {
+        self.query_stats.group_tests.set(0);
+        self.query_stats.group_rejections.set(0);
+        self.query_stats.accepted.set(0);
+    }
+
+    pub fn withOptimizationStrategy(strategy: RawBroadPhaseOptimizationStrategy) -> Self {
+        let strategy = match strategy {
+            RawBroadPhaseOptimizationStrategy::SubtreeOptimizer => {
+                BvhOptimizationStrategy::SubtreeOptimizer
+            }
+            RawBroadPhaseOptimizationStrategy::None => BvhOptimizationStrategy::None,
+        };
+        RawBroadPhase {
+            broad_phase: DefaultBroadPhase::with_optimization_strategy(strategy),
+            query_stats: QueryStats::default(),
+        }
+    }
+
+    pub fn castRay(
+        &self,
+        narrow_phase: &RawNarrowPhase,
+        bodies: &RawRigidBodySet,
+        colliders: &RawColliderSet,
+        rayOrig: &RawVector,
+        rayDir: &RawVector,
+        maxToi: f32,
+        solid: bool,
+        filter_flags: u32,
+        filter_groups: Option<u32>,
+        filter_exclude_collider: Option<FlatHandle>,
+        filter_exclude_rigid_body: Option<FlatHandle>,
+        filter_predicate: &js_sys::Function,
+    ) -> Option<RawRayColliderHit> {
+        let (handle, timeOfImpact) =
+            self.with_counted_filter(filter_groups, filter_predicate, |predicate| {
+                let query_filter = QueryFilter {
+                    flags: QueryFilterFlags::from_bits(filter_flags)
+                        .unwrap_or(QueryFilterFlags::empty()),
+                    groups: None,
+                    exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
+                    exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
+                    predicate,
+                };
+
+                let query_pipeline = self.broad_phase.as_query_pipeline(
+                    narrow_phase.narrow_phase.query_dispatcher(),
+                    &bodies.set,
+                    &colliders.set,
+                    query_filter,
+                );
+
+                let ray = Ray::new(rayOrig.0.into(), rayDir.0);
+                query_pipeline.cast_ray(&ray, maxToi, solid)
+            })?;
 
         Some(RawRayColliderHit {
             handle,
@@ -80,26 +315,27 @@ impl RawBroadPhase {
         filter_exclude_rigid_body: Option<FlatHandle>,
         filter_predicate: &js_sys::Function,
     ) -> Option<RawRayColliderIntersection> {
-        let (handle, inter) = utils::with_filter(filter_predicate, |predicate| {
-            let query_filter = QueryFilter {
-                flags: QueryFilterFlags::from_bits(filter_flags)
-                    .unwrap_or(QueryFilterFlags::empty()),
-                groups: filter_groups.map(crate::geometry::unpack_interaction_groups),
-                exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
-                exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
-                predicate,
-            };
+        let (handle, inter) =
+            self.with_counted_filter(filter_groups, filter_predicate, |predicate| {
+                let query_filter = QueryFilter {
+                    flags: QueryFilterFlags::from_bits(filter_flags)
+                        .unwrap_or(QueryFilterFlags::empty()),
+                    groups: None,
+                    exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
+                    exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
+                    predicate,
+                };
 
-            let query_pipeline = self.0.as_query_pipeline(
-                narrow_phase.0.query_dispatcher(),
-                &bodies.0,
-                &colliders.0,
-                query_filter,
-            );
+                let query_pipeline = self.broad_phase.as_query_pipeline(
+                    narrow_phase.narrow_phase.query_dispatcher(),
+                    &bodies.set,
+                    &colliders.set,
+                    query_filter,
+                );
 
-            let ray = Ray::new(rayOrig.0.into(), rayDir.0);
-            query_pipeline.cast_ray_and_get_normal(&ray, maxToi, solid)
-        })?;
+                let ray = Ray::new(rayOrig.0.into(), rayDir.0);
+                query_pipeline.cast_ray_and_get_normal(&ray, maxToi, solid)
+            })?;
 
         Some(RawRayColliderIntersection { handle, inter })
     }
@@ -121,11 +357,11 @@ impl RawBroadPhase {
         filter_exclude_rigid_body: Option<FlatHandle>,
         filter_predicate: &js_sys::Function,
     ) {
-        utils::with_filter(filter_predicate, |predicate| {
+        self.with_counted_filter(filter_groups, filter_predicate, |predicate| {
             let query_filter = QueryFilter {
                 flags: QueryFilterFlags::from_bits(filter_flags)
                     .unwrap_or(QueryFilterFlags::empty()),
-                groups: filter_groups.map(crate::geometry::unpack_interaction_groups),
+                groups: None,
                 exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
                 exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
                 predicate,
@@ -140,10 +376,10 @@ impl RawBroadPhase {
                 }
             };
 
-            let query_pipeline = self.0.as_query_pipeline(
-                narrow_phase.0.query_dispatcher(),
-                &bodies.0,
-                &colliders.0,
+            let query_pipeline = self.broad_phase.as_query_pipeline(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &bodies.set,
+                &colliders.set,
                 query_filter,
             );
 
@@ -169,20 +405,20 @@ impl RawBroadPhase {
         filter_exclude_rigid_body: Option<FlatHandle>,
         filter_predicate: &js_sys::Function,
     ) -> Option<FlatHandle> {
-        utils::with_filter(filter_predicate, |predicate| {
+        self.with_counted_filter(filter_groups, filter_predicate, |predicate| {
             let query_filter = QueryFilter {
                 flags: QueryFilterFlags::from_bits(filter_flags)
                     .unwrap_or(QueryFilterFlags::empty()),
-                groups: filter_groups.map(crate::geometry::unpack_interaction_groups),
+                groups: None,
                 exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
                 exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
                 predicate,
             };
 
-            let query_pipeline = self.0.as_query_pipeline(
-                narrow_phase.0.query_dispatcher(),
-                &bodies.0,
-                &colliders.0,
+            let query_pipeline = self.broad_phase.as_query_pipeline(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &bodies.set,
+                &colliders.set,
                 query_filter,
             );
 
@@ -211,20 +447,20 @@ impl RawBroadPhase {
         filter_exclude_rigid_body: Option<FlatHandle>,
         filter_predicate: &js_sys::Function,
     ) -> Option<RawPointColliderProjection> {
-        utils::with_filter(filter_predicate, |predicate| {
+        self.with_counted_filter(filter_groups, filter_predicate, |predicate| {
             let query_filter = QueryFilter {
                 flags: QueryFilterFlags::from_bits(filter_flags)
                     .unwrap_or(QueryFilterFlags::empty()),
-                groups: filter_groups.map(crate::geometry::unpack_interaction_groups),
+                groups: None,
                 exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
                 exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
                 predicate,
             };
 
-            let query_pipeline = self.0.as_query_pipeline(
-                narrow_phase.0.query_dispatcher(),
-                &bodies.0,
-                &colliders.0,
+            let query_pipeline = self.broad_phase.as_query_pipeline(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &bodies.set,
+                &colliders.set,
                 query_filter,
             );
 
@@ -250,20 +486,20 @@ impl RawBroadPhase {
         filter_exclude_rigid_body: Option<FlatHandle>,
         filter_predicate: &js_sys::Function,
     ) -> Option<RawPointColliderProjection> {
-        utils::with_filter(filter_predicate, |predicate| {
+        self.with_counted_filter(filter_groups, filter_predicate, |predicate| {
             let query_filter = QueryFilter {
                 flags: QueryFilterFlags::from_bits(filter_flags)
                     .unwrap_or(QueryFilterFlags::empty()),
-                groups: filter_groups.map(crate::geometry::unpack_interaction_groups),
+                groups: None,
                 exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
                 exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
                 predicate,
             };
 
-            let query_pipeline = self.0.as_query_pipeline(
-                narrow_phase.0.query_dispatcher(),
-                &bodies.0,
-                &colliders.0,
+            let query_pipeline = self.broad_phase.as_query_pipeline(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &bodies.set,
+                &colliders.set,
                 query_filter,
             );
 
@@ -291,20 +527,20 @@ impl RawBroadPhase {
         filter_exclude_rigid_body: Option<FlatHandle>,
         filter_predicate: &js_sys::Function,
     ) {
-        utils::with_filter(filter_predicate, |predicate| {
+        self.with_counted_filter(filter_groups, filter_predicate, |predicate| {
             let query_filter = QueryFilter {
                 flags: QueryFilterFlags::from_bits(filter_flags)
                     .unwrap_or(QueryFilterFlags::empty()),
-                groups: filter_groups.map(crate::geometry::unpack_interaction_groups),
+                groups: None,
                 exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
                 exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
                 predicate,
             };
 
-            let query_pipeline = self.0.as_query_pipeline(
-                narrow_phase.0.query_dispatcher(),
-                &bodies.0,
-                &colliders.0,
+            let query_pipeline = self.broad_phase.as_query_pipeline(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &bodies.set,
+                &colliders.set,
                 query_filter,
             );
 
@@ -342,20 +578,20 @@ impl RawBroadPhase {
         filter_exclude_rigid_body: Option<FlatHandle>,
         filter_predicate: &js_sys::Function,
     ) -> Option<RawColliderShapeCastHit> {
-        utils::with_filter(filter_predicate, |predicate| {
+        self.with_counted_filter(filter_groups, filter_predicate, |predicate| {
             let query_filter = QueryFilter {
                 flags: QueryFilterFlags::from_bits(filter_flags)
                     .unwrap_or(QueryFilterFlags::empty()),
-                groups: filter_groups.map(crate::geometry::unpack_interaction_groups),
+                groups: None,
                 exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
                 exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
                 predicate,
             };
 
-            let query_pipeline = self.0.as_query_pipeline(
-                narrow_phase.0.query_dispatcher(),
-                &bodies.0,
-                &colliders.0,
+            let query_pipeline = self.broad_phase.as_query_pipeline(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &bodies.set,
+                &colliders.set,
                 query_filter,
             );
 
@@ -376,56 +612,587 @@ impl RawBroadPhase {
         })
     }
 
-    // The callback has type (u32) => boolean
-    pub fn intersectionsWithShape(
+    /// Sweeps a shape between two poses, and returns the first collider it hits.
+    ///
+    /// The shape translates along a straight line from `startPos` to `endPos` while rotating
+    /// uniformly, around its local origin, from `startRot` to `endRot`. The time of impact
+    /// of the returned hit is the fraction of this motion, between `0` and `1`, traveled by
+    /// the shape before the impact.
+    pub fn sweepShapeBetweenPoses(
         &self,
         narrow_phase: &RawNarrowPhase,
         bodies: &RawRigidBodySet,
         colliders: &RawColliderSet,
-        shapePos: &RawVector,
-        shapeRot: &RawRotation,
         shape: &RawShape,
-        callback: &js_sys::Function,
+        startPos: &RawVector,
+        startRot: &RawRotation,
+        endPos: &RawVector,
+        endRot: &RawRotation,
+        stop_at_penetration: bool,
         filter_flags: u32,
         filter_groups: Option<u32>,
         filter_exclude_collider: Option<FlatHandle>,
         filter_exclude_rigid_body: Option<FlatHandle>,
         filter_predicate: &js_sys::Function,
-    ) {
-        utils::with_filter(filter_predicate, |predicate| {
+    ) -> Option<RawColliderShapeCastHit> {
+        self.with_counted_filter(filter_groups, filter_predicate, |predicate| {
             let query_filter = QueryFilter {
                 flags: QueryFilterFlags::from_bits(filter_flags)
                     .unwrap_or(QueryFilterFlags::empty()),
-                groups: filter_groups.map(crate::geometry::unpack_interaction_groups),
+                groups: None,
                 exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
                 exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
                 predicate,
             };
 
-            let query_pipeline = self.0.as_query_pipeline(
-                narrow_phase.0.query_dispatcher(),
-                &bodies.0,
-                &colliders.0,
+            let query_pipeline = self.broad_phase.as_query_pipeline(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &bodies.set,
+                &colliders.set,
                 query_filter,
             );
 
-            let rcallback = |handle: ColliderHandle| match callback.call1(
-                &JsValue::null(),
-                &JsValue::from(utils::flat_handle(handle.0)),
-            ) {
-                Err(_) => true,
-                Ok(val) => val.as_bool().unwrap_or(true),
+            let start = Isometry::from_parts(startPos.0.into(), startRot.0);
+            let end = Isometry::from_parts(endPos.0.into(), endRot.0);
+            let motion = NonlinearRigidMotion::new(
+                start,
+                Point::origin(),
+                end.translation.vector - start.translation.vector,
+                angular_displacement(&start, &end),
+            );
+
+            // The shape stays within this distance of the segment traveled by its origin.
+            let sphere = shape.0.compute_local_bounding_sphere();
+            let radius = sphere.center.coords.norm() + sphere.radius;
+            let (p1, p2) = (start.translation.vector, end.translation.vector);
+            let half_extents = Vector::repeat(radius);
+            let swept_aabb = Aabb::new(
+                (p1.inf(&p2) - half_extents).into(),
+                (p1.sup(&p2) + half_extents).into(),
+            );
+
+            query_pipeline
+                .intersect_aabb_conservative(swept_aabb)
+                .filter_map(|(handle, co)| {
+                    query::cast_shapes_nonlinear(
+                        &motion,
+                        &*shape.0,
+                        &NonlinearRigidMotion::constant_position(*co.position()),
+                        co.shape(),
+                        0.0,
+                        1.0,
+                        stop_at_penetration,
+                    )
+                    .ok()
+                    .flatten()
+                    .map(|hit| RawColliderShapeCastHit { handle, hit })
+                })
+                .min_by(|a, b| a.hit.time_of_impact.total_cmp(&b.hit.time_of_impact))
+        })
+    }
+
+    /// Casts a ray straight down (along `-Y`) from each of the given points, e.g., to place
+    /// props on a terrain.
+    ///
+    /// The points are packed as a flat array of coordinates. The candidate colliders are
+    /// found with a single traversal of the broad-phase for the whole batch, and bucketed
+    /// into a horizontal grid so each point is only tested against the colliders of its
+    /// grid cell. The result is
+    /// packed as `[handle, height, normal]` for each point, where `height` is the `Y`
+    /// coordinate of the ground below the point, at most `maxDrop` below it. The `handle`
+    /// and `height` of a point without ground below it are `NaN`, and its normal is zero.
+    /// Points inside of a collider are reported to be on the ground.
+    pub fn dropPointsToGround(
+        &self,
+        narrow_phase: &RawNarrowPhase,
+        bodies: &RawRigidBodySet,
+        colliders: &RawColliderSet,
+        points: Vec<f32>,
+        maxDrop: Real,
+        filter_flags: u32,
+        filter_groups: Option<u32>,
+        filter_exclude_collider: Option<FlatHandle>,
+        filter_exclude_rigid_body: Option<FlatHandle>,
+        filter_predicate: &js_sys::Function,
+    ) -> Vec<f64> {
+        let points: Vec<Point<Real>> = points.chunks_exact(DIM).map(Point::from_slice).collect();
+        if points.is_empty() {
+            return vec![];
+        }
+
+        self.with_counted_filter(filter_groups, filter_predicate, |predicate| {
+            let query_filter = QueryFilter {
+                flags: QueryFilterFlags::from_bits(filter_flags)
+                    .unwrap_or(QueryFilterFlags::empty()),
+                groups: None,
+                exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
+                exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
+                predicate,
             };
 
-            let pos = Isometry::from_parts(shapePos.0.into(), shapeRot.0);
-            for (handle, _) in query_pipeline.intersect_shape(pos, &*shape.0) {
-                if !rcallback(handle) {
-                    break;
+            let query_pipeline = self.broad_phase.as_query_pipeline(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &bodies.set,
+                &colliders.set,
+                query_filter,
+            );
+
+            // The AABB containing all the vertical segments along which the rays are cast.
+            let mut batch_aabb = Aabb::from_points(points.iter().copied());
+            batch_aabb.mins.y -= maxDrop;
+            let candidates: Vec<_> = query_pipeline
+                .intersect_aabb_conservative(batch_aabb)
+                .map(|(handle, co)| (handle, co, co.compute_aabb()))
+                .collect();
+            let grid = ColumnGrid::new(
+                &batch_aabb,
+                points.len(),
+                candidates.iter().map(|(_, _, aabb)| aabb),
+            );
+
+            let dir = -Vector::y();
+            let mut out = Vec::with_capacity(points.len() * (2 + DIM));
+            for point in &points {
+                let mut segment = Aabb::new(*point, *point);
+                segment.mins.y -= maxDrop;
+                let ray = Ray::new(*point, dir);
+
+                let hit = grid
+                    .candidates_at(point)
+                    .iter()
+                    .map(|i| &candidates[*i as usize])
+                    .filter(|(_, _, aabb)| aabb.intersects(&segment))
+                    .filter_map(|(handle, co, _)| {
+                        co.shape()
+                            .cast_ray_and_get_normal(co.position(), &ray, maxDrop, true)
+                            .map(|inter| (*handle, inter))
+                    })
+                    .min_by(|a, b| a.1.time_of_impact.total_cmp(&b.1.time_of_impact));
+
+                match hit {
+                    Some((handle, inter)) => {
+                        out.push(utils::flat_handle(handle.0));
+                        out.push((point.y - inter.time_of_impact) as f64);
+                        out.extend(inter.normal.iter().map(|x| *x as f64));
+                    }
+                    None => {
+                        out.push(f64::NAN);
+                        out.push(f64::NAN);
+                        out.extend(std::iter::repeat(0.0).take(DIM));
+                    }
+                }
+            }
+            out
+        })
+    }
+
+    /// Moves a rigid-body out of the colliders it penetrates, e.g., right after spawning it.
+    ///
+    /// At each iteration, the deepest penetration between one of the colliders attached to the
+    /// rigid-body and the other colliders is computed with a contact query, and the rigid-body
+    /// is translated along the contact normal to resolve it. This stops once the rigid-body
+    /// no longer penetrates anything, or after `maxIterations` iterations. Sensors are ignored.
+    ///
+    /// Returns the translation applied to the rigid-body.
+    pub fn depenetrate(
+        &self,
+        narrow_phase: &RawNarrowPhase,
+        bodies: &mut RawRigidBodySet,
+        colliders: &mut RawColliderSet,
+        bodyHandle: FlatHandle,
+        maxIterations: u32,
+        filter_flags: u32,
+        filter_groups: Option<u32>,
+        filter_exclude_collider: Option<FlatHandle>,
+        filter_predicate: &js_sys::Function,
+    ) -> RawVector {
+        let body_handle = utils::body_handle(bodyHandle);
+        let own_colliders = match bodies.set.get(body_handle) {
+            Some(rb) => rb.colliders().to_vec(),
+            None => return Vector::zeros().into(),
+        };
+
+        let correction = self.with_counted_filter(filter_groups, filter_predicate, |predicate| {
+            let flags = QueryFilterFlags::from_bits(filter_flags)
+                .unwrap_or(QueryFilterFlags::empty())
+                | QueryFilterFlags::EXCLUDE_SENSORS;
+            let query_filter = QueryFilter {
+                flags,
+                groups: None,
+                exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
+                exclude_rigid_body: Some(body_handle),
+                predicate,
+            };
+
+            let query_pipeline = self.broad_phase.as_query_pipeline(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &bodies.set,
+                &colliders.set,
+                query_filter,
+            );
+
+            let mut correction = Vector::zeros();
+            for _ in 0..maxIterations {
+                // The deepest penetration, as a negative distance, and its normal pointing
+                // towards the exterior of the collider attached to the rigid-body.
+                let mut deepest: Option<(Real, Vector<Real>)> = None;
+
+                for own_co in own_colliders
+                    .iter()
+                    .filter_map(|handle| colliders.set.get(*handle))
+                    .filter(|co| !co.is_sensor())
+                {
+                    let mut pos = *own_co.position();
+                    pos.translation.vector += correction;
+                    let aabb = own_co.shape().compute_aabb(&pos);
+
+                    for (_, co) in query_pipeline.intersect_aabb_conservative(aabb) {
+                        if let Ok(Some(contact)) =
+                            query::contact(&pos, own_co.shape(), co.position(), co.shape(), 0.0)
+                        {
+                            if contact.dist < deepest.map(|d| d.0).unwrap_or(0.0) {
+                                deepest = Some((contact.dist, *contact.normal1));
+                            }
+                        }
+                    }
+                }
+
+                match deepest {
+                    Some((dist, normal)) => correction += normal * dist,
+                    None => break,
+                }
+            }
+
+            correction
+        });
+
+        if correction != Vector::zeros() {
+            if let Some(rb) = bodies.set.get_mut(body_handle) {
+                let translation = rb.translation() + correction;
+                rb.set_translation(translation, true);
+            }
+            bodies
+                .set
+                .propagate_modified_body_positions_to_colliders(&mut colliders.set);
+        }
+
+        correction.into()
+    }
+
+    /// Predicts the ballistic trajectory of a shape, up to the first fixed collider it hits.
+    ///
+    /// The velocity of the shape is integrated the same way the physics pipeline integrates
+    /// the velocities of dynamic rigid-bodies: gravity is applied, then the linear damping.
+    /// At each substep of length `dt`, the shape is cast along its displacement (without
+    /// rotating) against fixed colliders only.
+    pub fn predictTrajectory(
+        &self,
+        narrow_phase: &RawNarrowPhase,
+        bodies: &RawRigidBodySet,
+        colliders: &RawColliderSet,
+        shapePos: &RawVector,
+        shapeRot: &RawRotation,
+        shapeVel: &RawVector,
+        shape: &RawShape,
+        gravity: &RawVector,
+        gravityScale: f32,
+        linearDamping: f32,
+        maxTime: f32,
+        dt: f32,
+        filter_flags: u32,
+        filter_groups: Option<u32>,
+        filter_exclude_collider: Option<FlatHandle>,
+        filter_exclude_rigid_body: Option<FlatHandle>,
+        filter_predicate: &js_sys::Function,
+    ) -> RawPredictedTrajectory {
+        self.with_counted_filter(filter_groups, filter_predicate, |predicate| {
+            let query_filter = QueryFilter {
+                flags: QueryFilterFlags::from_bits(filter_flags)
+                    .unwrap_or(QueryFilterFlags::empty())
+                    | QueryFilterFlags::ONLY_FIXED,
+                groups: None,
+                exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
+                exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
+                predicate,
+            };
+
+            let query_pipeline = self.broad_phase.as_query_pipeline(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &bodies.set,
+                &colliders.set,
+                query_filter,
+            );
+
+            let mut pos = Isometry::from_parts(shapePos.0.into(), shapeRot.0);
+            let mut vel = shapeVel.0;
+            let mut time = 0.0;
+            let mut points: Vec<f32> = pos.translation.vector.iter().copied().collect();
+            let dt = dt.max(1.0e-6);
+
+            while time < maxTime {
+                let step = dt.min(maxTime - time);
+                vel += gravity.0 * gravityScale * step;
+                vel *= 1.0 / (1.0 + step * linearDamping);
+                let displacement = vel * step;
+
+                let hit = query_pipeline.cast_shape(
+                    &pos,
+                    &displacement,
+                    &*shape.0,
+                    ShapeCastOptions {
+                        max_time_of_impact: 1.0,
+                        stop_at_penetration: true,
+                        compute_impact_geometry_on_penetration: true,
+                        target_distance: 0.0,
+                    },
+                );
+
+                if let Some((handle, hit)) = hit {
+                    pos.translation.vector += displacement * hit.time_of_impact;
+                    points.extend(pos.translation.vector.iter().copied());
+                    let normal = colliders.set[handle].position() * hit.normal1.into_inner();
+
+                    return RawPredictedTrajectory {
+                        points,
+                        hit: Some(TrajectoryHit {
+                            handle,
+                            time: time + step * hit.time_of_impact,
+                            position: pos.translation.vector,
+                            normal,
+                        }),
+                    };
                 }
+
+                pos.translation.vector += displacement;
+                points.extend(pos.translation.vector.iter().copied());
+                time += step;
             }
+
+            RawPredictedTrajectory { points, hit: None }
         })
     }
 
+    /// Casts a shape and collects all the colliders it hits, by increasing time of impact.
+    ///
+    /// The result is packed as `[handle, timeOfImpact, witness1, witness2, normal1, normal2]`
+    /// for each hit, i.e., 10 values per hit in 2D and 14 values per hit in 3D.
+    pub fn castShapeAll(
+        &self,
+        narrow_phase: &RawNarrowPhase,
+        bodies: &RawRigidBodySet,
+        colliders: &RawColliderSet,
+        shapePos: &RawVector,
+        shapeRot: &RawRotation,
+        shapeVel: &RawVector,
+        shape: &RawShape,
+        target_distance: f32,
+        maxToi: f32,
+        stop_at_penetration: bool,
+        filter_flags: u32,
+        filter_groups: Option<u32>,
+        filter_exclude_collider: Option<FlatHandle>,
+        filter_exclude_rigid_body: Option<FlatHandle>,
+        filter_predicate: &js_sys::Function,
+    ) -> Vec<f64> {
+        let hits = self.with_counted_filter(filter_groups, filter_predicate, |predicate| {
+            let query_filter = QueryFilter {
+                flags: QueryFilterFlags::from_bits(filter_flags)
+                    .unwrap_or(QueryFilterFlags::empty()),
+                groups: None,
+                exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
+                exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
+                predicate,
+            };
+
+            let query_pipeline = self.broad_phase.as_query_pipeline(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &bodies.set,
+                &colliders.set,
+                query_filter,
+            );
+
+            let pos = Isometry::from_parts(shapePos.0.into(), shapeRot.0);
+            let end_pos = Isometry::from_parts(
+                (pos.translation.vector + shapeVel.0 * maxToi).into(),
+                pos.rotation,
+            );
+            let swept_aabb = shape
+                .0
+                .compute_swept_aabb(&pos, &end_pos)
+                .loosened(target_distance);
+
+            let mut hits: Vec<_> = query_pipeline
+                .intersect_aabb_conservative(swept_aabb)
+                .filter_map(|(handle, co)| {
+                    shape
+                        .0
+                        .castShape(
+                            &pos,
+                            &shapeVel.0,
+                            co.shape(),
+                            co.position(),
+                            &Vector::zeros(),
+                            target_distance,
+                            maxToi,
+                            stop_at_penetration,
+                        )
+                        .map(|hit| RawColliderShapeCastHit {
+                            handle,
+                            hit: hit.hit,
+                        })
+                })
+                .collect();
+            hits.sort_by(|a, b| a.hit.time_of_impact.total_cmp(&b.hit.time_of_impact));
+            hits
+        });
+
+        let mut out = Vec::with_capacity(hits.len() * (2 + 4 * DIM));
+        for hit in hits {
+            out.push(utils::flat_handle(hit.handle.0));
+            out.push(hit.hit.time_of_impact as f64);
+            out.extend(hit.hit.witness1.iter().map(|x| *x as f64));
+            out.extend(hit.hit.witness2.iter().map(|x| *x as f64));
+            out.extend(hit.hit.normal1.iter().map(|x| *x as f64));
+            out.extend(hit.hit.normal2.iter().map(|x| *x as f64));
+        }
+        out
+    }
+
+    // The callback has type (u32) => boolean
+    pub fn intersectionsWithShape(
+        &self,
+        narrow_phase: &RawNarrowPhase,
+        bodies: &RawRigidBodySet,
+        colliders: &RawColliderSet,
+        shapePos: &RawVector,
+        shapeRot: &RawRotation,
+        shape: &RawShape,
+        callback: &js_sys::Function,
+        filter_flags: u32,
+        filter_groups: Option<u32>,
+        filter_exclude_collider: Option<FlatHandle>,
+        filter_exclude_rigid_body: Option<FlatHandle>,
+        filter_predicate: &js_sys::Function,
+    ) {
+        let pos = Isometry::from_parts(shapePos.0.into(), shapeRot.0);
+        self.intersections_with_shape(
+            narrow_phase,
+            bodies,
+            colliders,
+            pos,
+            &*shape.0,
+            callback,
+            filter_flags,
+            filter_groups,
+            filter_exclude_collider,
+            filter_exclude_rigid_body,
+            filter_predicate,
+        )
+    }
+
+    /// Finds all the colliders intersecting a cone, e.g., a vision cone.
+    ///
+    /// The cone starts at `apex`, is oriented along `dir`, has an half-aperture angle of
+    /// `halfAngle` radians, and is capped by a flat base at the distance `maxDist` from its
+    /// apex. In 2D, this cone is a triangle.
+    pub fn intersectionsWithCone(
+        &self,
+        narrow_phase: &RawNarrowPhase,
+        bodies: &RawRigidBodySet,
+        colliders: &RawColliderSet,
+        apex: &RawVector,
+        dir: &RawVector,
+        halfAngle: f32,
+        maxDist: f32,
+        callback: &js_sys::Function,
+        filter_flags: u32,
+        filter_groups: Option<u32>,
+        filter_exclude_collider: Option<FlatHandle>,
+        filter_exclude_rigid_body: Option<FlatHandle>,
+        filter_predicate: &js_sys::Function,
+    ) {
+        let dir = match Unit::try_new(dir.0, 1.0e-6) {
+            Some(dir) => dir,
+            None => return,
+        };
+        let half_angle = halfAngle.clamp(0.0, std::f32::consts::FRAC_PI_2 - 1.0e-3);
+
+        #[cfg(feature = "dim2")]
+        let (pos, shape) = {
+            let side = maxDist / half_angle.cos();
+            let a = apex.0.into();
+            let b = a + UnitComplex::new(half_angle) * *dir * side;
+            let c = a + UnitComplex::new(-half_angle) * *dir * side;
+            (Isometry::identity(), Triangle::new(a, b, c))
+        };
+        #[cfg(feature = "dim3")]
+        let (pos, shape) = {
+            let rot = UnitQuaternion::rotation_between(&-Vector::y(), &dir.into_inner())
+                .unwrap_or_else(|| {
+                    UnitQuaternion::from_axis_angle(&Vector::x_axis(), std::f32::consts::PI)
+                });
+            let center = apex.0 + *dir * (maxDist / 2.0);
+            let pos = Isometry::from_parts(center.into(), rot);
+            (pos, Cone::new(maxDist / 2.0, maxDist * half_angle.tan()))
+        };
+
+        self.intersections_with_shape(
+            narrow_phase,
+            bodies,
+            colliders,
+            pos,
+            &shape,
+            callback,
+            filter_flags,
+            filter_groups,
+            filter_exclude_collider,
+            filter_exclude_rigid_body,
+            filter_predicate,
+        )
+    }
+
+    /// Finds all the colliders intersecting the convex region bounded by the given planes,
+    /// e.g., a camera frustum.
+    ///
+    /// The planes are packed as `[nx, ny, (nz,) d]` and the region contains the points `x`
+    /// such that `n.dot(x) + d >= 0` for all the planes. Nothing is reported if the region
+    /// is empty or unbounded.
+    pub fn intersectionsWithFrustum(
+        &self,
+        narrow_phase: &RawNarrowPhase,
+        bodies: &RawRigidBodySet,
+        colliders: &RawColliderSet,
+        planes: Vec<f32>,
+        callback: &js_sys::Function,
+        filter_flags: u32,
+        filter_groups: Option<u32>,
+        filter_exclude_collider: Option<FlatHandle>,
+        filter_exclude_rigid_body: Option<FlatHandle>,
+        filter_predicate: &js_sys::Function,
+    ) {
+        let planes: Vec<_> = planes
+            .chunks_exact(DIM + 1)
+            .map(|p| (Vector::from_column_slice(&p[..DIM]), p[DIM]))
+            .collect();
+        let vertices = convex_region_vertices(&planes);
+
+        if let Some(shape) = SharedShape::convex_hull(&vertices) {
+            self.intersections_with_shape(
+                narrow_phase,
+                bodies,
+                colliders,
+                Isometry::identity(),
+                &*shape,
+                callback,
+                filter_flags,
+                filter_groups,
+                filter_exclude_collider,
+                filter_exclude_rigid_body,
+                filter_predicate,
+            )
+        }
+    }
+
     pub fn collidersWithAabbIntersectingAabb(
         &self,
         narrow_phase: &RawNarrowPhase,
@@ -443,10 +1210,10 @@ impl RawBroadPhase {
             Ok(val) => val.as_bool().unwrap_or(true),
         };
 
-        let query_pipeline = self.0.as_query_pipeline(
-            narrow_phase.0.query_dispatcher(),
-            &bodies.0,
-            &colliders.0,
+        let query_pipeline = self.broad_phase.as_query_pipeline(
+            narrow_phase.narrow_phase.query_dispatcher(),
+            &bodies.set,
+            &colliders.set,
             Default::default(),
         );
 
@@ -460,3 +1227,58 @@ impl RawBroadPhase {
         }
     }
 }
+
+/// The vertices of the convex region containing the points `x` such that
+/// `n.dot(x) + d >= 0` for all the given planes `(n, d)`.
+fn convex_region_vertices(planes: &[(Vector<Real>, Real)]) -> Vec<Point<Real>> {
+    const EPS: Real = 1.0e-4;
+    let mut vertices = vec![];
+    let mut push_if_inside = |x: Vector<Real>| {
+        if planes.iter().all(|(n, d)| n.dot(&x) + d >= -EPS) {
+            vertices.push(Point::from(x));
+        }
+    };
+
+    #[cfg(feature = "dim2")]
+    for i in 0..planes.len() {
+        for j in i + 1..planes.len() {
+            let (n1, d1) = planes[i];
+            let (n2, d2) = planes[j];
+            let mat = Matrix2::from_rows(&[n1.transpose(), n2.transpose()]);
+            if let Some(inv) = mat.try_inverse() {
+                push_if_inside(inv * -Vector::new(d1, d2));
+            }
+        }
+    }
+
+    #[cfg(feature = "dim3")]
+    for i in 0..planes.len() {
+        for j in i + 1..planes.len() {
+            for k in j + 1..planes.len() {
+                let (n1, d1) = planes[i];
+                let (n2, d2) = planes[j];
+                let (n3, d3) = planes[k];
+                let mat = Matrix3::from_rows(&[n1.transpose(), n2.transpose(), n3.transpose()]);
+                if let Some(inv) = mat.try_inverse() {
+                    push_if_inside(inv * -Vector::new(d1, d2, d3));
+                }
+            }
+        }
+    }
+
+    vertices
+}
+
+/// The rotation, as an angular velocity applied during one unit of time, from the
+/// orientation of `start` to the orientation of `end`.
+#[cfg(feature = "dim2")]
+fn angular_displacement(start: &Isometry<Real>, end: &Isometry<Real>) -> Real {
+    (end.rotation * start.rotation.inverse()).angle()
+}
+
+/// The rotation, as an angular velocity applied during one unit of time, from the
+/// orientation of `start` to the orientation of `end`.
+#[cfg(feature = "dim3")]
+fn angular_displacement(start: &Isometry<Real>, end: &Isometry<Real>) -> Vector<Real> {
+    (end.rotation * start.rotation.inverse()).scaled_axis()
+}
@@ -1,16 +1,25 @@
 use crate::geometry::shape::SharedShapeUtility;
 use crate::geometry::{
-    RawColliderSet, RawColliderShapeCastHit, RawPointProjection, RawRayIntersection, RawShape,
-    RawShapeCastHit, RawShapeContact, RawShapeType,
+    AccumulatedImpulse, RawAabb, RawBoundingSphere, RawColliderSet, RawColliderShapeCastHit,
+    RawPointProjection, RawRayIntersection, RawShape, RawShapeCastHit, RawShapeContact,
+    RawShapeType,
 };
 use crate::math::{RawRotation, RawVector};
+use crate::pipeline::OnewayPlatform;
 use crate::utils::{self, FlatHandle};
+#[cfg(feature = "dim3")]
+use na::DMatrix;
+#[cfg(feature = "dim2")]
+use na::DVector;
 use rapier::dynamics::MassProperties;
-use rapier::geometry::{ActiveCollisionTypes, ShapeType};
-use rapier::math::{Isometry, Point, Real, Vector};
+use rapier::geometry::{
+    ActiveCollisionTypes, ColliderBuilder, ColliderSet, HeightField, ShapeType, TriMesh,
+};
+use rapier::math::{Isometry, Point, Real, Vector, DIM};
 use rapier::parry::query;
 use rapier::parry::query::ShapeCastOptions;
 use rapier::pipeline::{ActiveEvents, ActiveHooks};
+use std::sync::Arc;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -169,6 +178,42 @@ impl RawColliderSet {
         })
     }
 
+    /// The world-space axis-aligned bounding box of this collider.
+    pub fn coAabb(&self, handle: FlatHandle) -> RawAabb {
+        self.map(handle, |co| RawAabb(co.compute_aabb()))
+    }
+
+    /// The world-space bounding sphere of this collider.
+    pub fn coBoundingSphere(&self, handle: FlatHandle) -> RawBoundingSphere {
+        self.map(handle, |co| {
+            RawBoundingSphere(co.shape().compute_bounding_sphere(co.position()))
+        })
+    }
+
+    /// The number of references to the shape of this collider.
+    ///
+    /// This is greater than 1 if the shape is shared with other colliders or shapes.
+    pub fn coShapeRefCount(&self, handle: FlatHandle) -> usize {
+        self.map(handle, |co| Arc::strong_count(&co.shared_shape().0))
+    }
+
+    /// The sub-shapes of the compound shape of this collider.
+    ///
+    /// Returns a new collider set with one parentless collider per sub-shape, in order,
+    /// each positioned at the pose of its sub-shape relative to the compound. The sub-shapes
+    /// are shared, not copied. Returns `None` if the shape of this collider isn't a compound.
+    pub fn coCompoundParts(&self, handle: FlatHandle) -> Option<RawColliderSet> {
+        self.map(handle, |co| {
+            co.shape().as_compound().map(|compound| {
+                let mut parts = ColliderSet::new();
+                for (pos, shape) in compound.shapes() {
+                    parts.insert(ColliderBuilder::new(shape.clone()).position(*pos).build());
+                }
+                parts.into()
+            })
+        })
+    }
+
     pub fn coHalfspaceNormal(&self, handle: FlatHandle) -> Option<RawVector> {
         self.map(handle, |co| {
             co.shape()
@@ -580,6 +625,100 @@ impl RawColliderSet {
         })
     }
 
+    /// A read-only view of the heights of this heightfield, aliasing the WASM memory, if it
+    /// is one.
+    ///
+    /// The view is invalidated whenever the WASM memory grows, and whenever the shape
+    /// of this collider is replaced (including by `coSetHeightfieldHeights`). The heights
+    /// may be shared with other colliders, so they must only be modified with
+    /// `coSetHeightfieldHeights`.
+    pub fn coHeightfieldHeightsView(&self, handle: FlatHandle) -> Option<js_sys::Float32Array> {
+        self.map(handle, |co| {
+            co.shape()
+                .as_heightfield()
+                .map(|h| unsafe { js_sys::Float32Array::view(h.heights().as_slice()) })
+        })
+    }
+
+    /// A read-only view of the vertex buffer of this triangle mesh, aliasing the WASM memory,
+    /// if it is one.
+    ///
+    /// The view is invalidated whenever the WASM memory grows, and whenever the shape
+    /// of this collider is replaced (including by `coSetTrimeshVertices`). The vertices
+    /// may be shared with other colliders, so they must only be modified with
+    /// `coSetTrimeshVertices`.
+    pub fn coTrimeshVerticesView(&self, handle: FlatHandle) -> Option<js_sys::Float32Array> {
+        self.map(handle, |co| {
+            co.shape().as_trimesh().map(|t| {
+                let vertices = t.vertices();
+                unsafe {
+                    let coords = std::slice::from_raw_parts(
+                        vertices.as_ptr() as *const Real,
+                        vertices.len() * DIM,
+                    );
+                    js_sys::Float32Array::view(coords)
+                }
+            })
+        })
+    }
+
+    /// Replaces the heights of this heightfield, keeping its dimensions, scale, and flags.
+    ///
+    /// If the heightfield is shared with other colliders, this collider gets its own copy
+    /// of it first, so the other colliders are left unchanged. The bounding volumes of the
+    /// shape are recomputed. Returns `false` (and does nothing) if the shape isn't a
+    /// heightfield, or if the number of heights doesn't match its dimensions.
+    pub fn coSetHeightfieldHeights(&mut self, handle: FlatHandle, heights: Vec<f32>) -> bool {
+        self.map_mut(handle, |co| {
+            let heightfield = match co.shape().as_heightfield() {
+                Some(h) if h.heights().len() == heights.len() => {
+                    #[cfg(feature = "dim2")]
+                    let heightfield = HeightField::new(DVector::from_vec(heights), *h.scale());
+                    #[cfg(feature = "dim3")]
+                    let heightfield = HeightField::with_flags(
+                        DMatrix::from_vec(h.nrows() + 1, h.ncols() + 1, heights),
+                        *h.scale(),
+                        h.flags(),
+                    );
+                    heightfield
+                }
+                _ => return false,
+            };
+
+            if let Some(h) = co.shape_mut().as_heightfield_mut() {
+                *h = heightfield;
+            }
+            true
+        })
+    }
+
+    /// Replaces the vertices of this triangle mesh, keeping its indices and flags.
+    ///
+    /// If the triangle mesh is shared with other colliders, this collider gets its own copy
+    /// of it first, so the other colliders are left unchanged. The acceleration structures
+    /// and bounding volumes of the shape are recomputed. Returns `false` (and does nothing)
+    /// if the shape isn't a triangle mesh, if the number of vertices changed, or if the
+    /// modified triangle mesh is invalid.
+    pub fn coSetTrimeshVertices(&mut self, handle: FlatHandle, vertices: Vec<f32>) -> bool {
+        self.map_mut(handle, |co| {
+            let trimesh = match co.shape().as_trimesh() {
+                Some(t) if t.vertices().len() * DIM == vertices.len() => {
+                    let vertices = vertices.chunks_exact(DIM).map(Point::from_slice).collect();
+                    match TriMesh::with_flags(vertices, t.indices().to_vec(), t.flags()) {
+                        Ok(trimesh) => trimesh,
+                        Err(_) => return false,
+                    }
+                }
+                _ => return false,
+            };
+
+            if let Some(t) = co.shape_mut().as_trimesh_mut() {
+                *t = trimesh;
+            }
+            true
+        })
+    }
+
     /// The scaling factor applied of this heightfield if it is one.
     pub fn coHeightfieldScale(&self, handle: FlatHandle) -> Option<RawVector> {
         self.map(handle, |co| match co.shape().shape_type() {
@@ -611,6 +750,15 @@ impl RawColliderSet {
         self.map(handle, |co| co.parent().map(|p| utils::flat_handle(p.0)))
     }
 
+    /// The number of steps simulated with the rigid-body set of this collider when it was created.
+    ///
+    /// Returns `None` if the collider was restored from a snapshot.
+    pub fn coCreationStep(&self, handle: FlatHandle) -> Option<u32> {
+        self.creation_steps
+            .get(&utils::collider_handle(handle))
+            .copied()
+    }
+
     pub fn coSetEnabled(&mut self, handle: FlatHandle, enabled: bool) {
         self.map_mut(handle, |co| co.set_enabled(enabled))
     }
@@ -667,7 +815,26 @@ impl RawColliderSet {
 
     /// The physics hooks enabled for this collider.
     pub fn coActiveHooks(&self, handle: FlatHandle) -> u32 {
-        self.map(handle, |co| co.active_hooks().bits())
+        self.map(handle, |co| {
+            self.native_hooks
+                .user_hooks(utils::collider_handle(handle), co)
+                .bits()
+        })
+    }
+
+    /// Is this collider a one-way platform?
+    pub fn coIsOnewayPlatform(&self, handle: FlatHandle) -> bool {
+        self.native_hooks
+            .oneway_platforms
+            .contains_key(&utils::collider_handle(handle))
+    }
+
+    /// The restitution velocity threshold of this collider, if it overrides the global one.
+    pub fn coRestitutionThreshold(&self, handle: FlatHandle) -> Option<Real> {
+        self.native_hooks
+            .restitution_thresholds
+            .get(&utils::collider_handle(handle))
+            .copied()
     }
 
     /// The collision types enabled for this collider.
@@ -733,7 +900,7 @@ impl RawColliderSet {
     ) -> Option<RawColliderShapeCastHit> {
         let handle2 = utils::collider_handle(collider2handle);
         let co2 = self
-            .0
+            .set
             .get(handle2)
             .expect("Invalid Collider reference. It may have been removed from the physics World.");
 
@@ -800,7 +967,7 @@ impl RawColliderSet {
         prediction: f32,
     ) -> Option<RawShapeContact> {
         let co2 = self
-            .0
+            .set
             .get(utils::collider_handle(collider2handle))
             .expect("Invalid Collider reference. It may have been removed from the physics World.");
 
@@ -890,7 +1057,9 @@ impl RawColliderSet {
     }
 
     pub fn coSetRestitution(&mut self, handle: FlatHandle, restitution: f32) {
-        self.map_mut(handle, |co| co.set_restitution(restitution))
+        self.map_mut(handle, |co| co.set_restitution(restitution));
+        // The global restitution threshold only needs to be applied to bouncy colliders.
+        self.update_native_hooks(utils::collider_handle(handle));
     }
 
     pub fn coSetFriction(&mut self, handle: FlatHandle, friction: f32) {
@@ -927,7 +1096,124 @@ impl RawColliderSet {
 
     pub fn coSetActiveHooks(&mut self, handle: FlatHandle, hooks: u32) {
         let hooks = ActiveHooks::from_bits(hooks).unwrap_or(ActiveHooks::empty());
-        self.map_mut(handle, |co| co.set_active_hooks(hooks));
+        self.set_active_hooks(handle, hooks);
+    }
+
+    /// Sets the physics hooks of all the given colliders at once.
+    pub fn coSetActiveHooksBulk(&mut self, handles: Vec<FlatHandle>, hooks: u32) {
+        let hooks = ActiveHooks::from_bits(hooks).unwrap_or(ActiveHooks::empty());
+        for handle in handles {
+            self.set_active_hooks(handle, hooks);
+        }
+    }
+
+    /// Makes this collider a one-way platform.
+    ///
+    /// Contacts with this collider are only kept if their normal, pointing towards the exterior
+    /// of this collider and expressed in its local-space, makes an angle smaller than
+    /// `allowedAngle` with `localNormal`. This is handled natively during contact modification,
+    /// without calling any JS hook.
+    pub fn coSetOnewayPlatform(
+        &mut self,
+        handle: FlatHandle,
+        localNormal: &RawVector,
+        allowedAngle: Real,
+    ) {
+        let handle = utils::collider_handle(handle);
+        self.native_hooks.oneway_platforms.insert(
+            handle,
+            OnewayPlatform {
+                local_normal: localNormal.0,
+                allowed_angle: allowedAngle,
+            },
+        );
+        self.update_native_hooks(handle);
+    }
+
+    /// Removes the one-way platform behavior of this collider.
+    pub fn coRemoveOnewayPlatform(&mut self, handle: FlatHandle) {
+        let handle = utils::collider_handle(handle);
+        self.native_hooks.oneway_platforms.remove(&handle);
+        self.update_native_hooks(handle);
+    }
+
+    /// Sets the minimum approach speed for the contacts of this collider to bounce, overriding
+    /// the restitution threshold of the integration parameters.
+    ///
+    /// Solver contacts with a smaller relative normal velocity get a restitution of zero. If
+    /// both colliders have a threshold, the largest one is used. This is handled natively during
+    /// contact modification, without calling any JS hook.
+    pub fn coSetRestitutionThreshold(&mut self, handle: FlatHandle, threshold: Real) {
+        let handle = utils::collider_handle(handle);
+        self.native_hooks
+            .restitution_thresholds
+            .insert(handle, threshold);
+        self.update_native_hooks(handle);
+    }
+
+    /// Removes the restitution threshold override of this collider.
+    pub fn coRemoveRestitutionThreshold(&mut self, handle: FlatHandle) {
+        let handle = utils::collider_handle(handle);
+        self.native_hooks.restitution_thresholds.remove(&handle);
+        self.update_native_hooks(handle);
+    }
+
+    /// Enables or disables the accumulation of the contact impulses applied to this collider.
+    ///
+    /// Disabling it discards the impulses accumulated so far.
+    pub fn coSetImpulseAccumulationEnabled(&mut self, handle: FlatHandle, enabled: bool) {
+        let handle = utils::collider_handle(handle);
+        if enabled {
+            self.accumulated_impulses
+                .entry(handle)
+                .or_insert(AccumulatedImpulse::zero());
+        } else {
+            self.accumulated_impulses.remove(&handle);
+        }
+    }
+
+    /// Are the contact impulses applied to this collider accumulated?
+    pub fn coIsImpulseAccumulationEnabled(&self, handle: FlatHandle) -> bool {
+        self.accumulated_impulses
+            .contains_key(&utils::collider_handle(handle))
+    }
+
+    /// The sum of the world-space normal impulses applied to this collider since the
+    /// accumulation was enabled or last reset.
+    pub fn coAccumulatedImpulse(&self, handle: FlatHandle) -> RawVector {
+        self.accumulated_impulses
+            .get(&utils::collider_handle(handle))
+            .map(|acc| acc.impulse)
+            .unwrap_or(Vector::zeros())
+            .into()
+    }
+
+    /// The sum of the magnitudes of the normal impulses applied to this collider since the
+    /// accumulation was enabled or last reset.
+    pub fn coAccumulatedNormalImpulse(&self, handle: FlatHandle) -> Real {
+        self.accumulated_impulses
+            .get(&utils::collider_handle(handle))
+            .map(|acc| acc.normal_impulse)
+            .unwrap_or(0.0)
+    }
+
+    /// The sum of the magnitudes of the friction impulses applied to this collider since the
+    /// accumulation was enabled or last reset.
+    pub fn coAccumulatedFrictionImpulse(&self, handle: FlatHandle) -> Real {
+        self.accumulated_impulses
+            .get(&utils::collider_handle(handle))
+            .map(|acc| acc.friction_impulse)
+            .unwrap_or(0.0)
+    }
+
+    /// Resets the contact impulses accumulated for this collider.
+    pub fn coResetAccumulatedImpulse(&mut self, handle: FlatHandle) {
+        if let Some(acc) = self
+            .accumulated_impulses
+            .get_mut(&utils::collider_handle(handle))
+        {
+            *acc = AccumulatedImpulse::zero();
+        }
     }
 
     pub fn coSetActiveEvents(&mut self, handle: FlatHandle, events: u32) {
@@ -940,6 +1226,32 @@ impl RawColliderSet {
         self.map_mut(handle, |co| co.set_active_collision_types(types));
     }
 
+    /// Sets the active collision types of all the given colliders at once.
+    pub fn coSetActiveCollisionTypesBulk(&mut self, handles: Vec<FlatHandle>, types: u16) {
+        let types = ActiveCollisionTypes::from_bits(types).unwrap_or(ActiveCollisionTypes::empty());
+        for handle in handles {
+            self.map_mut(handle, |co| co.set_active_collision_types(types));
+        }
+    }
+
+    /// Enables (if `enabled` is `true`) or disables the given active collision types on all
+    /// the given colliders at once, leaving their other active collision types unchanged.
+    pub fn coToggleActiveCollisionTypesBulk(
+        &mut self,
+        handles: Vec<FlatHandle>,
+        types: u16,
+        enabled: bool,
+    ) {
+        let types = ActiveCollisionTypes::from_bits(types).unwrap_or(ActiveCollisionTypes::empty());
+        for handle in handles {
+            self.map_mut(handle, |co| {
+                let mut current = co.active_collision_types();
+                current.set(types, enabled);
+                co.set_active_collision_types(current)
+            });
+        }
+    }
+
     pub fn coSetShape(&mut self, handle: FlatHandle, shape: &RawShape) {
         self.map_mut(handle, |co| co.set_shape(shape.0.clone()));
     }
@@ -1,8 +1,10 @@
 use crate::dynamics::{RawIslandManager, RawRigidBodySet};
 use crate::geometry::RawShape;
 use crate::math::{RawRotation, RawVector};
+use crate::pipeline::NativeHooks;
 use crate::utils::{self, FlatHandle};
 use rapier::prelude::*;
+use std::collections::{HashMap, HashSet};
 use wasm_bindgen::prelude::*;
 
 // NOTE: this MUST match the same enum on the TS side.
@@ -12,13 +14,146 @@ enum MassPropsMode {
     MassProps,
 }
 
+/// The contact impulses applied to a collider, accumulated over several timesteps.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub(crate) struct AccumulatedImpulse {
+    /// The sum of the world-space normal impulses applied to the collider.
+    pub(crate) impulse: Vector<Real>,
+    /// The sum of the magnitudes of the normal impulses applied to the collider.
+    pub(crate) normal_impulse: Real,
+    /// The sum of the magnitudes of the friction impulses applied to the collider.
+    pub(crate) friction_impulse: Real,
+}
+
+impl AccumulatedImpulse {
+    pub(crate) fn zero() -> Self {
+        Self {
+            impulse: Vector::zeros(),
+            normal_impulse: 0.0,
+            friction_impulse: 0.0,
+        }
+    }
+}
+
+/// A collider set, the step at which each of its colliders was created, the
+/// contact modifications applied natively to its colliders, and the contact impulses
+/// accumulated for the colliders watched by the user.
 #[wasm_bindgen]
-pub struct RawColliderSet(pub(crate) ColliderSet);
+pub struct RawColliderSet {
+    pub(crate) set: ColliderSet,
+    pub(crate) creation_steps: HashMap<ColliderHandle, u32>,
+    pub(crate) native_hooks: NativeHooks,
+    pub(crate) accumulated_impulses: HashMap<ColliderHandle, AccumulatedImpulse>,
+}
+
+impl From<ColliderSet> for RawColliderSet {
+    fn from(set: ColliderSet) -> Self {
+        RawColliderSet {
+            set,
+            creation_steps: HashMap::new(),
+            native_hooks: NativeHooks::default(),
+            accumulated_impulses: HashMap::new(),
+        }
+    }
+}
 
 impl RawColliderSet {
+    /// Removes the records kept for a collider that was removed from the collider set.
+    pub(crate) fn forget(&mut self, handle: &ColliderHandle) {
+        self.creation_steps.remove(handle);
+        self.native_hooks.forget_collider(handle);
+        self.accumulated_impulses.remove(handle);
+    }
+
+    /// Inserts the colliders again into an empty collider set in the given order, attached to
+    /// the rigid-bodies `body_map` maps their parents to, and updates the records kept for them.
+    ///
+    /// Returns the previous handles of the colliders mapped to their new handles.
+    pub(crate) fn rebuild(
+        &mut self,
+        order: &[ColliderHandle],
+        bodies: &mut RigidBodySet,
+        body_map: &HashMap<RigidBodyHandle, RigidBodyHandle>,
+    ) -> HashMap<ColliderHandle, ColliderHandle> {
+        let old = std::mem::replace(&mut self.set, ColliderSet::new());
+        let mut map = HashMap::new();
+        for handle in order {
+            let collider = old[*handle].clone();
+            let copy = match collider.parent() {
+                Some(parent) => self
+                    .set
+                    .insert_with_parent(collider, body_map[&parent], bodies),
+                None => self.set.insert(collider),
+            };
+            map.insert(*handle, copy);
+        }
+
+        utils::remap_keys(&mut self.creation_steps, &map);
+        utils::remap_keys(&mut self.accumulated_impulses, &map);
+        self.native_hooks.remap(&map, body_map);
+        map
+    }
+
+    /// Sets the physics hooks enabled by the user for a collider, keeping the
+    /// `MODIFY_SOLVER_CONTACTS` hook needed by the native contact modifications.
+    pub(crate) fn set_active_hooks(&mut self, handle: FlatHandle, hooks: ActiveHooks) {
+        let handle = utils::collider_handle(handle);
+        let collider = self
+            .set
+            .get_mut(handle)
+            .expect("Invalid Collider reference. It may have been removed from the physics World.");
+        self.native_hooks.set_user_hooks(handle, collider, hooks);
+    }
+
+    /// Updates the physics hooks of a collider after the native contact modifications
+    /// applying to it changed.
+    pub(crate) fn update_native_hooks(&mut self, handle: ColliderHandle) {
+        if let Some(collider) = self.set.get_mut(handle) {
+            self.native_hooks.update_collider_hooks(handle, collider);
+        }
+    }
+
+    /// Sets the restitution velocity threshold of the colliders without their own threshold.
+    ///
+    /// The global threshold only enables the `MODIFY_SOLVER_CONTACTS` hook of the colliders
+    /// with a non-zero restitution, so only their hooks are updated, and only when the
+    /// threshold is enabled or disabled.
+    pub(crate) fn set_restitution_threshold(&mut self, threshold: Real) {
+        let was_enabled = self.native_hooks.restitution_threshold > 0.0;
+        self.native_hooks.restitution_threshold = threshold;
+        if was_enabled != (threshold > 0.0) {
+            let handles: Vec<_> = self.native_hooks.bouncy_colliders.iter().copied().collect();
+            for handle in handles {
+                self.update_native_hooks(handle);
+            }
+        }
+    }
+
+    /// Clears the native contact modifications that only apply to the timestep that just ended,
+    /// and accumulates the contact impulses of the colliders watched by the user.
+    pub(crate) fn end_step(&mut self, narrow_phase: &NarrowPhase) {
+        for handle in std::mem::take(&mut self.native_hooks.reset_warmstart) {
+            self.update_native_hooks(handle);
+        }
+
+        for (handle, acc) in self.accumulated_impulses.iter_mut() {
+            for pair in narrow_phase.contact_pairs_with(*handle) {
+                // The contact normals point towards the exterior of the first collider.
+                let sign = if pair.collider1 == *handle { -1.0 } else { 1.0 };
+                for manifold in &pair.manifolds {
+                    for contact in &manifold.points {
+                        acc.impulse += manifold.data.normal * (contact.data.impulse * sign);
+                        acc.normal_impulse += contact.data.impulse;
+                        acc.friction_impulse += contact.data.tangent_impulse.norm();
+                    }
+                }
+            }
+        }
+    }
+
     pub(crate) fn map<T>(&self, handle: FlatHandle, f: impl FnOnce(&Collider) -> T) -> T {
         let collider = self
-            .0
+            .set
             .get(utils::collider_handle(handle))
             .expect("Invalid Collider reference. It may have been removed from the physics World.");
         f(collider)
@@ -30,7 +165,7 @@ impl RawColliderSet {
         f: impl FnOnce(&mut Collider) -> T,
     ) -> T {
         let collider = self
-            .0
+            .set
             .get_mut(utils::collider_handle(handle))
             .expect("Invalid Collider reference. It may have been removed from the physics World.");
         f(collider)
@@ -42,7 +177,7 @@ impl RawColliderSet {
         handle2: FlatHandle,
         f: impl FnOnce(Option<&mut Collider>, Option<&mut Collider>) -> T,
     ) -> T {
-        let (collider1, collider2) = self.0.get_pair_mut(
+        let (collider1, collider2) = self.set.get_pair_mut(
             utils::collider_handle(handle1),
             utils::collider_handle(handle2),
         );
@@ -122,15 +257,16 @@ impl RawColliderSet {
 
         let collider = builder.build();
 
-        if hasParent {
-            Some(utils::flat_handle(
-                self.0
-                    .insert_with_parent(collider, utils::body_handle(parent), &mut bodies.0)
-                    .0,
-            ))
+        let handle = if hasParent {
+            self.set
+                .insert_with_parent(collider, utils::body_handle(parent), &mut bodies.set)
         } else {
-            Some(utils::flat_handle(self.0.insert(collider).0))
-        }
+            self.set.insert(collider)
+        };
+        self.creation_steps
+            .insert(handle, bodies.lifetimes.num_steps);
+        self.update_native_hooks(handle);
+        Some(utils::flat_handle(handle.0))
     }
 }
 
@@ -138,15 +274,15 @@ impl RawColliderSet {
 impl RawColliderSet {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
-        RawColliderSet(ColliderSet::new())
+        RawColliderSet::from(ColliderSet::new())
     }
 
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.set.len()
     }
 
     pub fn contains(&self, handle: FlatHandle) -> bool {
-        self.0.get(utils::collider_handle(handle)).is_some()
+        self.set.get(utils::collider_handle(handle)).is_some()
     }
 
     #[cfg(feature = "dim2")]
@@ -263,6 +399,113 @@ impl RawColliderSet {
         )
     }
 
+    /// Inserts a copy of the given collider into another collider set.
+    ///
+    /// The copy is attached to the rigid-body `parent` of `targetBodies`, or left without
+    /// parent if `parent` is `None`. Returns the handle of the copy, or `None` if the
+    /// collider doesn't exist.
+    pub fn cloneInto(
+        &self,
+        handle: FlatHandle,
+        target: &mut RawColliderSet,
+        targetBodies: &mut RawRigidBodySet,
+        parent: Option<FlatHandle>,
+    ) -> Option<FlatHandle> {
+        let source = utils::collider_handle(handle);
+        let collider = self.set.get(source)?.clone();
+        let hooks = self.native_hooks.user_hooks(source, &collider);
+        let handle = match parent {
+            Some(parent) => target.set.insert_with_parent(
+                collider,
+                utils::body_handle(parent),
+                &mut targetBodies.set,
+            ),
+            None => target.set.insert(collider),
+        };
+        target
+            .creation_steps
+            .insert(handle, targetBodies.lifetimes.num_steps);
+        self.native_hooks
+            .copy_collider(source, &mut target.native_hooks, handle);
+        target.set_active_hooks(utils::flat_handle(handle.0), hooks);
+        Some(utils::flat_handle(handle.0))
+    }
+
+    /// The state of the given collider, packed in a single array, for inspecting its
+    /// properties in a single call.
+    ///
+    /// The array contains, in this order: the world-space translation, the world-space
+    /// rotation (angle in 2D, quaternion `[x, y, z, w]` in 3D), the friction, the restitution,
+    /// the density, the mass, the packed collision groups, the packed solver groups, whether
+    /// it is a sensor (`0` or `1`), whether it is enabled (`0` or `1`), and the handle of its
+    /// parent rigid-body (`NaN` if it doesn’t have any).
+    pub fn colliderState(&self, handle: FlatHandle) -> Vec<f64> {
+        self.map(handle, |co| {
+            let mut state = Vec::with_capacity(16);
+            utils::push_isometry(&mut state, co.position());
+            state.push(co.material().friction as f64);
+            state.push(co.material().restitution as f64);
+            state.push(co.density() as f64);
+            state.push(co.mass() as f64);
+            state.push(super::pack_interaction_groups(co.collision_groups()) as f64);
+            state.push(super::pack_interaction_groups(co.solver_groups()) as f64);
+            state.push(co.is_sensor() as u32 as f64);
+            state.push(co.is_enabled() as u32 as f64);
+            state.push(
+                co.parent()
+                    .map(|p| utils::flat_handle(p.0))
+                    .unwrap_or(f64::NAN),
+            );
+            state
+        })
+    }
+
+    /// Attaches a collider to a new parent rigid-body, or detaches it if `newParent` is `None`.
+    ///
+    /// The mass properties of the previous and new parents are recomputed immediately. The
+    /// parents can be links of a multibody: the mass matrix of the multibody is rebuilt from
+    /// the mass properties of its links at the beginning of the next timestep.
+    pub fn setParent(
+        &mut self,
+        handle: FlatHandle,
+        newParent: Option<FlatHandle>,
+        bodies: &mut RawRigidBodySet,
+    ) {
+        let handle = utils::collider_handle(handle);
+        let old_parent = self.set.get(handle).and_then(|co| co.parent());
+        let new_parent = newParent.map(utils::body_handle);
+        self.set.set_parent(handle, new_parent, &mut bodies.set);
+
+        for parent in [old_parent, new_parent].iter().flatten() {
+            if let Some(rb) = bodies.set.get_mut(*parent) {
+                rb.recompute_mass_properties_from_colliders(&self.set);
+                rb.wake_up(true);
+            }
+        }
+        self.update_native_hooks(handle);
+    }
+
+    /// Replaces the shape of a collider.
+    ///
+    /// The broad-phase proxy and the mass properties of the collider's parent are updated
+    /// at the beginning of the next timestep. If `preserveContacts` is `true` and the new
+    /// shape has the same type as the previous one, the contacts between matching features
+    /// keep their warmstart impulses, which avoids popping when a shape grows or shrinks
+    /// progressively. Otherwise, the warmstart impulses of the contacts of this collider
+    /// are discarded during the next timestep.
+    pub fn replaceShape(&mut self, handle: FlatHandle, shape: &RawShape, preserveContacts: bool) {
+        let handle = utils::collider_handle(handle);
+        if let Some(collider) = self.set.get_mut(handle) {
+            let same_type = collider.shape().shape_type() == shape.0.shape_type();
+            collider.set_shape(shape.0.clone());
+
+            if !preserveContacts || !same_type {
+                self.native_hooks.reset_warmstart.insert(handle);
+                self.update_native_hooks(handle);
+            }
+        }
+    }
+
     /// Removes a collider from this set and wake-up the rigid-body it is attached to.
     pub fn remove(
         &mut self,
@@ -272,12 +515,48 @@ impl RawColliderSet {
         wakeUp: bool,
     ) {
         let handle = utils::collider_handle(handle);
-        self.0.remove(handle, &mut islands.0, &mut bodies.0, wakeUp);
+        self.set
+            .remove(handle, &mut islands.0, &mut bodies.set, wakeUp);
+        self.forget(&handle);
+    }
+
+    /// Removes several colliders with a single call, waking-up the rigid-bodies they were
+    /// attached to only once.
+    ///
+    /// Apart from the wake-ups, this is equivalent to calling `remove` for each collider.
+    /// Handles of colliders that no longer exist are ignored. As with `remove`, the broad-phase
+    /// proxies of the removed colliders are cleaned up at the beginning of the next timestep.
+    pub fn removeBatch(
+        &mut self,
+        handles: Vec<FlatHandle>,
+        islands: &mut RawIslandManager,
+        bodies: &mut RawRigidBodySet,
+        wakeUp: bool,
+    ) {
+        let mut parents = HashSet::new();
+        for handle in handles {
+            let handle = utils::collider_handle(handle);
+            if let Some(co) = self
+                .set
+                .remove(handle, &mut islands.0, &mut bodies.set, false)
+            {
+                parents.extend(co.parent());
+                self.forget(&handle);
+            }
+        }
+
+        if wakeUp {
+            for parent in parents {
+                if bodies.set.contains(parent) {
+                    islands.0.wake_up(&mut bodies.set, parent, true);
+                }
+            }
+        }
     }
 
     /// Checks if a collider with the given integer handle exists.
     pub fn isHandleValid(&self, handle: FlatHandle) -> bool {
-        self.0.get(utils::collider_handle(handle)).is_some()
+        self.set.get(utils::collider_handle(handle)).is_some()
     }
 
     /// Applies the given JavaScript function to the integer handle of each collider managed by this collider set.
@@ -286,7 +565,7 @@ impl RawColliderSet {
     /// - `f(handle)`: the function to apply to the integer handle of each collider managed by this collider set. Called as `f(handle)`.
     pub fn forEachColliderHandle(&self, f: &js_sys::Function) {
         let this = JsValue::null();
-        for (handle, _) in self.0.iter() {
+        for (handle, _) in self.set.iter() {
             let _ = f.call1(&this, &JsValue::from(utils::flat_handle(handle.0)));
         }
     }
@@ -1,15 +1,21 @@
 //! Structures related to geometry: colliders, shapes, etc.
 
+pub use self::bounding_volume::*;
 pub use self::broad_phase::*;
 pub use self::collider_set::*;
 pub use self::contact::*;
 pub use self::feature::*;
 pub use self::narrow_phase::*;
 pub use self::point::*;
+pub use self::query_batch::*;
 pub use self::ray::*;
 pub use self::shape::*;
 pub use self::toi::*;
+pub use self::trajectory::*;
+#[cfg(feature = "dim3")]
+pub use self::walkable_surfaces::*;
 
+mod bounding_volume;
 mod broad_phase;
 mod collider;
 mod collider_set;
@@ -17,9 +23,13 @@ mod contact;
 mod feature;
 mod narrow_phase;
 mod point;
+mod query_batch;
 mod ray;
 mod shape;
 mod toi;
+mod trajectory;
+#[cfg(feature = "dim3")]
+mod walkable_surfaces;
 
 use rapier::dynamics::CoefficientCombineRule;
 use rapier::geometry::InteractionGroups;
@@ -1,23 +1,90 @@
-use crate::math::RawVector;
-use crate::utils::{self, FlatHandle};
-use rapier::geometry::{ContactManifold, ContactPair, NarrowPhase};
-use rapier::math::Real;
+use crate::dynamics::RawRigidBodySet;
+use crate::geometry::feature::IntoTypeValue;
+use crate::geometry::{RawColliderSet, RawFeatureType};
+use crate::math::{RawRotation, RawVector};
+use crate::utils::{self, FlatHandle, Liveness};
+use rapier::geometry::{ColliderHandle, ContactManifold, ContactPair, NarrowPhase};
+use rapier::math::{Isometry, Real, Vector};
+use rapier::pipeline::ActiveHooks;
+use rapier::utils::SimdBasis;
+use std::collections::HashSet;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
-pub struct RawNarrowPhase(pub(crate) NarrowPhase);
+pub struct RawNarrowPhase {
+    pub(crate) narrow_phase: NarrowPhase,
+    pub(crate) liveness: Liveness,
+}
+
+impl RawNarrowPhase {
+    /// Invalidates the contact pairs and manifolds given to JS so far, before the contacts
+    /// they point to are modified.
+    pub(crate) fn invalidate_borrows(&mut self) {
+        self.liveness.expire();
+        self.liveness = Liveness::new();
+    }
+
+    fn compute_contact_now(
+        &self,
+        colliders: &RawColliderSet,
+        handle1: FlatHandle,
+        pos1: Option<Isometry<Real>>,
+        handle2: FlatHandle,
+        prediction: Real,
+    ) -> Option<RawComputedContactPair> {
+        let collider1 = utils::collider_handle(handle1);
+        let collider2 = utils::collider_handle(handle2);
+        let co1 = colliders.set.get(collider1)?;
+        let co2 = colliders.set.get(collider2)?;
+        let pos1 = pos1.unwrap_or(*co1.position());
+        let pos12 = pos1.inv_mul(co2.position());
+
+        let mut manifolds = vec![];
+        self.narrow_phase
+            .query_dispatcher()
+            .contact_manifolds(
+                &pos12,
+                co1.shape(),
+                co2.shape(),
+                prediction,
+                &mut manifolds,
+                &mut None,
+            )
+            .ok()?;
+
+        for manifold in &mut manifolds {
+            manifold.data.normal = pos1 * manifold.local_n1;
+        }
+
+        Some(RawComputedContactPair {
+            collider1,
+            collider2,
+            manifolds,
+            liveness: Liveness::new(),
+        })
+    }
+}
+
+impl Drop for RawNarrowPhase {
+    fn drop(&mut self) {
+        self.liveness.expire();
+    }
+}
 
 #[wasm_bindgen]
 impl RawNarrowPhase {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
-        RawNarrowPhase(NarrowPhase::new())
+        RawNarrowPhase {
+            narrow_phase: NarrowPhase::new(),
+            liveness: Liveness::new(),
+        }
     }
 
     pub fn contact_pairs_with(&self, handle1: FlatHandle, f: js_sys::Function) {
         let this = JsValue::null();
         let handle1 = utils::collider_handle(handle1);
-        for pair in self.0.contact_pairs_with(handle1) {
+        for pair in self.narrow_phase.contact_pairs_with(handle1) {
             let handle2 = if pair.collider1 == handle1 {
                 utils::flat_handle(pair.collider2.0)
             } else {
@@ -31,15 +98,69 @@ impl RawNarrowPhase {
     pub fn contact_pair(&self, handle1: FlatHandle, handle2: FlatHandle) -> Option<RawContactPair> {
         let handle1 = utils::collider_handle(handle1);
         let handle2 = utils::collider_handle(handle2);
-        self.0
+        self.narrow_phase
             .contact_pair(handle1, handle2)
-            .map(|p| RawContactPair(p as *const ContactPair))
+            .map(|p| RawContactPair(p as *const ContactPair, self.liveness.clone()))
+    }
+
+    /// Applies the given JavaScript function to each contact manifold between the colliders
+    /// attached to two rigid-bodies.
+    ///
+    /// The function is called as `f(manifold, flipped, collider1, collider2)` where `collider1`
+    /// is attached to `body1` and `collider2` is attached to `body2`. If `flipped` is `true`,
+    /// the manifold data is flipped, i.e., its first collider is `collider2`.
+    pub fn contactsBetweenBodies(
+        &self,
+        bodies: &RawRigidBodySet,
+        body1: FlatHandle,
+        body2: FlatHandle,
+        f: &js_sys::Function,
+    ) {
+        let (rb1, rb2) = match (
+            bodies.set.get(utils::body_handle(body1)),
+            bodies.set.get(utils::body_handle(body2)),
+        ) {
+            (Some(rb1), Some(rb2)) => (rb1, rb2),
+            _ => return,
+        };
+
+        let this = JsValue::null();
+        let colliders2: HashSet<_> = rb2.colliders().iter().copied().collect();
+        for collider1 in rb1.colliders() {
+            for pair in self.narrow_phase.contact_pairs_with(*collider1) {
+                let flipped = pair.collider1 != *collider1;
+                let collider2 = if flipped {
+                    pair.collider1
+                } else {
+                    pair.collider2
+                };
+
+                if !colliders2.contains(&collider2) {
+                    continue;
+                }
+
+                for manifold in &pair.manifolds {
+                    let liveness = Liveness::new();
+                    let args = js_sys::Array::of4(
+                        &JsValue::from(RawContactManifold(
+                            manifold as *const ContactManifold,
+                            liveness.clone(),
+                        )),
+                        &JsValue::from_bool(flipped),
+                        &JsValue::from(utils::flat_handle(collider1.0)),
+                        &JsValue::from(utils::flat_handle(collider2.0)),
+                    );
+                    let _ = f.apply(&this, &args);
+                    liveness.expire();
+                }
+            }
+        }
     }
 
     pub fn intersection_pairs_with(&self, handle1: FlatHandle, f: js_sys::Function) {
         let this = JsValue::null();
         let handle1 = utils::collider_handle(handle1);
-        for (h1, h2, inter) in self.0.intersection_pairs_with(handle1) {
+        for (h1, h2, inter) in self.narrow_phase.intersection_pairs_with(handle1) {
             if inter {
                 let handle2 = if h1 == handle1 {
                     utils::flat_handle(h2.0)
@@ -55,163 +176,332 @@ impl RawNarrowPhase {
     pub fn intersection_pair(&self, handle1: FlatHandle, handle2: FlatHandle) -> bool {
         let handle1 = utils::collider_handle(handle1);
         let handle2 = utils::collider_handle(handle2);
-        self.0.intersection_pair(handle1, handle2) == Some(true)
+        self.narrow_phase.intersection_pair(handle1, handle2) == Some(true)
+    }
+
+    /// Computes the contact manifolds between two colliders right now, independently
+    /// from the contact pairs maintained by this narrow-phase.
+    ///
+    /// Returns `None` if one of the colliders doesn't exist, or if contacts between
+    /// their shapes aren't supported.
+    pub fn computeContactNow(
+        &self,
+        colliders: &RawColliderSet,
+        handle1: FlatHandle,
+        handle2: FlatHandle,
+        prediction: Real,
+    ) -> Option<RawComputedContactPair> {
+        self.compute_contact_now(colliders, handle1, None, handle2, prediction)
+    }
+
+    /// Computes the contact manifolds between two colliders right now, as if the first
+    /// collider was at the given world-space position.
+    pub fn computeContactNowAt(
+        &self,
+        colliders: &RawColliderSet,
+        handle1: FlatHandle,
+        pos1: &RawVector,
+        rot1: &RawRotation,
+        handle2: FlatHandle,
+        prediction: Real,
+    ) -> Option<RawComputedContactPair> {
+        let pos1 = Isometry::from_parts(pos1.0.into(), rot1.0);
+        self.compute_contact_now(colliders, handle1, Some(pos1), handle2, prediction)
+    }
+
+    /// The number of contact pairs for which the combined active hooks of both
+    /// colliders contain all the given `hooks` flags.
+    pub fn num_contact_pairs_with_active_hooks(
+        &self,
+        colliders: &RawColliderSet,
+        hooks: u32,
+    ) -> usize {
+        let hooks = ActiveHooks::from_bits(hooks).unwrap_or(ActiveHooks::empty());
+        let user_hooks = |handle: ColliderHandle| {
+            colliders
+                .set
+                .get(handle)
+                .map(|co| colliders.native_hooks.user_hooks(handle, co))
+                .unwrap_or(ActiveHooks::empty())
+        };
+        self.narrow_phase
+            .contact_pairs()
+            .filter(|pair| {
+                (user_hooks(pair.collider1) | user_hooks(pair.collider2)).contains(hooks)
+            })
+            .count()
     }
 }
 
 #[wasm_bindgen]
-pub struct RawContactPair(*const ContactPair);
+pub struct RawContactPair(*const ContactPair, Liveness);
 #[wasm_bindgen]
-pub struct RawContactManifold(pub(crate) *const ContactManifold);
+pub struct RawContactManifold(pub(crate) *const ContactManifold, pub(crate) Liveness);
 
 // SAFETY: the use of a raw pointer is very unsafe.
 //         We need this because wasm-bindgen doesn't support
-//         lifetimes. So for the moment, we have to make sure
-//         that our TypeScript wrapper properly free the pair
-//         before the user has a chance to invalidate this pointer.
+//         lifetimes. The `Liveness` flag is expired by the owner
+//         of the pointed data before the pointer becomes dangling,
+//         so the accessors throw a JS error instead of using it.
+impl RawContactPair {
+    fn pair(&self) -> &ContactPair {
+        self.1.check();
+        unsafe { &*self.0 }
+    }
+}
+
+impl RawContactManifold {
+    fn manifold(&self) -> &ContactManifold {
+        self.1.check();
+        unsafe { &*self.0 }
+    }
+}
+
 #[wasm_bindgen]
 impl RawContactPair {
     pub fn collider1(&self) -> FlatHandle {
-        unsafe { utils::flat_handle((*self.0).collider1.0) }
+        utils::flat_handle(self.pair().collider1.0)
     }
 
     pub fn collider2(&self) -> FlatHandle {
-        unsafe { utils::flat_handle((*self.0).collider2.0) }
+        utils::flat_handle(self.pair().collider2.0)
     }
 
     pub fn numContactManifolds(&self) -> usize {
-        unsafe { (*self.0).manifolds.len() }
+        self.pair().manifolds.len()
     }
     pub fn contactManifold(&self, i: usize) -> Option<RawContactManifold> {
-        unsafe {
-            (&(*self.0).manifolds)
-                .get(i)
-                .map(|m| RawContactManifold(m as *const ContactManifold))
-        }
+        self.pair()
+            .manifolds
+            .get(i)
+            .map(|m| RawContactManifold(m as *const ContactManifold, self.1.clone()))
+    }
+}
+
+/// The contact manifolds computed on demand by `RawNarrowPhase::computeContactNow`.
+///
+/// Unlike a `RawContactPair`, this owns its manifolds: the `RawContactManifold`
+/// returned by `contactManifold` remain valid until this is freed.
+#[wasm_bindgen]
+pub struct RawComputedContactPair {
+    collider1: ColliderHandle,
+    collider2: ColliderHandle,
+    manifolds: Vec<ContactManifold>,
+    liveness: Liveness,
+}
+
+impl Drop for RawComputedContactPair {
+    fn drop(&mut self) {
+        self.liveness.expire();
+    }
+}
+
+#[wasm_bindgen]
+impl RawComputedContactPair {
+    pub fn collider1(&self) -> FlatHandle {
+        utils::flat_handle(self.collider1.0)
+    }
+
+    pub fn collider2(&self) -> FlatHandle {
+        utils::flat_handle(self.collider2.0)
+    }
+
+    pub fn numContactManifolds(&self) -> usize {
+        self.manifolds.len()
+    }
+
+    pub fn contactManifold(&self, i: usize) -> Option<RawContactManifold> {
+        self.manifolds
+            .get(i)
+            .map(|m| RawContactManifold(m as *const ContactManifold, self.liveness.clone()))
     }
 }
 
 #[wasm_bindgen]
 impl RawContactManifold {
     pub fn normal(&self) -> RawVector {
-        unsafe { RawVector((*self.0).data.normal) }
+        RawVector(self.manifold().data.normal)
     }
 
     // pub fn user_data(&self) -> u32 {
-    //     unsafe { (*self.0).data.user_data }
+    //     self.manifold().data.user_data
     // }
 
     pub fn local_n1(&self) -> RawVector {
-        unsafe { (*self.0).local_n1.into() }
+        self.manifold().local_n1.into()
     }
 
     pub fn local_n2(&self) -> RawVector {
-        unsafe { (*self.0).local_n2.into() }
+        self.manifold().local_n2.into()
     }
 
     pub fn subshape1(&self) -> u32 {
-        unsafe { (*self.0).subshape1 }
+        self.manifold().subshape1
     }
 
     pub fn subshape2(&self) -> u32 {
-        unsafe { (*self.0).subshape2 }
+        self.manifold().subshape2
     }
 
     pub fn num_contacts(&self) -> usize {
-        unsafe { (*self.0).points.len() }
+        self.manifold().points.len()
     }
 
     pub fn contact_local_p1(&self, i: usize) -> Option<RawVector> {
-        unsafe { (&(*self.0).points).get(i).map(|c| c.local_p1.coords.into()) }
+        self.manifold()
+            .points
+            .get(i)
+            .map(|c| c.local_p1.coords.into())
     }
 
     pub fn contact_local_p2(&self, i: usize) -> Option<RawVector> {
-        unsafe { (&(*self.0).points).get(i).map(|c| c.local_p2.coords.into()) }
+        self.manifold()
+            .points
+            .get(i)
+            .map(|c| c.local_p2.coords.into())
     }
 
     pub fn contact_dist(&self, i: usize) -> Real {
-        unsafe { (&(*self.0).points).get(i).map(|c| c.dist).unwrap_or(0.0) }
+        self.manifold().points.get(i).map(|c| c.dist).unwrap_or(0.0)
     }
 
     pub fn contact_fid1(&self, i: usize) -> u32 {
-        unsafe { (&(*self.0).points).get(i).map(|c| c.fid1.0).unwrap_or(0) }
+        self.manifold().points.get(i).map(|c| c.fid1.0).unwrap_or(0)
     }
 
     pub fn contact_fid2(&self, i: usize) -> u32 {
-        unsafe { (&(*self.0).points).get(i).map(|c| c.fid2.0).unwrap_or(0) }
+        self.manifold().points.get(i).map(|c| c.fid2.0).unwrap_or(0)
+    }
+
+    /// The type of the feature of the first sub-shape that generated the i-th contact.
+    pub fn contact_feature_type1(&self, i: usize) -> RawFeatureType {
+        self.manifold()
+            .points
+            .get(i)
+            .map(|c| c.fid1.unpack().into_type())
+            .unwrap_or(RawFeatureType::Unknown)
+    }
+
+    /// The index of the feature of the first sub-shape that generated the i-th contact.
+    pub fn contact_feature_index1(&self, i: usize) -> Option<u32> {
+        self.manifold()
+            .points
+            .get(i)
+            .and_then(|c| c.fid1.unpack().into_value())
+    }
+
+    /// The type of the feature of the second sub-shape that generated the i-th contact.
+    pub fn contact_feature_type2(&self, i: usize) -> RawFeatureType {
+        self.manifold()
+            .points
+            .get(i)
+            .map(|c| c.fid2.unpack().into_type())
+            .unwrap_or(RawFeatureType::Unknown)
+    }
+
+    /// The index of the feature of the second sub-shape that generated the i-th contact.
+    pub fn contact_feature_index2(&self, i: usize) -> Option<u32> {
+        self.manifold()
+            .points
+            .get(i)
+            .and_then(|c| c.fid2.unpack().into_value())
     }
 
     pub fn contact_impulse(&self, i: usize) -> Real {
-        unsafe {
-            (&(*self.0).points)
-                .get(i)
-                .map(|c| c.data.impulse)
-                .unwrap_or(0.0)
-        }
+        self.manifold()
+            .points
+            .get(i)
+            .map(|c| c.data.impulse)
+            .unwrap_or(0.0)
     }
 
     #[cfg(feature = "dim2")]
     pub fn contact_tangent_impulse(&self, i: usize) -> Real {
-        unsafe {
-            (&(*self.0).points)
-                .get(i)
-                .map(|c| c.data.tangent_impulse.x)
-                .unwrap_or(0.0)
-        }
+        self.manifold()
+            .points
+            .get(i)
+            .map(|c| c.data.tangent_impulse.x)
+            .unwrap_or(0.0)
     }
 
     #[cfg(feature = "dim3")]
     pub fn contact_tangent_impulse_x(&self, i: usize) -> Real {
-        unsafe {
-            (&(*self.0).points)
-                .get(i)
-                .map(|c| c.data.tangent_impulse.x)
-                .unwrap_or(0.0)
-        }
+        self.manifold()
+            .points
+            .get(i)
+            .map(|c| c.data.tangent_impulse.x)
+            .unwrap_or(0.0)
     }
 
     #[cfg(feature = "dim3")]
     pub fn contact_tangent_impulse_y(&self, i: usize) -> Real {
-        unsafe {
-            (&(*self.0).points)
-                .get(i)
-                .map(|c| c.data.tangent_impulse.y)
-                .unwrap_or(0.0)
-        }
+        self.manifold()
+            .points
+            .get(i)
+            .map(|c| c.data.tangent_impulse.y)
+            .unwrap_or(0.0)
     }
 
     pub fn num_solver_contacts(&self) -> usize {
-        unsafe { (*self.0).data.solver_contacts.len() }
+        self.manifold().data.solver_contacts.len()
     }
 
     pub fn solver_contact_point(&self, i: usize) -> Option<RawVector> {
-        unsafe {
-            (&(*self.0).data)
-                .solver_contacts
-                .get(i)
-                .map(|c| c.point.coords.into())
-        }
+        self.manifold()
+            .data
+            .solver_contacts
+            .get(i)
+            .map(|c| c.point.coords.into())
     }
 
     pub fn solver_contact_dist(&self, i: usize) -> Real {
-        unsafe {
-            (&(*self.0).data)
-                .solver_contacts
-                .get(i)
-                .map(|c| c.dist)
-                .unwrap_or(0.0)
-        }
+        self.manifold()
+            .data
+            .solver_contacts
+            .get(i)
+            .map(|c| c.dist)
+            .unwrap_or(0.0)
     }
 
     pub fn solver_contact_friction(&self, i: usize) -> Real {
-        unsafe { (&(*self.0).data).solver_contacts[i].friction }
+        self.manifold().data.solver_contacts[i].friction
     }
 
     pub fn solver_contact_restitution(&self, i: usize) -> Real {
-        unsafe { (&(*self.0).data).solver_contacts[i].restitution }
+        self.manifold().data.solver_contacts[i].restitution
     }
 
     pub fn solver_contact_tangent_velocity(&self, i: usize) -> RawVector {
-        unsafe { (&(*self.0).data).solver_contacts[i].tangent_velocity.into() }
+        self.manifold().data.solver_contacts[i]
+            .tangent_velocity
+            .into()
+    }
+
+    /// The velocity of the second rigid-body relative to the first one, at the location
+    /// of the i-th solver contact.
+    ///
+    /// Colliders without parent are considered static.
+    pub fn relative_velocity_at(&self, i: usize, bodies: &RawRigidBodySet) -> Option<RawVector> {
+        let data = &self.manifold().data;
+        let point = data.solver_contacts.get(i)?.point;
+        let velocity_at = |handle: Option<_>| {
+            handle
+                .and_then(|h| bodies.set.get(h))
+                .map(|rb| rb.velocity_at_point(&point))
+                .unwrap_or_else(Vector::zeros)
+        };
+
+        Some((velocity_at(data.rigid_body2) - velocity_at(data.rigid_body1)).into())
+    }
+
+    /// The first friction direction used by the constraints solver for this manifold.
+    pub fn tangent1(&self) -> RawVector {
+        (-self.manifold().data.normal).orthonormal_basis()[0].into()
+    }
+
+    /// The second friction direction used by the constraints solver for this manifold.
+    #[cfg(feature = "dim3")]
+    pub fn tangent2(&self) -> RawVector {
+        (-self.manifold().data.normal).orthonormal_basis()[1].into()
     }
 }
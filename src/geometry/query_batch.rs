@@ -0,0 +1,298 @@
+use crate::dynamics::RawRigidBodySet;
+use crate::geometry::{
+    RawBroadPhase, RawColliderSet, RawNarrowPhase, RawRayColliderIntersection, RawShape,
+};
+use crate::utils::{self, FlatHandle};
+use rapier::geometry::{ColliderHandle, Ray, RayIntersection, SharedShape};
+use rapier::math::{Isometry, Point, Real, Rotation, Vector, DIM};
+use rapier::pipeline::{QueryFilter, QueryFilterFlags};
+use wasm_bindgen::prelude::*;
+
+/// Limits the amount of work done by one call to the `resume` method of a batch.
+struct QueryBudget {
+    max_queries: Option<usize>,
+    deadline: Option<f64>,
+    num_queries: usize,
+}
+
+impl QueryBudget {
+    fn new(max_queries: Option<usize>, max_time_ms: Option<f64>) -> Self {
+        Self {
+            max_queries,
+            deadline: max_time_ms.map(|t| js_sys::Date::now() + t),
+            num_queries: 0,
+        }
+    }
+
+    /// Records one more query, and checks if the budget allows another one.
+    ///
+    /// At least one query is always allowed so that each call makes some progress.
+    fn try_consume(&mut self) -> bool {
+        if self.num_queries > 0 {
+            if self
+                .max_queries
+                .map(|max| self.num_queries >= max)
+                .unwrap_or(false)
+            {
+                return false;
+            }
+            if self
+                .deadline
+                .map(|d| js_sys::Date::now() >= d)
+                .unwrap_or(false)
+            {
+                return false;
+            }
+        }
+
+        self.num_queries += 1;
+        true
+    }
+}
+
+/// A batch of ray-casts that can be spread across several frames.
+///
+/// Each call to `resume` casts the next rays of the batch, until the given budget is
+/// exhausted. The cursor, i.e., the index of the next ray to cast, is kept by the batch.
+#[wasm_bindgen]
+pub struct RawRayCastBatch {
+    rays: Vec<Ray>,
+    max_toi: Real,
+    solid: bool,
+    hits: Vec<Option<(ColliderHandle, RayIntersection)>>,
+}
+
+#[wasm_bindgen]
+impl RawRayCastBatch {
+    /// Creates a batch of rays from their origins and directions, packed as flat arrays
+    /// of coordinates.
+    #[wasm_bindgen(constructor)]
+    pub fn new(origins: Vec<f32>, dirs: Vec<f32>, maxToi: Real, solid: bool) -> Self {
+        let rays = origins
+            .chunks_exact(DIM)
+            .zip(dirs.chunks_exact(DIM))
+            .map(|(orig, dir)| Ray::new(Point::from_slice(orig), Vector::from_column_slice(dir)))
+            .collect();
+
+        Self {
+            rays,
+            max_toi: maxToi,
+            solid,
+            hits: vec![],
+        }
+    }
+
+    /// The number of rays in this batch.
+    pub fn len(&self) -> usize {
+        self.rays.len()
+    }
+
+    /// The index of the next ray to cast.
+    pub fn cursor(&self) -> usize {
+        self.hits.len()
+    }
+
+    /// Have all the rays of this batch been cast?
+    pub fn isComplete(&self) -> bool {
+        self.hits.len() == self.rays.len()
+    }
+
+    /// Forgets all the results, so the batch can be cast again from its first ray.
+    pub fn reset(&mut self) {
+        self.hits.clear();
+    }
+
+    /// Casts the next rays of this batch, until `maxRays` rays were cast or `maxTimeMs`
+    /// milliseconds elapsed. At least one ray is cast by each call.
+    ///
+    /// Returns `true` if all the rays of the batch have been cast.
+    pub fn resume(
+        &mut self,
+        broad_phase: &RawBroadPhase,
+        narrow_phase: &RawNarrowPhase,
+        bodies: &RawRigidBodySet,
+        colliders: &RawColliderSet,
+        maxRays: Option<usize>,
+        maxTimeMs: Option<f64>,
+        filter_flags: u32,
+        filter_groups: Option<u32>,
+        filter_exclude_collider: Option<FlatHandle>,
+        filter_exclude_rigid_body: Option<FlatHandle>,
+        filter_predicate: &js_sys::Function,
+    ) -> bool {
+        broad_phase.with_counted_filter(filter_groups, filter_predicate, |predicate| {
+            let query_filter = QueryFilter {
+                flags: QueryFilterFlags::from_bits(filter_flags)
+                    .unwrap_or(QueryFilterFlags::empty()),
+                groups: None,
+                exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
+                exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
+                predicate,
+            };
+
+            let query_pipeline = broad_phase.broad_phase.as_query_pipeline(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &bodies.set,
+                &colliders.set,
+                query_filter,
+            );
+
+            let mut budget = QueryBudget::new(maxRays, maxTimeMs);
+            while self.hits.len() < self.rays.len() && budget.try_consume() {
+                let ray = &self.rays[self.hits.len()];
+                let hit = query_pipeline.cast_ray_and_get_normal(ray, self.max_toi, self.solid);
+                self.hits.push(hit);
+            }
+        });
+
+        self.isComplete()
+    }
+
+    /// The hit of the `i`-th ray, if it has been cast and hit a collider.
+    pub fn hit(&self, i: usize) -> Option<RawRayColliderIntersection> {
+        let (handle, inter) = (*self.hits.get(i)?)?;
+        Some(RawRayColliderIntersection { handle, inter })
+    }
+
+    /// The results of all the rays cast so far, packed as `[handle, toi, normal]` for each ray.
+    ///
+    /// The `handle` and `toi` of a ray which didn't hit anything are `NaN`, and its normal is zero.
+    pub fn hitsPacked(&self) -> Vec<f64> {
+        let mut out = Vec::with_capacity(self.hits.len() * (2 + DIM));
+        for hit in &self.hits {
+            match hit {
+                Some((handle, inter)) => {
+                    out.push(utils::flat_handle(handle.0));
+                    out.push(inter.time_of_impact as f64);
+                    out.extend(inter.normal.iter().map(|x| *x as f64));
+                }
+                None => {
+                    out.push(f64::NAN);
+                    out.push(f64::NAN);
+                    out.extend(std::iter::repeat(0.0).take(DIM));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// A batch of intersection tests between a shape, at several poses, and the colliders, that
+/// can be spread across several frames.
+///
+/// Each call to `resume` tests the next poses of the batch, until the given budget is
+/// exhausted. The cursor, i.e., the index of the next pose to test, is kept by the batch.
+#[wasm_bindgen]
+pub struct RawShapeIntersectionBatch {
+    shape: SharedShape,
+    poses: Vec<Isometry<Real>>,
+    intersections: Vec<Vec<ColliderHandle>>,
+}
+
+#[wasm_bindgen]
+impl RawShapeIntersectionBatch {
+    /// Creates a batch of intersection tests from the positions and rotations of the shape,
+    /// packed as flat arrays.
+    ///
+    /// Each rotation is an angle in 2D, and the coordinates `[x, y, z, w]` of a quaternion in 3D.
+    #[wasm_bindgen(constructor)]
+    pub fn new(shape: &RawShape, positions: Vec<f32>, rotations: Vec<f32>) -> Self {
+        #[cfg(feature = "dim2")]
+        let rotations = rotations.iter().map(|angle| Rotation::new(*angle));
+        #[cfg(feature = "dim3")]
+        let rotations = rotations
+            .chunks_exact(4)
+            .map(|q| Rotation::new_normalize(na::Quaternion::new(q[3], q[0], q[1], q[2])));
+
+        let poses = positions
+            .chunks_exact(DIM)
+            .zip(rotations)
+            .map(|(pos, rot)| Isometry::from_parts(Vector::from_column_slice(pos).into(), rot))
+            .collect();
+
+        Self {
+            shape: shape.0.clone(),
+            poses,
+            intersections: vec![],
+        }
+    }
+
+    /// The number of poses in this batch.
+    pub fn len(&self) -> usize {
+        self.poses.len()
+    }
+
+    /// The index of the next pose to test.
+    pub fn cursor(&self) -> usize {
+        self.intersections.len()
+    }
+
+    /// Have all the poses of this batch been tested?
+    pub fn isComplete(&self) -> bool {
+        self.intersections.len() == self.poses.len()
+    }
+
+    /// Forgets all the results, so the batch can be tested again from its first pose.
+    pub fn reset(&mut self) {
+        self.intersections.clear();
+    }
+
+    /// Tests the next poses of this batch, until `maxTests` poses were tested or `maxTimeMs`
+    /// milliseconds elapsed. At least one pose is tested by each call.
+    ///
+    /// Returns `true` if all the poses of the batch have been tested.
+    pub fn resume(
+        &mut self,
+        broad_phase: &RawBroadPhase,
+        narrow_phase: &RawNarrowPhase,
+        bodies: &RawRigidBodySet,
+        colliders: &RawColliderSet,
+        maxTests: Option<usize>,
+        maxTimeMs: Option<f64>,
+        filter_flags: u32,
+        filter_groups: Option<u32>,
+        filter_exclude_collider: Option<FlatHandle>,
+        filter_exclude_rigid_body: Option<FlatHandle>,
+        filter_predicate: &js_sys::Function,
+    ) -> bool {
+        broad_phase.with_counted_filter(filter_groups, filter_predicate, |predicate| {
+            let query_filter = QueryFilter {
+                flags: QueryFilterFlags::from_bits(filter_flags)
+                    .unwrap_or(QueryFilterFlags::empty()),
+                groups: None,
+                exclude_collider: filter_exclude_collider.map(crate::utils::collider_handle),
+                exclude_rigid_body: filter_exclude_rigid_body.map(crate::utils::body_handle),
+                predicate,
+            };
+
+            let query_pipeline = broad_phase.broad_phase.as_query_pipeline(
+                narrow_phase.narrow_phase.query_dispatcher(),
+                &bodies.set,
+                &colliders.set,
+                query_filter,
+            );
+
+            let mut budget = QueryBudget::new(maxTests, maxTimeMs);
+            while self.intersections.len() < self.poses.len() && budget.try_consume() {
+                let pos = self.poses[self.intersections.len()];
+                let handles = query_pipeline
+                    .intersect_shape(pos, &*self.shape)
+                    .map(|(handle, _)| handle)
+                    .collect();
+                self.intersections.push(handles);
+            }
+        });
+
+        self.isComplete()
+    }
+
+    /// The handles of the colliders intersecting the shape at the `i`-th pose.
+    ///
+    /// Empty if this pose hasn't been tested yet.
+    pub fn intersections(&self, i: usize) -> Vec<FlatHandle> {
+        self.intersections
+            .get(i)
+            .map(|handles| handles.iter().map(|h| utils::flat_handle(h.0)).collect())
+            .unwrap_or_default()
+    }
+}
@@ -1,14 +1,20 @@
-use crate::geometry::{RawPointProjection, RawRayIntersection, RawShapeCastHit, RawShapeContact};
+use crate::geometry::{
+    RawAabb, RawBoundingSphere, RawPointProjection, RawRayIntersection, RawShapeCastHit,
+    RawShapeContact,
+};
 use crate::math::{RawRotation, RawVector};
 #[cfg(feature = "dim3")]
 use na::DMatrix;
 #[cfg(feature = "dim2")]
 use na::DVector;
 use na::Unit;
-use rapier::geometry::{Shape, SharedShape, TriMeshFlags};
+#[cfg(feature = "dim3")]
+use na::{Quaternion, UnitQuaternion};
+use rapier::geometry::{Shape, ShapeType, SharedShape, TriMeshFlags};
 use rapier::math::{Isometry, Point, Real, Vector, DIM};
 use rapier::parry::query;
 use rapier::parry::query::{Ray, ShapeCastOptions};
+use std::sync::Arc;
 use wasm_bindgen::prelude::*;
 
 pub trait SharedShapeUtility {
@@ -383,10 +389,19 @@ impl RawShape {
         SharedShape::round_convex_polyline(vertices, borderRadius).map(|s| Self(s))
     }
 
+    /// Creates a convex polyhedron from the given convex mesh, without recomputing its convex-hull.
+    ///
+    /// If `validate` is `true`, `None` is returned if the mesh isn't actually convex, or if
+    /// some of its triangles are degenerate.
     #[cfg(feature = "dim3")]
-    pub fn convexMesh(vertices: Vec<f32>, indices: Vec<u32>) -> Option<RawShape> {
-        let vertices = vertices.chunks(DIM).map(|v| Point::from_slice(v)).collect();
+    pub fn convexMesh(vertices: Vec<f32>, indices: Vec<u32>, validate: bool) -> Option<RawShape> {
+        let vertices: Vec<_> = vertices.chunks(DIM).map(|v| Point::from_slice(v)).collect();
         let indices: Vec<_> = indices.chunks(3).map(|v| [v[0], v[1], v[2]]).collect();
+
+        if validate && !is_valid_convex_mesh(&vertices, &indices) {
+            return None;
+        }
+
         SharedShape::convex_mesh(vertices, &indices).map(|s| Self(s))
     }
 
@@ -401,6 +416,71 @@ impl RawShape {
         SharedShape::round_convex_mesh(vertices, &indices, borderRadius).map(|s| Self(s))
     }
 
+    /// Creates a compound shape from existing shapes.
+    ///
+    /// The sub-shapes are shared with the compound instead of being copied. The poses are
+    /// packed as `[tx, ty, angle]` in 2D and `[tx, ty, tz, qx, qy, qz, qw]` in 3D for each
+    /// sub-shape. Returns `None` if the compound is empty, if the number of poses doesn't
+    /// match the number of shapes, or if one of the shapes is itself a composite shape.
+    pub fn compound(shapes: Vec<RawShape>, poses: Vec<f32>) -> Option<RawShape> {
+        #[cfg(feature = "dim2")]
+        const POSE_LEN: usize = 3;
+        #[cfg(feature = "dim3")]
+        const POSE_LEN: usize = 7;
+
+        if shapes.is_empty() || shapes.len() * POSE_LEN != poses.len() {
+            return None;
+        }
+
+        let mut parts = Vec::with_capacity(shapes.len());
+        for (shape, pose) in shapes.into_iter().zip(poses.chunks(POSE_LEN)) {
+            match shape.0.shape_type() {
+                ShapeType::Compound
+                | ShapeType::TriMesh
+                | ShapeType::Polyline
+                | ShapeType::HeightField
+                | ShapeType::Voxels => return None,
+                _ => {}
+            }
+
+            #[cfg(feature = "dim2")]
+            let pos = Isometry::new(Vector::new(pose[0], pose[1]), pose[2]);
+            #[cfg(feature = "dim3")]
+            let pos = Isometry::from_parts(
+                Vector::new(pose[0], pose[1], pose[2]).into(),
+                UnitQuaternion::from_quaternion(Quaternion::new(
+                    pose[6], pose[3], pose[4], pose[5],
+                )),
+            );
+
+            parts.push((pos, shape.0));
+        }
+
+        Some(Self(SharedShape::compound(parts)))
+    }
+
+    /// The axis-aligned bounding box of this shape in its local-space.
+    pub fn localAabb(&self) -> RawAabb {
+        RawAabb(self.0.compute_local_aabb())
+    }
+
+    /// The bounding sphere of this shape in its local-space.
+    pub fn localBoundingSphere(&self) -> RawBoundingSphere {
+        RawBoundingSphere(self.0.compute_local_bounding_sphere())
+    }
+
+    /// Creates a new reference to this shape, without copying its internal data.
+    pub fn shallowClone(&self) -> RawShape {
+        Self(self.0.clone())
+    }
+
+    /// The number of references (including this one) to the internal data of this shape.
+    ///
+    /// This counts all the colliders and compound shapes sharing this shape.
+    pub fn shapeRefCount(&self) -> usize {
+        Arc::strong_count(&self.0 .0)
+    }
+
     pub fn castShape(
         &self,
         shapePos1: &RawVector,
@@ -525,3 +605,40 @@ impl RawShape {
             .castRayAndGetNormal(&pos, rayOrig.0.into(), rayDir.0.into(), maxToi, solid)
     }
 }
+
+/// Checks that the given triangle mesh is convex, i.e., that none of its vertices lie in
+/// front of any of its (non-degenerate) triangles.
+#[cfg(feature = "dim3")]
+fn is_valid_convex_mesh(vertices: &[Point<Real>], indices: &[[u32; 3]]) -> bool {
+    if vertices.len() < 4 || indices.len() < 4 {
+        return false;
+    }
+
+    let mut mins = vertices[0];
+    let mut maxs = vertices[0];
+    for pt in vertices {
+        mins = mins.inf(pt);
+        maxs = maxs.sup(pt);
+    }
+    let eps = (maxs - mins).norm() * 1.0e-4;
+
+    for idx in indices {
+        if idx.iter().any(|i| *i as usize >= vertices.len()) {
+            return false;
+        }
+
+        let a = vertices[idx[0] as usize];
+        let b = vertices[idx[1] as usize];
+        let c = vertices[idx[2] as usize];
+        let normal = match (b - a).cross(&(c - a)).try_normalize(Real::EPSILON) {
+            Some(normal) => normal,
+            None => return false,
+        };
+
+        if vertices.iter().any(|pt| normal.dot(&(pt - a)) > eps) {
+            return false;
+        }
+    }
+
+    true
+}
@@ -0,0 +1,61 @@
+use crate::math::RawVector;
+use crate::utils::{self, FlatHandle};
+use rapier::geometry::ColliderHandle;
+use rapier::math::{Real, Vector, DIM};
+use wasm_bindgen::prelude::*;
+
+pub(crate) struct TrajectoryHit {
+    pub(crate) handle: ColliderHandle,
+    pub(crate) time: Real,
+    pub(crate) position: Vector<Real>,
+    pub(crate) normal: Vector<Real>,
+}
+
+/// The sampled path of a projectile, and the first collider it hits, if any.
+#[wasm_bindgen]
+pub struct RawPredictedTrajectory {
+    pub(crate) points: Vec<Real>,
+    pub(crate) hit: Option<TrajectoryHit>,
+}
+
+#[wasm_bindgen]
+impl RawPredictedTrajectory {
+    /// The number of points sampled along the path.
+    pub fn numPoints(&self) -> usize {
+        self.points.len() / DIM
+    }
+
+    /// The sampled points, packed as a flat array of coordinates.
+    ///
+    /// The first point is the starting position of the projectile. If the projectile
+    /// hit something, the last point is its position at the time of impact.
+    pub fn points(&self) -> Vec<Real> {
+        self.points.clone()
+    }
+
+    pub fn hasHit(&self) -> bool {
+        self.hit.is_some()
+    }
+
+    /// The handle of the collider hit by the projectile.
+    pub fn hitColliderHandle(&self) -> Option<FlatHandle> {
+        self.hit
+            .as_ref()
+            .map(|hit| utils::flat_handle(hit.handle.0))
+    }
+
+    /// The time, since the beginning of the trajectory, when the projectile hit a collider.
+    pub fn hitTime(&self) -> Option<Real> {
+        self.hit.as_ref().map(|hit| hit.time)
+    }
+
+    /// The position of the projectile at the time of impact.
+    pub fn hitPosition(&self) -> Option<RawVector> {
+        self.hit.as_ref().map(|hit| hit.position.into())
+    }
+
+    /// The world-space normal of the collider hit by the projectile, at the time of impact.
+    pub fn hitNormal(&self) -> Option<RawVector> {
+        self.hit.as_ref().map(|hit| hit.normal.into())
+    }
+}
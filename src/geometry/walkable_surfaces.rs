@@ -0,0 +1,169 @@
+use crate::dynamics::RawRigidBodySet;
+use crate::geometry::RawColliderSet;
+use crate::math::RawVector;
+use rapier::geometry::Collider;
+use rapier::math::{Isometry, Point, Real, Vector};
+use rapier::parry::shape::{Shape, TypedShape};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// The triangles of the fixed colliders that are flat enough to be walked on, e.g., to be
+/// given to a navigation mesh baker.
+#[wasm_bindgen]
+pub struct RawWalkableSurfaces {
+    vertices: Vec<Real>,
+    indices: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl RawWalkableSurfaces {
+    /// The world-space vertices of the walkable triangles, packed as a flat array of coordinates.
+    pub fn vertices(&self) -> Vec<Real> {
+        self.vertices.clone()
+    }
+
+    /// The indices of the vertices of each walkable triangle, packed as a flat array.
+    pub fn indices(&self) -> Vec<u32> {
+        self.indices.clone()
+    }
+
+    pub fn numTriangles(&self) -> usize {
+        self.indices.len() / 3
+    }
+}
+
+/// Accumulates walkable triangles, welding their vertices.
+struct WalkableSurfacesBuilder {
+    up: Vector<Real>,
+    min_cos_slope: Real,
+    weld_distance: Real,
+    subdivisions: u32,
+    vertex_ids: HashMap<[i64; 3], u32>,
+    result: RawWalkableSurfaces,
+}
+
+impl WalkableSurfacesBuilder {
+    fn vertex_id(&mut self, pt: Point<Real>) -> u32 {
+        let key: [i64; 3] = if self.weld_distance > 0.0 {
+            pt.coords
+                .map(|x| (x / self.weld_distance).round() as i64)
+                .into()
+        } else {
+            pt.coords.map(|x| x.to_bits() as i64).into()
+        };
+
+        let vertices = &mut self.result.vertices;
+        *self.vertex_ids.entry(key).or_insert_with(|| {
+            vertices.extend_from_slice(pt.coords.as_slice());
+            (vertices.len() / 3 - 1) as u32
+        })
+    }
+
+    fn add_triangles(
+        &mut self,
+        pos: &Isometry<Real>,
+        vertices: &[Point<Real>],
+        indices: &[[u32; 3]],
+    ) {
+        for idx in indices {
+            let [a, b, c] = idx.map(|i| pos * vertices[i as usize]);
+            let normal = (b - a).cross(&(c - a));
+            let area = normal.norm();
+            if area <= Real::EPSILON || normal.dot(&self.up) < self.min_cos_slope * area {
+                continue;
+            }
+
+            let ids = [a, b, c].map(|pt| self.vertex_id(pt));
+            if ids[0] != ids[1] && ids[1] != ids[2] && ids[2] != ids[0] {
+                self.result.indices.extend_from_slice(&ids);
+            }
+        }
+    }
+
+    fn add_shape(&mut self, pos: &Isometry<Real>, shape: &dyn Shape) {
+        let n = self.subdivisions;
+        let (vertices, indices) = match shape.as_typed_shape() {
+            TypedShape::Cuboid(s) => s.to_trimesh(),
+            TypedShape::RoundCuboid(s) => s.inner_shape.to_trimesh(),
+            TypedShape::Ball(s) => s.to_trimesh(n, n / 2),
+            TypedShape::Capsule(s) => s.to_trimesh(n, n / 2),
+            TypedShape::Cylinder(s) => s.to_trimesh(n),
+            TypedShape::RoundCylinder(s) => s.inner_shape.to_trimesh(n),
+            TypedShape::Cone(s) => s.to_trimesh(n),
+            TypedShape::RoundCone(s) => s.inner_shape.to_trimesh(n),
+            TypedShape::ConvexPolyhedron(s) => s.to_trimesh(),
+            TypedShape::RoundConvexPolyhedron(s) => s.inner_shape.to_trimesh(),
+            TypedShape::HeightField(s) => s.to_trimesh(),
+            TypedShape::Triangle(s) => (vec![s.a, s.b, s.c], vec![[0, 1, 2]]),
+            TypedShape::RoundTriangle(s) => {
+                let t = &s.inner_shape;
+                (vec![t.a, t.b, t.c], vec![[0, 1, 2]])
+            }
+            TypedShape::TriMesh(s) => {
+                self.add_triangles(pos, s.vertices(), s.indices());
+                return;
+            }
+            TypedShape::Compound(s) => {
+                for (sub_pos, sub_shape) in s.shapes() {
+                    self.add_shape(&(pos * sub_pos), &**sub_shape);
+                }
+                return;
+            }
+            // Infinite, non-solid, or unsupported shapes.
+            _ => return,
+        };
+
+        self.add_triangles(pos, &vertices, &indices);
+    }
+}
+
+#[wasm_bindgen]
+impl RawColliderSet {
+    /// Extracts the triangles of the fixed colliders whose slope is small enough to be walked on.
+    ///
+    /// Colliders without parent, or attached to a fixed rigid-body, are tessellated, except
+    /// sensors and disabled colliders. A triangle is walkable if the angle between its normal
+    /// (given by its counter-clockwise winding) and `up` is at most `maxSlopeAngle`. The
+    /// triangles of all the colliders are merged into a single mesh, welding the vertices closer
+    /// than about `weldDistance` (only identical vertices are welded if it is zero).
+    ///
+    /// # Parameters
+    /// - `up`: the unit vector pointing upward.
+    /// - `maxSlopeAngle`: the steepest walkable slope, in radians.
+    /// - `weldDistance`: the size of the grid cells used to weld the vertices.
+    /// - `subdivisions`: the number of subdivisions used to tessellate curved shapes.
+    pub fn walkableSurfaces(
+        &self,
+        bodies: &RawRigidBodySet,
+        up: &RawVector,
+        maxSlopeAngle: Real,
+        weldDistance: Real,
+        subdivisions: u32,
+    ) -> RawWalkableSurfaces {
+        let mut builder = WalkableSurfacesBuilder {
+            up: up.0,
+            min_cos_slope: maxSlopeAngle.cos(),
+            weld_distance: weldDistance,
+            subdivisions: subdivisions.max(3),
+            vertex_ids: HashMap::new(),
+            result: RawWalkableSurfaces {
+                vertices: vec![],
+                indices: vec![],
+            },
+        };
+
+        let is_static = |co: &Collider| {
+            co.parent()
+                .map(|parent| bodies.set.get(parent).map(|rb| rb.is_fixed()) == Some(true))
+                .unwrap_or(true)
+        };
+
+        for (_, co) in self.set.iter() {
+            if co.is_enabled() && !co.is_sensor() && is_static(co) {
+                builder.add_shape(co.position(), co.shape());
+            }
+        }
+
+        builder.result
+    }
+}
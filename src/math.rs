@@ -4,9 +4,8 @@
 use js_sys::Float32Array;
 #[cfg(feature = "dim3")]
 use na::{Quaternion, Unit};
-#[cfg(feature = "dim3")]
-use rapier::math::Real;
-use rapier::math::{Point, Rotation, Vector};
+use rapier::dynamics::MassProperties;
+use rapier::math::{Isometry, Point, Real, Rotation, Vector};
 #[cfg(feature = "dim3")]
 use rapier::parry::utils::SdpMatrix3;
 use wasm_bindgen::prelude::*;
@@ -264,3 +263,117 @@ impl RawSdpMatrix3 {
         output
     }
 }
+
+#[wasm_bindgen]
+#[derive(Copy, Clone)]
+/// The mass-properties of a rigid-body, a collider, or any part of them.
+pub struct RawMassProperties(pub(crate) MassProperties);
+
+#[wasm_bindgen]
+impl RawMassProperties {
+    /// The mass-properties of a ball with the given density and radius.
+    pub fn ball(density: Real, radius: Real) -> Self {
+        Self(MassProperties::from_ball(density, radius))
+    }
+
+    /// The mass-properties of a cuboid with the given density and half-extents.
+    pub fn cuboid(density: Real, halfExtents: &RawVector) -> Self {
+        Self(MassProperties::from_cuboid(density, halfExtents.0))
+    }
+
+    /// The mass-properties of a capsule aligned with the `y` axis, with the given density,
+    /// half-height, and radius.
+    pub fn capsule(density: Real, halfHeight: Real, radius: Real) -> Self {
+        let a = Point::from(Vector::y() * -halfHeight);
+        let b = Point::from(Vector::y() * halfHeight);
+        Self(MassProperties::from_capsule(density, a, b, radius))
+    }
+
+    /// The mass.
+    pub fn mass(&self) -> Real {
+        self.0.mass()
+    }
+
+    /// The center-of-mass, in the local-space of these mass-properties.
+    pub fn localCom(&self) -> RawVector {
+        RawVector(self.0.local_com.coords)
+    }
+
+    /// These mass-properties, expressed in the frame obtained after applying the given
+    /// translation and rotation.
+    pub fn transformBy(&self, translation: &RawVector, rotation: &RawRotation) -> Self {
+        let pose = Isometry::from_parts(translation.0.into(), rotation.0);
+        Self(self.0.transform_by(&pose))
+    }
+
+    /// The mass-properties of the union of the parts described by `self` and `other`.
+    pub fn add(&self, other: &RawMassProperties) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+#[wasm_bindgen]
+#[cfg(feature = "dim2")]
+impl RawMassProperties {
+    #[wasm_bindgen(constructor)]
+    pub fn new(centerOfMass: &RawVector, mass: Real, principalInertia: Real) -> Self {
+        Self(MassProperties::new(
+            centerOfMass.0.into(),
+            mass,
+            principalInertia,
+        ))
+    }
+
+    /// The angular inertia along the principal inertia axes, relative to the center-of-mass.
+    pub fn principalInertia(&self) -> Real {
+        self.0.principal_inertia()
+    }
+}
+
+#[wasm_bindgen]
+#[cfg(feature = "dim3")]
+impl RawMassProperties {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        centerOfMass: &RawVector,
+        mass: Real,
+        principalInertia: &RawVector,
+        principalInertiaFrame: &RawRotation,
+    ) -> Self {
+        Self(MassProperties::with_principal_inertia_frame(
+            centerOfMass.0.into(),
+            mass,
+            principalInertia.0,
+            principalInertiaFrame.0,
+        ))
+    }
+
+    /// The mass-properties of a cylinder aligned with the `y` axis, with the given density,
+    /// half-height, and radius.
+    pub fn cylinder(density: Real, halfHeight: Real, radius: Real) -> Self {
+        Self(MassProperties::from_cylinder(density, halfHeight, radius))
+    }
+
+    /// The mass-properties of a cone aligned with the `y` axis, with the given density,
+    /// half-height, and radius.
+    pub fn cone(density: Real, halfHeight: Real, radius: Real) -> Self {
+        Self(MassProperties::from_cone(density, halfHeight, radius))
+    }
+
+    /// The angular inertia along the principal inertia axes, relative to the center-of-mass.
+    pub fn principalInertia(&self) -> RawVector {
+        RawVector(self.0.principal_inertia())
+    }
+
+    /// The local-space rotation of the principal inertia axes.
+    pub fn principalInertiaFrame(&self) -> RawRotation {
+        RawRotation(self.0.principal_inertia_local_frame)
+    }
+
+    /// The angular inertia tensor relative to the center-of-mass, in the local-space of these
+    /// mass-properties.
+    pub fn angularInertia(&self) -> RawSdpMatrix3 {
+        let m = self.0.reconstruct_inertia_matrix();
+        RawSdpMatrix3(SdpMatrix3::new(m.m11, m.m12, m.m13, m.m22, m.m23, m.m33))
+    }
+}
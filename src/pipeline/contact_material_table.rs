@@ -0,0 +1,108 @@
+use crate::utils::{self, FlatHandle};
+use rapier::geometry::{ColliderHandle, ContactForceEvent};
+use rapier::math::Real;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// A table resolving a tag for each contact force event from the materials of
+/// the two colliders involved and the magnitude of the contact force.
+#[wasm_bindgen]
+pub struct RawContactMaterialTable {
+    materials: HashMap<ColliderHandle, u32>,
+    // For each (sorted) pair of materials, the lower bounds on the force magnitude
+    // of each bucket (in increasing order), and the tag of each bucket.
+    tags: HashMap<(u32, u32), Vec<(Real, u32)>>,
+}
+
+impl RawContactMaterialTable {
+    /// Replaces the handles of the colliders by the ones they are mapped to, forgetting the
+    /// materials of the colliders that aren't mapped.
+    pub(crate) fn remap(&mut self, colliders: &HashMap<ColliderHandle, ColliderHandle>) {
+        utils::remap_keys(&mut self.materials, colliders);
+    }
+
+    pub(crate) fn tag(&self, event: &ContactForceEvent) -> u32 {
+        let material1 = match self.materials.get(&event.collider1) {
+            Some(material) => *material,
+            None => return 0,
+        };
+        let material2 = match self.materials.get(&event.collider2) {
+            Some(material) => *material,
+            None => return 0,
+        };
+        let key = (material1.min(material2), material1.max(material2));
+
+        self.tags
+            .get(&key)
+            .and_then(|buckets| {
+                buckets
+                    .iter()
+                    .rev()
+                    .find(|(threshold, _)| event.total_force_magnitude >= *threshold)
+            })
+            .map(|(_, tag)| *tag)
+            .unwrap_or(0)
+    }
+}
+
+#[wasm_bindgen]
+impl RawContactMaterialTable {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            materials: HashMap::new(),
+            tags: HashMap::new(),
+        }
+    }
+
+    /// Sets the material identifier of the given collider.
+    pub fn setColliderMaterial(&mut self, collider: FlatHandle, material: u32) {
+        self.materials
+            .insert(utils::collider_handle(collider), material);
+    }
+
+    /// The material identifier of the given collider, if one was set.
+    pub fn colliderMaterial(&self, collider: FlatHandle) -> Option<u32> {
+        self.materials
+            .get(&utils::collider_handle(collider))
+            .copied()
+    }
+
+    /// Removes the material identifier of the given collider.
+    pub fn removeColliderMaterial(&mut self, collider: FlatHandle) {
+        self.materials.remove(&utils::collider_handle(collider));
+    }
+
+    /// Sets the tags given to the contact force events between colliders with the two given
+    /// materials.
+    ///
+    /// The tag `tags[i]` is given to events with a total force magnitude greater than or equal
+    /// to `thresholds[i]`, and smaller than the next threshold. Events with a force magnitude
+    /// smaller than all the thresholds are given the tag `0`. Returns `false` (and does nothing)
+    /// if both arrays don't have the same length.
+    pub fn setTags(
+        &mut self,
+        material1: u32,
+        material2: u32,
+        thresholds: Vec<Real>,
+        tags: Vec<u32>,
+    ) -> bool {
+        if thresholds.len() != tags.len() {
+            return false;
+        }
+
+        let mut buckets: Vec<_> = thresholds.into_iter().zip(tags).collect();
+        buckets.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let key = (material1.min(material2), material1.max(material2));
+        self.tags.insert(key, buckets);
+        true
+    }
+
+    /// Removes the tags given to the contact force events between colliders with the two
+    /// given materials.
+    pub fn removeTags(&mut self, material1: u32, material2: u32) {
+        let key = (material1.min(material2), material1.max(material2));
+        self.tags.remove(&key);
+    }
+}
@@ -64,19 +64,19 @@ impl RawDebugRenderPipeline {
                     exclude_rigid_body: None,
                     predicate,
                 },
-                bodies: &bodies.0,
-                colliders: &colliders.0,
+                bodies: &bodies.set,
+                colliders: &colliders.set,
                 vertices: &mut self.vertices,
                 colors: &mut self.colors,
             };
 
             self.raw.render(
                 &mut backend,
-                &bodies.0,
-                &colliders.0,
-                &impulse_joints.0,
+                &bodies.set,
+                &colliders.set,
+                &impulse_joints.set,
                 &multibody_joints.0,
-                &narrow_phase.0,
+                &narrow_phase.narrow_phase,
             )
         })
     }
@@ -1,39 +1,306 @@
+use crate::dynamics::RawRigidBodySet;
+use crate::geometry::RawColliderSet;
 use crate::math::RawVector;
+use crate::pipeline::RawContactMaterialTable;
 use crate::utils;
 use crate::utils::FlatHandle;
-use rapier::geometry::{CollisionEvent, ContactForceEvent};
-use rapier::pipeline::ChannelEventCollector;
+use rapier::dynamics::{IntegrationParameters, IslandManager, RigidBodyHandle, RigidBodySet};
+use rapier::geometry::{ColliderSet, CollisionEvent, ContactForceEvent, ContactPair};
+use rapier::math::{Isometry, Real};
+use rapier::pipeline::{ChannelEventCollector, EventHandler};
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::sync::mpsc::Receiver;
 use wasm_bindgen::prelude::*;
 
 /// A structure responsible for collecting events generated
 /// by the physics engine.
+///
+/// Each collision and contact force event is given a sequence number once received from
+/// the physics pipeline. Sequence numbers are shared by both kinds of events, and increase
+/// monotonically over the lifetime of the queue (they are not reset by drains). Within one
+/// step, the collision events are numbered before the contact force events. Coalesced
+/// collision events leave gaps in the sequence. Events are always drained by increasing
+/// sequence number.
 #[wasm_bindgen]
 pub struct RawEventQueue {
     pub(crate) collector: ChannelEventCollector,
     collision_events: Receiver<CollisionEvent>,
     contact_force_events: Receiver<ContactForceEvent>,
     pub(crate) auto_drain: bool,
+    // The collision events received since the last drain, the index of the
+    // step (since the last drain) and of the CCD substep they were generated at,
+    // and their sequence number.
+    pending_collision_events: Vec<(CollisionEvent, u32, u32, u32)>,
+    step_index: u32,
+    // The contact force events received since the last drain, the index of the
+    // step (since the last drain) they were generated at, and their sequence number.
+    pending_contact_force_events: Vec<(ContactForceEvent, u32, u32)>,
+    contact_force_step_index: u32,
+    // The sequence number of the next event received.
+    next_sequence_number: u32,
+    coalesce_collision_events: bool,
+    // The rigid-bodies that fell asleep (`true`) or woke up (`false`) since the last drain.
+    sleep_events: Vec<(RigidBodyHandle, bool)>,
 }
 
+impl RawEventQueue {
+    /// Moves the events received from the physics pipeline to the pending events, and
+    /// gives them their sequence numbers.
+    ///
+    /// The collision events are received before the contact force events, matching the order
+    /// they are generated in by the physics pipeline. `substeps` are the indices of the CCD
+    /// substeps that generated the collision events, in the same order.
+    fn receive_events(&mut self, substeps: &[u32]) {
+        let mut substeps = substeps.iter();
+        while let Ok(event) = self.collision_events.try_recv() {
+            let substep = substeps.next().copied().unwrap_or(0);
+            if self.coalesce_collision_events {
+                // Remove the opposite event received since the last drain for the same
+                // colliders, if any, instead of reporting both events. Since collisions are
+                // detected again after each CCD substep, this also happens within one step.
+                let opposite = self.pending_collision_events.iter().rposition(|(e, ..)| {
+                    e.started() != event.started()
+                        && ((e.collider1(), e.collider2())
+                            == (event.collider1(), event.collider2())
+                            || (e.collider1(), e.collider2())
+                                == (event.collider2(), event.collider1()))
+                });
+                if let Some(i) = opposite {
+                    self.pending_collision_events.remove(i);
+                    continue;
+                }
+            }
+
+            let seq = self.take_sequence_number();
+            self.pending_collision_events
+                .push((event, self.step_index, substep, seq));
+        }
+
+        while let Ok(event) = self.contact_force_events.try_recv() {
+            let seq = self.take_sequence_number();
+            self.pending_contact_force_events
+                .push((event, self.contact_force_step_index, seq));
+        }
+    }
+
+    fn take_sequence_number(&mut self) -> u32 {
+        let seq = self.next_sequence_number;
+        self.next_sequence_number = self.next_sequence_number.wrapping_add(1);
+        seq
+    }
+
+    /// An event handler for one step of the physics pipeline, recording the CCD substep of
+    /// each collision event.
+    pub(crate) fn substep_collector<'a>(
+        &'a self,
+        params: &IntegrationParameters,
+        islands: &IslandManager,
+        bodies: &RigidBodySet,
+    ) -> SubstepEventCollector<'a> {
+        let mut tracker = SubstepTracker {
+            enabled: params.max_ccd_substeps > 1,
+            ..SubstepTracker::default()
+        };
+        if tracker.enabled {
+            tracker.bodies = islands
+                .active_bodies()
+                .iter()
+                .filter_map(|h| bodies.get(*h).map(|rb| (*h, rb)))
+                .filter(|(_, rb)| rb.is_ccd_enabled())
+                .map(|(h, rb)| (h, *rb.position()))
+                .collect();
+        }
+
+        SubstepEventCollector {
+            collector: &self.collector,
+            tracker: RefCell::new(tracker),
+        }
+    }
+
+    /// Records the events generated by one step of the physics pipeline, given the CCD
+    /// substeps of its collision events.
+    pub(crate) fn end_step(&mut self, substeps: &[u32]) {
+        self.receive_events(substeps);
+        self.step_index += 1;
+        self.contact_force_step_index += 1;
+    }
+
+    /// Records the rigid-bodies that fell asleep or woke up during one step of the physics
+    /// pipeline, given the set of active rigid-bodies before that step.
+    ///
+    /// The rigid-bodies inserted since the previous step aren't reported as woken up.
+    pub(crate) fn record_sleep_events(
+        &mut self,
+        active_before: &HashSet<RigidBodyHandle>,
+        islands: &IslandManager,
+        bodies: &RawRigidBodySet,
+    ) {
+        let active_after = islands.active_bodies();
+        let lifetimes = &bodies.lifetimes;
+
+        for handle in active_after {
+            let is_new = lifetimes.creation_steps.get(handle) == Some(&lifetimes.num_steps);
+            if !active_before.contains(handle) && !is_new {
+                self.sleep_events.push((*handle, false));
+            }
+        }
+
+        for handle in active_before {
+            if bodies.set.get(*handle).map(|rb| rb.is_sleeping()) == Some(true) {
+                self.sleep_events.push((*handle, true));
+            }
+        }
+    }
+
+    fn take_collision_events(&mut self) -> Vec<(CollisionEvent, u32, u32, u32)> {
+        self.receive_events(&[]);
+        self.step_index = 0;
+        std::mem::take(&mut self.pending_collision_events)
+    }
+
+    fn take_contact_force_events(&mut self) -> Vec<(ContactForceEvent, u32, u32)> {
+        self.receive_events(&[]);
+        self.contact_force_step_index = 0;
+        std::mem::take(&mut self.pending_contact_force_events)
+    }
+}
+
+/// Tells apart the CCD substeps of one step of the physics pipeline.
+///
+/// Collisions are detected at the beginning of the step, then again after each CCD substep but
+/// the last one. The event handler isn't told which detection generated an event, so the
+/// substeps are told apart by the motion of the active CCD-enabled rigid-bodies, and of the
+/// rigid-bodies involved in the previous events of the step: more than one substep is only
+/// run if a CCD-enabled rigid-body would otherwise tunnel through a collider, and that
+/// rigid-body moves during the substep.
+#[derive(Default)]
+struct SubstepTracker {
+    // Whether the step can run several substeps.
+    enabled: bool,
+    // The index of the substep of the last event.
+    substep: u32,
+    // The rigid-bodies telling the substeps apart, and their positions at the last event.
+    bodies: Vec<(RigidBodyHandle, Isometry<Real>)>,
+    // The substep of each collision event of the step, in the order they were generated.
+    collision_substeps: Vec<u32>,
+}
+
+impl SubstepTracker {
+    /// The index of the substep generating the given event.
+    fn substep(
+        &mut self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        event: &CollisionEvent,
+    ) -> u32 {
+        if !self.enabled {
+            return 0;
+        }
+
+        let moved = self
+            .bodies
+            .iter()
+            .any(|(handle, pos)| bodies.get(*handle).is_some_and(|rb| rb.position() != pos));
+        if moved {
+            self.substep += 1;
+            for (handle, pos) in &mut self.bodies {
+                if let Some(rb) = bodies.get(*handle) {
+                    *pos = *rb.position();
+                }
+            }
+        }
+
+        for collider in [event.collider1(), event.collider2()] {
+            if let Some(handle) = colliders.get(collider).and_then(|co| co.parent()) {
+                if !self.bodies.iter().any(|(tracked, _)| *tracked == handle) {
+                    if let Some(rb) = bodies.get(handle) {
+                        self.bodies.push((handle, *rb.position()));
+                    }
+                }
+            }
+        }
+
+        self.substep
+    }
+}
+
+/// The event handler given to the physics pipeline by `RawPhysicsPipeline::stepWithEvents`,
+/// forwarding the events to an event queue.
+pub(crate) struct SubstepEventCollector<'a> {
+    collector: &'a ChannelEventCollector,
+    tracker: RefCell<SubstepTracker>,
+}
+
+impl<'a> SubstepEventCollector<'a> {
+    /// The CCD substep of each collision event handled, in the order they were handled.
+    pub(crate) fn into_collision_substeps(self) -> Vec<u32> {
+        self.tracker.into_inner().collision_substeps
+    }
+}
+
+impl<'a> EventHandler for SubstepEventCollector<'a> {
+    fn handle_collision_event(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        event: CollisionEvent,
+        contact_pair: Option<&ContactPair>,
+    ) {
+        let mut tracker = self.tracker.borrow_mut();
+        let substep = tracker.substep(bodies, colliders, &event);
+        tracker.collision_substeps.push(substep);
+        self.collector
+            .handle_collision_event(bodies, colliders, event, contact_pair);
+    }
+
+    fn handle_contact_force_event(
+        &self,
+        dt: Real,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        contact_pair: &ContactPair,
+        total_force_magnitude: Real,
+    ) {
+        self.collector.handle_contact_force_event(
+            dt,
+            bodies,
+            colliders,
+            contact_pair,
+            total_force_magnitude,
+        );
+    }
+}
+
+// The event handler is only used on the thread running the physics pipeline.
+unsafe impl<'a> Send for SubstepEventCollector<'a> {}
+unsafe impl<'a> Sync for SubstepEventCollector<'a> {}
+
+/// A contact force event, the tag resolved by a contact material table, the index of the
+/// step it was generated at, and its sequence number.
 #[wasm_bindgen]
-pub struct RawContactForceEvent(ContactForceEvent);
+pub struct RawContactForceEvent {
+    event: ContactForceEvent,
+    material_tag: u32,
+    step_index: u32,
+    sequence_number: u32,
+}
 
 #[wasm_bindgen]
 impl RawContactForceEvent {
     /// The first collider involved in the contact.
     pub fn collider1(&self) -> FlatHandle {
-        crate::utils::flat_handle(self.0.collider1.0)
+        crate::utils::flat_handle(self.event.collider1.0)
     }
 
     /// The second collider involved in the contact.
     pub fn collider2(&self) -> FlatHandle {
-        crate::utils::flat_handle(self.0.collider2.0)
+        crate::utils::flat_handle(self.event.collider2.0)
     }
 
     /// The sum of all the forces between the two colliders.
     pub fn total_force(&self) -> RawVector {
-        RawVector(self.0.total_force)
+        RawVector(self.event.total_force)
     }
 
     /// The sum of the magnitudes of each force between the two colliders.
@@ -42,17 +309,34 @@ impl RawContactForceEvent {
     /// Here we are summing the magnitude of all the forces, instead of taking
     /// the magnitude of their sum.
     pub fn total_force_magnitude(&self) -> f32 {
-        self.0.total_force_magnitude
+        self.event.total_force_magnitude
     }
 
     /// The world-space (unit) direction of the force with strongest magnitude.
     pub fn max_force_direction(&self) -> RawVector {
-        RawVector(self.0.max_force_direction)
+        RawVector(self.event.max_force_direction)
     }
 
     /// The magnitude of the largest force at a contact point of this contact pair.
     pub fn max_force_magnitude(&self) -> f32 {
-        self.0.max_force_magnitude
+        self.event.max_force_magnitude
+    }
+
+    /// The tag resolved by the contact material table given when draining this event,
+    /// or `0` if no table was given or none of its entries matched.
+    pub fn material_tag(&self) -> u32 {
+        self.material_tag
+    }
+
+    /// The index of the step, since the last drain of the contact force events, that
+    /// generated this event.
+    pub fn step_index(&self) -> u32 {
+        self.step_index
+    }
+
+    /// The sequence number of this event, see `RawEventQueue`.
+    pub fn sequence_number(&self) -> u32 {
+        self.sequence_number
     }
 }
 
@@ -87,54 +371,194 @@ impl RawEventQueue {
             collision_events: collision_channel.1,
             contact_force_events: contact_force_channel.1,
             auto_drain: autoDrain,
+            pending_collision_events: vec![],
+            step_index: 0,
+            pending_contact_force_events: vec![],
+            contact_force_step_index: 0,
+            next_sequence_number: 0,
+            coalesce_collision_events: false,
+            sleep_events: vec![],
         }
     }
 
+    /// Sets whether a collision that starts and stops (or stops and starts again) between two
+    /// drains of this queue is reported as two events, or removed from the queue.
+    ///
+    /// Collisions are detected again after each CCD substep, so this coalesces the events of
+    /// different substeps of a single step, whether the queue is automatically drained or not.
+    /// If it isn't, coalescing also spans all the steps since the last drain.
+    pub fn setCoalesceCollisionEvents(&mut self, coalesce: bool) {
+        self.coalesce_collision_events = coalesce;
+    }
+
+    /// Whether collisions that start and stop, or stop and start again, between two drains of
+    /// this queue are removed.
+    pub fn coalesceCollisionEvents(&self) -> bool {
+        self.coalesce_collision_events
+    }
+
     /// Applies the given javascript closure on each collision event of this collector, then clear
     /// the internal collision event buffer.
     ///
     /// # Parameters
-    /// - `f(handle1, handle2, started)`:  JavaScript closure applied to each collision event. The
-    /// closure should take three arguments: two integers representing the handles of the colliders
-    /// involved in the collision, and a boolean indicating if the collision started (true) or stopped
-    /// (false).
+    /// - `f(handle1, handle2, started, stepIndex, seq, substepIndex)`:  JavaScript closure applied
+    /// to each collision event. The closure should take six arguments: two integers representing
+    /// the handles of the colliders involved in the collision, a boolean indicating if the
+    /// collision started (true) or stopped (false), the index of the step, since the last drain,
+    /// that generated the event, the sequence number of the event, and the index of the CCD
+    /// substep, within its step, that generated the event (see `SubstepTracker`).
     pub fn drainCollisionEvents(&mut self, f: &js_sys::Function) {
         let this = JsValue::null();
-        while let Ok(event) = self.collision_events.try_recv() {
-            match event {
-                CollisionEvent::Started(co1, co2, _) => {
-                    let h1 = utils::flat_handle(co1.0);
-                    let h2 = utils::flat_handle(co2.0);
-                    let _ = f.call3(
-                        &this,
-                        &JsValue::from(h1),
-                        &JsValue::from(h2),
-                        &JsValue::from_bool(true),
-                    );
-                }
-                CollisionEvent::Stopped(co1, co2, _) => {
-                    let h1 = utils::flat_handle(co1.0);
-                    let h2 = utils::flat_handle(co2.0);
-                    let _ = f.call3(
-                        &this,
-                        &JsValue::from(h1),
-                        &JsValue::from(h2),
-                        &JsValue::from_bool(false),
-                    );
+        for (event, step_index, substep_index, seq) in self.take_collision_events() {
+            let args = js_sys::Array::of5(
+                &JsValue::from(utils::flat_handle(event.collider1().0)),
+                &JsValue::from(utils::flat_handle(event.collider2().0)),
+                &JsValue::from_bool(event.started()),
+                &JsValue::from(step_index),
+                &JsValue::from(seq),
+            );
+            args.push(&JsValue::from(substep_index));
+            let _ = f.apply(&this, &args);
+        }
+    }
+
+    /// Applies the given JavaScript function to each collision event, with additional information
+    /// about the colliders involved, then clears the collision event buffer.
+    ///
+    /// The function is called as
+    /// `f(handle1, handle2, started, sensor1, sensor2, body1, body2, removed, stepIndex, seq,
+    /// substepIndex)`.
+    /// If only one of the two colliders is a sensor, it is always given as the first collider.
+    /// `body1` and `body2` are the handles of the colliders' parent rigid-bodies, or `null` if
+    /// they have none. The sensor flags are only set if the event involved a sensor, and
+    /// `removed` tells if the event was caused by the removal of a collider. The parents of
+    /// colliders removed from `colliders` are reported as `null`. If the sensor of the event
+    /// was removed or is no longer a sensor, the sensor flag is set on the removed collider,
+    /// or on the first one.
+    pub fn drainCollisionEventsWithColliderInfo(
+        &mut self,
+        colliders: &RawColliderSet,
+        f: &js_sys::Function,
+    ) {
+        let this = JsValue::null();
+        for (event, step_index, substep_index, seq) in self.take_collision_events() {
+            let mut handles = [event.collider1(), event.collider2()];
+            let current = handles.map(|h| colliders.set.get(h));
+            let mut sensors = [false; 2];
+            if event.sensor() {
+                // The event flags only tell that at least one of the colliders was a sensor
+                // when the event was generated.
+                sensors = current.map(|co| co.is_some_and(|co| co.is_sensor()));
+                if !sensors[0] && !sensors[1] {
+                    // The sensor was removed, or is no longer a sensor.
+                    let removed_second = current[0].is_some() && current[1].is_none();
+                    sensors[removed_second as usize] = true;
                 }
             }
+            let mut parents = current.map(|co| co.and_then(|co| co.parent()));
+
+            if !sensors[0] && sensors[1] {
+                handles.swap(0, 1);
+                sensors.swap(0, 1);
+                parents.swap(0, 1);
+            }
+
+            let body = |parent: Option<RigidBodyHandle>| {
+                parent
+                    .map(|h| JsValue::from(utils::flat_handle(h.0)))
+                    .unwrap_or(JsValue::NULL)
+            };
+
+            let args = js_sys::Array::new();
+            args.push(&JsValue::from(utils::flat_handle(handles[0].0)));
+            args.push(&JsValue::from(utils::flat_handle(handles[1].0)));
+            args.push(&JsValue::from_bool(event.started()));
+            args.push(&JsValue::from_bool(sensors[0]));
+            args.push(&JsValue::from_bool(sensors[1]));
+            args.push(&body(parents[0]));
+            args.push(&body(parents[1]));
+            args.push(&JsValue::from_bool(event.removed()));
+            args.push(&JsValue::from(step_index));
+            args.push(&JsValue::from(seq));
+            args.push(&JsValue::from(substep_index));
+            let _ = f.apply(&this, &args);
         }
     }
 
     pub fn drainContactForceEvents(&mut self, f: &js_sys::Function) {
         let this = JsValue::null();
-        while let Ok(event) = self.contact_force_events.try_recv() {
-            let _ = f.call1(&this, &JsValue::from(RawContactForceEvent(event)));
+        for (event, step_index, seq) in self.take_contact_force_events() {
+            let event = RawContactForceEvent {
+                event,
+                material_tag: 0,
+                step_index,
+                sequence_number: seq,
+            };
+            let _ = f.call1(&this, &JsValue::from(event));
+        }
+    }
+
+    /// Applies the given JavaScript function to each contact force event, tagged using the
+    /// given contact material table, then clears the contact force event buffer.
+    pub fn drainContactForceEventsWithMaterialTags(
+        &mut self,
+        table: &RawContactMaterialTable,
+        f: &js_sys::Function,
+    ) {
+        let this = JsValue::null();
+        for (event, step_index, seq) in self.take_contact_force_events() {
+            let tag = table.tag(&event);
+            let event = RawContactForceEvent {
+                event,
+                material_tag: tag,
+                step_index,
+                sequence_number: seq,
+            };
+            let _ = f.call1(&this, &JsValue::from(event));
+        }
+    }
+
+    /// The number of rigid-bodies that woke up since the last drain of the sleep events.
+    pub fn numBodiesWokenUp(&self) -> usize {
+        self.sleep_events
+            .iter()
+            .filter(|(_, sleeping)| !*sleeping)
+            .count()
+    }
+
+    /// The number of rigid-bodies that fell asleep since the last drain of the sleep events.
+    pub fn numBodiesFellAsleep(&self) -> usize {
+        self.sleep_events
+            .iter()
+            .filter(|(_, sleeping)| *sleeping)
+            .count()
+    }
+
+    /// Applies the given JavaScript function to each rigid-body that fell asleep or woke up
+    /// during a simulation step, then clears the sleep event buffer.
+    ///
+    /// The function is called as `f(handle, sleeping)` where `sleeping` is `true` if the
+    /// rigid-body fell asleep, and `false` if it woke up. The active rigid-bodies are
+    /// compared at the beginning and at the end of each step.
+    pub fn drainSleepEvents(&mut self, f: &js_sys::Function) {
+        let this = JsValue::null();
+        for (handle, sleeping) in self.sleep_events.drain(..) {
+            let _ = f.call2(
+                &this,
+                &JsValue::from(utils::flat_handle(handle.0)),
+                &JsValue::from_bool(sleeping),
+            );
         }
     }
 
     /// Removes all events contained by this collector.
-    pub fn clear(&self) {
+    pub fn clear(&mut self) {
         while let Ok(_) = self.collision_events.try_recv() {}
+        while let Ok(_) = self.contact_force_events.try_recv() {}
+        self.pending_collision_events.clear();
+        self.step_index = 0;
+        self.pending_contact_force_events.clear();
+        self.contact_force_step_index = 0;
+        self.sleep_events.clear();
     }
 }
@@ -0,0 +1,131 @@
+use crate::dynamics::RawRigidBodySet;
+use crate::geometry::RawColliderSet;
+use crate::math::RawVector;
+use crate::pipeline::NativePhysicsHooks;
+use crate::utils::{self, FlatHandle};
+use rapier::dynamics::{
+    CCDSolver, ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet,
+};
+use rapier::geometry::{DefaultBroadPhase, NarrowPhase};
+use rapier::math::Real;
+use rapier::pipeline::PhysicsPipeline;
+use wasm_bindgen::prelude::*;
+
+/// A lightweight simulator for a handful of rigid-bodies and colliders.
+///
+/// All the auxiliary structures needed by the physics pipeline (island manager,
+/// broad-phase, narrow-phase, joint sets, CCD solver) are owned by the simulator
+/// itself so that a full step only takes a single call. Joints, events, and hooks
+/// are not supported.
+#[wasm_bindgen]
+pub struct RawMiniSimulator {
+    pipeline: PhysicsPipeline,
+    params: IntegrationParameters,
+    islands: IslandManager,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    ccd_solver: CCDSolver,
+}
+
+#[wasm_bindgen]
+impl RawMiniSimulator {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        let mut pipeline = PhysicsPipeline::new();
+        pipeline.counters.disable();
+
+        Self {
+            pipeline,
+            params: IntegrationParameters::default(),
+            islands: IslandManager::new(),
+            broad_phase: DefaultBroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            impulse_joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            ccd_solver: CCDSolver::new(),
+        }
+    }
+
+    /// Advances the simulation of the given sets by `dt` seconds.
+    pub fn step(
+        &mut self,
+        gravity: &RawVector,
+        dt: Real,
+        bodies: &mut RawRigidBodySet,
+        colliders: &mut RawColliderSet,
+    ) {
+        self.params.dt = dt;
+        self.pipeline.step(
+            &gravity.0,
+            &self.params,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut bodies.set,
+            &mut colliders.set,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.ccd_solver,
+            &NativePhysicsHooks(&colliders.native_hooks),
+            &(),
+        );
+        colliders.end_step(&self.narrow_phase);
+        bodies.lifetimes.num_steps = bodies.lifetimes.num_steps.wrapping_add(1);
+    }
+
+    /// Steps the simulation `numSteps` times, with a timestep of `dt` seconds each.
+    pub fn advance(
+        &mut self,
+        gravity: &RawVector,
+        dt: Real,
+        numSteps: u32,
+        bodies: &mut RawRigidBodySet,
+        colliders: &mut RawColliderSet,
+    ) {
+        for _ in 0..numSteps {
+            self.step(gravity, dt, bodies, colliders);
+        }
+    }
+
+    /// Removes a rigid-body, and the colliders attached to it, from the given sets.
+    pub fn removeRigidBody(
+        &mut self,
+        handle: FlatHandle,
+        bodies: &mut RawRigidBodySet,
+        colliders: &mut RawColliderSet,
+    ) {
+        let handle = utils::body_handle(handle);
+        if let Some(rb) = bodies.set.get(handle) {
+            for collider in rb.colliders() {
+                colliders.forget(collider);
+            }
+        }
+
+        bodies.set.remove(
+            handle,
+            &mut self.islands,
+            &mut colliders.set,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            true,
+        );
+        bodies.lifetimes.creation_steps.remove(&handle);
+        colliders.native_hooks.contact_responses.remove(&handle);
+    }
+
+    /// Removes a collider from the given sets.
+    pub fn removeCollider(
+        &mut self,
+        handle: FlatHandle,
+        bodies: &mut RawRigidBodySet,
+        colliders: &mut RawColliderSet,
+    ) {
+        let handle = utils::collider_handle(handle);
+        colliders
+            .set
+            .remove(handle, &mut self.islands, &mut bodies.set, true);
+        colliders.forget(&handle);
+    }
+}
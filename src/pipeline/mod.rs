@@ -1,11 +1,17 @@
+pub use self::contact_material_table::*;
 pub use self::debug_render_pipeline::*;
 pub use self::event_queue::*;
+pub use self::mini_simulator::*;
 pub use self::physics_hooks::*;
 pub use self::physics_pipeline::*;
 pub use self::serialization_pipeline::*;
+pub use self::simulation_lod::*;
 
+mod contact_material_table;
 mod debug_render_pipeline;
 mod event_queue;
+mod mini_simulator;
 mod physics_hooks;
 mod physics_pipeline;
 mod serialization_pipeline;
+mod simulation_lod;
@@ -1,26 +1,421 @@
 use crate::geometry::RawContactManifold;
 use crate::math::RawVector;
-use crate::utils::{self, FlatHandle};
+use crate::utils::{self, FlatHandle, Liveness};
 use na::ComplexField;
-use rapier::geometry::SolverFlags;
-use rapier::math::{Real, Vector};
-use rapier::pipeline::{ContactModificationContext, PairFilterContext, PhysicsHooks};
+use rapier::counters::Timer;
+use rapier::dynamics::RigidBodyHandle;
+use rapier::geometry::{Collider, ColliderHandle, ColliderSet, NarrowPhase, SolverFlags};
+use rapier::math::{Point, Real, Vector};
+use rapier::pipeline::{ActiveHooks, ContactModificationContext, PairFilterContext, PhysicsHooks};
 use rapier::prelude::{ContactManifold, SolverContact};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+/// Statistics about the calls made to the JS physics hooks during one timestep.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PhysicsHooksStats {
+    /// Number of contact pairs rejected by `filterContactPair`.
+    pub contact_pairs_filtered: u32,
+    /// Number of contact pairs accepted by `filterContactPair`.
+    pub contact_pairs_passed: u32,
+    /// Number of intersection pairs rejected by `filterIntersectionPair`.
+    pub intersection_pairs_filtered: u32,
+    /// Number of intersection pairs accepted by `filterIntersectionPair`.
+    pub intersection_pairs_passed: u32,
+    /// Number of contact pairs given to `modifySolverContacts`.
+    pub contact_pairs_modified: u32,
+    /// Total time spent in the JS hook callbacks, in milliseconds.
+    pub time_ms: f64,
+}
+
+/// The one-way platform behavior of a collider, applied natively during contact modification.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub(crate) struct OnewayPlatform {
+    pub(crate) local_normal: Vector<Real>,
+    pub(crate) allowed_angle: Real,
+}
+
+/// Scale factors applied to the solver contacts of a rigid-body, to make its contacts softer.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub(crate) struct ContactResponse {
+    pub(crate) stiffness_scale: Real,
+    pub(crate) restitution_scale: Real,
+}
+
+/// The contact modifications applied natively during the timestep, without calling any JS hook.
+///
+/// Rapier only modifies the contacts of colliders with the `MODIFY_SOLVER_CONTACTS` hook, so
+/// this hook is added to the active hooks of the colliders these modifications apply to. The
+/// active hooks of a collider must therefore be read with `user_hooks`, never directly.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct NativeHooks {
+    pub(crate) oneway_platforms: HashMap<ColliderHandle, OnewayPlatform>,
+    pub(crate) contact_responses: HashMap<RigidBodyHandle, ContactResponse>,
+    // The minimum approach speeds for the contacts of a collider to bounce, overriding the
+    // global `restitution_threshold`.
+    pub(crate) restitution_thresholds: HashMap<ColliderHandle, Real>,
+    pub(crate) restitution_threshold: Real,
+    // The colliders with a non-zero restitution, the only ones the global
+    // `restitution_threshold` needs to be applied to.
+    pub(crate) bouncy_colliders: HashSet<ColliderHandle>,
+    // The colliders with the `MODIFY_SOLVER_CONTACTS` hook enabled only for the native
+    // contact modifications. The JS hook isn't called for them.
+    native_only: HashSet<ColliderHandle>,
+    // The colliders which shape was replaced without preserving their contacts. The
+    // warmstart impulses of their contacts are discarded during the next timestep.
+    pub(crate) reset_warmstart: HashSet<ColliderHandle>,
+}
+
+impl NativeHooks {
+    /// Applies the native contact modifications to the given contact.
+    ///
+    /// If both colliders are one-way platforms, only the first one is taken into account.
+    /// If both rigid-bodies have a contact response, their scale factors are multiplied.
+    /// If both colliders have a restitution threshold, the largest one is used.
+    pub(crate) fn modify_solver_contacts(&self, ctxt: &mut ContactModificationContext) {
+        let platform = if let Some(platform) = self.oneway_platforms.get(&ctxt.collider1) {
+            Some((platform, ctxt.manifold.local_n1))
+        } else {
+            self.oneway_platforms
+                .get(&ctxt.collider2)
+                .map(|platform| (platform, ctxt.manifold.local_n2))
+        };
+
+        if let Some((platform, local_n)) = platform {
+            update_as_oneway_platform(
+                &local_n,
+                ctxt.solver_contacts,
+                ctxt.user_data,
+                &platform.local_normal,
+                platform.allowed_angle,
+            );
+        }
+
+        let threshold = [ctxt.collider1, ctxt.collider2]
+            .iter()
+            .filter_map(|handle| self.restitution_thresholds.get(handle).copied())
+            .reduce(Real::max)
+            .unwrap_or(self.restitution_threshold);
+
+        if threshold > 0.0 {
+            let bodies = ctxt.bodies;
+            let velocity_at = |body: Option<RigidBodyHandle>, pt: &Point<Real>| {
+                body.and_then(|handle| bodies.get(handle))
+                    .map(|rb| rb.velocity_at_point(pt))
+                    .unwrap_or_else(Vector::zeros)
+            };
+            let (rigid_body1, rigid_body2) = (ctxt.rigid_body1, ctxt.rigid_body2);
+            // The normal points from the first collider towards the second one.
+            let normal = *ctxt.normal;
+
+            for contact in ctxt.solver_contacts.iter_mut() {
+                let approach_speed = (velocity_at(rigid_body1, &contact.point)
+                    - velocity_at(rigid_body2, &contact.point))
+                .dot(&normal);
+                if approach_speed < threshold {
+                    contact.restitution = 0.0;
+                }
+            }
+        }
+
+        let mut stiffness_scale = 1.0;
+        let mut restitution_scale = 1.0;
+        let mut has_response = false;
+        for body in [ctxt.rigid_body1, ctxt.rigid_body2].iter().flatten() {
+            if let Some(response) = self.contact_responses.get(body) {
+                stiffness_scale *= response.stiffness_scale;
+                restitution_scale *= response.restitution_scale;
+                has_response = true;
+            }
+        }
 
-pub struct RawPhysicsHooks {
+        if has_response {
+            for contact in ctxt.solver_contacts.iter_mut() {
+                contact.restitution *= restitution_scale;
+                if contact.dist < 0.0 {
+                    // Only the penetrating part of the distance is corrected by the solver.
+                    contact.dist *= stiffness_scale;
+                }
+            }
+        }
+
+        if self.reset_warmstart.contains(&ctxt.collider1)
+            || self.reset_warmstart.contains(&ctxt.collider2)
+        {
+            reset_warmstart_impulses(ctxt.solver_contacts);
+        }
+    }
+
+    /// Checks if the JS `modifySolverContacts` hook was enabled by the user for at least one of
+    /// the colliders of the given contact.
+    fn js_hook_requested(&self, ctxt: &ContactModificationContext) -> bool {
+        let requested = |handle: ColliderHandle| {
+            !self.native_only.contains(&handle)
+                && ctxt
+                    .colliders
+                    .get(handle)
+                    .map(|co| {
+                        co.active_hooks()
+                            .contains(ActiveHooks::MODIFY_SOLVER_CONTACTS)
+                    })
+                    .unwrap_or(false)
+        };
+        requested(ctxt.collider1) || requested(ctxt.collider2)
+    }
+
+    fn needs_modify_hook(&self, handle: ColliderHandle, co: &Collider) -> bool {
+        self.oneway_platforms.contains_key(&handle)
+            || self.reset_warmstart.contains(&handle)
+            || self.restitution_thresholds.contains_key(&handle)
+            || (self.restitution_threshold > 0.0 && self.bouncy_colliders.contains(&handle))
+            || co
+                .parent()
+                .map(|parent| self.contact_responses.contains_key(&parent))
+                .unwrap_or(false)
+    }
+
+    /// Enables or disables the `MODIFY_SOLVER_CONTACTS` hook of a collider, after the native
+    /// contact modifications applying to it changed.
+    pub(crate) fn update_collider_hooks(&mut self, handle: ColliderHandle, co: &mut Collider) {
+        if co.material().restitution > 0.0 {
+            self.bouncy_colliders.insert(handle);
+        } else {
+            self.bouncy_colliders.remove(&handle);
+        }
+
+        let hooks = co.active_hooks();
+        if self.needs_modify_hook(handle, co) {
+            if !hooks.contains(ActiveHooks::MODIFY_SOLVER_CONTACTS) {
+                co.set_active_hooks(hooks | ActiveHooks::MODIFY_SOLVER_CONTACTS);
+                self.native_only.insert(handle);
+            }
+        } else if self.native_only.remove(&handle) {
+            co.set_active_hooks(hooks - ActiveHooks::MODIFY_SOLVER_CONTACTS);
+        }
+    }
+
+    /// Sets the physics hooks enabled by the user for a collider.
+    pub(crate) fn set_user_hooks(
+        &mut self,
+        handle: ColliderHandle,
+        co: &mut Collider,
+        hooks: ActiveHooks,
+    ) {
+        co.set_active_hooks(hooks);
+        self.native_only.remove(&handle);
+        self.update_collider_hooks(handle, co);
+    }
+
+    /// The physics hooks enabled by the user for a collider.
+    pub(crate) fn user_hooks(&self, handle: ColliderHandle, co: &Collider) -> ActiveHooks {
+        if self.native_only.contains(&handle) {
+            co.active_hooks() - ActiveHooks::MODIFY_SOLVER_CONTACTS
+        } else {
+            co.active_hooks()
+        }
+    }
+
+    /// Copies the native contact modifications of a collider to its copy `copy`, inserted
+    /// into the collider set `target` belongs to.
+    pub(crate) fn copy_collider(
+        &self,
+        source: ColliderHandle,
+        target: &mut NativeHooks,
+        copy: ColliderHandle,
+    ) {
+        if let Some(platform) = self.oneway_platforms.get(&source) {
+            target.oneway_platforms.insert(copy, *platform);
+        }
+        if let Some(threshold) = self.restitution_thresholds.get(&source) {
+            target.restitution_thresholds.insert(copy, *threshold);
+        }
+    }
+
+    /// Removes the records kept for a collider that was removed from its collider set.
+    pub(crate) fn forget_collider(&mut self, handle: &ColliderHandle) {
+        self.oneway_platforms.remove(handle);
+        self.restitution_thresholds.remove(handle);
+        self.bouncy_colliders.remove(handle);
+        self.native_only.remove(handle);
+        self.reset_warmstart.remove(handle);
+    }
+
+    /// Replaces the handles of the colliders and rigid-bodies the contact modifications apply
+    /// to, after they were inserted again into new sets.
+    pub(crate) fn remap(
+        &mut self,
+        colliders: &HashMap<ColliderHandle, ColliderHandle>,
+        bodies: &HashMap<RigidBodyHandle, RigidBodyHandle>,
+    ) {
+        let remap_set = |set: &HashSet<ColliderHandle>| {
+            set.iter()
+                .filter_map(|handle| colliders.get(handle).copied())
+                .collect()
+        };
+
+        utils::remap_keys(&mut self.oneway_platforms, colliders);
+        utils::remap_keys(&mut self.contact_responses, bodies);
+        utils::remap_keys(&mut self.restitution_thresholds, colliders);
+        self.bouncy_colliders = remap_set(&self.bouncy_colliders);
+        self.native_only = remap_set(&self.native_only);
+        self.reset_warmstart = remap_set(&self.reset_warmstart);
+    }
+}
+
+/// The physics hooks used when no JS hooks are given. They only apply the native contact
+/// modifications.
+pub(crate) struct NativePhysicsHooks<'a>(pub(crate) &'a NativeHooks);
+
+impl<'a> PhysicsHooks for NativePhysicsHooks<'a> {
+    fn modify_solver_contacts(&self, ctxt: &mut ContactModificationContext) {
+        self.0.modify_solver_contacts(ctxt);
+    }
+}
+
+pub struct RawPhysicsHooks<'a> {
     pub this: js_sys::Object,
     pub filter_contact_pair: js_sys::Function,
     pub filter_intersection_pair: js_sys::Function,
     pub modify_solver_contacts: Option<js_sys::Function>,
+    pub filter_contact_pairs: Option<js_sys::Function>,
+    // The results of `filter_contact_pairs` for the contact pairs known at the
+    // beginning of the timestep.
+    pub contact_pair_flags: HashMap<(ColliderHandle, ColliderHandle), Option<SolverFlags>>,
+    pub(crate) native_hooks: &'a NativeHooks,
+    pub stats: Cell<PhysicsHooksStats>,
+    // The first error thrown, or invalid result returned, by `filter_contact_pairs`.
+    pub error: Cell<Option<JsValue>>,
+}
+
+type ContactPairKey = (
+    ColliderHandle,
+    ColliderHandle,
+    Option<RigidBodyHandle>,
+    Option<RigidBodyHandle>,
+);
+
+impl<'a> RawPhysicsHooks<'a> {
+    fn record<T>(
+        &self,
+        callback: impl FnOnce() -> T,
+        update: impl FnOnce(&mut PhysicsHooksStats, &T),
+    ) -> T {
+        let mut timer = Timer::new();
+        timer.start();
+        let result = callback();
+        timer.pause();
+
+        let mut stats = self.stats.get();
+        stats.time_ms += timer.time_ms();
+        update(&mut stats, &result);
+        self.stats.set(stats);
+        result
+    }
+
+    /// Calls the batched contact pair filter once for all the given pairs.
+    ///
+    /// The filter is called with four arrays containing the handles of the first colliders,
+    /// the second colliders, the first rigid-bodies, and the second rigid-bodies (`NaN` for
+    /// colliders without parent). It returns one solver flags value per pair, where a negative
+    /// or `NaN` value means that the pair is filtered out.
+    ///
+    /// If the filter throws, or doesn't return one value per pair, all the pairs are filtered
+    /// out and the error is kept in `self.error`.
+    fn call_filter_contact_pairs(
+        &self,
+        filter: &js_sys::Function,
+        pairs: &[ContactPairKey],
+    ) -> Vec<Option<SolverFlags>> {
+        let handles = |f: &dyn Fn(&ContactPairKey) -> f64| {
+            let handles: Vec<f64> = pairs.iter().map(f).collect();
+            js_sys::Float64Array::from(&handles[..])
+        };
+        let body_handle =
+            |rb: Option<RigidBodyHandle>| rb.map(|rb| utils::flat_handle(rb.0)).unwrap_or(f64::NAN);
+        let args = js_sys::Array::of4(
+            &handles(&|p| utils::flat_handle(p.0 .0)),
+            &handles(&|p| utils::flat_handle(p.1 .0)),
+            &handles(&|p| body_handle(p.2)),
+            &handles(&|p| body_handle(p.3)),
+        );
+
+        let flags = filter.apply(&self.this, &args).and_then(|result| {
+            let is_array =
+                js_sys::Array::is_array(&result) || result.is_instance_of::<js_sys::Float64Array>();
+            let flags = if is_array {
+                js_sys::Float64Array::new(&result).to_vec()
+            } else {
+                vec![]
+            };
+            if flags.len() == pairs.len() {
+                Ok(flags)
+            } else {
+                Err(js_sys::Error::new(
+                    "filterContactPairs must return an array with one value per contact pair",
+                )
+                .into())
+            }
+        });
+        let flags = flags.unwrap_or_else(|err| {
+            let first = self.error.take();
+            self.error.set(first.or(Some(err)));
+            vec![]
+        });
+
+        (0..pairs.len())
+            .map(|i| {
+                flags
+                    .get(i)
+                    .filter(|flags| **flags >= 0.0)
+                    .and_then(|flags| SolverFlags::from_bits(*flags as u32))
+            })
+            .collect()
+    }
+
+    /// Evaluates the batched contact pair filter, if any, on all the contact pairs of the
+    /// narrow-phase involving a collider with the `FILTER_CONTACT_PAIRS` hook.
+    ///
+    /// Only the pairs persisting from the previous timestep are batched: the pairs created
+    /// during the next timestep are not known yet, so they will be evaluated individually by
+    /// `filter_contact_pair`.
+    pub fn prefilter_contact_pairs(&mut self, narrow_phase: &NarrowPhase, colliders: &ColliderSet) {
+        let filter = match &self.filter_contact_pairs {
+            Some(filter) => filter.clone(),
+            None => return,
+        };
+
+        let pairs: Vec<_> = narrow_phase
+            .contact_pairs()
+            .filter_map(|pair| {
+                let co1 = colliders.get(pair.collider1)?;
+                let co2 = colliders.get(pair.collider2)?;
+                (self.native_hooks.user_hooks(pair.collider1, co1)
+                    | self.native_hooks.user_hooks(pair.collider2, co2))
+                .contains(ActiveHooks::FILTER_CONTACT_PAIRS)
+                .then(|| (pair.collider1, pair.collider2, co1.parent(), co2.parent()))
+            })
+            .collect();
+
+        if pairs.is_empty() {
+            return;
+        }
+
+        let flags = self.record(
+            || self.call_filter_contact_pairs(&filter, &pairs),
+            |_, _| {},
+        );
+        self.contact_pair_flags = pairs.iter().map(|p| (p.0, p.1)).zip(flags).collect();
+    }
 }
 
 // HACK: the RawPhysicsHooks is no longer Send+Sync because the JS objects are
 //       no longer Send+Sync since https://github.com/rustwasm/wasm-bindgen/pull/955
 //       As far as this is confined to the bindings this should be fine since we
 //       never use threading in wasm.
-unsafe impl Send for RawPhysicsHooks {}
-unsafe impl Sync for RawPhysicsHooks {}
+unsafe impl<'a> Send for RawPhysicsHooks<'a> {}
+unsafe impl<'a> Sync for RawPhysicsHooks<'a> {}
 
 #[wasm_bindgen]
 extern "C" {
@@ -30,8 +425,38 @@ extern "C" {
     fn log(s: &str);
 }
 
-impl PhysicsHooks for RawPhysicsHooks {
+impl<'a> PhysicsHooks for RawPhysicsHooks<'a> {
     fn filter_contact_pair(&self, ctxt: &PairFilterContext) -> Option<SolverFlags> {
+        if let Some(filter) = &self.filter_contact_pairs {
+            let flags = match self
+                .contact_pair_flags
+                .get(&(ctxt.collider1, ctxt.collider2))
+            {
+                Some(flags) => *flags,
+                None => {
+                    let pair = (
+                        ctxt.collider1,
+                        ctxt.collider2,
+                        ctxt.rigid_body1,
+                        ctxt.rigid_body2,
+                    );
+                    self.record(
+                        || self.call_filter_contact_pairs(filter, &[pair])[0],
+                        |_, _| {},
+                    )
+                }
+            };
+
+            let mut stats = self.stats.get();
+            if flags.is_some() {
+                stats.contact_pairs_passed += 1;
+            } else {
+                stats.contact_pairs_filtered += 1;
+            }
+            self.stats.set(stats);
+            return flags;
+        }
+
         let rb1 = ctxt
             .rigid_body1
             .map(|rb| JsValue::from(utils::flat_handle(rb.0)))
@@ -41,19 +466,30 @@ impl PhysicsHooks for RawPhysicsHooks {
             .map(|rb| JsValue::from(utils::flat_handle(rb.0)))
             .unwrap_or(JsValue::NULL);
 
-        let result = self
-            .filter_contact_pair
-            .bind2(
-                &self.this,
-                &JsValue::from(utils::flat_handle(ctxt.collider1.0)),
-                &JsValue::from(utils::flat_handle(ctxt.collider2.0)),
-            )
-            .call2(&self.this, &rb1, &rb2)
-            .ok()?;
-        let flags = result.as_f64()?;
-        // TODO: not sure exactly why we have to do `flags as u32` instead
-        //       of `flags.to_bits() as u32`.
-        SolverFlags::from_bits(flags as u32)
+        self.record(
+            || {
+                let result = self
+                    .filter_contact_pair
+                    .bind2(
+                        &self.this,
+                        &JsValue::from(utils::flat_handle(ctxt.collider1.0)),
+                        &JsValue::from(utils::flat_handle(ctxt.collider2.0)),
+                    )
+                    .call2(&self.this, &rb1, &rb2)
+                    .ok()?;
+                let flags = result.as_f64()?;
+                // TODO: not sure exactly why we have to do `flags as u32` instead
+                //       of `flags.to_bits() as u32`.
+                SolverFlags::from_bits(flags as u32)
+            },
+            |stats, flags| {
+                if flags.is_some() {
+                    stats.contact_pairs_passed += 1;
+                } else {
+                    stats.contact_pairs_filtered += 1;
+                }
+            },
+        )
     }
 
     fn filter_intersection_pair(&self, ctxt: &PairFilterContext) -> bool {
@@ -66,22 +502,39 @@ impl PhysicsHooks for RawPhysicsHooks {
             .map(|rb| JsValue::from(utils::flat_handle(rb.0)))
             .unwrap_or(JsValue::NULL);
 
-        self.filter_intersection_pair
-            .bind2(
-                &self.this,
-                &JsValue::from(utils::flat_handle(ctxt.collider1.0)),
-                &JsValue::from(utils::flat_handle(ctxt.collider2.0)),
-            )
-            .call2(&self.this, &rb1, &rb2)
-            .ok()
-            .and_then(|res| res.as_bool())
-            .unwrap_or(false)
+        self.record(
+            || {
+                self.filter_intersection_pair
+                    .bind2(
+                        &self.this,
+                        &JsValue::from(utils::flat_handle(ctxt.collider1.0)),
+                        &JsValue::from(utils::flat_handle(ctxt.collider2.0)),
+                    )
+                    .call2(&self.this, &rb1, &rb2)
+                    .ok()
+                    .and_then(|res| res.as_bool())
+                    .unwrap_or(false)
+            },
+            |stats, passed| {
+                if *passed {
+                    stats.intersection_pairs_passed += 1;
+                } else {
+                    stats.intersection_pairs_filtered += 1;
+                }
+            },
+        )
     }
 
     fn modify_solver_contacts(&self, ctxt: &mut ContactModificationContext) {
+        self.native_hooks.modify_solver_contacts(ctxt);
+
         let Some(modify_solver_contacts) = &self.modify_solver_contacts else {
             return;
         };
+        if !self.native_hooks.js_hook_requested(ctxt) {
+            return;
+        }
+        let liveness = Liveness::new();
         let raw_context = RawContactModificationContext {
             collider1: utils::flat_handle(ctxt.collider1.0),
             collider2: utils::flat_handle(ctxt.collider2.0),
@@ -91,11 +544,31 @@ impl PhysicsHooks for RawPhysicsHooks {
             solver_contacts: ctxt.solver_contacts as *mut Vec<SolverContact>,
             normal: ctxt.normal as *mut Vector<Real>,
             user_data: ctxt.user_data as *mut u32,
+            liveness: liveness.clone(),
         };
-        let _ = modify_solver_contacts.call1(&self.this, &JsValue::from(raw_context));
+        self.record(
+            || {
+                let _ = modify_solver_contacts.call1(&self.this, &JsValue::from(raw_context));
+            },
+            |stats, _| stats.contact_pairs_modified += 1,
+        );
+        // The context may be retained by JS, but the contact it points to is only borrowed
+        // for the duration of the call.
+        liveness.expire();
     }
 }
 
+/// The strategy used to select the solver contacts kept when reducing a contact manifold.
+#[wasm_bindgen]
+#[derive(Copy, Clone)]
+pub enum RawContactReductionStrategy {
+    /// Keep the most penetrating solver contacts.
+    Deepest,
+    /// Keep the most penetrating solver contact, then the solver contacts farthest
+    /// from the ones already kept.
+    Spread,
+}
+
 #[wasm_bindgen]
 pub struct RawContactModificationContext {
     collider1: FlatHandle,
@@ -106,34 +579,57 @@ pub struct RawContactModificationContext {
     solver_contacts: *mut Vec<SolverContact>,
     normal: *mut Vector<Real>,
     user_data: *mut u32,
+    // Expired once the `modifySolverContacts` hook returns, after which the pointers above
+    // are dangling.
+    liveness: Liveness,
+}
+
+// SAFETY: every accessor checks the liveness of the context before dereferencing its
+//         pointers, which are valid until the hook it is given to returns.
+impl RawContactModificationContext {
+    fn contacts(&self) -> &Vec<SolverContact> {
+        self.liveness.check();
+        unsafe { &*self.solver_contacts }
+    }
+
+    fn contacts_mut(&mut self) -> &mut Vec<SolverContact> {
+        self.liveness.check();
+        unsafe { &mut *self.solver_contacts }
+    }
 }
 
 #[wasm_bindgen]
 impl RawContactModificationContext {
     // Simple getters and setters for the fields.
     pub fn collider1(&self) -> FlatHandle {
+        self.liveness.check();
         self.collider1
     }
 
     pub fn collider2(&self) -> FlatHandle {
+        self.liveness.check();
         self.collider2
     }
 
     pub fn rigid_body1(&self) -> Option<FlatHandle> {
+        self.liveness.check();
         self.rigid_body1
     }
 
     pub fn rigid_body2(&self) -> Option<FlatHandle> {
+        self.liveness.check();
         self.rigid_body2
     }
 
     #[wasm_bindgen(getter)]
     pub fn normal(&self) -> RawVector {
+        self.liveness.check();
         unsafe { RawVector(*self.normal) }
     }
 
     #[wasm_bindgen(setter)]
     pub fn set_normal(&mut self, normal: &RawVector) {
+        self.liveness.check();
         unsafe {
             *self.normal = normal.0.into();
         }
@@ -141,11 +637,13 @@ impl RawContactModificationContext {
 
     #[wasm_bindgen(getter)]
     pub fn user_data(&self) -> u32 {
+        self.liveness.check();
         unsafe { *self.user_data }
     }
 
     #[wasm_bindgen(setter)]
     pub fn set_user_data(&mut self, user_data: u32) {
+        self.liveness.check();
         unsafe {
             *self.user_data = user_data;
         }
@@ -153,200 +651,264 @@ impl RawContactModificationContext {
 
     // Solver contacts manipulation methods.
     pub fn num_solver_contacts(&self) -> usize {
-        unsafe { (*self.solver_contacts).len() }
+        self.contacts().len()
     }
 
     pub fn clear_solver_contacts(&mut self) {
-        unsafe { (*self.solver_contacts).clear() }
+        self.contacts_mut().clear()
     }
 
-    pub fn remove_solver_contact(&mut self, i: usize) {
-        unsafe {
-            if i < self.num_solver_contacts() {
-                (*self.solver_contacts).swap_remove(i);
+    /// Reduces the number of solver contacts to at most `max_contacts`, using the given
+    /// selection strategy.
+    ///
+    /// Returns the number of solver contacts removed.
+    pub fn reduce_solver_contacts(
+        &mut self,
+        max_contacts: usize,
+        strategy: RawContactReductionStrategy,
+    ) -> usize {
+        let contacts = self.contacts_mut();
+        if contacts.len() <= max_contacts {
+            return 0;
+        }
+
+        let num_removed = contacts.len() - max_contacts;
+        match strategy {
+            RawContactReductionStrategy::Deepest => {
+                contacts.sort_by(|a, b| a.dist.total_cmp(&b.dist));
+            }
+            RawContactReductionStrategy::Spread => {
+                if max_contacts > 0 {
+                    let mut deepest = 0;
+                    for (i, c) in contacts.iter().enumerate() {
+                        if c.dist < contacts[deepest].dist {
+                            deepest = i;
+                        }
+                    }
+                    contacts.swap(0, deepest);
+                }
+
+                for k in 1..max_contacts {
+                    let mut best = k;
+                    let mut best_dist = -1.0;
+
+                    for j in k..contacts.len() {
+                        let dist = contacts[..k]
+                            .iter()
+                            .map(|c| (c.point - contacts[j].point).norm_squared())
+                            .fold(Real::MAX, Real::min);
+                        if dist > best_dist {
+                            best = j;
+                            best_dist = dist;
+                        }
+                    }
+
+                    contacts.swap(k, best);
+                }
             }
         }
+
+        contacts.truncate(max_contacts);
+        num_removed
     }
 
-    pub fn solver_contact_point(&self, i: usize) -> Option<RawVector> {
-        unsafe {
-            (&(*self.solver_contacts))
-                .get(i)
-                .map(|c| c.point.coords.into())
+    pub fn remove_solver_contact(&mut self, i: usize) {
+        if i < self.num_solver_contacts() {
+            self.contacts_mut().swap_remove(i);
         }
     }
 
+    pub fn solver_contact_point(&self, i: usize) -> Option<RawVector> {
+        self.contacts().get(i).map(|c| c.point.coords.into())
+    }
+
     pub fn set_solver_contact_point(&mut self, i: usize, pt: &RawVector) {
-        unsafe {
-            if let Some(c) = (&mut (*self.solver_contacts)).get_mut(i) {
-                c.point = pt.0.into()
-            }
+        if let Some(c) = self.contacts_mut().get_mut(i) {
+            c.point = pt.0.into()
         }
     }
 
     pub fn solver_contact_dist(&self, i: usize) -> Real {
-        unsafe {
-            (&(*self.solver_contacts))
-                .get(i)
-                .map(|c| c.dist)
-                .unwrap_or(0.0)
-        }
+        self.contacts().get(i).map(|c| c.dist).unwrap_or(0.0)
     }
 
     pub fn set_solver_contact_dist(&mut self, i: usize, dist: Real) {
-        unsafe {
-            if let Some(c) = (&mut (*self.solver_contacts)).get_mut(i) {
-                c.dist = dist
-            }
+        if let Some(c) = self.contacts_mut().get_mut(i) {
+            c.dist = dist
         }
     }
 
     pub fn solver_contact_friction(&self, i: usize) -> Real {
-        unsafe { (&(*self.solver_contacts))[i].friction }
+        self.contacts()[i].friction
     }
 
     pub fn set_solver_contact_friction(&mut self, i: usize, friction: Real) {
-        unsafe {
-            if let Some(c) = (&mut (*self.solver_contacts)).get_mut(i) {
-                c.friction = friction
-            }
+        if let Some(c) = self.contacts_mut().get_mut(i) {
+            c.friction = friction
         }
     }
 
     pub fn solver_contact_restitution(&self, i: usize) -> Real {
-        unsafe { (&(*self.solver_contacts))[i].restitution }
+        self.contacts()[i].restitution
     }
 
     pub fn set_solver_contact_restitution(&mut self, i: usize, restitution: Real) {
-        unsafe {
-            if let Some(c) = (&mut (*self.solver_contacts)).get_mut(i) {
-                c.restitution = restitution
-            }
+        if let Some(c) = self.contacts_mut().get_mut(i) {
+            c.restitution = restitution
         }
     }
 
     pub fn solver_contact_tangent_velocity(&self, i: usize) -> RawVector {
-        unsafe { (&(*self.solver_contacts))[i].tangent_velocity.into() }
+        self.contacts()[i].tangent_velocity.into()
     }
 
     pub fn set_solver_contact_tangent_velocity(&mut self, i: usize, vel: &RawVector) {
-        unsafe {
-            if let Some(c) = (&mut (*self.solver_contacts)).get_mut(i) {
-                c.tangent_velocity = vel.0.into()
-            }
+        if let Some(c) = self.contacts_mut().get_mut(i) {
+            c.tangent_velocity = vel.0.into()
         }
     }
 
     pub fn solver_contact_warmstart_impulse(&self, i: usize) -> Real {
-        unsafe { (&(*self.solver_contacts))[i].warmstart_impulse }
+        self.contacts()[i].warmstart_impulse
     }
 
     pub fn set_solver_contact_warmstart_impulse(&mut self, i: usize, impulse: Real) {
-        unsafe {
-            if let Some(c) = (&mut (*self.solver_contacts)).get_mut(i) {
-                c.warmstart_impulse = impulse
-            }
+        if let Some(c) = self.contacts_mut().get_mut(i) {
+            c.warmstart_impulse = impulse
         }
     }
 
     pub fn solver_contact_warmstart_tangent_impulse(&self, i: usize) -> Real {
-        unsafe { (&(*self.solver_contacts))[i].warmstart_tangent_impulse.x }
+        self.contacts()[i].warmstart_tangent_impulse.x
     }
 
     pub fn set_solver_contact_warmstart_tangent_impulse(&mut self, i: usize, impulse: Real) {
-        unsafe {
-            if let Some(c) = (&mut (*self.solver_contacts)).get_mut(i) {
-                c.warmstart_tangent_impulse.x = impulse;
-            }
+        if let Some(c) = self.contacts_mut().get_mut(i) {
+            c.warmstart_tangent_impulse.x = impulse;
         }
     }
 
     pub fn solver_contact_warmstart_twist_impulse(&self, i: usize) -> Real {
-        unsafe { (&(*self.solver_contacts))[i].warmstart_twist_impulse }
+        self.contacts()[i].warmstart_twist_impulse
     }
 
     pub fn set_solver_contact_warmstart_twist_impulse(&mut self, i: usize, impulse: Real) {
-        unsafe {
-            if let Some(c) = (&mut (*self.solver_contacts)).get_mut(i) {
-                c.warmstart_twist_impulse = impulse
-            }
+        if let Some(c) = self.contacts_mut().get_mut(i) {
+            c.warmstart_twist_impulse = impulse
         }
     }
 
+    /// Sets the warmstart impulses of all the solver contacts to zero, so the constraints
+    /// solver starts from scratch for this contact pair.
+    pub fn reset_warmstart_impulses(&mut self) {
+        reset_warmstart_impulses(self.contacts_mut());
+    }
+
     pub fn solver_contact_is_new(&self, i: usize) -> bool {
-        unsafe { (&(*self.solver_contacts))[i].is_new == 1.0 }
+        self.contacts()[i].is_new == 1.0
     }
 
     pub fn set_solver_contact_is_new(&mut self, i: usize, is_new: bool) {
-        unsafe {
-            if let Some(c) = (&mut (*self.solver_contacts)).get_mut(i) {
-                c.is_new = if is_new { 1.0 } else { 0.0 };
-            }
+        if let Some(c) = self.contacts_mut().get_mut(i) {
+            c.is_new = if is_new { 1.0 } else { 0.0 };
         }
     }
 
+    /// The contact manifold of this context, which becomes invalid along with it.
     #[wasm_bindgen(getter)]
     pub fn contact_manifold(&self) -> RawContactManifold {
-        RawContactManifold(self.manifold)
+        self.liveness.check();
+        RawContactManifold(self.manifold, self.liveness.clone())
     }
 
     /// Helper function to update `self` to emulate a oneway-platform.
     ///
     /// Duplicated from ContactModificationContext::update_as_oneway_platform
     pub fn update_as_oneway_platform(&mut self, allowed_local_n1: &RawVector, allowed_angle: Real) {
-        const CONTACT_CONFIGURATION_UNKNOWN: u32 = 0;
-        const CONTACT_CURRENTLY_ALLOWED: u32 = 1;
-        const CONTACT_CURRENTLY_FORBIDDEN: u32 = 2;
+        self.liveness.check();
+        unsafe {
+            update_as_oneway_platform(
+                &(*self.manifold).local_n1,
+                &mut *self.solver_contacts,
+                &mut *self.user_data,
+                &allowed_local_n1.0,
+                allowed_angle,
+            )
+        }
+    }
+}
 
-        let cang = ComplexField::cos(allowed_angle);
+/// Sets the normal, tangent, and twist warmstart impulses of the given solver contacts to zero.
+fn reset_warmstart_impulses(contacts: &mut [SolverContact]) {
+    for c in contacts {
+        c.warmstart_impulse = 0.0;
+        c.warmstart_tangent_impulse.fill(0.0);
+        c.warmstart_twist_impulse = 0.0;
+    }
+}
 
-        // Test the allowed normal with the local-space contact normal that
-        // points towards the exterior of context.collider1.
-        unsafe {
-            let contact_is_ok = (*self.manifold).local_n1.dot((&allowed_local_n1.0).into()) >= cang;
-
-            match *self.user_data {
-                CONTACT_CONFIGURATION_UNKNOWN => {
-                    if contact_is_ok {
-                        // The contact is close enough to the allowed normal.
-                        *self.user_data = CONTACT_CURRENTLY_ALLOWED;
-                    } else {
-                        // The contact normal isn't close enough to the allowed
-                        // normal, so remove all the contacts and mark further contacts
-                        // as forbidden.
-                        (&mut (*self.solver_contacts)).clear();
-
-                        // NOTE: in some very rare cases `local_n1` will be
-                        // zero if the objects are exactly touching at one point.
-                        // So in this case we can't really conclude.
-                        // If the norm is non-zero, then we can tell we need to forbid
-                        // further contacts. Otherwise we have to wait for the next frame.
-                        if (*self.manifold).local_n1.norm_squared() > 0.1 {
-                            *self.user_data = CONTACT_CURRENTLY_FORBIDDEN;
-                        }
-                    }
-                }
-                CONTACT_CURRENTLY_FORBIDDEN => {
-                    // Contacts are forbidden so we need to continue forbidding contacts
-                    // until all the contacts are non-penetrating again. In that case, if
-                    // the contacts are OK with respect to the contact normal, then we can
-                    // mark them as allowed.
-                    if contact_is_ok && (&mut (*self.solver_contacts)).iter().all(|c| c.dist > 0.0)
-                    {
-                        *self.user_data = CONTACT_CURRENTLY_ALLOWED;
-                    } else {
-                        // Discard all the contacts.
-                        (&mut (*self.solver_contacts)).clear();
-                    }
-                }
-                CONTACT_CURRENTLY_ALLOWED => {
-                    // We allow all the contacts right now. The configuration becomes
-                    // uncertain again when the contact manifold no longer contains any contact.
-                    if (&mut (*self.solver_contacts)).is_empty() {
-                        *self.user_data = CONTACT_CONFIGURATION_UNKNOWN;
-                    }
+/// Updates the solver contacts of a contact manifold to emulate a one-way platform.
+///
+/// The `local_n` normal is the local-space contact normal pointing towards the exterior
+/// of the platform, and `user_data` keeps track of the state of the contact between
+/// timesteps.
+fn update_as_oneway_platform(
+    local_n: &Vector<Real>,
+    solver_contacts: &mut Vec<SolverContact>,
+    user_data: &mut u32,
+    allowed_local_n: &Vector<Real>,
+    allowed_angle: Real,
+) {
+    const CONTACT_CONFIGURATION_UNKNOWN: u32 = 0;
+    const CONTACT_CURRENTLY_ALLOWED: u32 = 1;
+    const CONTACT_CURRENTLY_FORBIDDEN: u32 = 2;
+
+    let cang = ComplexField::cos(allowed_angle);
+
+    // Test the allowed normal with the local-space contact normal that
+    // points towards the exterior of the platform.
+    let contact_is_ok = local_n.dot(allowed_local_n) >= cang;
+
+    match *user_data {
+        CONTACT_CURRENTLY_FORBIDDEN => {
+            // Contacts are forbidden so we need to continue forbidding contacts
+            // until all the contacts are non-penetrating again. In that case, if
+            // the contacts are OK with respect to the contact normal, then we can
+            // mark them as allowed.
+            if contact_is_ok && solver_contacts.iter().all(|c| c.dist > 0.0) {
+                *user_data = CONTACT_CURRENTLY_ALLOWED;
+            } else {
+                // Discard all the contacts.
+                solver_contacts.clear();
+            }
+        }
+        CONTACT_CURRENTLY_ALLOWED => {
+            // We allow all the contacts right now. The configuration becomes
+            // uncertain again when the contact manifold no longer contains any contact.
+            if solver_contacts.is_empty() {
+                *user_data = CONTACT_CONFIGURATION_UNKNOWN;
+            }
+        }
+        // `CONTACT_CONFIGURATION_UNKNOWN`, or any other value written to the user data of
+        // the manifold by a JS hook.
+        _ => {
+            if contact_is_ok {
+                // The contact is close enough to the allowed normal.
+                *user_data = CONTACT_CURRENTLY_ALLOWED;
+            } else {
+                // The contact normal isn't close enough to the allowed
+                // normal, so remove all the contacts and mark further contacts
+                // as forbidden.
+                solver_contacts.clear();
+
+                // NOTE: in some very rare cases `local_n` will be
+                // zero if the objects are exactly touching at one point.
+                // So in this case we can't really conclude.
+                // If the norm is non-zero, then we can tell we need to forbid
+                // further contacts. Otherwise we have to wait for the next frame.
+                if local_n.norm_squared() > 0.1 {
+                    *user_data = CONTACT_CURRENTLY_FORBIDDEN;
                 }
-                _ => unreachable!(),
             }
         }
     }
@@ -4,12 +4,58 @@ use crate::dynamics::{
 };
 use crate::geometry::{RawBroadPhase, RawColliderSet, RawNarrowPhase};
 use crate::math::RawVector;
-use crate::pipeline::{RawEventQueue, RawPhysicsHooks};
+use crate::pipeline::{NativePhysicsHooks, PhysicsHooksStats, RawEventQueue, RawPhysicsHooks};
+use crate::rapier::dynamics::{IntegrationParameters, IslandManager, RigidBodySet};
 use crate::rapier::pipeline::PhysicsPipeline;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use wasm_bindgen::prelude::*;
 
+/// The solver iterations configured for one timestep.
+///
+/// This is derived from the integration parameters and the active rigid-bodies only: Rapier
+/// doesn't report the iterations its solver ran. Each island is configured with
+/// `numSolverIterations` iterations, plus the largest number of additional solver iterations
+/// of its rigid-bodies.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SolverIterationConfig {
+    /// The largest number of solver iterations configured for an island.
+    pub max_configured_iterations: usize,
+    /// The number of active dynamic rigid-bodies requesting additional solver iterations.
+    pub num_bodies_with_additional_iterations: u32,
+}
+
+impl SolverIterationConfig {
+    fn from_configuration(
+        params: &IntegrationParameters,
+        islands: &IslandManager,
+        bodies: &RigidBodySet,
+    ) -> Self {
+        let mut config = SolverIterationConfig::default();
+        for rb in islands
+            .active_bodies()
+            .iter()
+            .filter_map(|handle| bodies.get(*handle))
+            .filter(|rb| rb.is_dynamic())
+        {
+            let additional_iterations = rb.additional_solver_iterations();
+            if additional_iterations > 0 {
+                config.num_bodies_with_additional_iterations += 1;
+            }
+            config.max_configured_iterations = config
+                .max_configured_iterations
+                .max(params.num_solver_iterations + additional_iterations);
+        }
+        config
+    }
+}
+
 #[wasm_bindgen]
-pub struct RawPhysicsPipeline(pub(crate) PhysicsPipeline);
+pub struct RawPhysicsPipeline {
+    pub(crate) pipeline: PhysicsPipeline,
+    pub(crate) hooks_stats: PhysicsHooksStats,
+    pub(crate) solver_iterations: SolverIterationConfig,
+}
 
 #[wasm_bindgen]
 impl RawPhysicsPipeline {
@@ -17,83 +63,131 @@ impl RawPhysicsPipeline {
     pub fn new() -> Self {
         let mut pipeline = PhysicsPipeline::new();
         pipeline.counters.disable(); // Disable perf counters by default.
-        RawPhysicsPipeline(pipeline)
+        RawPhysicsPipeline {
+            pipeline,
+            hooks_stats: PhysicsHooksStats::default(),
+            solver_iterations: SolverIterationConfig::default(),
+        }
     }
 
     pub fn set_profiler_enabled(&mut self, enabled: bool) {
         if enabled {
-            self.0.counters.enable();
+            self.pipeline.counters.enable();
         } else {
-            self.0.counters.disable();
+            self.pipeline.counters.disable();
         }
     }
 
     pub fn is_profiler_enabled(&self) -> bool {
-        self.0.counters.enabled()
+        self.pipeline.counters.enabled()
     }
 
     pub fn timing_step(&self) -> f64 {
-        self.0.counters.step_time_ms()
+        self.pipeline.counters.step_time_ms()
     }
 
     pub fn timing_collision_detection(&self) -> f64 {
-        self.0.counters.collision_detection_time_ms()
+        self.pipeline.counters.collision_detection_time_ms()
     }
 
     pub fn timing_broad_phase(&self) -> f64 {
-        self.0.counters.broad_phase_time_ms()
+        self.pipeline.counters.broad_phase_time_ms()
     }
 
     pub fn timing_narrow_phase(&self) -> f64 {
-        self.0.counters.narrow_phase_time_ms()
+        self.pipeline.counters.narrow_phase_time_ms()
     }
 
     pub fn timing_solver(&self) -> f64 {
-        self.0.counters.solver_time_ms()
+        self.pipeline.counters.solver_time_ms()
     }
 
     pub fn timing_velocity_assembly(&self) -> f64 {
-        self.0.counters.solver.velocity_assembly_time.time_ms()
+        self.pipeline
+            .counters
+            .solver
+            .velocity_assembly_time
+            .time_ms()
     }
 
     pub fn timing_velocity_resolution(&self) -> f64 {
-        self.0.counters.velocity_resolution_time_ms()
+        self.pipeline.counters.velocity_resolution_time_ms()
     }
 
     pub fn timing_velocity_update(&self) -> f64 {
-        self.0.counters.velocity_update_time_ms()
+        self.pipeline.counters.velocity_update_time_ms()
     }
 
     pub fn timing_velocity_writeback(&self) -> f64 {
-        self.0.counters.solver.velocity_writeback_time.time_ms()
+        self.pipeline
+            .counters
+            .solver
+            .velocity_writeback_time
+            .time_ms()
     }
 
     pub fn timing_ccd(&self) -> f64 {
-        self.0.counters.ccd_time_ms()
+        self.pipeline.counters.ccd_time_ms()
     }
 
     pub fn timing_ccd_toi_computation(&self) -> f64 {
-        self.0.counters.ccd.toi_computation_time.time_ms()
+        self.pipeline.counters.ccd.toi_computation_time.time_ms()
     }
 
     pub fn timing_ccd_broad_phase(&self) -> f64 {
-        self.0.counters.ccd.broad_phase_time.time_ms()
+        self.pipeline.counters.ccd.broad_phase_time.time_ms()
     }
 
     pub fn timing_ccd_narrow_phase(&self) -> f64 {
-        self.0.counters.ccd.narrow_phase_time.time_ms()
+        self.pipeline.counters.ccd.narrow_phase_time.time_ms()
     }
 
     pub fn timing_ccd_solver(&self) -> f64 {
-        self.0.counters.ccd.solver_time.time_ms()
+        self.pipeline.counters.ccd.solver_time.time_ms()
     }
 
     pub fn timing_island_construction(&self) -> f64 {
-        self.0.counters.island_construction_time_ms()
+        self.pipeline.counters.island_construction_time_ms()
     }
 
     pub fn timing_user_changes(&self) -> f64 {
-        self.0.counters.stages.user_changes.time_ms()
+        self.pipeline.counters.stages.user_changes.time_ms()
+    }
+
+    pub fn timing_hooks(&self) -> f64 {
+        self.hooks_stats.time_ms
+    }
+
+    pub fn hooks_num_contact_pairs_filtered(&self) -> u32 {
+        self.hooks_stats.contact_pairs_filtered
+    }
+
+    pub fn hooks_num_contact_pairs_passed(&self) -> u32 {
+        self.hooks_stats.contact_pairs_passed
+    }
+
+    pub fn hooks_num_intersection_pairs_filtered(&self) -> u32 {
+        self.hooks_stats.intersection_pairs_filtered
+    }
+
+    pub fn hooks_num_intersection_pairs_passed(&self) -> u32 {
+        self.hooks_stats.intersection_pairs_passed
+    }
+
+    pub fn hooks_num_contact_pairs_modified(&self) -> u32 {
+        self.hooks_stats.contact_pairs_modified
+    }
+
+    /// The largest number of solver iterations configured for an island during the last
+    /// timestep. This is the configuration, not a measurement of the solver.
+    pub fn solver_max_configured_iterations(&self) -> usize {
+        self.solver_iterations.max_configured_iterations
+    }
+
+    /// The number of active dynamic rigid-bodies that requested additional solver iterations
+    /// during the last timestep.
+    pub fn solver_num_bodies_with_additional_iterations(&self) -> u32 {
+        self.solver_iterations.num_bodies_with_additional_iterations
     }
 
     pub fn step(
@@ -109,20 +203,36 @@ impl RawPhysicsPipeline {
         articulations: &mut RawMultibodyJointSet,
         ccd_solver: &mut RawCCDSolver,
     ) {
-        self.0.step(
+        self.hooks_stats = PhysicsHooksStats::default();
+        colliders.set_restitution_threshold(integrationParameters.restitution_threshold);
+        integrationParameters.resting_jitter.apply(
+            &mut bodies.set,
+            &islands.0,
+            integrationParameters.params.length_unit,
+        );
+        joints.update_motor_targets(integrationParameters.params.dt);
+        narrowPhase.invalidate_borrows();
+        self.pipeline.step(
             &gravity.0,
-            &integrationParameters.0,
+            &integrationParameters.params,
             &mut islands.0,
-            &mut broadPhase.0,
-            &mut narrowPhase.0,
-            &mut bodies.0,
-            &mut colliders.0,
-            &mut joints.0,
+            &mut broadPhase.broad_phase,
+            &mut narrowPhase.narrow_phase,
+            &mut bodies.set,
+            &mut colliders.set,
+            &mut joints.set,
             &mut articulations.0,
             &mut ccd_solver.0,
-            &(),
+            &NativePhysicsHooks(&colliders.native_hooks),
             &(),
         );
+        colliders.end_step(&narrowPhase.narrow_phase);
+        bodies.lifetimes.num_steps = bodies.lifetimes.num_steps.wrapping_add(1);
+        self.solver_iterations = SolverIterationConfig::from_configuration(
+            &integrationParameters.params,
+            &islands.0,
+            &bodies.set,
+        );
     }
 
     pub fn stepWithEvents(
@@ -142,31 +252,63 @@ impl RawPhysicsPipeline {
         hookFilterContactPair: js_sys::Function,
         hookFilterIntersectionPair: js_sys::Function,
         hookModifySolverContacts: Option<js_sys::Function>,
-    ) {
+        hookFilterContactPairs: Option<js_sys::Function>,
+    ) -> Result<(), JsValue> {
         if eventQueue.auto_drain {
             eventQueue.clear();
         }
 
-        let hooks = RawPhysicsHooks {
+        colliders.set_restitution_threshold(integrationParameters.restitution_threshold);
+        let mut hooks = RawPhysicsHooks {
             this: hookObject,
             filter_contact_pair: hookFilterContactPair,
             filter_intersection_pair: hookFilterIntersectionPair,
             modify_solver_contacts: hookModifySolverContacts,
+            filter_contact_pairs: hookFilterContactPairs,
+            contact_pair_flags: HashMap::new(),
+            native_hooks: &colliders.native_hooks,
+            stats: Cell::new(PhysicsHooksStats::default()),
+            error: Cell::new(None),
         };
+        hooks.prefilter_contact_pairs(&narrowPhase.narrow_phase, &colliders.set);
 
-        self.0.step(
+        integrationParameters.resting_jitter.apply(
+            &mut bodies.set,
+            &islands.0,
+            integrationParameters.params.length_unit,
+        );
+        let active_before: HashSet<_> = islands.0.active_bodies().iter().copied().collect();
+        joints.update_motor_targets(integrationParameters.params.dt);
+        narrowPhase.invalidate_borrows();
+        let events =
+            eventQueue.substep_collector(&integrationParameters.params, &islands.0, &bodies.set);
+        self.pipeline.step(
             &gravity.0,
-            &integrationParameters.0,
+            &integrationParameters.params,
             &mut islands.0,
-            &mut broadPhase.0,
-            &mut narrowPhase.0,
-            &mut bodies.0,
-            &mut colliders.0,
-            &mut joints.0,
+            &mut broadPhase.broad_phase,
+            &mut narrowPhase.narrow_phase,
+            &mut bodies.set,
+            &mut colliders.set,
+            &mut joints.set,
             &mut articulations.0,
             &mut ccd_solver.0,
             &hooks,
-            &eventQueue.collector,
+            &events,
+        );
+        let substeps = events.into_collision_substeps();
+        eventQueue.end_step(&substeps);
+        eventQueue.record_sleep_events(&active_before, &islands.0, bodies);
+        bodies.lifetimes.num_steps = bodies.lifetimes.num_steps.wrapping_add(1);
+        self.solver_iterations = SolverIterationConfig::from_configuration(
+            &integrationParameters.params,
+            &islands.0,
+            &bodies.set,
         );
+
+        self.hooks_stats = hooks.stats.get();
+        let error = hooks.error.take();
+        colliders.end_step(&narrowPhase.narrow_phase);
+        error.map_or(Ok(()), Err)
     }
 }
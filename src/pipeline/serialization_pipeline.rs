@@ -1,17 +1,33 @@
 use crate::dynamics::{
-    RawImpulseJointSet, RawIntegrationParameters, RawIslandManager, RawMultibodyJointSet,
-    RawRigidBodySet,
+    BodyLifetimes, MotorTarget, RawImpulseJointSet, RawIntegrationParameters, RawIslandManager,
+    RawMultibodyJointSet, RawRigidBodySet, RestingJitter,
+};
+use crate::geometry::{
+    AccumulatedImpulse, QueryStats, RawBroadPhase, RawColliderSet, RawNarrowPhase,
 };
-use crate::geometry::{RawBroadPhase, RawColliderSet, RawNarrowPhase};
 use crate::math::RawVector;
+use crate::pipeline::{NativeHooks, RawSimulationLod, SimulationTiers};
+use crate::utils::Liveness;
 use js_sys::Uint8Array;
 use rapier::dynamics::{
-    ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet, RigidBodySet,
+    ImpulseJointHandle, ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet,
+    RigidBodyBuilder, RigidBodyHandle, RigidBodySet,
+};
+use rapier::geometry::{
+    Collider, ColliderBuilder, ColliderHandle, ColliderSet, DefaultBroadPhase, NarrowPhase,
 };
-use rapier::geometry::{ColliderSet, DefaultBroadPhase, NarrowPhase};
-use rapier::math::Vector;
+use rapier::math::{Real, Vector};
+use std::collections::HashMap;
+use std::convert::TryInto;
 use wasm_bindgen::prelude::*;
 
+/// The bytes starting all the snapshots, followed by the version of their format.
+///
+/// The snapshots written before the format was versioned start directly with the gravity.
+const SNAPSHOT_MAGIC: [u8; 4] = *b"RJSS";
+/// The version of the snapshot format, to be increased whenever the serialized state changes.
+const SNAPSHOT_VERSION: u32 = 1;
+
 #[derive(Serialize)]
 struct SerializableWorld<'a> {
     gravity: &'a Vector<f32>,
@@ -23,6 +39,59 @@ struct SerializableWorld<'a> {
     colliders: &'a ColliderSet,
     impulse_joints: &'a ImpulseJointSet,
     multibody_joints: &'a MultibodyJointSet,
+    // The state of the behaviors implemented on top of Rapier.
+    resting_jitter: &'a RestingJitter,
+    restitution_threshold: Real,
+    body_lifetimes: &'a BodyLifetimes,
+    world_anchor: Option<RigidBodyHandle>,
+    collider_creation_steps: &'a HashMap<ColliderHandle, u32>,
+    native_hooks: &'a NativeHooks,
+    accumulated_impulses: &'a HashMap<ColliderHandle, AccumulatedImpulse>,
+    motor_targets: &'a HashMap<(ImpulseJointHandle, usize), MotorTarget>,
+    simulation_tiers: &'a SimulationTiers,
+}
+
+impl<'a> SerializableWorld<'a> {
+    fn new(
+        gravity: &'a RawVector,
+        integrationParameters: &'a RawIntegrationParameters,
+        islands: &'a RawIslandManager,
+        broadPhase: &'a RawBroadPhase,
+        narrowPhase: &'a RawNarrowPhase,
+        bodies: &'a RawRigidBodySet,
+        colliders: &'a RawColliderSet,
+        impulse_joints: &'a RawImpulseJointSet,
+        multibody_joints: &'a RawMultibodyJointSet,
+        simulationLod: &'a RawSimulationLod,
+    ) -> Self {
+        SerializableWorld {
+            gravity: &gravity.0,
+            integration_parameters: &integrationParameters.params,
+            islands: &islands.0,
+            broad_phase: &broadPhase.broad_phase,
+            narrow_phase: &narrowPhase.narrow_phase,
+            bodies: &bodies.set,
+            colliders: &colliders.set,
+            impulse_joints: &impulse_joints.set,
+            multibody_joints: &multibody_joints.0,
+            resting_jitter: &integrationParameters.resting_jitter,
+            restitution_threshold: integrationParameters.restitution_threshold,
+            body_lifetimes: &bodies.lifetimes,
+            world_anchor: bodies.world_anchor,
+            collider_creation_steps: &colliders.creation_steps,
+            native_hooks: &colliders.native_hooks,
+            accumulated_impulses: &colliders.accumulated_impulses,
+            motor_targets: &impulse_joints.motor_targets,
+            simulation_tiers: &simulationLod.tiers,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SerializableWorldWithoutStaticGeometry<'a> {
+    world: SerializableWorld<'a>,
+    static_bodies: &'a [RigidBodyHandle],
+    static_colliders: &'a [ColliderHandle],
 }
 
 #[derive(Deserialize)]
@@ -36,6 +105,109 @@ struct DeserializableWorld {
     colliders: ColliderSet,
     impulse_joints: ImpulseJointSet,
     multibody_joints: MultibodyJointSet,
+    resting_jitter: RestingJitter,
+    restitution_threshold: Real,
+    body_lifetimes: BodyLifetimes,
+    world_anchor: Option<RigidBodyHandle>,
+    collider_creation_steps: HashMap<ColliderHandle, u32>,
+    native_hooks: NativeHooks,
+    accumulated_impulses: HashMap<ColliderHandle, AccumulatedImpulse>,
+    motor_targets: HashMap<(ImpulseJointHandle, usize), MotorTarget>,
+    simulation_tiers: SimulationTiers,
+}
+
+/// A snapshot written before the format was versioned, i.e., without the state of the
+/// behaviors implemented on top of Rapier.
+#[derive(Deserialize)]
+struct LegacyDeserializableWorld {
+    gravity: Vector<f32>,
+    integration_parameters: IntegrationParameters,
+    islands: IslandManager,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+}
+
+impl From<LegacyDeserializableWorld> for DeserializableWorld {
+    fn from(d: LegacyDeserializableWorld) -> Self {
+        let defaults = RawIntegrationParameters::new();
+        let mut native_hooks = NativeHooks::default();
+        native_hooks.bouncy_colliders = d
+            .colliders
+            .iter()
+            .filter(|(_, co)| co.material().restitution > 0.0)
+            .map(|(handle, _)| handle)
+            .collect();
+        DeserializableWorld {
+            gravity: d.gravity,
+            integration_parameters: d.integration_parameters,
+            islands: d.islands,
+            broad_phase: d.broad_phase,
+            narrow_phase: d.narrow_phase,
+            bodies: d.bodies,
+            colliders: d.colliders,
+            impulse_joints: d.impulse_joints,
+            multibody_joints: d.multibody_joints,
+            resting_jitter: defaults.resting_jitter,
+            restitution_threshold: defaults.restitution_threshold,
+            body_lifetimes: BodyLifetimes::default(),
+            world_anchor: None,
+            collider_creation_steps: HashMap::new(),
+            native_hooks,
+            accumulated_impulses: HashMap::new(),
+            motor_targets: HashMap::new(),
+            simulation_tiers: SimulationTiers::default(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct DeserializableWorldWithoutStaticGeometry {
+    world: DeserializableWorld,
+    static_bodies: Vec<RigidBodyHandle>,
+    static_colliders: Vec<ColliderHandle>,
+}
+
+impl From<DeserializableWorld> for RawDeserializedWorld {
+    fn from(d: DeserializableWorld) -> Self {
+        RawDeserializedWorld {
+            gravity: Some(RawVector(d.gravity)),
+            integrationParameters: Some(RawIntegrationParameters {
+                params: d.integration_parameters,
+                resting_jitter: d.resting_jitter,
+                restitution_threshold: d.restitution_threshold,
+            }),
+            islands: Some(RawIslandManager(d.islands)),
+            broadPhase: Some(RawBroadPhase {
+                broad_phase: d.broad_phase,
+                query_stats: QueryStats::default(),
+            }),
+            narrowPhase: Some(RawNarrowPhase {
+                narrow_phase: d.narrow_phase,
+                liveness: Liveness::new(),
+            }),
+            bodies: Some(RawRigidBodySet {
+                set: d.bodies,
+                lifetimes: d.body_lifetimes,
+                world_anchor: d.world_anchor,
+            }),
+            colliders: Some(RawColliderSet {
+                set: d.colliders,
+                creation_steps: d.collider_creation_steps,
+                native_hooks: d.native_hooks,
+                accumulated_impulses: d.accumulated_impulses,
+            }),
+            impulse_joints: Some(RawImpulseJointSet {
+                set: d.impulse_joints,
+                motor_targets: d.motor_targets,
+            }),
+            multibody_joints: Some(RawMultibodyJointSet(d.multibody_joints)),
+            simulation_lod: Some(RawSimulationLod::from_tiers(d.simulation_tiers)),
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -49,6 +221,7 @@ pub struct RawDeserializedWorld {
     colliders: Option<RawColliderSet>,
     impulse_joints: Option<RawImpulseJointSet>,
     multibody_joints: Option<RawMultibodyJointSet>,
+    simulation_lod: Option<RawSimulationLod>,
 }
 
 #[wasm_bindgen]
@@ -88,6 +261,74 @@ impl RawDeserializedWorld {
     pub fn takeMultibodyJoints(&mut self) -> Option<RawMultibodyJointSet> {
         self.multibody_joints.take()
     }
+
+    pub fn takeSimulationLod(&mut self) -> Option<RawSimulationLod> {
+        self.simulation_lod.take()
+    }
+}
+
+#[cfg(feature = "compression")]
+fn compress_snapshot(snap: &[u8]) -> Option<Vec<u8>> {
+    Some(lz4_flex::compress_prepend_size(snap))
+}
+
+#[cfg(feature = "compression")]
+fn decompress_snapshot(data: &[u8]) -> Option<Vec<u8>> {
+    lz4_flex::decompress_size_prepended(data).ok()
+}
+
+#[cfg(not(feature = "compression"))]
+fn compress_snapshot(_: &[u8]) -> Option<Vec<u8>> {
+    None
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress_snapshot(_: &[u8]) -> Option<Vec<u8>> {
+    None
+}
+
+/// Serializes a snapshot, prefixed with the magic bytes and the version of the format.
+fn serialize_snapshot<T: serde::Serialize>(world: &T) -> Option<Vec<u8>> {
+    let mut snap = SNAPSHOT_MAGIC.to_vec();
+    snap.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+    bincode::serialize_into(&mut snap, world).ok()?;
+    Some(snap)
+}
+
+/// Splits the format version from the serialized state of a snapshot.
+///
+/// The version of the snapshots written before the format was versioned is 0.
+fn split_snapshot_version(snap: &[u8]) -> (u32, &[u8]) {
+    match snap.strip_prefix(&SNAPSHOT_MAGIC[..]) {
+        Some(rest) if rest.len() >= 4 => {
+            let (version, rest) = rest.split_at(4);
+            (u32::from_le_bytes(version.try_into().unwrap()), rest)
+        }
+        _ => (0, snap),
+    }
+}
+
+/// Converts a serialized snapshot into a byte array, compressing it with LZ4 if `compress`
+/// is `true`.
+///
+/// Returns `None` if compression is requested but the `compression` feature is disabled.
+fn encode_snapshot(snap: Vec<u8>, compress: bool) -> Option<Uint8Array> {
+    let snap = if compress {
+        compress_snapshot(&snap)?
+    } else {
+        snap
+    };
+    Some(Uint8Array::from(&snap[..]))
+}
+
+/// Retrieves the serialized snapshot from a byte array produced by `encode_snapshot`.
+fn decode_snapshot(data: Uint8Array, compressed: bool) -> Option<Vec<u8>> {
+    let data = data.to_vec();
+    if compressed {
+        decompress_snapshot(&data)
+    } else {
+        Some(data)
+    }
 }
 
 #[wasm_bindgen]
@@ -100,6 +341,14 @@ impl RawSerializationPipeline {
         RawSerializationPipeline
     }
 
+    /// Can snapshots be compressed, i.e., was this module built with the `compression` feature?
+    pub fn compressionSupported() -> bool {
+        cfg!(feature = "compression")
+    }
+
+    /// Serializes the physics state, compressing it with LZ4 if `compress` is `true`.
+    ///
+    /// Returns `None` if compression is requested but not supported by this module.
     pub fn serializeAll(
         &self,
         gravity: &RawVector,
@@ -111,35 +360,203 @@ impl RawSerializationPipeline {
         colliders: &RawColliderSet,
         impulse_joints: &RawImpulseJointSet,
         multibody_joints: &RawMultibodyJointSet,
+        simulationLod: &RawSimulationLod,
+        compress: bool,
     ) -> Option<Uint8Array> {
-        let to_serialize = SerializableWorld {
-            gravity: &gravity.0,
-            integration_parameters: &integrationParameters.0,
-            islands: &islands.0,
-            broad_phase: &broadPhase.0,
-            narrow_phase: &narrowPhase.0,
-            bodies: &bodies.0,
-            colliders: &colliders.0,
-            impulse_joints: &impulse_joints.0,
-            multibody_joints: &multibody_joints.0,
+        let to_serialize = SerializableWorld::new(
+            gravity,
+            integrationParameters,
+            islands,
+            broadPhase,
+            narrowPhase,
+            bodies,
+            colliders,
+            impulse_joints,
+            multibody_joints,
+            simulationLod,
+        );
+        let snap = serialize_snapshot(&to_serialize)?;
+        encode_snapshot(snap, compress)
+    }
+
+    /// Deserializes a physics state serialized with `serializeAll`, including the snapshots
+    /// written by previous versions of this module.
+    ///
+    /// The state of the behaviors implemented on top of Rapier, e.g., the resting jitter or the
+    /// native physics hooks, wasn't saved before the snapshot format was versioned: it is reset
+    /// to its default when reading these snapshots.
+    pub fn deserializeAll(
+        &self,
+        data: Uint8Array,
+        compressed: bool,
+    ) -> Option<RawDeserializedWorld> {
+        let data = decode_snapshot(data, compressed)?;
+        let d: DeserializableWorld = match split_snapshot_version(&data) {
+            (SNAPSHOT_VERSION, snap) => bincode::deserialize(snap).ok()?,
+            (0, snap) => bincode::deserialize::<LegacyDeserializableWorld>(snap)
+                .ok()?
+                .into(),
+            _ => return None,
         };
-        let snap = bincode::serialize(&to_serialize).ok()?;
-        Some(Uint8Array::from(&snap[..]))
+        Some(d.into())
     }
 
-    pub fn deserializeAll(&self, data: Uint8Array) -> Option<RawDeserializedWorld> {
-        let data = data.to_vec();
-        let d: DeserializableWorld = bincode::deserialize(&data[..]).ok()?;
-        Some(RawDeserializedWorld {
-            gravity: Some(RawVector(d.gravity)),
-            integrationParameters: Some(RawIntegrationParameters(d.integration_parameters)),
-            islands: Some(RawIslandManager(d.islands)),
-            broadPhase: Some(RawBroadPhase(d.broad_phase)),
-            narrowPhase: Some(RawNarrowPhase(d.narrow_phase)),
-            bodies: Some(RawRigidBodySet(d.bodies)),
-            colliders: Some(RawColliderSet(d.colliders)),
-            impulse_joints: Some(RawImpulseJointSet(d.impulse_joints)),
-            multibody_joints: Some(RawMultibodyJointSet(d.multibody_joints)),
-        })
+    /// Serializes the physics state, except for the fixed rigid-bodies and the static colliders.
+    ///
+    /// A collider is static if it isn't attached to any rigid-body, or if it is attached
+    /// to a fixed rigid-body. Only the handles of the fixed rigid-bodies and static colliders
+    /// are recorded: they are saved as empty placeholders, their leaves are removed from the
+    /// broad-phase, and the static colliders that only interact with other static colliders
+    /// are removed from the narrow-phase. They must be provided again with
+    /// `deserializeAllWithStaticGeometry`. The contacts between static and non-static
+    /// colliders are saved, since they are part of the state of the non-static rigid-bodies.
+    /// The result is compressed with LZ4 if `compress` is `true`.
+    pub fn serializeAllWithoutStaticGeometry(
+        &self,
+        gravity: &RawVector,
+        integrationParameters: &RawIntegrationParameters,
+        islands: &RawIslandManager,
+        broadPhase: &RawBroadPhase,
+        narrowPhase: &RawNarrowPhase,
+        bodies: &RawRigidBodySet,
+        colliders: &RawColliderSet,
+        impulse_joints: &RawImpulseJointSet,
+        multibody_joints: &RawMultibodyJointSet,
+        simulationLod: &RawSimulationLod,
+        compress: bool,
+    ) -> Option<Uint8Array> {
+        // The world anchor is kept, since it doesn't exist in worlds without world constraints.
+        let static_bodies: Vec<_> = bodies
+            .set
+            .iter()
+            .filter(|(handle, rb)| rb.is_fixed() && Some(*handle) != bodies.world_anchor)
+            .map(|(handle, _)| handle)
+            .collect();
+        let is_static = |co: &Collider| {
+            co.parent()
+                .and_then(|parent| bodies.set.get(parent))
+                .map(|rb| rb.is_fixed())
+                .unwrap_or(true)
+        };
+        let static_colliders: Vec<_> = colliders
+            .set
+            .iter()
+            .filter(|(_, co)| is_static(co))
+            .map(|(handle, _)| handle)
+            .collect();
+
+        // The shapes are reference-counted so cloning the sets is cheap.
+        let mut stripped_bodies = bodies.set.clone();
+        for handle in &static_bodies {
+            if let Some(rb) = stripped_bodies.get_mut(*handle) {
+                *rb = RigidBodyBuilder::fixed().build();
+            }
+        }
+        let mut stripped_colliders = colliders.set.clone();
+        for handle in &static_colliders {
+            if let Some(co) = stripped_colliders.get_mut(*handle) {
+                *co = ColliderBuilder::ball(0.0).build();
+            }
+        }
+
+        let params = &integrationParameters.params;
+        let mut stripped_broad_phase = broadPhase.broad_phase.clone();
+        stripped_broad_phase.update(
+            params,
+            &colliders.set,
+            &bodies.set,
+            &[],
+            &static_colliders,
+            &mut vec![],
+        );
+
+        // The pairs of a static collider are added again by the broad-phase after the snapshot
+        // is restored, so the static colliders are removed from the narrow-phase unless they
+        // interact with non-static colliders, or touch static colliders.
+        let is_kept_pair = |h1: ColliderHandle, h2: ColliderHandle, touching| {
+            let other1 = colliders.set.get(h1).is_none_or(|co| !is_static(co));
+            let other2 = colliders.set.get(h2).is_none_or(|co| !is_static(co));
+            other1 || other2 || touching
+        };
+        let narrow_phase = &narrowPhase.narrow_phase;
+        let isolated_colliders: Vec<_> = static_colliders
+            .iter()
+            .copied()
+            .filter(|handle| {
+                !narrow_phase.contact_pairs_with(*handle).any(|pair| {
+                    is_kept_pair(pair.collider1, pair.collider2, pair.has_any_active_contact)
+                }) && !narrow_phase
+                    .intersection_pairs_with(*handle)
+                    .any(|(h1, h2, intersecting)| is_kept_pair(h1, h2, intersecting))
+            })
+            .collect();
+        let mut stripped_narrow_phase = narrow_phase.clone();
+        stripped_narrow_phase.handle_user_changes(
+            None,
+            &[],
+            &isolated_colliders,
+            &mut stripped_colliders,
+            &mut stripped_bodies,
+            &(),
+        );
+
+        let mut world = SerializableWorld::new(
+            gravity,
+            integrationParameters,
+            islands,
+            broadPhase,
+            narrowPhase,
+            bodies,
+            colliders,
+            impulse_joints,
+            multibody_joints,
+            simulationLod,
+        );
+        world.bodies = &stripped_bodies;
+        world.colliders = &stripped_colliders;
+        world.broad_phase = &stripped_broad_phase;
+        world.narrow_phase = &stripped_narrow_phase;
+        let to_serialize = SerializableWorldWithoutStaticGeometry {
+            world,
+            static_bodies: &static_bodies,
+            static_colliders: &static_colliders,
+        };
+        let snap = serialize_snapshot(&to_serialize)?;
+        encode_snapshot(snap, compress)
+    }
+
+    /// Deserializes a physics state serialized with `serializeAllWithoutStaticGeometry`.
+    ///
+    /// The fixed rigid-bodies and static colliders are copied from the ones with the same
+    /// handles in `staticBodies` and `staticColliders`, and the static colliders are inserted
+    /// again into the broad-phase. Returns `None` if one of them doesn't exist.
+    pub fn deserializeAllWithStaticGeometry(
+        &self,
+        data: Uint8Array,
+        staticBodies: &RawRigidBodySet,
+        staticColliders: &RawColliderSet,
+        compressed: bool,
+    ) -> Option<RawDeserializedWorld> {
+        let data = decode_snapshot(data, compressed)?;
+        let mut d: DeserializableWorldWithoutStaticGeometry = match split_snapshot_version(&data) {
+            (SNAPSHOT_VERSION, snap) => bincode::deserialize(snap).ok()?,
+            _ => return None,
+        };
+
+        for handle in d.static_bodies {
+            let rb = staticBodies.set.get(handle)?.clone();
+            *d.world.bodies.get_mut(handle)? = rb;
+        }
+        for handle in d.static_colliders {
+            let co = staticColliders.set.get(handle)?.clone();
+            let aabb =
+                co.compute_broad_phase_aabb(&d.world.integration_parameters, &d.world.bodies);
+            *d.world.colliders.get_mut(handle)? = co;
+            d.world
+                .broad_phase
+                .set_aabb(&d.world.integration_parameters, handle, aabb);
+        }
+
+        Some(d.world.into())
     }
 }
@@ -0,0 +1,276 @@
+use crate::dynamics::{RawIntegrationParameters, RawRigidBodySet};
+use crate::utils::{self, FlatHandle};
+use rapier::dynamics::{RigidBodyHandle, RigidBodyType, RigidBodyVelocity};
+use rapier::math::Real;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// The level of detail of the simulation of a rigid-body.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RawSimulationTier {
+    Full,
+    Reduced,
+    Frozen,
+}
+
+/// The state of a rigid-body that isn't in the `Full` tier, with the properties its tier
+/// overrides.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+enum TierState {
+    Reduced {
+        // The sleep thresholds of the body before it entered the tier.
+        linear_threshold: Real,
+        angular_threshold: Real,
+    },
+    Frozen {
+        // The type and velocities of the body before it was locked.
+        body_type: RigidBodyType,
+        vels: RigidBodyVelocity<Real>,
+    },
+}
+
+impl TierState {
+    fn tier(&self) -> RawSimulationTier {
+        match self {
+            TierState::Reduced { .. } => RawSimulationTier::Reduced,
+            TierState::Frozen { .. } => RawSimulationTier::Frozen,
+        }
+    }
+}
+
+/// The simulation tiers of the rigid-bodies, saved in snapshots.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SimulationTiers {
+    // The bodies that are not in the `Full` tier.
+    bodies: HashMap<RigidBodyHandle, TierState>,
+    reduced_iterations: usize,
+    reduced_sleep_threshold_scale: Real,
+}
+
+impl Default for SimulationTiers {
+    fn default() -> Self {
+        Self {
+            bodies: HashMap::new(),
+            reduced_iterations: 1,
+            reduced_sleep_threshold_scale: 2.0,
+        }
+    }
+}
+
+/// The solver settings modified by `beginStep`, restored by `endStep`.
+struct ScaledStep {
+    num_solver_iterations: usize,
+    // The additional solver iterations of the boosted `Full` bodies before the step.
+    boosted: Vec<(RigidBodyHandle, usize)>,
+}
+
+/// Assigns a simulation tier to rigid-bodies, e.g., to simulate distant debris more cheaply.
+///
+/// Bodies are in the `Full` tier unless assigned another tier. While some bodies are in the
+/// `Reduced` tier, the solver runs `reducedTierSolverIterations` iterations, and the dynamic
+/// `Full` bodies get additional iterations making up for the difference with the configured
+/// number of solver iterations. The sleep thresholds of the `Reduced` bodies are also scaled
+/// by `reducedTierSleepThresholdScale`. `Frozen` bodies are locked as fixed bodies until they
+/// are assigned another tier.
+#[wasm_bindgen]
+pub struct RawSimulationLod {
+    pub(crate) tiers: SimulationTiers,
+    scaled_step: Option<ScaledStep>,
+}
+
+impl RawSimulationLod {
+    pub(crate) fn from_tiers(tiers: SimulationTiers) -> Self {
+        Self {
+            tiers,
+            scaled_step: None,
+        }
+    }
+
+    /// Replaces the handles of the rigid-bodies by the ones they are mapped to, forgetting the
+    /// rigid-bodies that aren't mapped.
+    pub(crate) fn remap(&mut self, bodies: &HashMap<RigidBodyHandle, RigidBodyHandle>) {
+        utils::remap_keys(&mut self.tiers.bodies, bodies);
+    }
+}
+
+#[wasm_bindgen]
+impl RawSimulationLod {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::from_tiers(SimulationTiers::default())
+    }
+
+    pub fn reducedTierSolverIterations(&self) -> usize {
+        self.tiers.reduced_iterations
+    }
+
+    pub fn setReducedTierSolverIterations(&mut self, iters: usize) {
+        self.tiers.reduced_iterations = iters.max(1);
+    }
+
+    pub fn reducedTierSleepThresholdScale(&self) -> Real {
+        self.tiers.reduced_sleep_threshold_scale
+    }
+
+    /// Sets the factor applied to the sleep thresholds of the `Reduced` bodies, including the
+    /// ones already in that tier.
+    pub fn setReducedTierSleepThresholdScale(&mut self, bodies: &mut RawRigidBodySet, scale: Real) {
+        let scale = scale.max(0.0);
+        self.tiers.reduced_sleep_threshold_scale = scale;
+        for (handle, state) in &self.tiers.bodies {
+            if let TierState::Reduced {
+                linear_threshold,
+                angular_threshold,
+            } = *state
+            {
+                if let Some(rb) = bodies.set.get_mut(*handle) {
+                    let activation = rb.activation_mut();
+                    activation.normalized_linear_threshold = linear_threshold * scale;
+                    activation.angular_threshold = angular_threshold * scale;
+                }
+            }
+        }
+    }
+
+    /// The simulation tier of the given rigid-body.
+    pub fn simulationTier(&self, handle: FlatHandle) -> RawSimulationTier {
+        self.tiers
+            .bodies
+            .get(&utils::body_handle(handle))
+            .map(|state| state.tier())
+            .unwrap_or(RawSimulationTier::Full)
+    }
+
+    /// Assigns a simulation tier to the given rigid-body.
+    ///
+    /// The properties overridden by the previous tier of the rigid-body are restored first:
+    /// its sleep thresholds when leaving the `Reduced` tier, and its type and velocities when
+    /// leaving the `Frozen` tier, unless its type was changed while it was frozen. Does nothing
+    /// if the rigid-body doesn't exist.
+    pub fn setSimulationTier(
+        &mut self,
+        bodies: &mut RawRigidBodySet,
+        handle: FlatHandle,
+        tier: RawSimulationTier,
+    ) {
+        let handle = utils::body_handle(handle);
+        let rb = match bodies.set.get_mut(handle) {
+            Some(rb) => rb,
+            None => return,
+        };
+        let prev = self.tiers.bodies.get(&handle).map(|state| state.tier());
+        if prev.unwrap_or(RawSimulationTier::Full) == tier {
+            return;
+        }
+
+        match self.tiers.bodies.remove(&handle) {
+            Some(TierState::Reduced {
+                linear_threshold,
+                angular_threshold,
+            }) => {
+                let activation = rb.activation_mut();
+                activation.normalized_linear_threshold = linear_threshold;
+                activation.angular_threshold = angular_threshold;
+            }
+            Some(TierState::Frozen { body_type, vels }) if rb.is_fixed() => {
+                rb.set_body_type(body_type, true);
+                rb.set_vels(vels, true);
+            }
+            _ => {}
+        }
+
+        let state = match tier {
+            RawSimulationTier::Full => return,
+            RawSimulationTier::Reduced => {
+                let scale = self.tiers.reduced_sleep_threshold_scale;
+                let activation = rb.activation_mut();
+                let state = TierState::Reduced {
+                    linear_threshold: activation.normalized_linear_threshold,
+                    angular_threshold: activation.angular_threshold,
+                };
+                activation.normalized_linear_threshold *= scale;
+                activation.angular_threshold *= scale;
+                state
+            }
+            RawSimulationTier::Frozen => {
+                let state = TierState::Frozen {
+                    body_type: rb.body_type(),
+                    vels: *rb.vels(),
+                };
+                rb.set_body_type(RigidBodyType::Fixed, false);
+                state
+            }
+        };
+        self.tiers.bodies.insert(handle, state);
+    }
+
+    /// The number of rigid-bodies assigned to the given tier, excluding the bodies
+    /// of the `Full` tier that were never assigned a tier explicitly.
+    pub fn numBodiesInTier(&self, tier: RawSimulationTier) -> usize {
+        self.tiers
+            .bodies
+            .values()
+            .filter(|state| state.tier() == tier)
+            .count()
+    }
+
+    /// Lowers the solver iterations for the next step if some rigid-bodies are in the
+    /// `Reduced` tier, and forgets the rigid-bodies that no longer exist.
+    ///
+    /// The number of solver iterations is set to `reducedTierSolverIterations`, and all the
+    /// dynamic `Full` bodies get the difference as additional solver iterations, including the
+    /// sleeping ones since they may be woken up during the step. Since the solver iterations
+    /// are chosen per island, a `Reduced` body touching a `Full` body is solved with the
+    /// iterations of the `Full` body. This must be followed by `endStep` once the step is done.
+    pub fn beginStep(
+        &mut self,
+        params: &mut RawIntegrationParameters,
+        bodies: &mut RawRigidBodySet,
+    ) {
+        if self.tiers.bodies.is_empty() {
+            return;
+        }
+
+        let set = &bodies.set;
+        self.tiers.bodies.retain(|handle, _| set.contains(*handle));
+        let num_reduced = self.numBodiesInTier(RawSimulationTier::Reduced);
+
+        let num_solver_iterations = params.params.num_solver_iterations;
+        let reduced_iterations = self.tiers.reduced_iterations;
+        if num_reduced == 0 || reduced_iterations >= num_solver_iterations {
+            return;
+        }
+
+        let extra_iterations = num_solver_iterations - reduced_iterations;
+        let boosted: Vec<_> = bodies
+            .set
+            .iter()
+            .filter(|(handle, rb)| rb.is_dynamic() && !self.tiers.bodies.contains_key(handle))
+            .map(|(handle, rb)| (handle, rb.additional_solver_iterations()))
+            .collect();
+        for (handle, additional_iterations) in &boosted {
+            if let Some(rb) = bodies.set.get_mut(*handle) {
+                rb.set_additional_solver_iterations(additional_iterations + extra_iterations);
+            }
+        }
+
+        params.params.num_solver_iterations = reduced_iterations;
+        self.scaled_step = Some(ScaledStep {
+            num_solver_iterations,
+            boosted,
+        });
+    }
+
+    /// Restores the solver iterations modified by `beginStep`.
+    pub fn endStep(&mut self, params: &mut RawIntegrationParameters, bodies: &mut RawRigidBodySet) {
+        if let Some(scaled_step) = self.scaled_step.take() {
+            params.params.num_solver_iterations = scaled_step.num_solver_iterations;
+            for (handle, additional_iterations) in scaled_step.boosted {
+                if let Some(rb) = bodies.set.get_mut(handle) {
+                    rb.set_additional_solver_iterations(additional_iterations);
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,11 @@
 use rapier::data::Index;
 use rapier::dynamics::{ImpulseJointHandle, MultibodyJointHandle, RigidBodyHandle};
 use rapier::geometry::{Collider, ColliderHandle};
+use rapier::math::{Isometry, Real};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
 use wasm_bindgen::JsValue;
 
 pub type FlatHandle = f64;
@@ -31,6 +36,55 @@ pub fn flat_handle(id: Index) -> FlatHandle {
     FlatHandle::from_bits(i as u64 | ((g as u64) << 32))
 }
 
+/// Appends the translation then the rotation (angle in 2D, quaternion coordinates
+/// `[x, y, z, w]` in 3D) of `pos` to `out`.
+pub(crate) fn push_isometry(out: &mut Vec<f64>, pos: &Isometry<Real>) {
+    out.extend(pos.translation.vector.iter().map(|x| *x as f64));
+    #[cfg(feature = "dim2")]
+    out.push(pos.rotation.angle() as f64);
+    #[cfg(feature = "dim3")]
+    out.extend(pos.rotation.coords.iter().map(|x| *x as f64));
+}
+
+/// Replaces the keys of `map` by the handles they are mapped to by `handles`, dropping the
+/// entries which key isn't mapped.
+pub(crate) fn remap_keys<K: Copy + Eq + Hash, V>(map: &mut HashMap<K, V>, handles: &HashMap<K, K>) {
+    *map = map
+        .drain()
+        .filter_map(|(key, value)| handles.get(&key).map(|key| (*key, value)))
+        .collect();
+}
+
+/// A flag shared by the owner of some data and the raw wrappers pointing to it, cleared once
+/// the pointed data may no longer be valid.
+///
+/// wasm-bindgen doesn't support lifetimes, so the wrappers holding raw pointers (e.g.
+/// `RawContactManifold`) check this in every accessor, to throw a JS error instead of reading
+/// a dangling pointer when they are used after the data they point to changed.
+#[derive(Clone)]
+pub(crate) struct Liveness(Rc<Cell<bool>>);
+
+impl Liveness {
+    pub(crate) fn new() -> Self {
+        Liveness(Rc::new(Cell::new(true)))
+    }
+
+    /// Invalidates all the wrappers sharing this flag.
+    pub(crate) fn expire(&self) {
+        self.0.set(false);
+    }
+
+    /// Throws a JS error if this flag expired.
+    pub(crate) fn check(&self) {
+        if !self.0.get() {
+            wasm_bindgen::throw_str(
+                "This object refers to contact data that is no longer valid: it must not be \
+                 used after the callback it was given to returns, or after the next timestep.",
+            );
+        }
+    }
+}
+
 // pub type FlatHandle = u32;
 //
 // #[inline(always)]